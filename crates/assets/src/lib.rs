@@ -1,7 +1,9 @@
 use slotmap::new_key_type;
 
 pub mod error;
+pub mod prefab;
 pub mod prelude;
+pub mod preload;
 pub mod save_load;
 pub mod scene;
 pub mod ser_component;
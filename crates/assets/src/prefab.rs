@@ -0,0 +1,140 @@
+use std::fs::{File, read_to_string};
+use std::path::Path;
+
+use flatbox_core::{color::Color, math::transform::Transform};
+use flatbox_ecs::{Entity, EntityBuilder, World};
+use ron::ser::{Serializer, PrettyConfig};
+use serde::{Serialize, Deserialize};
+use slotmap::SlotMap;
+
+use crate::{
+    AssetHandle,
+    error::{AssetError, RonError},
+    scene::SerializableEntity,
+};
+
+/// Template of an entity, which can be spawned multiple times with [`SpawnPrefabExt::spawn_prefab`].
+/// Unlike [`Scene`](crate::scene::Scene), its components are cloned on each spawn, so the prefab is kept
+#[derive(Default, Serialize, Deserialize)]
+pub struct Prefab {
+    pub entity: SerializableEntity,
+}
+
+impl Prefab {
+    pub fn new(entity: SerializableEntity) -> Self {
+        Prefab { entity }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AssetError> {
+        Ok(ron::from_str::<Prefab>(
+            &read_to_string(path)?
+        ).map_err(RonError::from)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AssetError> {
+        let buf = File::create(path)?;
+        let mut ser = Serializer::new(buf, Some(
+            PrettyConfig::new()
+                .struct_names(true)
+        )).map_err(RonError::from)?;
+
+        self.serialize(&mut ser).map_err(RonError::from)?;
+
+        Ok(())
+    }
+
+    /// Builder of the entity with the components of the prefab and the overrides applied
+    pub fn builder(&self, overrides: &PrefabOverrides) -> EntityBuilder {
+        let mut entity_builder = EntityBuilder::new();
+
+        for component in &self.entity.components {
+            component.lock().add_overridden(&mut entity_builder, overrides);
+        }
+
+        if let Some(transform) = overrides.transform {
+            entity_builder.add(transform);
+        }
+
+        entity_builder
+    }
+}
+
+/// Fields of the prefab, which are replaced on spawn
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PrefabOverrides {
+    /// Transform of the entity, which is added, even if the prefab has none
+    pub transform: Option<Transform>,
+    /// Color of the material, e.g. `DefaultMaterial`
+    pub color: Option<Color>,
+}
+
+impl PrefabOverrides {
+    pub fn new() -> Self {
+        PrefabOverrides::default()
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// Singleton component with prefabs, which are spawned by their handles
+#[derive(Default)]
+pub struct Prefabs {
+    prefabs: SlotMap<AssetHandle, Prefab>,
+}
+
+impl Prefabs {
+    pub fn new() -> Self {
+        Prefabs::default()
+    }
+
+    pub fn insert(&mut self, prefab: Prefab) -> AssetHandle {
+        self.prefabs.insert(prefab)
+    }
+
+    pub fn get(&self, handle: AssetHandle) -> Option<&Prefab> {
+        self.prefabs.get(handle)
+    }
+
+    pub fn get_mut(&mut self, handle: AssetHandle) -> Option<&mut Prefab> {
+        self.prefabs.get_mut(handle)
+    }
+
+    pub fn remove(&mut self, handle: AssetHandle) -> Option<Prefab> {
+        self.prefabs.remove(handle)
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefabs.is_empty()
+    }
+}
+
+pub trait SpawnPrefabExt {
+    /// Spawn entity of the prefab from [`Prefabs`] singleton with the overrides applied.
+    /// Returns [`AssetError::InvalidHandle`], if there's no such prefab
+    fn spawn_prefab(&mut self, handle: AssetHandle, overrides: PrefabOverrides) -> Result<Entity, AssetError>;
+}
+
+impl SpawnPrefabExt for World {
+    fn spawn_prefab(&mut self, handle: AssetHandle, overrides: PrefabOverrides) -> Result<Entity, AssetError> {
+        let mut entity_builder = self
+            .query_mut::<&Prefabs>()
+            .into_iter()
+            .find_map(|(_, prefabs)| prefabs.get(handle))
+            .ok_or(AssetError::InvalidHandle)?
+            .builder(&overrides);
+
+        Ok(self.spawn(entity_builder.build()))
+    }
+}
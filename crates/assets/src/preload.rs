@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fs::{read, read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+
+use crate::error::{AssetError, RonError};
+
+/// List of assets to be preloaded. Entries are either paths or glob patterns with
+/// `*` (any characters in a file name), `?` (any single character) and `**` (any
+/// number of directories), e.g. `assets/textures/**/*.png`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetManifest {
+    pub entries: Vec<String>,
+}
+
+impl AssetManifest {
+    pub fn new<S: Into<String>>(entries: impl IntoIterator<Item = S>) -> Self {
+        AssetManifest {
+            entries: entries.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Load manifest from RON file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AssetError> {
+        Ok(ron::from_str::<AssetManifest>(
+            &read_to_string(path)?
+        ).map_err(RonError::from)?)
+    }
+
+    /// Resolve entries into the list of existing files
+    pub fn resolve(&self) -> Vec<PathBuf> {
+        let mut paths = vec![];
+
+        for entry in &self.entries {
+            if entry.contains(['*', '?']) {
+                let mut matched = vec![];
+                let (base, pattern) = split_pattern(entry);
+
+                collect_matches(&base, &pattern, &mut matched);
+                matched.sort();
+                paths.extend(matched);
+            } else {
+                paths.push(PathBuf::from(entry));
+            }
+        }
+
+        paths.dedup();
+        paths
+    }
+}
+
+/// In-memory cache of preloaded files
+#[derive(Debug, Default, Clone)]
+pub struct PreloadedAssets {
+    files: HashMap<PathBuf, Arc<[u8]>>,
+}
+
+impl PreloadedAssets {
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<Arc<[u8]>> {
+        self.files.get(path.as_ref()).cloned()
+    }
+
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.files.contains_key(path.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.keys()
+    }
+}
+
+#[derive(Debug, Default)]
+struct PreloadState {
+    loaded: usize,
+    assets: PreloadedAssets,
+    errors: Vec<(PathBuf, AssetError)>,
+}
+
+/// Loads assets of the manifest on a background thread and reports the progress
+#[derive(Debug, Clone)]
+pub struct AssetPreloader {
+    total: usize,
+    state: Arc<Mutex<PreloadState>>,
+}
+
+impl AssetPreloader {
+    pub fn start(manifest: &AssetManifest) -> Self {
+        let paths = manifest.resolve();
+        let state = Arc::new(Mutex::new(PreloadState::default()));
+        let thread_state = state.clone();
+        let total = paths.len();
+
+        thread::spawn(move || {
+            for path in paths {
+                let result = read(&path);
+                let mut state = thread_state.lock();
+
+                match result {
+                    Ok(bytes) => { state.assets.files.insert(path, bytes.into()); },
+                    Err(e) => state.errors.push((path, e.into())),
+                }
+
+                state.loaded += 1;
+            }
+        });
+
+        AssetPreloader { total, state }
+    }
+
+    /// Number of assets in the manifest
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Number of processed assets, including failed ones
+    pub fn loaded(&self) -> usize {
+        self.state.lock().loaded
+    }
+
+    /// Loading progress in range `0.0..=1.0`
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.loaded() as f32 / self.total as f32
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.loaded() >= self.total
+    }
+
+    /// Get loaded assets. It's complete only after [`AssetPreloader::is_done`]
+    pub fn assets(&self) -> PreloadedAssets {
+        self.state.lock().assets.clone()
+    }
+
+    /// Take errors, occurred during loading
+    pub fn take_errors(&self) -> Vec<(PathBuf, AssetError)> {
+        std::mem::take(&mut self.state.lock().errors)
+    }
+}
+
+/// Split pattern into the base directory without wildcards and the rest of components
+fn split_pattern(pattern: &str) -> (PathBuf, Vec<String>) {
+    let mut base = PathBuf::new();
+    let mut rest = vec![];
+
+    for component in pattern.split(['/', '\\']) {
+        if rest.is_empty() && !component.contains(['*', '?']) {
+            base.push(if component.is_empty() { "/" } else { component });
+        } else {
+            rest.push(component.to_owned());
+        }
+    }
+
+    if base.as_os_str().is_empty() {
+        base.push(".");
+    }
+
+    (base, rest)
+}
+
+fn collect_matches(dir: &Path, pattern: &[String], matched: &mut Vec<PathBuf>) {
+    let Some((component, rest)) = pattern.split_first() else { return };
+
+    if component == "**" {
+        // `**` matches zero directories...
+        collect_matches(dir, rest, matched);
+    }
+
+    let Ok(entries) = read_dir(dir) else { return };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_dir = path.is_dir();
+
+        if component == "**" {
+            // ...or any number of them
+            if is_dir {
+                collect_matches(&path, pattern, matched);
+            } else if rest.is_empty() {
+                matched.push(path);
+            }
+        } else if wildcard_match(component, &entry.file_name().to_string_lossy()) {
+            if rest.is_empty() {
+                if !is_dir {
+                    matched.push(path);
+                }
+            } else if is_dir {
+                collect_matches(&path, rest, matched);
+            }
+        }
+    }
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            },
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                },
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
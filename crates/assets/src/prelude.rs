@@ -1,6 +1,7 @@
 pub use crate::error::*;
 // pub use crate::manager::*;
-// pub use crate::resources::*;
+pub use crate::prefab::*;
+pub use crate::preload::*;
 pub use crate::save_load::*;
 pub use crate::scene::*;
 pub use crate::ser_component::*;
\ No newline at end of file
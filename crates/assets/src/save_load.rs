@@ -17,39 +17,35 @@ pub trait SaveLoad {
 
 /// Macro that is used to create custom [`SaveLoad`]ers, 
 /// that are capable of saving and loading individual serializable
-/// components from the [`World`], scene's [`PhysicsHandler`] and 
-/// [`AssetManager`]
+/// components of the [`World`]
 /// 
 /// # Usage example
 /// 
-/// ```rust 
+/// ```rust,no_run
+/// use flatbox_assets::{impl_save_load, save_load::SaveLoad, error::AssetError};
+/// use flatbox_core::math::transform::Transform;
+/// use flatbox_ecs::World;
+/// use serde::{Serialize, Deserialize};
+///
 /// #[derive(Serialize, Deserialize)]
 /// struct MyComponent(u32);
-/// 
+///
 /// #[derive(Default)]
 /// struct MySaveLoader {
 ///     components: Vec<String>, // required field
 /// }
-/// 
+///
 /// impl_save_load! {
-///     loader: MySaveLoader, 
+///     loader: MySaveLoader,
 ///     components: [
-///         Camera, 
-///         Timer, 
 ///         Transform,
-///         AssetHandle<'M'>,
 ///         MyComponent
 ///     ]
 /// }
-/// 
-/// fn save_world(
-///     world: Read<World>,
-/// ) -> FlatboxResult<()> {
-///     let ws = MyWorldSaver::default();
-/// 
-///     ws.save("/path/to/save", &world)?;
+///
+/// fn save_world(world: &World) -> Result<(), AssetError> {
+///     MySaveLoader::default().save(world, "/path/to/save")
 /// }
-/// 
 /// ```
 #[macro_export]
 macro_rules! impl_save_load {
@@ -140,113 +136,76 @@ macro_rules! impl_save_load {
 
         }
         
-        impl SaveLoad for $ctx {
+        impl $crate::save_load::SaveLoad for $ctx {
             fn save<P: AsRef<std::path::Path>>(
                 &mut self,
                 world: &::flatbox_ecs::World,
-                asset_manager: &$crate::manager::AssetManager,
                 path: P,
             ) -> Result<(), $crate::error::AssetError> {
                 use std::fs::File;
                 use std::io::Cursor;
-                use ron::ser::PrettyConfig;
+                use $crate::ron::ser::PrettyConfig;
 
-                let mut buf = vec![];                    
-                let mut ser = ron::Serializer::new(&mut buf, Some(PrettyConfig::new()))
-                    .map_err(|e| $crate::error::RonError::from(e))?;    
+                let mut buf = vec![];
+                let mut ser = $crate::ron::Serializer::new(&mut buf, Some(PrettyConfig::new()))
+                    .map_err($crate::error::RonError::from)?;
 
-                ::flatbox_ecs::serialize_world(&world, self, &mut ser)
-                    .map_err(|e| $crate::error::RonError::from(e))?;
+                ::flatbox_ecs::serialize_world(world, self, &mut ser)
+                    .map_err($crate::error::RonError::from)?;
 
-                let mut a = vec![];
-                let mut archive = tar::Builder::new(&mut a);
+                let mut header = $crate::tar::Header::new_gnu();
+                header.set_entry_type($crate::tar::EntryType::Regular);
+                header.set_path("world.ron")?;
+                header.set_size(buf.len() as u64);
+                header.set_cksum();
 
-                let world = &*buf;
-                let world_header = create_header("world.ron", world.len());
-                archive.append(&world_header, world)?;
+                let mut archive = $crate::tar::Builder::new(vec![]);
+                archive.append(&header, &*buf)?;
 
-                let assets = ron::ser::to_string_pretty(&asset_manager, PrettyConfig::default())
-                    .map_err(|e| $crate::error::RonError::from(e))?;
-                let assets_bytes = assets.as_bytes();
-                let assets_header = create_header("assets.ron", assets_bytes.len());
-                archive.append(&assets_header, assets_bytes)?;
-
-                let inner = archive.into_inner()?;
-                let mut cursor = Cursor::new(inner);
-
-                let file = File::create(path)?;
-                let mut encoder = lz4::EncoderBuilder::new()
+                let mut cursor = Cursor::new(archive.into_inner()?);
+                let mut encoder = $crate::lz4::EncoderBuilder::new()
                     .level(4)
-                    .build(file)?;  
+                    .build(File::create(path)?)?;
 
-                std::io::copy(&mut cursor, &mut encoder)?; 
+                std::io::copy(&mut cursor, &mut encoder)?;
 
                 let (_, result) = encoder.finish();
-                
                 result?;
 
                 Ok(())
             }
-            
+
             fn load<P: AsRef<std::path::Path>>(
                 &mut self,
                 path: P,
-            ) -> Result<(::flatbox_ecs::World, $crate::manager::AssetManager), $crate::error::AssetError> {
+            ) -> Result<::flatbox_ecs::World, $crate::error::AssetError> {
                 use std::fs::File;
-                use std::io::Read;
-                use ::serde::Deserialize;
+                use std::io::{Error, ErrorKind, Read};
 
-                let package = File::open(path)?;
-                let decoded = lz4::Decoder::new(package)?;
-                let mut archive = tar::Archive::new(decoded);
+                let decoded = $crate::lz4::Decoder::new(File::open(path)?)?;
+                let mut archive = $crate::tar::Archive::new(decoded);
 
-                let mut world = None;
-                let mut asset_manager = None;
-                // let mut physics_handler = None;
+                for file in archive.entries()? {
+                    let mut file = file?;
+                    let is_world = file.header().entry_type() == $crate::tar::EntryType::Regular
+                        && file.path()?.as_os_str() == "world.ron";
 
-                for file in archive.entries().unwrap() {
-                    let mut file = file.unwrap();
-                    let header = file.header().clone();
+                    if !is_world {
+                        continue;
+                    }
 
                     let mut buffer = vec![];
                     file.read_to_end(&mut buffer)?;
-                    let mut de = ron::Deserializer::from_bytes(&buffer)
-                        .map_err(|e| $crate::error::RonError::from(e))?;
-                    
-                    if header.entry_type() == tar::EntryType::Regular {
-                        match header.path().unwrap().to_str().unwrap() {
-                            "world.ron" => {
-                                world = Some(::flatbox_ecs::deserialize_world(self, &mut de)
-                                    .map_err(|e| $crate::error::RonError::from(e))?);
-                            },
-                            "assets.ron" => {
-                                asset_manager = Some($crate::manager::AssetManager::deserialize(&mut de)
-                                    .map_err(|e| $crate::error::RonError::from(e))?);
-                            },
-                            // "physics.ron" => {
-                            //     physics_handler = Some(PhysicsHandler::deserialize(&mut de)?);
-                            // },
-                            _ => {},
-                        }
-                    }
-                }
-                
-                Ok((
-                    world.unwrap(), 
-                    asset_manager.unwrap(), 
-                    // physics_handler.unwrap()
-                ))
-            }
-        }
 
-        fn create_header(path: &'static str, size: usize) -> tar::Header {
-            let mut header = tar::Header::new_gnu();
-            header.set_entry_type(tar::EntryType::Regular);
-            header.set_path(path).unwrap();
-            header.set_size(size as u64);
-            header.set_cksum();
+                    let mut de = $crate::ron::Deserializer::from_bytes(&buffer)
+                        .map_err($crate::error::RonError::from)?;
+
+                    return ::flatbox_ecs::deserialize_world(self, &mut de)
+                        .map_err(|e| $crate::error::RonError::from(e).into());
+                }
 
-            header
+                Err(Error::new(ErrorKind::InvalidData, "Save doesn't contain `world.ron`").into())
+            }
         }
     };
 }
\ No newline at end of file
@@ -23,9 +23,12 @@ pub struct SerializableEntity {
 /// 
 /// # Usage example
 /// ```rust
+/// use flatbox_assets::{entity, scene::SerializableEntity};
+/// use flatbox_core::math::transform::Transform;
+///
 /// let entity = entity![
-///     Model::cube(),
-///     Transform::default()
+///     Transform::default(),
+///     42u32
 /// ];
 /// ```
 #[macro_export]
@@ -76,28 +79,32 @@ impl Scene {
 /// Macro for easy [`Scene`] creation. `entities` can be created with [`entity!`] 
 /// macro or manually:
 /// ```rust
+/// # use std::sync::Arc;
+/// # use flatbox_assets::{parking_lot::Mutex, scene::SerializableEntity, ser_component::SerializableComponent};
+/// # use flatbox_core::math::transform::Transform;
+/// let comp1: Box<dyn SerializableComponent> = Box::new(Transform::default());
+/// let comp2: Box<dyn SerializableComponent> = Box::new(42u32);
+///
 /// let entity = SerializableEntity {
 ///     components: vec![
-///         Arc::new(comp1),
-///         Arc::new(comp2),
+///         Arc::new(Mutex::new(comp1)),
+///         Arc::new(Mutex::new(comp2)),
 ///     ],
 /// };
 /// ```
 /// 
 /// # Usage example
 /// ```rust
+/// use flatbox_assets::{entity, scene, scene::{Scene, SerializableEntity}, AssetHandle};
+/// use flatbox_core::math::transform::Transform;
+///
 /// let scene = scene! {
 ///     entities: [
 ///         entity![
-///             Camera::builder()
-///                 .is_active(true)
-///                 .camera_type(CameraType::FirstPerson)
-///                 .build(),
 ///             Transform::default()
 ///         ],
 ///         entity![
-///             AssetHandle::null(), 
-///             Model::cube(),
+///             AssetHandle::default(),
 ///             Transform::default()
 ///         ]
 ///     ]
@@ -121,22 +128,49 @@ macro_rules! scene {
     };
 }
 
+/// Marker component of entities, which are kept by [`SpawnSceneExt::replace_scene`],
+/// e.g. singletons like GUI backend or diagnostics
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Persistent;
+
 pub trait SpawnSceneExt {
+    /// Clear the world and spawn entities of the scene
     fn spawn_scene(&mut self, scene: Scene);
+
+    /// Despawn all entities except [`Persistent`] ones and spawn entities of the scene
+    fn replace_scene(&mut self, scene: Scene);
 }
 
 impl SpawnSceneExt for World {
     fn spawn_scene(&mut self, scene: Scene) {
         self.clear();
+        spawn_entities(self, scene);
+    }
 
-        for entity in scene.entities {
-            let mut entity_builder = EntityBuilder::new();
-            
-            for component in entity.components {
-                component.lock().add_into(&mut entity_builder);
-            }
-            
-            self.spawn(entity_builder.build());
-        }        
+    fn replace_scene(&mut self, scene: Scene) {
+        let despawned = self
+            .query::<()>()
+            .without::<&Persistent>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+
+        for entity in despawned {
+            let _ = self.despawn(entity);
+        }
+
+        spawn_entities(self, scene);
     }
-}
\ No newline at end of file
+}
+
+fn spawn_entities(world: &mut World, scene: Scene) {
+    for entity in scene.entities {
+        let mut entity_builder = EntityBuilder::new();
+
+        for component in entity.components {
+            component.lock().add_into(&mut entity_builder);
+        }
+
+        world.spawn(entity_builder.build());
+    }
+}
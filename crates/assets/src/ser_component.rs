@@ -2,11 +2,17 @@ use as_any::AsAny;
 use flatbox_core::math::transform::Transform;
 use flatbox_ecs::{Component, EntityBuilder};
 
-use crate::AssetHandle;
+use crate::{AssetHandle, prefab::PrefabOverrides};
 
 #[typetag::serde(tag = "component")]
 pub trait SerializableComponent: Component + AsAny {
     fn add_into(&self, entity_builder: &mut EntityBuilder);
+
+    /// Add the component with the prefab overrides applied, e.g. the color of a material.
+    /// The component is added as is by default
+    fn add_overridden(&self, entity_builder: &mut EntityBuilder, _overrides: &PrefabOverrides) {
+        self.add_into(entity_builder);
+    }
 }
 
 /// Macro for implementing [`SerializableComponent`] trait for multiple types, that implement [`Clone`] trait; for using in [`Scene`]'s. Use to avoid boilerplate
@@ -14,17 +20,19 @@ pub trait SerializableComponent: Component + AsAny {
 /// # Usage example
 /// 
 /// ```rust
-/// #[derive(Clone)]
+/// use flatbox_assets::{impl_ser_component, typetag};
+/// use serde::{Serialize, Deserialize};
+///
+/// #[derive(Clone, Serialize, Deserialize)]
 /// struct ComponentA;
-/// 
-/// #[derive(Clone)]
+///
+/// #[derive(Clone, Serialize, Deserialize)]
 /// struct ComponentB;
-/// 
-/// #[derive(Clone)]
+///
+/// #[derive(Clone, Serialize, Deserialize)]
 /// struct ComponentC;
-/// 
+///
 /// impl_ser_component!(ComponentA, ComponentB, ComponentC);
-/// 
 /// ```
 /// 
 #[macro_export]
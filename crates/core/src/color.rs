@@ -0,0 +1,255 @@
+use serde::{Serialize, Deserialize};
+use nalgebra_glm as glm;
+use palette::{
+    FromColor, Hsla, Hsva, IntoColor, LinSrgba, Srgba,
+    RgbHue,
+};
+
+pub use palette;
+
+/// RGBA color in the (non-linear) sRGB color space with components in range `0.0..=1.0`.
+/// It's used by materials, lights, clear color etc. and can be converted
+/// into other color spaces, including the ones of `palette` crate
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const GRAY: Color = Color::rgb(0.5, 0.5, 0.5);
+    pub const RED: Color = Color::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Color = Color::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Color = Color::rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Color = Color::rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Color = Color::rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Color = Color::rgb(1.0, 0.0, 1.0);
+    pub const TRANSPARENT: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Color {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    pub fn rgb_u8(r: u8, g: u8, b: u8) -> Color {
+        Color::rgba_u8(r, g, b, 255)
+    }
+
+    pub fn rgba_u8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color::rgba(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    }
+
+    /// Parse color from hex string of form `RGB`, `RGBA`, `RRGGBB` or `RRGGBBAA`
+    /// with optional leading `#`
+    pub fn hex(hex: &str) -> Option<Color> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        let digit = |i: usize| u8::from_str_radix(hex.get(i..i + 1)?, 16).ok();
+        let byte = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+        match hex.len() {
+            3 => Some(Color::rgb_u8(digit(0)? * 17, digit(1)? * 17, digit(2)? * 17)),
+            4 => Some(Color::rgba_u8(digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, digit(3)? * 17)),
+            6 => Some(Color::rgb_u8(byte(0)?, byte(2)?, byte(4)?)),
+            8 => Some(Color::rgba_u8(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
+            _ => None,
+        }
+    }
+
+    /// Create color from linear sRGB components
+    pub fn linear_rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Srgba::from_linear(LinSrgba::new(r, g, b, a)).into()
+    }
+
+    /// Create color from hue (in degrees), saturation and value
+    pub fn hsva(hue: f32, saturation: f32, value: f32, alpha: f32) -> Color {
+        Srgba::from_color(Hsva::new(RgbHue::from_degrees(hue), saturation, value, alpha)).into()
+    }
+
+    /// Create color from hue (in degrees), saturation and lightness
+    pub fn hsla(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Color {
+        Srgba::from_color(Hsla::new(RgbHue::from_degrees(hue), saturation, lightness, alpha)).into()
+    }
+
+    /// Get the same color with a different alpha
+    pub fn with_alpha(self, a: f32) -> Color {
+        Color { a, ..self }
+    }
+
+    /// Get linear sRGB components `[r, g, b, a]`
+    pub fn to_linear(&self) -> [f32; 4] {
+        let linear: LinSrgba = Srgba::from(*self).into_linear();
+        [linear.red, linear.green, linear.blue, linear.alpha]
+    }
+
+    /// Get `[hue (in degrees), saturation, value, alpha]`
+    pub fn to_hsva(&self) -> [f32; 4] {
+        let hsva: Hsva = Srgba::from(*self).into_color();
+        [hsva.hue.into_positive_degrees(), hsva.saturation, hsva.value, hsva.alpha]
+    }
+
+    /// Get `[hue (in degrees), saturation, lightness, alpha]`
+    pub fn to_hsla(&self) -> [f32; 4] {
+        let hsla: Hsla = Srgba::from(*self).into_color();
+        [hsla.hue.into_positive_degrees(), hsla.saturation, hsla.lightness, hsla.alpha]
+    }
+
+    pub fn to_rgba_u8(&self) -> [u8; 4] {
+        let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [to_u8(self.r), to_u8(self.g), to_u8(self.b), to_u8(self.a)]
+    }
+
+    pub fn to_vec3(&self) -> glm::Vec3 {
+        glm::vec3(self.r, self.g, self.b)
+    }
+
+    pub fn to_vec4(&self) -> glm::Vec4 {
+        glm::vec4(self.r, self.g, self.b, self.a)
+    }
+
+    /// Linearly interpolate between two colors in linear sRGB space
+    pub fn lerp(&self, other: &Color, t: f32) -> Color {
+        let a = self.to_linear();
+        let b = other.to_linear();
+        let mix = |i: usize| a[i] + (b[i] - a[i]) * t;
+
+        Color::linear_rgba(mix(0), mix(1), mix(2), mix(3))
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::WHITE
+    }
+}
+
+impl From<Srgba> for Color {
+    fn from(color: Srgba) -> Self {
+        Color::rgba(color.red, color.green, color.blue, color.alpha)
+    }
+}
+
+impl From<Color> for Srgba {
+    fn from(color: Color) -> Self {
+        Srgba::new(color.r, color.g, color.b, color.a)
+    }
+}
+
+impl From<LinSrgba> for Color {
+    fn from(color: LinSrgba) -> Self {
+        Srgba::from_linear(color).into()
+    }
+}
+
+impl From<Color> for LinSrgba {
+    fn from(color: Color) -> Self {
+        Srgba::from(color).into_linear()
+    }
+}
+
+impl From<glm::Vec3> for Color {
+    fn from(color: glm::Vec3) -> Self {
+        Color::rgb(color.x, color.y, color.z)
+    }
+}
+
+impl From<glm::Vec4> for Color {
+    fn from(color: glm::Vec4) -> Self {
+        Color::rgba(color.x, color.y, color.z, color.w)
+    }
+}
+
+impl From<[f32; 3]> for Color {
+    fn from([r, g, b]: [f32; 3]) -> Self {
+        Color::rgb(r, g, b)
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Color::rgba(r, g, b, a)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_color_eq(a: Color, b: Color) {
+        let (a, b): ([f32; 4], [f32; 4]) = (a.into(), b.into());
+        assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-3), "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(Color::hex("#ff0000"), Some(Color::RED));
+        assert_eq!(Color::hex("0f0"), Some(Color::GREEN));
+        assert_eq!(Color::hex("0000ff80").map(|c| c.to_rgba_u8()), Some([0, 0, 255, 128]));
+        assert_eq!(Color::hex("#fff0"), Some(Color::rgba(1.0, 1.0, 1.0, 0.0)));
+
+        assert_eq!(Color::hex("#ff00"), Some(Color::rgba(1.0, 1.0, 0.0, 0.0)));
+        assert_eq!(Color::hex("#ff000"), None);
+        assert_eq!(Color::hex("zzzzzz"), None);
+        assert_eq!(Color::hex("ффф"), None);
+    }
+
+    #[test]
+    fn u8_round_trip() {
+        let color = Color::rgba_u8(12, 34, 56, 78);
+
+        assert_eq!(color.to_rgba_u8(), [12, 34, 56, 78]);
+        assert_eq!(Color::rgba(2.0, -1.0, 0.5, 1.0).to_rgba_u8(), [255, 0, 128, 255]);
+    }
+
+    #[test]
+    fn linear() {
+        assert_eq!(Color::BLACK.to_linear(), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(Color::WHITE.to_linear(), [1.0, 1.0, 1.0, 1.0]);
+
+        // sRGB mid gray is darker in linear space
+        let [r, ..] = Color::GRAY.to_linear();
+        assert!((r - 0.214).abs() < 1e-3);
+
+        let [r, g, b, a] = Color::GRAY.to_linear();
+        assert_color_eq(Color::linear_rgba(r, g, b, a), Color::GRAY);
+    }
+
+    #[test]
+    fn hsv_and_hsl() {
+        assert_color_eq(Color::hsva(120.0, 1.0, 1.0, 1.0), Color::GREEN);
+        assert_color_eq(Color::hsla(240.0, 1.0, 0.5, 0.5), Color::BLUE.with_alpha(0.5));
+
+        let [h, s, v, _] = Color::YELLOW.to_hsva();
+        assert_eq!([h.round(), s, v], [60.0, 1.0, 1.0]);
+
+        let [h, s, l, _] = Color::CYAN.to_hsla();
+        assert_eq!([h.round(), s, l], [180.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn lerp() {
+        assert_color_eq(Color::RED.lerp(&Color::BLUE, 0.0), Color::RED);
+        assert_color_eq(Color::RED.lerp(&Color::BLUE, 1.0), Color::BLUE);
+
+        // Interpolation happens in linear space
+        let [r, _, b, _] = Color::RED.lerp(&Color::BLUE, 0.5).to_linear();
+        assert!((r - 0.5).abs() < 1e-3 && (b - 0.5).abs() < 1e-3);
+    }
+}
@@ -1,7 +1,10 @@
 pub mod catch;
+pub mod color;
 pub mod logger;
 pub mod math;
 pub mod prelude;
+pub mod profile;
+pub mod random;
 pub mod time;
 
 pub struct AppExit;
\ No newline at end of file
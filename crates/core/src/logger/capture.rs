@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use log::Level;
+
+/// Default number of records kept in the log capture buffer
+pub const DEFAULT_CAPTURE_CAPACITY: usize = 256;
+
+/// Log record, captured by [`FlatboxLogger`](super::FlatboxLogger)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// Sequential number of the record since the logger's initialization
+    pub index: u64,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    /// UTC time of the record in format `YYYY-MM-DD hh:mm:ss.mmm`
+    pub timestamp: String,
+}
+
+pub(crate) struct LogCapture {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+    next_index: u64,
+}
+
+impl LogCapture {
+    const fn new() -> LogCapture {
+        LogCapture {
+            records: VecDeque::new(),
+            capacity: DEFAULT_CAPTURE_CAPACITY,
+            next_index: 0,
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.truncate();
+    }
+
+    pub fn push(&mut self, level: Level, target: &str, message: String, timestamp: String) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.records.push_back(LogRecord {
+            index: self.next_index,
+            level,
+            target: target.to_owned(),
+            message,
+            timestamp,
+        });
+
+        self.next_index += 1;
+        self.truncate();
+    }
+
+    fn truncate(&mut self) {
+        while self.records.len() > self.capacity {
+            self.records.pop_front();
+        }
+    }
+}
+
+static LOG_CAPTURE: Mutex<LogCapture> = Mutex::new(LogCapture::new());
+
+pub(crate) fn log_capture() -> MutexGuard<'static, LogCapture> {
+    LOG_CAPTURE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Get up to `count` most recent log records (the oldest go first)
+pub fn recent_logs(count: usize) -> Vec<LogRecord> {
+    let capture = log_capture();
+    let skip = capture.records.len().saturating_sub(count);
+
+    capture.records.iter().skip(skip).cloned().collect()
+}
+
+/// Subscription to the log records. Each subscriber has its own cursor, so
+/// several readers (in-game console, crash reporter, tests) can read the
+/// same records independently. Records, which were pushed out of the capped
+/// buffer before being read, are lost for the subscriber
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEvents {
+    cursor: u64,
+}
+
+impl LogEvents {
+    /// Subscribe to the records logged from now on
+    pub fn subscribe() -> LogEvents {
+        LogEvents {
+            cursor: log_capture().next_index,
+        }
+    }
+
+    /// Subscribe to all the records, which are still kept in the buffer
+    pub fn subscribe_from_start() -> LogEvents {
+        LogEvents { cursor: 0 }
+    }
+
+    /// Read the records logged since the previous read
+    pub fn read(&mut self) -> Vec<LogRecord> {
+        let capture = log_capture();
+        let records: Vec<LogRecord> = capture.records
+            .iter()
+            .filter(|record| record.index >= self.cursor)
+            .cloned()
+            .collect();
+
+        self.cursor = capture.next_index;
+
+        records
+    }
+
+    /// Check whether there are records, which haven't been read yet
+    pub fn is_empty(&self) -> bool {
+        log_capture().next_index <= self.cursor
+    }
+}
+
+impl Default for LogEvents {
+    fn default() -> Self {
+        LogEvents::subscribe()
+    }
+}
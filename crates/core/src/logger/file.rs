@@ -0,0 +1,140 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Configuration of the logger's file output. When the log file exceeds `max_size`,
+/// it's renamed to `<path>.1` (older backups are shifted further) and a new file is created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileConfig {
+    /// Path to the current log file
+    pub path: PathBuf,
+    /// Maximal size of a single log file in bytes
+    pub max_size: u64,
+    /// Maximal number of rotated backups to keep
+    pub max_files: usize,
+}
+
+impl LogFileConfig {
+    pub fn new<P: AsRef<Path>>(path: P) -> LogFileConfig {
+        LogFileConfig {
+            path: path.as_ref().to_path_buf(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for LogFileConfig {
+    fn default() -> Self {
+        LogFileConfig {
+            path: PathBuf::from("flatbox.log"),
+            max_size: 5 * 1024 * 1024,
+            max_files: 3,
+        }
+    }
+}
+
+pub(crate) struct RotatingFile {
+    config: LogFileConfig,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub fn open(config: LogFileConfig) -> io::Result<RotatingFile> {
+        if let Some(parent) = config.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)?;
+
+        let size = file.metadata()?.len();
+
+        Ok(RotatingFile { config, file, size })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+
+        if self.size > 0 && self.size + len > self.config.max_size {
+            self.rotate()?;
+        }
+
+        writeln!(self.file, "{line}")?;
+        self.size += len;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.config.max_files == 0 {
+            self.file = File::create(&self.config.path)?;
+        } else {
+            for i in (1..self.config.max_files).rev() {
+                let from = self.backup_path(i);
+                if from.exists() {
+                    fs::rename(from, self.backup_path(i + 1))?;
+                }
+            }
+
+            fs::rename(&self.config.path, self.backup_path(1))?;
+            self.file = File::create(&self.config.path)?;
+        }
+
+        self.size = 0;
+
+        Ok(())
+    }
+
+    fn backup_path(&self, index: usize) -> PathBuf {
+        let mut path = self.config.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        PathBuf::from(path)
+    }
+}
+
+/// Get current UTC time formatted as `YYYY-MM-DD hh:mm:ss.mmm`
+pub fn timestamp() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+    let (year, month, day) = civil_from_days(days as i64);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:03}",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis(),
+    )
+}
+
+/// Convert days since Unix epoch into `(year, month, day)`. 
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
@@ -0,0 +1,260 @@
+/* 
+ *
+ * Heavily inspired by `pretty_env_logger` https://crates.io/crates/pretty_env_logger/
+ * 
+ */
+
+use std::fmt;
+use std::io;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use colored::*;
+use thiserror::Error;
+use log::{Metadata, Record, Log, LevelFilter, SetLoggerError};
+
+pub use log::{info, error, warn, debug, trace, Level};
+
+pub mod capture;
+pub mod file;
+
+pub use capture::{LogEvents, LogRecord, recent_logs};
+pub use file::LogFileConfig;
+use capture::log_capture;
+use file::{RotatingFile, timestamp};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum LoggerLevel {
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+    None,
+}
+
+impl From<LoggerLevel> for Option<Level> {
+    fn from(value: LoggerLevel) -> Self {
+        match value {
+            LoggerLevel::Debug => Some(Level::Debug),
+            LoggerLevel::Error => Some(Level::Error),
+            LoggerLevel::Info => Some(Level::Info),
+            LoggerLevel::Trace => Some(Level::Trace),
+            LoggerLevel::Warning => Some(Level::Warn),
+            LoggerLevel::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggerConfig {
+    /// Maximal level of records to be logged
+    pub level: LoggerLevel,
+    /// Whether records are printed to the console
+    pub console: bool,
+    /// Whether records are prefixed with UTC timestamp
+    pub timestamps: bool,
+    /// Optional rotating file output
+    pub file: Option<LogFileConfig>,
+    /// Number of recent records kept in memory and available through [`LogEvents`].
+    /// `0` disables capturing
+    pub capture_capacity: usize,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        LoggerConfig {
+            #[cfg(not(debug_assertions))]
+            level: LoggerLevel::Info,
+            #[cfg(debug_assertions)]
+            level: LoggerLevel::Debug,
+            console: true,
+            timestamps: false,
+            file: None,
+            capture_capacity: capture::DEFAULT_CAPTURE_CAPACITY,
+        }
+    }
+}
+
+pub struct FlatboxLogger {
+    log_level: Level,
+    console: bool,
+    timestamps: bool,
+    file: Option<Mutex<RotatingFile>>,
+}
+
+impl FlatboxLogger {
+    pub fn init(){
+        FlatboxLogger::try_init().expect("Failed to set logger");
+    }
+
+    pub fn try_init() -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::<FlatboxLogger>::default())?;
+        #[cfg(not(debug_assertions))]
+        log::set_max_level(LevelFilter::Info);
+        #[cfg(debug_assertions)]
+        log::set_max_level(LevelFilter::Debug);
+
+        Ok(())
+    }
+
+    pub fn init_with_level(logger_level: LoggerLevel){
+        FlatboxLogger::try_init_with_level(logger_level).expect("Failed to set logger with level");
+    }
+
+    pub fn try_init_with_level(logger_level: LoggerLevel) -> Result<(), SetLoggerError> {
+        if let Some(log_level) = logger_level.into() {
+            log::set_boxed_logger(Box::new(FlatboxLogger { 
+                log_level,
+                ..Default::default()
+            }))?;
+            log::set_max_level(log_level.to_level_filter());
+        }
+
+        Ok(())
+    }
+
+    pub fn init_with_config(config: LoggerConfig) {
+        FlatboxLogger::try_init_with_config(config).expect("Failed to set logger with config");
+    }
+
+    pub fn try_init_with_config(config: LoggerConfig) -> Result<(), LoggerError> {
+        let Some(log_level) = config.level.into() else { return Ok(()) };
+
+        let file = match config.file {
+            Some(file_config) => Some(Mutex::new(RotatingFile::open(file_config)?)),
+            None => None,
+        };
+
+        log_capture().set_capacity(config.capture_capacity);
+
+        log::set_boxed_logger(Box::new(FlatboxLogger {
+            log_level,
+            console: config.console,
+            timestamps: config.timestamps,
+            file,
+        }))?;
+        log::set_max_level(log_level.to_level_filter());
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LoggerError {
+    #[error("Cannot set logger")]
+    SetLogger(#[from] SetLoggerError),
+    #[error("Cannot open log file")]
+    FileOutput(#[from] io::Error),
+}
+
+
+impl Default for FlatboxLogger {
+    fn default() -> Self {
+        FlatboxLogger  {
+            #[cfg(not(debug_assertions))]
+            log_level: Level::Info,
+            #[cfg(debug_assertions)]
+            log_level: Level::Debug,
+            console: true,
+            timestamps: false,
+            file: None,
+        }
+    }
+}
+
+impl Log for FlatboxLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.log_level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let target = split_target(record.target());
+        let max_width = max_target_width(target);
+        let timestamp = timestamp();
+
+        if self.console {
+            let level = colored_level(record.level());
+
+            let target = Padded {
+                value: target.bold(),
+                width: max_width,
+            };
+
+            if self.timestamps {
+                println!("{} {} {} > {}", timestamp.dimmed(), level, target, record.args());
+            } else {
+                println!("{} {} > {}", level, target, record.args());
+            }
+        }
+
+        if let Some(file) = &self.file {
+            let line = format!(
+                "{} {:<5} {} > {}", 
+                timestamp, 
+                record.level(), 
+                Padded { value: target, width: max_width }, 
+                record.args(),
+            );
+
+            if let Ok(mut file) = file.lock() {
+                if let Err(e) = file.write_line(&line) {
+                    eprintln!("Cannot write log record to file: {e}");
+                }
+            }
+        }
+
+        log_capture().push(record.level(), target, record.args().to_string(), timestamp);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+struct Padded<T> {
+    value: T,
+    width: usize,
+}
+
+impl<T: fmt::Display> fmt::Display for Padded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{: <width$}", self.value, width = self.width)
+    }
+}
+
+static MAX_MODULE_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+fn split_target(target: &str) -> &str {
+    match target.split_once("::") {
+        Some((module, _)) => module,
+        None => target,
+    }
+}
+
+fn max_target_width(target: &str) -> usize {
+    let max_width = MAX_MODULE_WIDTH.load(Ordering::Relaxed);
+    if max_width < target.len() {
+        MAX_MODULE_WIDTH.store(target.len(), Ordering::Relaxed);
+        target.len()
+    } else {
+        max_width
+    }
+}
+
+fn colored_level(level: Level) -> ColoredString {
+    match level {
+        Level::Trace => "TRACE".magenta(),
+        Level::Debug => "DEBUG".blue(),
+        Level::Info =>  "INFO ".green(),
+        Level::Warn =>  "WARN ".yellow(),
+        Level::Error => "ERROR".red(),
+    }
+}
\ No newline at end of file
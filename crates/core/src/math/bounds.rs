@@ -0,0 +1,289 @@
+use serde::{Serialize, Deserialize};
+use nalgebra_glm as glm;
+
+use super::transform::Transform;
+
+/// Axis-aligned bounding box, described by its minimal and maximal corners
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: glm::Vec3, max: glm::Vec3) -> Aabb {
+        Aabb {
+            min: glm::min2(&min, &max),
+            max: glm::max2(&min, &max),
+        }
+    }
+
+    pub fn from_center_half_extents(center: glm::Vec3, half_extents: glm::Vec3) -> Aabb {
+        Aabb::new(center - half_extents, center + half_extents)
+    }
+
+    /// Create the smallest box which contains all the `points`.
+    /// Returns `None` if `points` is empty
+    pub fn from_points<'a, I: IntoIterator<Item = &'a glm::Vec3>>(points: I) -> Option<Aabb> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+
+        Some(points.fold(Aabb { min: first, max: first }, |aabb, point| {
+            Aabb {
+                min: glm::min2(&aabb.min, point),
+                max: glm::max2(&aabb.max, point),
+            }
+        }))
+    }
+
+    pub fn center(&self) -> glm::Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> glm::Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn size(&self) -> glm::Vec3 {
+        self.max - self.min
+    }
+
+    /// Get all 8 corners of the box
+    pub fn corners(&self) -> [glm::Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+
+        [
+            glm::vec3(min.x, min.y, min.z),
+            glm::vec3(max.x, min.y, min.z),
+            glm::vec3(min.x, max.y, min.z),
+            glm::vec3(max.x, max.y, min.z),
+            glm::vec3(min.x, min.y, max.z),
+            glm::vec3(max.x, min.y, max.z),
+            glm::vec3(min.x, max.y, max.z),
+            glm::vec3(max.x, max.y, max.z),
+        ]
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x
+            && point.y >= self.min.y && point.y <= self.max.y
+            && point.z >= self.min.z && point.z <= self.max.z
+    }
+
+    pub fn intersects_aabb(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x
+            && self.min.y <= other.max.y && self.max.y >= other.min.y
+            && self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        let closest = self.closest_point(&sphere.center);
+        glm::length2(&(closest - sphere.center)) <= sphere.radius * sphere.radius
+    }
+
+    /// Get the point inside the box, which is the closest to `point`
+    pub fn closest_point(&self, point: &glm::Vec3) -> glm::Vec3 {
+        glm::clamp_vec(point, &self.min, &self.max)
+    }
+
+    /// Get the smallest box containing both `self` and `other`
+    pub fn merge(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: glm::min2(&self.min, &other.min),
+            max: glm::max2(&self.max, &other.max),
+        }
+    }
+
+    /// Get the axis-aligned box, which bounds `self` transformed with `transform`
+    pub fn transformed(&self, transform: &Transform) -> Aabb {
+        let (matrix, _) = transform.to_matrices();
+        self.transformed_by_matrix(&matrix)
+    }
+
+    pub fn transformed_by_matrix(&self, matrix: &glm::Mat4) -> Aabb {
+        let corners = self.corners().map(|corner| {
+            let transformed = matrix * glm::vec4(corner.x, corner.y, corner.z, 1.0);
+            transformed.xyz()
+        });
+
+        Aabb::from_points(&corners).expect("Box always has corners")
+    }
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Aabb::from_center_half_extents(glm::Vec3::zeros(), glm::vec3(0.5, 0.5, 0.5))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: glm::Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: glm::Vec3, radius: f32) -> BoundingSphere {
+        BoundingSphere { center, radius: radius.abs() }
+    }
+
+    /// Create a sphere, which contains all the `points`. The sphere is centered
+    /// in the center of the points' bounding box, so it's not always the smallest one.
+    /// Returns `None` if `points` is empty
+    pub fn from_points<'a, I>(points: I) -> Option<BoundingSphere>
+    where
+        I: IntoIterator<Item = &'a glm::Vec3>,
+        I::IntoIter: Clone,
+    {
+        let points = points.into_iter();
+        let center = Aabb::from_points(points.clone())?.center();
+        let radius = points
+            .map(|point| glm::distance2(&center, point))
+            .fold(0.0, f32::max)
+            .sqrt();
+
+        Some(BoundingSphere { center, radius })
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool {
+        glm::distance2(&self.center, point) <= self.radius * self.radius
+    }
+
+    pub fn intersects_sphere(&self, other: &BoundingSphere) -> bool {
+        let radii = self.radius + other.radius;
+        glm::distance2(&self.center, &other.center) <= radii * radii
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        aabb.intersects_sphere(self)
+    }
+
+    /// Get the sphere which bounds `self` transformed with `transform`
+    pub fn transformed(&self, transform: &Transform) -> BoundingSphere {
+        let scale = transform.scale_f32();
+        let center = transform.translation_f32() + glm::quat_rotate_vec3(&transform.rotation_f32(), &(self.center * scale));
+
+        BoundingSphere {
+            center,
+            radius: self.radius * scale.abs(),
+        }
+    }
+}
+
+impl Default for BoundingSphere {
+    fn default() -> Self {
+        BoundingSphere::new(glm::Vec3::zeros(), 1.0)
+    }
+}
+
+impl From<Aabb> for BoundingSphere {
+    fn from(aabb: Aabb) -> Self {
+        BoundingSphere {
+            center: aabb.center(),
+            radius: glm::length(&aabb.half_extents()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec_eq(a: &glm::Vec3, b: &glm::Vec3) {
+        assert!(glm::distance(a, b) < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn aabb_construction() {
+        let aabb = Aabb::new(glm::vec3(1.0, -1.0, 2.0), glm::vec3(-1.0, 1.0, 0.0));
+        assert_eq!(aabb.min, glm::vec3(-1.0, -1.0, 0.0));
+        assert_eq!(aabb.max, glm::vec3(1.0, 1.0, 2.0));
+        assert_eq!(aabb.center(), glm::vec3(0.0, 0.0, 1.0));
+        assert_eq!(aabb.half_extents(), glm::vec3(1.0, 1.0, 1.0));
+
+        let points = [glm::vec3(0.0, 2.0, 0.0), glm::vec3(-3.0, 0.0, 1.0), glm::vec3(1.0, -1.0, 5.0)];
+        let aabb = Aabb::from_points(&points).unwrap();
+        assert_eq!(aabb, Aabb::new(glm::vec3(-3.0, -1.0, 0.0), glm::vec3(1.0, 2.0, 5.0)));
+        assert!(Aabb::from_points(&[]).is_none());
+    }
+
+    #[test]
+    fn aabb_contains_and_intersects() {
+        let aabb = Aabb::default();
+
+        assert!(aabb.contains_point(&glm::vec3(0.5, 0.0, -0.5)));
+        assert!(!aabb.contains_point(&glm::vec3(0.6, 0.0, 0.0)));
+
+        assert!(aabb.intersects_aabb(&Aabb::from_center_half_extents(glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.5, 0.5, 0.5))));
+        assert!(!aabb.intersects_aabb(&Aabb::from_center_half_extents(glm::vec3(1.1, 0.0, 0.0), glm::vec3(0.5, 0.5, 0.5))));
+
+        // The corner is sqrt(3) * 0.5 away from the origin
+        assert!(aabb.intersects_sphere(&BoundingSphere::new(glm::vec3(1.0, 1.0, 1.0), 0.9)));
+        assert!(!aabb.intersects_sphere(&BoundingSphere::new(glm::vec3(1.0, 1.0, 1.0), 0.8)));
+    }
+
+    #[test]
+    fn aabb_merge() {
+        let a = Aabb::new(glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 1.0));
+        let b = Aabb::new(glm::vec3(-1.0, 0.5, 2.0), glm::vec3(0.5, 3.0, 4.0));
+
+        assert_eq!(a.merge(&b), Aabb::new(glm::vec3(-1.0, 0.0, 0.0), glm::vec3(1.0, 3.0, 4.0)));
+        assert_eq!(a.merge(&b), b.merge(&a));
+    }
+
+    #[test]
+    fn aabb_transform() {
+        let aabb = Aabb::new(glm::vec3(0.0, 0.0, 0.0), glm::vec3(2.0, 1.0, 1.0));
+        let transform = Transform::new(
+            glm::vec3(10.0, 0.0, 0.0),
+            glm::quat_angle_axis(std::f32::consts::FRAC_PI_2 as _, &glm::vec3(0.0, 0.0, 1.0)),
+            2.0,
+        );
+
+        // Rotated by 90 degrees around Z, the box spans 2 units along Y and 4 along X after scaling
+        let transformed = aabb.transformed(&transform);
+        assert_vec_eq(&transformed.min, &glm::vec3(8.0, 0.0, 0.0));
+        assert_vec_eq(&transformed.max, &glm::vec3(10.0, 4.0, 2.0));
+    }
+
+    #[test]
+    fn sphere_construction() {
+        let sphere = BoundingSphere::new(glm::vec3(1.0, 0.0, 0.0), -2.0);
+        assert_eq!(sphere.radius, 2.0);
+
+        let points = [glm::vec3(-1.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 0.5, 0.0)];
+        let sphere = BoundingSphere::from_points(&points).unwrap();
+        assert_vec_eq(&sphere.center, &glm::vec3(0.0, 0.25, 0.0));
+        assert!(points.iter().all(|point| sphere.contains_point(point)));
+        assert!(BoundingSphere::from_points(&[]).is_none());
+
+        let sphere = BoundingSphere::from(Aabb::default());
+        assert!(Aabb::default().corners().iter().all(|corner| glm::distance(&sphere.center, corner) <= sphere.radius + 1e-6));
+    }
+
+    #[test]
+    fn sphere_contains_and_intersects() {
+        let sphere = BoundingSphere::default();
+
+        assert!(sphere.contains_point(&glm::vec3(0.0, 1.0, 0.0)));
+        assert!(!sphere.contains_point(&glm::vec3(0.8, 0.8, 0.0)));
+
+        assert!(sphere.intersects_sphere(&BoundingSphere::new(glm::vec3(2.0, 0.0, 0.0), 1.0)));
+        assert!(!sphere.intersects_sphere(&BoundingSphere::new(glm::vec3(2.1, 0.0, 0.0), 1.0)));
+        assert!(sphere.intersects_aabb(&Aabb::from_center_half_extents(glm::vec3(1.4, 0.0, 0.0), glm::vec3(0.5, 0.5, 0.5))));
+    }
+
+    #[test]
+    fn sphere_transform() {
+        let sphere = BoundingSphere::new(glm::vec3(1.0, 0.0, 0.0), 1.0);
+        let transform = Transform::new(
+            glm::vec3(0.0, 0.0, 5.0),
+            glm::quat_angle_axis(std::f32::consts::FRAC_PI_2 as _, &glm::vec3(0.0, 0.0, 1.0)),
+            3.0,
+        );
+
+        let transformed = sphere.transformed(&transform);
+        assert_vec_eq(&transformed.center, &glm::vec3(0.0, 3.0, 5.0));
+        assert!((transformed.radius - 3.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,478 @@
+use serde::{Serialize, Deserialize};
+use nalgebra_glm as glm;
+
+/// Parametric curve, which can be sampled with `t` in range `0.0..=1.0`
+pub trait Curve {
+    fn sample(&self, t: f32) -> glm::Vec3;
+
+    /// Get the (non-normalized) tangent of the curve at `t`. By default
+    /// it's computed with central differences
+    fn tangent(&self, t: f32) -> glm::Vec3 {
+        const STEP: f32 = 1e-3;
+
+        let t0 = (t - STEP).max(0.0);
+        let t1 = (t + STEP).min(1.0);
+
+        (self.sample(t1) - self.sample(t0)) / (t1 - t0)
+    }
+}
+
+/// Piecewise cubic Bézier curve. Control points are stored as
+/// `[p0, c0, c1, p1, c2, c3, p2, ...]`, so every segment shares
+/// its end point with the next one
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BezierCurve {
+    pub points: Vec<glm::Vec3>,
+}
+
+impl BezierCurve {
+    pub fn new(points: Vec<glm::Vec3>) -> BezierCurve {
+        if points.len() < 4 || !(points.len() - 1).is_multiple_of(3) {
+            log::error!("Bézier curve must consist of `3n + 1` points, got {}", points.len());
+        }
+
+        BezierCurve { points }
+    }
+
+    pub fn segments(&self) -> usize {
+        self.points.len().saturating_sub(1) / 3
+    }
+}
+
+impl Curve for BezierCurve {
+    fn sample(&self, t: f32) -> glm::Vec3 {
+        let segments = self.segments();
+        if segments == 0 {
+            return self.points.first().copied().unwrap_or_else(glm::Vec3::zeros);
+        }
+
+        let (segment, t) = split_segment(t, segments);
+        let p = &self.points[segment * 3..segment * 3 + 4];
+        let u = 1.0 - t;
+
+        p[0] * (u * u * u)
+            + p[1] * (3.0 * u * u * t)
+            + p[2] * (3.0 * u * t * t)
+            + p[3] * (t * t * t)
+    }
+}
+
+/// Uniform Catmull-Rom spline, which passes through all its points
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CatmullRomCurve {
+    pub points: Vec<glm::Vec3>,
+    /// Whether the last point is connected to the first one
+    pub looped: bool,
+}
+
+impl CatmullRomCurve {
+    pub fn new(points: Vec<glm::Vec3>, looped: bool) -> CatmullRomCurve {
+        if points.len() < 2 {
+            log::error!("Catmull-Rom spline must consist of at least 2 points, got {}", points.len());
+        }
+
+        CatmullRomCurve { points, looped }
+    }
+
+    pub fn segments(&self) -> usize {
+        if self.looped {
+            self.points.len()
+        } else {
+            self.points.len().saturating_sub(1)
+        }
+    }
+
+    fn point(&self, index: isize) -> glm::Vec3 {
+        let len = self.points.len() as isize;
+        let index = if self.looped {
+            index.rem_euclid(len)
+        } else {
+            index.clamp(0, len - 1)
+        };
+
+        self.points[index as usize]
+    }
+}
+
+impl Curve for CatmullRomCurve {
+    fn sample(&self, t: f32) -> glm::Vec3 {
+        let segments = self.segments();
+        if segments == 0 {
+            return self.points.first().copied().unwrap_or_else(glm::Vec3::zeros);
+        }
+
+        let (segment, t) = split_segment(t, segments);
+        let i = segment as isize;
+        let (p0, p1, p2, p3) = (self.point(i - 1), self.point(i), self.point(i + 1), self.point(i + 2));
+
+        let t2 = t * t;
+        let t3 = t2 * t;
+
+        (p1 * 2.0
+            + (p2 - p0) * t
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5
+    }
+}
+
+/// Point of [`HermiteCurve`] with the tangent of the curve at it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct HermitePoint {
+    pub position: glm::Vec3,
+    pub tangent: glm::Vec3,
+}
+
+/// Cubic Hermite spline, defined with points and tangents at them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HermiteCurve {
+    pub points: Vec<HermitePoint>,
+}
+
+impl HermiteCurve {
+    pub fn new(points: Vec<HermitePoint>) -> HermiteCurve {
+        if points.len() < 2 {
+            log::error!("Hermite spline must consist of at least 2 points, got {}", points.len());
+        }
+
+        HermiteCurve { points }
+    }
+
+    pub fn segments(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+}
+
+impl Curve for HermiteCurve {
+    fn sample(&self, t: f32) -> glm::Vec3 {
+        let segments = self.segments();
+        if segments == 0 {
+            return self.points.first().map(|p| p.position).unwrap_or_else(glm::Vec3::zeros);
+        }
+
+        let (segment, t) = split_segment(t, segments);
+        let (a, b) = (&self.points[segment], &self.points[segment + 1]);
+
+        let t2 = t * t;
+        let t3 = t2 * t;
+
+        a.position * (2.0 * t3 - 3.0 * t2 + 1.0)
+            + a.tangent * (t3 - 2.0 * t2 + t)
+            + b.position * (-2.0 * t3 + 3.0 * t2)
+            + b.tangent * (t3 - t2)
+    }
+}
+
+/// Any of the engine's curves, e.g. to be stored inside a component
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Spline {
+    Bezier(BezierCurve),
+    CatmullRom(CatmullRomCurve),
+    Hermite(HermiteCurve),
+}
+
+impl Curve for Spline {
+    fn sample(&self, t: f32) -> glm::Vec3 {
+        match self {
+            Spline::Bezier(curve) => curve.sample(t),
+            Spline::CatmullRom(curve) => curve.sample(t),
+            Spline::Hermite(curve) => curve.sample(t),
+        }
+    }
+}
+
+impl From<BezierCurve> for Spline {
+    fn from(curve: BezierCurve) -> Self {
+        Spline::Bezier(curve)
+    }
+}
+
+impl From<CatmullRomCurve> for Spline {
+    fn from(curve: CatmullRomCurve) -> Self {
+        Spline::CatmullRom(curve)
+    }
+}
+
+impl From<HermiteCurve> for Spline {
+    fn from(curve: HermiteCurve) -> Self {
+        Spline::Hermite(curve)
+    }
+}
+
+/// Table for arc-length reparameterization of curves. It allows to sample
+/// a curve by the distance travelled along it, which gives constant speed movement
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArcLengthTable {
+    /// Accumulated lengths at uniformly distributed `t`
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    pub fn new<C: Curve + ?Sized>(curve: &C, samples: usize) -> ArcLengthTable {
+        let samples = samples.max(1);
+        let mut lengths = Vec::with_capacity(samples + 1);
+        let mut previous = curve.sample(0.0);
+        let mut length = 0.0;
+
+        lengths.push(0.0);
+
+        for i in 1..=samples {
+            let point = curve.sample(i as f32 / samples as f32);
+            length += glm::distance(&previous, &point);
+            lengths.push(length);
+            previous = point;
+        }
+
+        ArcLengthTable { lengths }
+    }
+
+    /// Total length of the curve
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    /// Convert the distance along the curve into curve parameter `t`
+    pub fn parameter(&self, distance: f32) -> f32 {
+        let length = self.length();
+        if length <= 0.0 {
+            return 0.0;
+        }
+
+        let distance = distance.clamp(0.0, length);
+        let index = self.lengths.partition_point(|&l| l < distance).max(1);
+        let (l0, l1) = (self.lengths[index - 1], self.lengths[index]);
+        let segment = if l1 > l0 { (distance - l0) / (l1 - l0) } else { 0.0 };
+
+        ((index - 1) as f32 + segment) / (self.lengths.len() - 1) as f32
+    }
+
+    /// Sample the curve at the `distance` along it
+    pub fn sample<C: Curve + ?Sized>(&self, curve: &C, distance: f32) -> glm::Vec3 {
+        curve.sample(self.parameter(distance))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathMode {
+    /// Stop at the end of the path
+    #[default]
+    Once,
+    /// Jump back to the start of the path after reaching its end
+    Loop,
+    /// Move back and forth along the path
+    PingPong,
+}
+
+/// Component, which moves entity's [`Transform`] along the spline with constant speed.
+/// It's driven by `flatbox_systems::path::follow_paths` system
+///
+/// [`Transform`]: crate::math::transform::Transform
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PathFollower {
+    pub spline: Spline,
+    /// Speed in units per second
+    pub speed: f32,
+    pub mode: PathMode,
+    /// Whether entity must be rotated to face the movement direction
+    pub align_to_path: bool,
+    /// Distance travelled along the path
+    pub distance: f32,
+    table: ArcLengthTable,
+    reversed: bool,
+}
+
+impl PathFollower {
+    pub fn new<S: Into<Spline>>(spline: S, speed: f32) -> PathFollower {
+        let spline = spline.into();
+        let table = ArcLengthTable::new(&spline, 256);
+
+        PathFollower {
+            spline,
+            speed,
+            mode: PathMode::default(),
+            align_to_path: false,
+            distance: 0.0,
+            table,
+            reversed: false,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: PathMode) -> PathFollower {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_align_to_path(mut self, align_to_path: bool) -> PathFollower {
+        self.align_to_path = align_to_path;
+        self
+    }
+
+    pub fn length(&self) -> f32 {
+        self.table.length()
+    }
+
+    /// Whether non-looping path has been passed completely
+    pub fn finished(&self) -> bool {
+        self.mode == PathMode::Once && self.distance >= self.length()
+    }
+
+    /// Move along the path by `delta` seconds
+    pub fn advance(&mut self, delta: f32) {
+        let length = self.length();
+        let step = self.speed * delta;
+
+        match self.mode {
+            PathMode::Once => {
+                self.distance = (self.distance + step).min(length);
+            },
+            PathMode::Loop => {
+                self.distance = if length > 0.0 { (self.distance + step).rem_euclid(length) } else { 0.0 };
+            },
+            PathMode::PingPong => {
+                self.distance += if self.reversed { -step } else { step };
+
+                if self.distance >= length {
+                    self.distance = length - (self.distance - length);
+                    self.reversed = true;
+                } else if self.distance <= 0.0 {
+                    self.distance = -self.distance;
+                    self.reversed = false;
+                }
+
+                self.distance = self.distance.clamp(0.0, length);
+            },
+        }
+    }
+
+    /// Current position on the path
+    pub fn position(&self) -> glm::Vec3 {
+        self.table.sample(&self.spline, self.distance)
+    }
+
+    /// Current movement direction (normalized)
+    pub fn direction(&self) -> glm::Vec3 {
+        let tangent = self.spline.tangent(self.table.parameter(self.distance));
+        let tangent = if self.reversed { -tangent } else { tangent };
+
+        if glm::length2(&tangent) > f32::EPSILON {
+            glm::normalize(&tangent)
+        } else {
+            glm::Vec3::z()
+        }
+    }
+}
+
+/// Split global curve parameter into segment index and local parameter
+fn split_segment(t: f32, segments: usize) -> (usize, f32) {
+    let scaled = t.clamp(0.0, 1.0) * segments as f32;
+    let segment = (scaled.floor() as usize).min(segments - 1);
+
+    (segment, scaled - segment as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec_eq(a: &glm::Vec3, b: &glm::Vec3) {
+        assert!(glm::distance(a, b) < 1e-4, "{a:?} != {b:?}");
+    }
+
+    fn line() -> BezierCurve {
+        BezierCurve::new(vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+            glm::vec3(3.0, 0.0, 0.0),
+        ])
+    }
+
+    #[test]
+    fn bezier() {
+        let curve = BezierCurve::new(vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(1.0, -1.0, 0.0),
+            glm::vec3(2.0, -1.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+        ]);
+
+        assert_eq!(curve.segments(), 2);
+        assert_vec_eq(&curve.sample(0.0), &glm::vec3(0.0, 0.0, 0.0));
+        assert_vec_eq(&curve.sample(0.25), &glm::vec3(0.5, 0.75, 0.0));
+        assert_vec_eq(&curve.sample(0.5), &glm::vec3(1.0, 0.0, 0.0));
+        assert_vec_eq(&curve.sample(1.0), &glm::vec3(2.0, 0.0, 0.0));
+
+        // Out of range parameters are clamped
+        assert_vec_eq(&curve.sample(2.0), &glm::vec3(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn catmull_rom() {
+        let points = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+        ];
+
+        let curve = CatmullRomCurve::new(points.clone(), false);
+        assert_eq!(curve.segments(), 2);
+        assert_vec_eq(&curve.sample(0.0), &points[0]);
+        assert_vec_eq(&curve.sample(0.5), &points[1]);
+        assert_vec_eq(&curve.sample(1.0), &points[2]);
+
+        let curve = CatmullRomCurve::new(points.clone(), true);
+        assert_eq!(curve.segments(), 3);
+        assert_vec_eq(&curve.sample(1.0 / 3.0), &points[1]);
+        assert_vec_eq(&curve.sample(1.0), &points[0]);
+    }
+
+    #[test]
+    fn hermite() {
+        let curve = HermiteCurve::new(vec![
+            HermitePoint { position: glm::vec3(0.0, 0.0, 0.0), tangent: glm::vec3(1.0, 0.0, 0.0) },
+            HermitePoint { position: glm::vec3(1.0, 0.0, 0.0), tangent: glm::vec3(1.0, 0.0, 0.0) },
+        ]);
+
+        assert_eq!(curve.segments(), 1);
+        assert_vec_eq(&curve.sample(0.5), &glm::vec3(0.5, 0.0, 0.0));
+        assert_vec_eq(&curve.tangent(0.5), &glm::vec3(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn degenerate() {
+        assert_vec_eq(&BezierCurve { points: vec![] }.sample(0.5), &glm::Vec3::zeros());
+        assert_vec_eq(&CatmullRomCurve { points: vec![glm::vec3(1.0, 2.0, 3.0)], looped: false }.sample(0.5), &glm::vec3(1.0, 2.0, 3.0));
+        assert_eq!(ArcLengthTable::new(&BezierCurve { points: vec![] }, 16).parameter(1.0), 0.0);
+    }
+
+    #[test]
+    fn arc_length() {
+        let curve = line();
+        let table = ArcLengthTable::new(&curve, 64);
+
+        assert!((table.length() - 3.0).abs() < 1e-4);
+        assert!((table.parameter(1.5) - 0.5).abs() < 1e-4);
+        assert_vec_eq(&table.sample(&curve, 1.0), &glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(table.parameter(10.0), 1.0);
+    }
+
+    #[test]
+    fn path_follower_modes() {
+        let mut follower = PathFollower::new(line(), 1.0);
+        follower.advance(4.0);
+        assert!(follower.finished());
+        assert_vec_eq(&follower.position(), &glm::vec3(3.0, 0.0, 0.0));
+
+        let mut follower = PathFollower::new(line(), 1.0).with_mode(PathMode::Loop);
+        follower.advance(4.0);
+        assert!(!follower.finished());
+        assert!((follower.distance - 1.0).abs() < 1e-3);
+
+        let mut follower = PathFollower::new(line(), 1.0).with_mode(PathMode::PingPong);
+        follower.advance(4.0);
+        assert!((follower.distance - 2.0).abs() < 1e-3);
+        assert_vec_eq(&follower.direction(), &glm::vec3(-1.0, 0.0, 0.0));
+    }
+}
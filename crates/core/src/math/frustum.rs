@@ -0,0 +1,181 @@
+use serde::{Serialize, Deserialize};
+use nalgebra_glm as glm;
+
+use super::bounds::{Aabb, BoundingSphere};
+
+/// Plane in the form `dot(normal, point) + distance = 0`. Normal is always normalized
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: glm::Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn new(normal: glm::Vec3, distance: f32) -> Plane {
+        let length = glm::length(&normal);
+
+        Plane {
+            normal: normal / length,
+            distance: distance / length,
+        }
+    }
+
+    pub fn from_point_normal(point: &glm::Vec3, normal: &glm::Vec3) -> Plane {
+        let normal = glm::normalize(normal);
+
+        Plane {
+            normal,
+            distance: -glm::dot(&normal, point),
+        }
+    }
+
+    /// Create plane from `(a, b, c, d)` coefficients of equation `ax + by + cz + d = 0`
+    pub fn from_vec4(coefficients: &glm::Vec4) -> Plane {
+        Plane::new(coefficients.xyz(), coefficients.w)
+    }
+
+    /// Signed distance from the plane to the point. Positive values are
+    /// located in front of the plane (in the direction of the normal)
+    pub fn signed_distance(&self, point: &glm::Vec3) -> f32 {
+        glm::dot(&self.normal, point) + self.distance
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.signed_distance(&sphere.center).abs() <= sphere.radius
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let radius = glm::dot(&aabb.half_extents(), &glm::abs(&self.normal));
+        self.signed_distance(&aabb.center()).abs() <= radius
+    }
+}
+
+/// Camera view volume, consisting of 6 inward-facing planes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// Planes in order: left, right, bottom, top, near, far
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extract frustum planes from the combined `projection * view` matrix
+    /// (Gribb-Hartmann method)
+    pub fn from_view_projection(matrix: &glm::Mat4) -> Frustum {
+        let row = |i: usize| -> glm::Vec4 { matrix.row(i).transpose() };
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        Frustum {
+            planes: [
+                Plane::from_vec4(&(w + x)),
+                Plane::from_vec4(&(w - x)),
+                Plane::from_vec4(&(w + y)),
+                Plane::from_vec4(&(w - y)),
+                Plane::from_vec4(&(w + z)),
+                Plane::from_vec4(&(w - z)),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    /// Check whether the sphere is at least partially inside the frustum
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(&sphere.center) >= -sphere.radius)
+    }
+
+    /// Check whether the box is at least partially inside the frustum. The test is
+    /// conservative, so some boxes near frustum corners may be reported as visible
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let positive_vertex = glm::vec3(
+                if plane.normal.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if plane.normal.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if plane.normal.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+            );
+
+            plane.signed_distance(&positive_vertex) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    /// Camera at the origin looking down `-Z`
+    fn view() -> glm::Mat4 {
+        glm::look_at(&glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 0.0, -1.0), &glm::vec3(0.0, 1.0, 0.0))
+    }
+
+    fn perspective() -> Frustum {
+        Frustum::from_view_projection(&(glm::perspective(1.0, FRAC_PI_2, 0.1, 100.0) * view()))
+    }
+
+    fn orthographic() -> Frustum {
+        Frustum::from_view_projection(&(glm::ortho(-10.0, 10.0, -10.0, 10.0, 0.1, 100.0) * view()))
+    }
+
+    fn cube(center: glm::Vec3) -> Aabb {
+        Aabb::from_center_half_extents(center, glm::vec3(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn plane_distance() {
+        let plane = Plane::from_point_normal(&glm::vec3(0.0, 2.0, 0.0), &glm::vec3(0.0, 3.0, 0.0));
+
+        assert_eq!(plane.signed_distance(&glm::vec3(5.0, 4.0, 0.0)), 2.0);
+        assert_eq!(plane.signed_distance(&glm::vec3(0.0, 0.0, 1.0)), -2.0);
+        assert_eq!(Plane::new(glm::vec3(0.0, 3.0, 0.0), -6.0), plane);
+    }
+
+    #[test]
+    fn perspective_aabb() {
+        let frustum = perspective();
+
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, -10.0))));
+        assert!(frustum.contains_point(&glm::vec3(0.0, 0.0, -10.0)));
+
+        // Behind the camera, beyond the far plane and off to the side
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, 10.0))));
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, -110.0))));
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(30.0, 0.0, -10.0))));
+
+        // Straddling the left plane (x = z at 90 degrees FOV) and the far plane
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(-10.0, 0.0, -10.0))));
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, -100.5))));
+        assert!(!frustum.contains_point(&glm::vec3(0.0, 0.0, -100.5)));
+    }
+
+    #[test]
+    fn orthographic_aabb() {
+        let frustum = orthographic();
+
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(5.0, -5.0, -50.0))));
+
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, 5.0))));
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(12.0, 0.0, -50.0))));
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(0.0, -12.0, -50.0))));
+
+        // Straddling the right, top and near planes
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(10.5, 0.0, -50.0))));
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(0.0, 10.5, -50.0))));
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, 0.5))));
+    }
+
+    #[test]
+    fn spheres() {
+        for frustum in [perspective(), orthographic()] {
+            assert!(frustum.intersects_sphere(&BoundingSphere::new(glm::vec3(0.0, 0.0, -10.0), 1.0)));
+            assert!(frustum.intersects_sphere(&BoundingSphere::new(glm::vec3(0.0, 0.0, 0.5), 1.0)));
+            assert!(!frustum.intersects_sphere(&BoundingSphere::new(glm::vec3(0.0, 0.0, 5.0), 1.0)));
+        }
+    }
+}
@@ -1,3 +1,8 @@
+pub mod bounds;
+pub mod curve;
+pub mod frustum;
+pub mod ray;
+pub mod shape;
 pub mod transform;
 
 pub mod glm {
@@ -0,0 +1,120 @@
+use serde::{Serialize, Deserialize};
+use nalgebra_glm as glm;
+
+use super::bounds::{Aabb, BoundingSphere};
+
+/// Half-line starting at `origin` and going along `direction`. Direction is always normalized,
+/// so intersection distances, returned by `intersect_*` methods, are world-space distances
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: glm::Vec3,
+    pub direction: glm::Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: glm::Vec3, direction: glm::Vec3) -> Ray {
+        Ray {
+            origin,
+            direction: glm::normalize(&direction),
+        }
+    }
+
+    /// Create ray, which starts at `from` and goes through `to`
+    pub fn from_points(from: &glm::Vec3, to: &glm::Vec3) -> Ray {
+        Ray::new(*from, to - from)
+    }
+
+    /// Get the point located at `distance` along the ray
+    pub fn at(&self, distance: f32) -> glm::Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Get the distance to the nearest intersection with the box (slab method).
+    /// Returns `Some(0.0)` if the origin is inside the box
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::INFINITY;
+
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let direction = self.direction[axis];
+
+            if direction.abs() < f32::EPSILON {
+                if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                    return None;
+                }
+            } else {
+                let inv = 1.0 / direction;
+                let mut t1 = (aabb.min[axis] - origin) * inv;
+                let mut t2 = (aabb.max[axis] - origin) * inv;
+
+                if t1 > t2 {
+                    std::mem::swap(&mut t1, &mut t2);
+                }
+
+                t_min = t_min.max(t1);
+                t_max = t_max.min(t2);
+
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+
+        Some(t_min)
+    }
+
+    /// Get the distance to the nearest intersection with the sphere.
+    /// Returns `Some(0.0)` if the origin is inside the sphere
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let to_center = sphere.center - self.origin;
+        let projection = glm::dot(&to_center, &self.direction);
+        let distance2 = glm::length2(&to_center) - projection * projection;
+        let radius2 = sphere.radius * sphere.radius;
+
+        if distance2 > radius2 {
+            return None;
+        }
+
+        let half_chord = (radius2 - distance2).sqrt();
+        let (t0, t1) = (projection - half_chord, projection + half_chord);
+
+        if t1 < 0.0 {
+            None
+        } else {
+            Some(t0.max(0.0))
+        }
+    }
+
+    /// Get the distance to the intersection with the triangle `(a, b, c)` (Möller-Trumbore
+    /// algorithm). Both sides of the triangle are taken into account
+    pub fn intersect_triangle(&self, a: &glm::Vec3, b: &glm::Vec3, c: &glm::Vec3) -> Option<f32> {
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = glm::cross(&self.direction, &edge2);
+        let determinant = glm::dot(&edge1, &p);
+
+        if determinant.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inv_determinant = 1.0 / determinant;
+        let s = self.origin - a;
+        let u = glm::dot(&s, &p) * inv_determinant;
+
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = glm::cross(&s, &edge1);
+        let v = glm::dot(&self.direction, &q) * inv_determinant;
+
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = glm::dot(&edge2, &q) * inv_determinant;
+
+        if t >= 0.0 { Some(t) } else { None }
+    }
+}
@@ -0,0 +1,291 @@
+use serde::{Serialize, Deserialize};
+use nalgebra_glm as glm;
+
+/// Axis-aligned 2D rectangle, described by its minimal and maximal corners.
+/// Used for UI hit testing, sprite culling, texture atlas regions etc.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub min: glm::Vec2,
+    pub max: glm::Vec2,
+}
+
+impl Rect {
+    pub fn new(min: glm::Vec2, max: glm::Vec2) -> Rect {
+        Rect {
+            min: glm::min2(&min, &max),
+            max: glm::max2(&min, &max),
+        }
+    }
+
+    /// Create rectangle from its minimal corner and size
+    pub fn from_position_size(position: glm::Vec2, size: glm::Vec2) -> Rect {
+        Rect::new(position, position + size)
+    }
+
+    pub fn from_center_size(center: glm::Vec2, size: glm::Vec2) -> Rect {
+        Rect::new(center - size * 0.5, center + size * 0.5)
+    }
+
+    /// Create the smallest rectangle which contains all the `points`.
+    /// Returns `None` if `points` is empty
+    pub fn from_points<'a, I: IntoIterator<Item = &'a glm::Vec2>>(points: I) -> Option<Rect> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+
+        Some(points.fold(Rect { min: first, max: first }, |rect, point| {
+            Rect {
+                min: glm::min2(&rect.min, point),
+                max: glm::max2(&rect.max, point),
+            }
+        }))
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(&self) -> glm::Vec2 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> glm::Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width() * self.height()
+    }
+
+    /// Whether the rectangle has zero area
+    pub fn is_empty(&self) -> bool {
+        self.width() <= 0.0 || self.height() <= 0.0
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec2) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x
+            && point.y >= self.min.y && point.y <= self.max.y
+    }
+
+    /// Whether `other` lies completely inside the rectangle
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        self.contains_point(&other.min) && self.contains_point(&other.max)
+    }
+
+    pub fn intersects_rect(&self, other: &Rect) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x
+            && self.min.y <= other.max.y && self.max.y >= other.min.y
+    }
+
+    pub fn intersects_circle(&self, circle: &Circle) -> bool {
+        let closest = self.closest_point(&circle.center);
+        glm::distance2(&closest, &circle.center) <= circle.radius * circle.radius
+    }
+
+    /// Get the overlapping part of two rectangles. Returns `None` if they don't intersect
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects_rect(other) {
+            return None;
+        }
+
+        Some(Rect {
+            min: glm::max2(&self.min, &other.min),
+            max: glm::min2(&self.max, &other.max),
+        })
+    }
+
+    /// Get the smallest rectangle containing both `self` and `other`
+    pub fn merge(&self, other: &Rect) -> Rect {
+        Rect {
+            min: glm::min2(&self.min, &other.min),
+            max: glm::max2(&self.max, &other.max),
+        }
+    }
+
+    /// Get the point inside the rectangle, which is the closest to `point`
+    pub fn closest_point(&self, point: &glm::Vec2) -> glm::Vec2 {
+        glm::clamp_vec(point, &self.min, &self.max)
+    }
+
+    /// Get the rectangle grown by `margin` on every side (or shrunk, if it's negative).
+    /// Sides, which are shrunk more than by half of the size, collapse to the center
+    pub fn expanded(&self, margin: f32) -> Rect {
+        let margin = glm::vec2(margin, margin);
+        let center = self.center();
+
+        Rect {
+            min: glm::min2(&(self.min - margin), &center),
+            max: glm::max2(&(self.max + margin), &center),
+        }
+    }
+
+    pub fn translated(&self, offset: &glm::Vec2) -> Rect {
+        Rect {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+}
+
+impl Default for Rect {
+    fn default() -> Self {
+        Rect::new(glm::Vec2::zeros(), glm::vec2(1.0, 1.0))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: glm::Vec2,
+    pub radius: f32,
+}
+
+impl Circle {
+    pub fn new(center: glm::Vec2, radius: f32) -> Circle {
+        Circle { center, radius: radius.abs() }
+    }
+
+    pub fn area(&self) -> f32 {
+        std::f32::consts::PI * self.radius * self.radius
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec2) -> bool {
+        glm::distance2(&self.center, point) <= self.radius * self.radius
+    }
+
+    pub fn intersects_circle(&self, other: &Circle) -> bool {
+        let radii = self.radius + other.radius;
+        glm::distance2(&self.center, &other.center) <= radii * radii
+    }
+
+    pub fn intersects_rect(&self, rect: &Rect) -> bool {
+        rect.intersects_circle(self)
+    }
+
+    /// Get the smallest rectangle containing the circle
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_center_size(self.center, glm::vec2(self.radius, self.radius) * 2.0)
+    }
+}
+
+impl Default for Circle {
+    fn default() -> Self {
+        Circle::new(glm::Vec2::zeros(), 1.0)
+    }
+}
+
+/// Check whether segments `a0-a1` and `b0-b1` intersect and get the intersection point
+pub fn segments_intersection(
+    a0: &glm::Vec2,
+    a1: &glm::Vec2,
+    b0: &glm::Vec2,
+    b1: &glm::Vec2,
+) -> Option<glm::Vec2> {
+    let a = a1 - a0;
+    let b = b1 - b0;
+    let denominator = a.x * b.y - a.y * b.x;
+
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let offset = b0 - a0;
+    let t = (offset.x * b.y - offset.y * b.x) / denominator;
+    let u = (offset.x * a.y - offset.y * a.x) / denominator;
+
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some(a0 + a * t)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_construction() {
+        let rect = Rect::new(glm::vec2(2.0, 0.0), glm::vec2(0.0, 1.0));
+        assert_eq!(rect, Rect::from_position_size(glm::vec2(0.0, 0.0), glm::vec2(2.0, 1.0)));
+        assert_eq!(rect, Rect::from_center_size(glm::vec2(1.0, 0.5), glm::vec2(2.0, 1.0)));
+        assert_eq!((rect.width(), rect.height(), rect.area()), (2.0, 1.0, 2.0));
+
+        let points = [glm::vec2(1.0, -1.0), glm::vec2(-2.0, 3.0), glm::vec2(0.0, 0.0)];
+        assert_eq!(Rect::from_points(&points), Some(Rect::new(glm::vec2(-2.0, -1.0), glm::vec2(1.0, 3.0))));
+        assert_eq!(Rect::from_points(&[]), None);
+    }
+
+    #[test]
+    fn rect_contains_and_intersects() {
+        let rect = Rect::from_position_size(glm::vec2(0.0, 0.0), glm::vec2(2.0, 2.0));
+        let other = Rect::from_position_size(glm::vec2(1.0, 1.0), glm::vec2(2.0, 2.0));
+
+        assert!(rect.contains_point(&glm::vec2(2.0, 0.0)));
+        assert!(!rect.contains_point(&glm::vec2(2.1, 0.0)));
+        assert!(rect.contains_rect(&Rect::default()));
+        assert!(!rect.contains_rect(&other));
+
+        assert_eq!(rect.intersection(&other), Some(Rect::new(glm::vec2(1.0, 1.0), glm::vec2(2.0, 2.0))));
+        assert_eq!(rect.intersection(&other.translated(&glm::vec2(5.0, 0.0))), None);
+        assert_eq!(rect.merge(&other), Rect::new(glm::vec2(0.0, 0.0), glm::vec2(3.0, 3.0)));
+
+        assert!(rect.intersects_circle(&Circle::new(glm::vec2(3.0, 1.0), 1.0)));
+        assert!(!rect.intersects_circle(&Circle::new(glm::vec2(3.0, 3.0), 1.0)));
+    }
+
+    #[test]
+    fn rect_expanded() {
+        let rect = Rect::from_position_size(glm::vec2(0.0, 0.0), glm::vec2(4.0, 2.0));
+
+        assert_eq!(rect.expanded(1.0), Rect::new(glm::vec2(-1.0, -1.0), glm::vec2(5.0, 3.0)));
+        assert_eq!(rect.expanded(-0.5), Rect::new(glm::vec2(0.5, 0.5), glm::vec2(3.5, 1.5)));
+    }
+
+    #[test]
+    fn rect_over_shrunk() {
+        let rect = Rect::from_position_size(glm::vec2(0.0, 0.0), glm::vec2(4.0, 2.0));
+
+        // Height collapses to the center, while width is still shrunk
+        assert_eq!(rect.expanded(-1.5), Rect::new(glm::vec2(1.5, 1.0), glm::vec2(2.5, 1.0)));
+
+        let collapsed = rect.expanded(-10.0);
+        assert!(collapsed.is_empty());
+        assert_eq!(collapsed.size(), glm::vec2(0.0, 0.0));
+        assert_eq!(collapsed.center(), rect.center());
+    }
+
+    #[test]
+    fn circle() {
+        let circle = Circle::new(glm::vec2(1.0, 1.0), -2.0);
+
+        assert_eq!(circle.radius, 2.0);
+        assert!(circle.contains_point(&glm::vec2(3.0, 1.0)));
+        assert!(!circle.contains_point(&glm::vec2(3.0, 3.0)));
+        assert!(circle.intersects_circle(&Circle::new(glm::vec2(4.0, 1.0), 1.0)));
+        assert!(!circle.intersects_circle(&Circle::new(glm::vec2(4.1, 1.0), 1.0)));
+        assert!(circle.intersects_rect(&Rect::from_position_size(glm::vec2(2.5, 0.0), glm::vec2(1.0, 1.0))));
+        assert_eq!(circle.bounding_rect(), Rect::new(glm::vec2(-1.0, -1.0), glm::vec2(3.0, 3.0)));
+    }
+
+    #[test]
+    fn segments() {
+        let point = segments_intersection(
+            &glm::vec2(0.0, 0.0), &glm::vec2(2.0, 2.0),
+            &glm::vec2(0.0, 2.0), &glm::vec2(2.0, 0.0),
+        );
+        assert_eq!(point, Some(glm::vec2(1.0, 1.0)));
+
+        // Parallel and non-overlapping segments
+        assert_eq!(segments_intersection(
+            &glm::vec2(0.0, 0.0), &glm::vec2(1.0, 0.0),
+            &glm::vec2(0.0, 1.0), &glm::vec2(1.0, 1.0),
+        ), None);
+        assert_eq!(segments_intersection(
+            &glm::vec2(0.0, 0.0), &glm::vec2(1.0, 1.0),
+            &glm::vec2(3.0, 0.0), &glm::vec2(2.0, 1.0),
+        ), None);
+    }
+}
@@ -1,15 +1,25 @@
 use serde::{Serialize, Deserialize};
 use nalgebra_glm as glm;
 
+/// Scalar type of [`Transform`]. It's `f64` with `f64-transforms` feature
+/// enabled, so that objects far from the origin don't jitter
+#[cfg(not(feature = "f64-transforms"))]
+pub type Real = f32;
+
+/// Scalar type of [`Transform`]. It's `f64` with `f64-transforms` feature
+/// enabled, so that objects far from the origin don't jitter
+#[cfg(feature = "f64-transforms")]
+pub type Real = f64;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Transform {
-    pub translation: glm::Vec3,
-    pub rotation: glm::Quat,
-    pub scale: f32,
+    pub translation: glm::TVec3<Real>,
+    pub rotation: glm::Qua<Real>,
+    pub scale: Real,
 }
 
 impl Transform {
-    pub fn new(translation: glm::Vec3, rotation: glm::Quat, scale: f32) -> Transform {
+    pub fn new(translation: glm::TVec3<Real>, rotation: glm::Qua<Real>, scale: Real) -> Transform {
         Transform { translation, rotation, scale }
     }
 
@@ -17,22 +27,42 @@ impl Transform {
         Transform::default()
     }
 
-    pub fn new_from_translation(translation: glm::Vec3) -> Transform {
+    pub fn new_from_translation(translation: glm::TVec3<Real>) -> Transform {
         Transform { translation, ..Default::default() }
     }
 
-    pub fn new_from_rotation(rotation: glm::Quat) -> Transform {
+    pub fn new_from_rotation(rotation: glm::Qua<Real>) -> Transform {
         Transform { rotation, ..Default::default() }
     }
 
+    pub fn translation_f32(&self) -> glm::Vec3 {
+        glm::convert(self.translation)
+    }
+
+    pub fn rotation_f32(&self) -> glm::Quat {
+        glm::convert(self.rotation)
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn scale_f32(&self) -> f32 {
+        self.scale as f32
+    }
+
     pub fn to_matrices(&self) -> (glm::Mat4, glm::Mat4) {
-        let matrix = glm::Mat4::identity()
-            * glm::translation(&self.translation)
+        self.to_relative_matrices(&glm::TVec3::zeros())
+    }
+
+    /// Get model matrix and its inverse, translated relative to `origin`. Matrices
+    /// are computed with [`Real`] precision and only then converted to `f32`, which
+    /// is used for camera-relative rendering
+    pub fn to_relative_matrices(&self, origin: &glm::TVec3<Real>) -> (glm::Mat4, glm::Mat4) {
+        let matrix = glm::TMat4::<Real>::identity()
+            * glm::translation(&(self.translation - origin))
             * glm::quat_cast(&self.rotation)
             * glm::scaling(&glm::vec3(self.scale, self.scale, self.scale));
 
         let inversed = matrix.try_inverse().unwrap();
-        (matrix, inversed)
+        (glm::convert(matrix), glm::convert(inversed))
     }
 }
 
@@ -40,8 +70,8 @@ impl Default for Transform {
     fn default() -> Self {
         Transform {
             translation: glm::vec3(0.0, 0.0, 0.0),
-            rotation: glm::Quat::identity(),
+            rotation: glm::Qua::identity(),
             scale: 1.0,
         }
     }
-}
\ No newline at end of file
+}
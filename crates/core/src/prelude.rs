@@ -1,4 +1,6 @@
 pub use crate::catch::*;
+pub use crate::color::Color;
 pub use crate::logger::*;
 pub use crate::math::*;
-pub use crate::time::*;
\ No newline at end of file
+pub use crate::random::Rng;
+pub use crate::time::{*, stopwatch::*, timer::*};
\ No newline at end of file
@@ -0,0 +1,45 @@
+//! Lightweight scope profiler. Scopes are opened with [`profile_scope!`] and
+//! collected until the end of the frame, marked with [`profile_frame!`].
+//! With `profile-tracing` feature every scope is also emitted as a `tracing`
+//! span, so it can be forwarded to an external profiler (e.g. Tracy or puffin)
+//! through the corresponding `tracing` subscriber
+//!
+//! [`profile_scope!`]: crate::profile_scope
+//! [`profile_frame!`]: crate::profile_frame
+
+#[cfg(feature = "profile")]
+mod scope;
+
+#[cfg(feature = "profile")]
+pub use scope::*;
+
+/// Open profiling scope, which lasts until the end of the current block.
+/// Expands to nothing if `profile` feature is disabled
+#[cfg(feature = "profile")]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::profile::ProfileScope::new($name);
+    };
+}
+
+#[cfg(not(feature = "profile"))]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {};
+}
+
+/// Mark the end of the frame. Expands to nothing if `profile` feature is disabled
+#[cfg(feature = "profile")]
+#[macro_export]
+macro_rules! profile_frame {
+    () => {
+        $crate::profile::finish_frame();
+    };
+}
+
+#[cfg(not(feature = "profile"))]
+#[macro_export]
+macro_rules! profile_frame {
+    () => {};
+}
@@ -0,0 +1,145 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+/// Single measured scope of the frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileRecord {
+    pub name: Cow<'static, str>,
+    pub thread: ThreadId,
+    /// Nesting level of the scope within its thread
+    pub depth: usize,
+    /// Start of the scope relative to the start of the frame
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// All scopes, measured during a single frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileFrame {
+    pub index: u64,
+    pub duration: Duration,
+    pub scopes: Vec<ProfileRecord>,
+}
+
+impl ProfileFrame {
+    /// Total time spent in the scopes with the given name
+    pub fn total(&self, name: &str) -> Duration {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.name == name)
+            .map(|scope| scope.duration)
+            .sum()
+    }
+}
+
+struct Profiler {
+    enabled: bool,
+    frame_index: u64,
+    frame_start: Option<Instant>,
+    scopes: Vec<ProfileRecord>,
+    last_frame: Option<ProfileFrame>,
+}
+
+static PROFILER: Mutex<Profiler> = Mutex::new(Profiler {
+    enabled: true,
+    frame_index: 0,
+    frame_start: None,
+    scopes: Vec::new(),
+    last_frame: None,
+});
+
+thread_local! {
+    static SCOPE_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+fn profiler() -> MutexGuard<'static, Profiler> {
+    PROFILER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Enable or disable collecting of the scopes
+pub fn set_enabled(enabled: bool) {
+    profiler().enabled = enabled;
+}
+
+pub fn is_enabled() -> bool {
+    profiler().enabled
+}
+
+/// Finish the current frame and start a new one. It's called by
+/// [`profile_frame!`](crate::profile_frame) macro
+pub fn finish_frame() {
+    let now = Instant::now();
+    let mut profiler = profiler();
+    let frame_start = profiler.frame_start.replace(now).unwrap_or(now);
+
+    if profiler.enabled {
+        let scopes = std::mem::take(&mut profiler.scopes);
+        profiler.last_frame = Some(ProfileFrame {
+            index: profiler.frame_index,
+            duration: now - frame_start,
+            scopes,
+        });
+    } else {
+        profiler.scopes.clear();
+    }
+
+    #[cfg(feature = "profile-tracing")]
+    tracing::trace!(target: "flatbox::profile", frame = profiler.frame_index, "frame mark");
+
+    profiler.frame_index += 1;
+}
+
+/// Get scopes of the latest finished frame
+pub fn last_frame() -> Option<ProfileFrame> {
+    profiler().last_frame.clone()
+}
+
+/// Guard, which measures the time until it's dropped. Created
+/// with [`profile_scope!`](crate::profile_scope) macro
+pub struct ProfileScope {
+    name: Cow<'static, str>,
+    start: Instant,
+    depth: usize,
+    #[cfg(feature = "profile-tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+impl ProfileScope {
+    pub fn new<N: Into<Cow<'static, str>>>(name: N) -> ProfileScope {
+        let name = name.into();
+        let depth = SCOPE_DEPTH.with(|depth| depth.replace(depth.get() + 1));
+
+        ProfileScope {
+            #[cfg(feature = "profile-tracing")]
+            _span: tracing::trace_span!("profile_scope", name = &*name).entered(),
+            name,
+            start: Instant::now(),
+            depth,
+        }
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        SCOPE_DEPTH.with(|depth| depth.set(depth.get().saturating_sub(1)));
+
+        let mut profiler = profiler();
+        if !profiler.enabled {
+            return;
+        }
+
+        let frame_start = *profiler.frame_start.get_or_insert(self.start);
+
+        profiler.scopes.push(ProfileRecord {
+            name: std::mem::take(&mut self.name),
+            thread: thread::current().id(),
+            depth: self.depth,
+            start: self.start.saturating_duration_since(frame_start),
+            duration,
+        });
+    }
+}
@@ -1,11 +1,83 @@
 use std::time::{Instant, Duration};
+use std::sync::{Arc, atomic::{AtomicBool, AtomicU64, Ordering}};
 
+pub mod stopwatch;
 pub mod timer;
 
+/// Shared speed of the game time, e.g. `0.5` for slow motion. It's honored by the fixed
+/// update loop and by [`Time`], and is available to all stages as `Read<TimeScale>`.
+/// Scale `0.0` or [`TimeScale::pause`] stops the game time, while rendering continues
+#[derive(Debug, Clone)]
+pub struct TimeScale {
+    scale: Arc<AtomicU64>,
+    paused: Arc<AtomicBool>,
+}
+
+impl TimeScale {
+    pub fn new(scale: f64) -> Self {
+        TimeScale {
+            scale: Arc::new(AtomicU64::new(scale.max(0.0).to_bits())),
+            paused: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Current scale. It's `0.0` while paused
+    pub fn scale(&self) -> f64 {
+        if self.is_paused() {
+            0.0
+        } else {
+            f64::from_bits(self.scale.load(Ordering::Relaxed))
+        }
+    }
+
+    pub fn set_scale(&self, scale: f64) {
+        self.scale.store(scale.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Stop the game time. The scale is kept and restored with [`TimeScale::resume`]
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale::new(1.0)
+    }
+}
+
+/// ECS event, which pauses the game time (see [`TimeScale::pause`]). Fixed updates
+/// aren't executed while paused, so [`Resume`] must be sent from render stages
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pause;
+
+/// ECS event, which resumes the game time (see [`TimeScale::resume`])
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Resume;
+
+/// Frame timing information. It's updated by the context every frame and is
+/// available to `Update` and render systems as `Read<Time>`
+#[derive(Debug, Clone, Copy)]
 pub struct Time {
     startup_time: Instant,
     latest_update: Option<Instant>,
     delta_time: Duration,
+    unscaled_delta_time: Duration,
+    fixed_delta_time: Duration,
+    elapsed_time: Duration,
+    unscaled_elapsed_time: Duration,
+    time_scale: f64,
+    frame_count: u64,
+    fixed_update_count: u64,
+    overstep_fraction: f64,
 }
 
 impl Default for Time {
@@ -14,6 +86,14 @@ impl Default for Time {
             startup_time: Instant::now(),
             latest_update: None,
             delta_time: Duration::ZERO,
+            unscaled_delta_time: Duration::ZERO,
+            fixed_delta_time: Duration::from_secs_f64(1.0 / 60.0),
+            elapsed_time: Duration::ZERO,
+            unscaled_elapsed_time: Duration::ZERO,
+            time_scale: 1.0,
+            frame_count: 0,
+            fixed_update_count: 0,
+            overstep_fraction: 0.0,
         }
     }
 }
@@ -22,16 +102,148 @@ impl Time {
     pub fn new() -> Self {
         Time::default()
     }
-    
+
+    pub fn startup_time(&self) -> Instant {
+        self.startup_time
+    }
+
+    /// Game time passed since the previous frame, multiplied by the time scale
     pub fn delta_time(&self) -> Duration {
         self.delta_time
     }
-    
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_time.as_secs_f32()
+    }
+
+    /// Real time passed since the previous frame, e.g. for UI animations,
+    /// which must run while the game is paused
+    pub fn unscaled_delta_time(&self) -> Duration {
+        self.unscaled_delta_time
+    }
+
+    pub fn unscaled_delta_seconds(&self) -> f32 {
+        self.unscaled_delta_time.as_secs_f32()
+    }
+
+    /// Time scale, applied during the latest frame
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Time step of fixed updates (`Update` stage). Headless apps run updates even while
+    /// paused, so there it's multiplied by the time scale instead
+    pub fn fixed_delta_time(&self) -> Duration {
+        self.fixed_delta_time
+    }
+
+    pub fn fixed_delta_seconds(&self) -> f32 {
+        self.fixed_delta_time.as_secs_f32()
+    }
+
+    /// Total game time, accumulated by the frames
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_time
+    }
+
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.elapsed_time.as_secs_f64()
+    }
+
+    /// Total real time, accumulated by the frames
+    pub fn unscaled_elapsed(&self) -> Duration {
+        self.unscaled_elapsed_time
+    }
+
+    /// Number of frames rendered so far
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Number of fixed updates executed so far
+    pub fn fixed_update_count(&self) -> u64 {
+        self.fixed_update_count
+    }
+
+    /// Fraction of the fixed time step accumulated, but not yet consumed by updates.
+    /// Can be used to interpolate state between two fixed updates during rendering
+    pub fn overstep_fraction(&self) -> f64 {
+        self.overstep_fraction
+    }
+
+    pub fn set_fixed_delta_time(&mut self, fixed_delta_time: Duration) {
+        self.fixed_delta_time = fixed_delta_time;
+    }
+
+    pub fn set_overstep_fraction(&mut self, overstep_fraction: f64) {
+        self.overstep_fraction = overstep_fraction;
+    }
+
+    /// Set the scale, which is applied to the next frames
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    /// Measure the time passed since the previous call and advance by it
     pub fn update(&mut self){
         let now = Instant::now();
         let delta = now - self.latest_update.unwrap_or(self.startup_time);
-        
+
         self.latest_update = Some(now);
-        self.delta_time = delta;
+        self.advance(delta);
+    }
+
+    /// Advance a new frame by the given real delta. Game time is advanced
+    /// by the delta multiplied by the time scale
+    pub fn advance(&mut self, delta: Duration) {
+        self.unscaled_delta_time = delta;
+        self.unscaled_elapsed_time += delta;
+        self.delta_time = delta.mul_f64(self.time_scale);
+        self.elapsed_time += self.delta_time;
+        self.frame_count += 1;
+    }
+
+    /// Register a single fixed update
+    pub fn advance_fixed(&mut self) {
+        self.fixed_update_count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_scaled() {
+        let mut time = Time::new();
+
+        time.advance(Duration::from_secs(1));
+        time.set_time_scale(0.5);
+        time.advance(Duration::from_secs(1));
+
+        assert_eq!(time.delta_time(), Duration::from_millis(500));
+        assert_eq!(time.unscaled_delta_time(), Duration::from_secs(1));
+        assert_eq!(time.elapsed(), Duration::from_millis(1500));
+        assert_eq!(time.unscaled_elapsed(), Duration::from_secs(2));
+        assert_eq!(time.frame_count(), 2);
+
+        time.set_time_scale(-1.0);
+        assert_eq!(time.time_scale(), 0.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn time_scale_pause() {
+        let time_scale = TimeScale::new(2.0);
+        let shared = time_scale.clone();
+
+        shared.pause();
+        assert!(time_scale.is_paused());
+        assert_eq!(time_scale.scale(), 0.0);
+
+        shared.resume();
+        assert_eq!(time_scale.scale(), 2.0);
+
+        time_scale.set_scale(-1.0);
+        assert_eq!(shared.scale(), 0.0);
+    }
+}
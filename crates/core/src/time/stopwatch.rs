@@ -0,0 +1,71 @@
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+/// Measures time passed while it's not paused. Time is accumulated
+/// manually with [`Stopwatch::tick`], e.g. with [`Time::delta_time`]
+///
+/// [`Time::delta_time`]: super::Time::delta_time
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stopwatch {
+    elapsed_time: Duration,
+    paused: bool,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Stopwatch::default()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_time
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed_time.as_secs_f32()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed_time = Duration::ZERO;
+    }
+
+    pub fn tick(&mut self, step: Duration) {
+        if !self.paused {
+            self.elapsed_time += step;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_and_reset() {
+        let mut stopwatch = Stopwatch::new();
+
+        stopwatch.tick(Duration::from_secs(1));
+        stopwatch.pause();
+        stopwatch.tick(Duration::from_secs(1));
+        assert!(stopwatch.is_paused());
+        assert_eq!(stopwatch.elapsed(), Duration::from_secs(1));
+
+        stopwatch.unpause();
+        stopwatch.tick(Duration::from_millis(500));
+        assert_eq!(stopwatch.elapsed_seconds(), 1.5);
+
+        stopwatch.reset();
+        assert_eq!(stopwatch.elapsed(), Duration::ZERO);
+    }
+}
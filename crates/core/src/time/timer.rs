@@ -1,7 +1,7 @@
 use std::time::Duration;
 use serde::{Serialize, Deserialize};
 
-#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Timer {
     duration: Duration,
     repeating: bool,
@@ -23,6 +23,14 @@ impl Timer {
         }
     }
     
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn repeating(&self) -> bool {
+        self.repeating
+    }
+
     pub fn iteration(&self) -> u32 {
         self.iteration
     }
@@ -31,17 +39,41 @@ impl Timer {
         self.elapsed_time
     }
     
+    pub fn remaining(&self) -> Duration {
+        self.duration.saturating_sub(self.elapsed_time)
+    }
+
+    /// Progress of the current iteration in range `0.0..=1.0`
+    pub fn percent(&self) -> f32 {
+        if self.duration.is_zero() {
+            1.0
+        } else {
+            self.elapsed_time.as_secs_f32() / self.duration.as_secs_f32()
+        }
+    }
+    
     pub fn finished(&self) -> bool {
         self.finished
     }
+
+    pub fn reset(&mut self) {
+        self.iteration = 1;
+        self.finished = false;
+        self.elapsed_time = Duration::ZERO;
+    }
     
     pub fn tick(&mut self, step: Duration){
         if self.repeating {
             self.elapsed_time += step;
             
-            if self.elapsed_time >= self.duration {
-                self.elapsed_time -= self.duration;
+            if self.duration.is_zero() {
+                self.elapsed_time = Duration::ZERO;
                 self.iteration += 1;
+            } else if self.elapsed_time >= self.duration {
+                // The step may span several iterations
+                let iterations = (self.elapsed_time.as_nanos() / self.duration.as_nanos()) as u32;
+                self.elapsed_time -= self.duration * iterations;
+                self.iteration += iterations;
             }
         } else if !self.finished {
             self.elapsed_time += step;
@@ -52,4 +84,60 @@ impl Timer {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn once() {
+        let mut timer = Timer::init(Duration::from_secs(2), false);
+
+        timer.tick(Duration::from_millis(500));
+        assert!(!timer.finished());
+        assert_eq!(timer.remaining(), Duration::from_millis(1500));
+        assert_eq!(timer.percent(), 0.25);
+
+        timer.tick(Duration::from_secs(3));
+        assert!(timer.finished());
+        assert_eq!(timer.elapsed(), timer.duration());
+        assert_eq!(timer.iteration(), 1);
+
+        timer.reset();
+        assert!(!timer.finished());
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn repeating() {
+        let mut timer = Timer::init(Duration::from_secs(1), true);
+
+        timer.tick(Duration::from_millis(700));
+        timer.tick(Duration::from_millis(700));
+        assert_eq!(timer.iteration(), 2);
+        assert_eq!(timer.elapsed(), Duration::from_millis(400));
+        assert!(!timer.finished());
+    }
+
+    #[test]
+    fn repeating_long_step() {
+        let mut timer = Timer::init(Duration::from_secs(1), true);
+
+        // A single step spans two full iterations
+        timer.tick(Duration::from_millis(2500));
+        assert_eq!(timer.iteration(), 3);
+        assert_eq!(timer.elapsed(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn repeating_zero_duration() {
+        let mut timer = Timer::init(Duration::ZERO, true);
+
+        timer.tick(Duration::from_millis(10));
+        timer.tick(Duration::from_millis(10));
+        assert_eq!(timer.iteration(), 3);
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+        assert_eq!(timer.percent(), 1.0);
+    }
+}
@@ -0,0 +1,32 @@
+use hecs::{Entity, NoSuchEntity, World};
+
+/// Marker component of entities, which are skipped by the built-in systems: they aren't
+/// rendered and their physics bodies are disabled. Toggling it is cheaper than despawning
+/// and spawning the entity again
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Disabled;
+
+pub trait EnableEntityExt {
+    /// Remove or insert [`Disabled`] marker of the entity
+    fn set_enabled(&mut self, entity: Entity, enabled: bool) -> Result<(), NoSuchEntity>;
+
+    /// Whether the entity exists and isn't [`Disabled`]
+    fn is_enabled(&self, entity: Entity) -> bool;
+}
+
+impl EnableEntityExt for World {
+    fn set_enabled(&mut self, entity: Entity, enabled: bool) -> Result<(), NoSuchEntity> {
+        if enabled {
+            match self.remove_one::<Disabled>(entity) {
+                Err(hecs::ComponentError::NoSuchEntity) => Err(NoSuchEntity),
+                _ => Ok(()),
+            }
+        } else {
+            self.insert_one(entity, Disabled)
+        }
+    }
+
+    fn is_enabled(&self, entity: Entity) -> bool {
+        self.contains(entity) && self.get::<&Disabled>(entity).is_err()
+    }
+}
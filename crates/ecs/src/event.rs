@@ -0,0 +1,513 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use as_any::{AsAny, Downcast};
+use pretty_type_name::pretty_type_name;
+
+use crate::{
+    Context, IntoAccess, Read, System, SystemName, Write,
+    borrow::{Borrows, ComponentBorrow, ContextBorrow, IntoBorrow},
+};
+
+/// Any type, which can be sent as an event
+pub trait Event: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Event for T {}
+
+/// Storage of events of a single type. Events are double-buffered: they're kept
+/// for the current and the previous frame, so each [`EventCursor`] sees every event
+/// once, even if it runs before the sender in the frame
+#[derive(Debug)]
+pub struct EventHandler<E: Event> {
+    /// Events along with their ids
+    previous: Vec<(usize, E)>,
+    current: Vec<(usize, E)>,
+    /// Number of the sent events, which is the id of the next one
+    event_count: usize,
+}
+
+impl<E: Event> EventHandler<E> {
+    pub fn new() -> Self {
+        EventHandler::default()
+    }
+
+    pub fn send(&mut self, event: E) {
+        self.current.push((self.event_count, event));
+        self.event_count += 1;
+    }
+
+    /// The latest event of the current frame
+    pub fn read(&self) -> Option<&E> {
+        self.current.last().map(|(_, event)| event)
+    }
+
+    /// Mutable reference to the latest event of the current frame, e.g. to veto a request
+    pub fn last_mut(&mut self) -> Option<&mut E> {
+        self.current.last_mut().map(|(_, event)| event)
+    }
+
+    /// Remove the latest event of the current frame. Event ids aren't reused, so cursors,
+    /// which have already read it, see the event, sent instead of it
+    pub fn take(&mut self) -> Option<E> {
+        self.current.pop().map(|(_, event)| event)
+    }
+
+    /// Events of the previous and the current frame, from the oldest to the latest
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &E> {
+        self.iter_with_ids().map(|(_, event)| event)
+    }
+
+    fn iter_with_ids(&self) -> impl DoubleEndedIterator<Item = &(usize, E)> {
+        self.previous.iter().chain(self.current.iter())
+    }
+
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty() && self.current.is_empty()
+    }
+
+    /// Cursor, which reads only the events sent after its creation
+    pub fn cursor(&self) -> EventCursor<E> {
+        EventCursor {
+            last_event: self.event_count,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Drop events of the previous frame and keep the current ones as previous
+    pub fn update(&mut self) {
+        self.previous.clear();
+        std::mem::swap(&mut self.previous, &mut self.current);
+    }
+
+    pub fn clear(&mut self) {
+        self.previous.clear();
+        self.current.clear();
+    }
+}
+
+impl<E: Event> Default for EventHandler<E> {
+    fn default() -> Self {
+        EventHandler {
+            previous: vec![],
+            current: vec![],
+            event_count: 0,
+        }
+    }
+}
+
+/// Position of a reader in [`EventHandler`], e.g. captured by the system's closure or stored
+/// in a component. The reader sees every stored event once. Default cursor starts from the oldest stored event
+#[derive(Debug)]
+pub struct EventCursor<E: Event> {
+    last_event: usize,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E: Event> EventCursor<E> {
+    pub fn new() -> Self {
+        EventCursor::default()
+    }
+
+    /// Events of type `E`, which haven't been read with the cursor yet
+    pub fn read<'a>(&mut self, events: &'a Events) -> impl DoubleEndedIterator<Item = &'a E> {
+        events.get_handler::<E>()
+            .map(|handler| self.read_handler(handler))
+            .into_iter()
+            .flatten()
+    }
+
+    /// Events of the handler, which haven't been read with the cursor yet
+    pub fn read_handler<'a>(&mut self, handler: &'a EventHandler<E>) -> impl DoubleEndedIterator<Item = &'a E> {
+        let unread = self.unread(handler);
+        self.last_event = handler.event_count;
+        unread
+    }
+
+    /// Events of the handler, which haven't been read yet, without marking them as read
+    fn unread<'a>(&self, handler: &'a EventHandler<E>) -> impl DoubleEndedIterator<Item = &'a E> {
+        let last_event = self.last_event;
+
+        handler.iter_with_ids()
+            .filter(move |(id, _)| *id >= last_event)
+            .map(|(_, event)| event)
+    }
+}
+
+impl<E: Event> Default for EventCursor<E> {
+    fn default() -> Self {
+        EventCursor {
+            last_event: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E: Event> Clone for EventCursor<E> {
+    fn clone(&self) -> Self {
+        EventCursor {
+            last_event: self.last_event,
+            _marker: PhantomData,
+        }
+    }
+}
+
+trait AnyEventHandler: AsAny + Send + Sync {
+    fn update(&mut self);
+
+    fn clear(&mut self);
+
+    fn event_name(&self) -> String;
+}
+
+impl<E: Event> AnyEventHandler for EventHandler<E> {
+    fn update(&mut self) {
+        EventHandler::update(self);
+    }
+
+    fn clear(&mut self) {
+        EventHandler::clear(self);
+    }
+
+    fn event_name(&self) -> String {
+        pretty_type_name::<E>()
+    }
+}
+
+/// Registry of event handlers of different types, which is owned by the app. It's available
+/// to systems as `Read<Events>`/`Write<Events>` or as [`EventReader`]/[`EventWriter`] of a single type
+#[derive(Default)]
+pub struct Events {
+    handlers: HashMap<TypeId, Box<dyn AnyEventHandler>>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        Events::default()
+    }
+
+    /// Register handler for events of type `E`. If it's already registered, nothing happens
+    pub fn push_handler<E: Event>(&mut self) {
+        self.handlers
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::<EventHandler<E>>::default());
+    }
+
+    pub fn get_handler<E: Event>(&self) -> Option<&EventHandler<E>> {
+        self.handlers
+            .get(&TypeId::of::<E>())
+            .and_then(|handler| handler.as_ref().downcast_ref())
+    }
+
+    pub fn get_handler_mut<E: Event>(&mut self) -> Option<&mut EventHandler<E>> {
+        self.handlers
+            .get_mut(&TypeId::of::<E>())
+            .and_then(|handler| handler.as_mut().downcast_mut())
+    }
+
+    /// Send the event, registering its handler if needed
+    pub fn send<E: Event>(&mut self, event: E) {
+        self.push_handler::<E>();
+        self.get_handler_mut::<E>()
+            .expect("Handler has just been registered")
+            .send(event);
+    }
+
+    /// Read the latest event of type `E`, sent in the current frame
+    pub fn read<E: Event>(&self) -> Option<&E> {
+        self.get_handler::<E>()?.read()
+    }
+
+    /// Mutable reference to the latest event of type `E`, sent in the current frame
+    pub fn read_mut<E: Event>(&mut self) -> Option<&mut E> {
+        self.get_handler_mut::<E>()?.last_mut()
+    }
+
+    /// Stored events of type `E`, from the oldest to the latest
+    pub fn iter<E: Event>(&self) -> impl DoubleEndedIterator<Item = &E> {
+        self.get_handler::<E>()
+            .into_iter()
+            .flat_map(|handler| handler.iter())
+    }
+
+    /// Start the next frame: drop events of the previous frame and keep the current ones
+    /// as previous. It's done by [`clear_events`] system at the end of each frame
+    pub fn update(&mut self) {
+        for handler in self.handlers.values_mut() {
+            handler.update();
+        }
+    }
+
+    /// Remove events of all types
+    pub fn clear(&mut self) {
+        for handler in self.handlers.values_mut() {
+            handler.clear();
+        }
+    }
+}
+
+/// Drop events of the previous frame. The app executes it at the end of each frame
+pub fn clear_events(mut events: Write<Events>) {
+    events.update();
+}
+
+impl Debug for Events {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.handlers.values().map(|handler| handler.event_name()))
+            .finish()
+    }
+}
+
+/// System parameter, which reads events of type `E`. It borrows [`Events`] like `Read<Events>`
+pub struct EventReader<'a, E: Event> {
+    events: Read<'a, Events>,
+    cursor: RefCell<EventCursor<E>>,
+}
+
+impl<'a, E: Event> EventReader<'a, E> {
+    /// The latest event, which the reader hasn't seen yet. Older unseen events are skipped
+    pub fn read(&self) -> Option<&E> {
+        self.iter().next_back()
+    }
+
+    /// Events, which the reader hasn't seen yet, from the oldest to the latest
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &E> {
+        self.cursor.borrow_mut().read(&self.events)
+    }
+
+    /// Events, which haven't been read with the cursor yet
+    pub fn read_with(&self, cursor: &mut EventCursor<E>) -> impl Iterator<Item = &E> {
+        cursor.read(&self.events)
+    }
+
+    /// Whether there are no events, which the reader hasn't seen yet. Events aren't marked as seen
+    pub fn is_empty(&self) -> bool {
+        self.events.get_handler::<E>()
+            .is_none_or(|handler| self.cursor.borrow().unread(handler).next().is_none())
+    }
+}
+
+impl<E: Event> Drop for EventReader<'_, E> {
+    fn drop(&mut self) {
+        let cursor = std::mem::take(self.cursor.get_mut());
+
+        READER_CURSORS.with(|cursors| {
+            if let Some(cursors) = cursors.borrow_mut().as_mut() {
+                cursors.insert(TypeId::of::<E>(), Box::new(cursor));
+            }
+        });
+    }
+}
+
+/// Cursors of [`EventReader`]s of a system by event type
+type ReaderCursors = HashMap<TypeId, Box<dyn Any + Send>>;
+
+thread_local! {
+    /// Cursors of the system, which is being executed on the thread
+    static READER_CURSORS: RefCell<Option<ReaderCursors>> = const { RefCell::new(None) };
+}
+
+/// System, which keeps cursors of its [`EventReader`]s between executions, so each reader
+/// sees every event once. Systems are wrapped with it by [`Schedules::add_system`](crate::Schedules::add_system).
+/// Readers of systems, which aren't wrapped, see all stored events on each execution
+pub struct ReaderSystem<S> {
+    system: S,
+    cursors: ReaderCursors,
+}
+
+impl<S> ReaderSystem<S> {
+    pub fn new(system: S) -> Self {
+        ReaderSystem {
+            system,
+            cursors: HashMap::new(),
+        }
+    }
+}
+
+impl<Args, Ret, S: System<Args, Ret>> System<Args, Ret> for ReaderSystem<S> {
+    fn execute(&mut self, context: &Context) -> crate::error::Result<()> {
+        // Cursors of the outer system are restored afterwards, e.g. when a schedule is executed by a system
+        let outer = READER_CURSORS.with(|cursors| cursors.replace(Some(std::mem::take(&mut self.cursors))));
+        let result = self.system.execute(context);
+        self.cursors = READER_CURSORS.with(|cursors| cursors.replace(outer)).unwrap_or_default();
+
+        result
+    }
+
+    fn name(&self) -> SystemName {
+        self.system.name()
+    }
+
+    fn borrows() -> Borrows {
+        S::borrows()
+    }
+}
+
+/// System parameter, which sends events of type `E`. It borrows [`Events`] like `Write<Events>`
+pub struct EventWriter<'a, E: Event> {
+    events: Write<'a, Events>,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<'a, E: Event> EventWriter<'a, E> {
+    pub fn send(&mut self, event: E) {
+        self.events.send(event);
+    }
+
+    /// Mutable reference to the latest event of the current frame, e.g. to veto a request
+    pub fn last_mut(&mut self) -> Option<&mut E> {
+        self.events.read_mut::<E>()
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        for event in events {
+            self.events.send(event);
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct EventReaderBorrow<E: Event>(PhantomData<E>);
+
+impl<E: Event> IntoBorrow for EventReader<'_, E> {
+    type Borrow = EventReaderBorrow<E>;
+}
+
+impl<'a, E: Event> ContextBorrow<'a> for EventReaderBorrow<E> {
+    type Target = EventReader<'a, E>;
+
+    fn borrow(context: &'a Context) -> crate::error::Result<Self::Target> {
+        let cursor = READER_CURSORS.with(|cursors| {
+            cursors.borrow_mut()
+                .as_mut()
+                .and_then(|cursors| cursors.remove(&TypeId::of::<E>()))
+                .and_then(|cursor| cursor.downcast::<EventCursor<E>>().ok())
+        });
+
+        Ok(EventReader {
+            events: <Read<'a, Events> as ContextBorrow<'a>>::borrow(context)?,
+            cursor: RefCell::new(cursor.map(|cursor| *cursor).unwrap_or_default()),
+        })
+    }
+}
+
+impl<E: Event> ComponentBorrow for EventReader<'_, E> {
+    fn borrows() -> Borrows {
+        <Read<Events> as ComponentBorrow>::borrows()
+    }
+
+    fn has<U: IntoAccess>() -> bool {
+        <Read<Events> as ComponentBorrow>::has::<U>()
+    }
+
+    fn has_dynamic(id: TypeId, exclusive: bool) -> bool {
+        <Read<Events> as ComponentBorrow>::has_dynamic(id, exclusive)
+    }
+}
+
+#[doc(hidden)]
+pub struct EventWriterBorrow<E: Event>(PhantomData<E>);
+
+impl<E: Event> IntoBorrow for EventWriter<'_, E> {
+    type Borrow = EventWriterBorrow<E>;
+}
+
+impl<'a, E: Event> ContextBorrow<'a> for EventWriterBorrow<E> {
+    type Target = EventWriter<'a, E>;
+
+    fn borrow(context: &'a Context) -> crate::error::Result<Self::Target> {
+        Ok(EventWriter {
+            events: <Write<'a, Events> as ContextBorrow<'a>>::borrow(context)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<E: Event> ComponentBorrow for EventWriter<'_, E> {
+    fn borrows() -> Borrows {
+        <Write<Events> as ComponentBorrow>::borrows()
+    }
+
+    fn has<U: IntoAccess>() -> bool {
+        <Write<Events> as ComponentBorrow>::has::<U>()
+    }
+
+    fn has_dynamic(id: TypeId, exclusive: bool) -> bool {
+        <Write<Events> as ComponentBorrow>::has_dynamic(id, exclusive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_reads_each_event_once() {
+        let mut handler = EventHandler::<u32>::new();
+        let mut cursor = EventCursor::new();
+
+        handler.send(1);
+        handler.send(2);
+        assert_eq!(cursor.read_handler(&handler).copied().collect::<Vec<_>>(), [1, 2]);
+
+        // The event is replaced after the cursor has read it
+        assert_eq!(handler.take(), Some(2));
+        handler.send(3);
+        assert_eq!(cursor.read_handler(&handler).copied().collect::<Vec<_>>(), [3]);
+
+        handler.update();
+        handler.send(4);
+        assert_eq!(cursor.read_handler(&handler).copied().collect::<Vec<_>>(), [4]);
+
+        handler.update();
+        handler.update();
+        assert_eq!(cursor.read_handler(&handler).count(), 0);
+    }
+
+    #[test]
+    fn new_cursor_skips_sent_events() {
+        let mut handler = EventHandler::<u32>::new();
+
+        handler.send(1);
+        let mut cursor = handler.cursor();
+        assert_eq!(handler.take(), Some(1));
+        handler.send(2);
+
+        assert_eq!(cursor.read_handler(&handler).copied().collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn reader_sees_each_event_once() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::Schedule;
+
+        let seen = Arc::new(Mutex::new(vec![]));
+        let reader_seen = seen.clone();
+
+        let mut builder = Schedule::builder();
+        builder.add_system(ReaderSystem::new(move |reader: EventReader<u32>| {
+            reader_seen.lock().unwrap().push(reader.iter().copied().collect::<Vec<_>>());
+        }));
+        let mut schedule = builder.build();
+
+        let mut events = Events::new();
+        events.send(1u32);
+        events.send(2u32);
+        schedule.execute_seq((&mut events,)).unwrap();
+        schedule.execute_seq((&mut events,)).unwrap();
+
+        events.update();
+        events.send(3u32);
+        schedule.execute_seq((&mut events,)).unwrap();
+
+        assert_eq!(*seen.lock().unwrap(), [vec![1, 2], vec![], vec![3]]);
+    }
+}
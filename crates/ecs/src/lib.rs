@@ -1,5 +1,11 @@
 use std::collections::HashMap;
 
+use crate::event::ReaderSystem;
+
+pub mod disabled;
+pub mod event;
+pub mod resources;
+
 pub use hecs::{
     *,
     serialize::column::{
@@ -14,6 +20,9 @@ pub use hecs_schedule::{
     Access, Batch, CommandBuffer, QueryOne,
 };
 
+/// Stage of the app loop. Each stage has its own [`CommandBuffer`], which is available
+/// to systems as `Write<CommandBuffer>` and applied to the world at the end of the stage,
+/// so spawned entities exist before the next stage runs
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum SystemStage {
     Setup,
@@ -21,10 +30,33 @@ pub enum SystemStage {
     PreRender,
     Render,
     PostRender,
+    /// Executed instead of the other stages, while the context is paused due to
+    /// `OcclusionPolicy::Pause`: once on pausing and after each batch of window events
+    Paused,
+    /// Executed once on exit, e.g. to save state and release resources
+    Shutdown,
+    /// Stage, added with [`Schedules::add_stage`]
+    Custom(&'static str),
+}
+
+/// Execute the schedule, running systems with disjoint borrows in parallel on the rayon
+/// thread pool, or sequentially without `parallel` feature. Render stages can't run in
+/// parallel, because OpenGL context is bound to the main thread
+pub fn execute_parallel<D>(schedule: &mut Schedule, data: D) -> hecs_schedule::error::Result<()>
+where
+    D: IntoData<CommandBuffer> + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    return schedule.execute(data);
+
+    #[cfg(not(feature = "parallel"))]
+    return schedule.execute_seq(data);
 }
 
 pub struct Schedules {
     schedules: HashMap<SystemStage, ScheduleBuilder>,
+    /// Custom stages in order of adding along with the stages, which they're executed after
+    custom_stages: Vec<(SystemStage, SystemStage)>,
 }
 
 impl Default for Schedules {
@@ -36,7 +68,10 @@ impl Default for Schedules {
                 (SystemStage::PreRender, Schedule::builder()),
                 (SystemStage::Render, Schedule::builder()),
                 (SystemStage::PostRender, Schedule::builder()),
+                (SystemStage::Paused, Schedule::builder()),
+                (SystemStage::Shutdown, Schedule::builder()),
             ]),
+            custom_stages: vec![],
         }
     }
 }
@@ -46,11 +81,65 @@ impl Schedules {
         Self::default() 
     }
 
+    /// Add system to the stage. Its [`EventReader`](event::EventReader)s keep their cursors between executions
     pub fn add_system<Args, Ret, S>(&mut self, system_stage: SystemStage, system: S)
     where
         S: 'static + System<Args, Ret> + Send,
     {
-        self.schedules.get_mut(&system_stage).unwrap().add_system(system);
+        self.schedules
+            .get_mut(&system_stage)
+            .unwrap_or_else(|| panic!("Stage `{system_stage:?}` doesn't exist"))
+            .add_system(ReaderSystem::new(system));
+    }
+
+    /// Add custom stage, which is executed right after the `after` stage with the same data,
+    /// e.g. after `Update` on each fixed update. Stages, added after the same stage, are executed
+    /// in order of adding. Commands of the previous stage are applied before the custom one
+    pub fn add_stage(&mut self, name: &'static str, after: SystemStage) -> SystemStage {
+        let stage = SystemStage::Custom(name);
+
+        assert!(self.schedules.contains_key(&after), "Stage `{after:?}` doesn't exist");
+        assert!(!self.schedules.contains_key(&stage), "Stage `{name}` is already added");
+
+        self.schedules.insert(stage, Schedule::builder());
+        self.custom_stages.push((stage, after));
+
+        stage
+    }
+
+    pub fn contains_stage(&self, stage: SystemStage) -> bool {
+        self.schedules.contains_key(&stage)
+    }
+
+    /// Custom stages, which are executed after the stage, in execution order
+    pub fn stages_after(&self, stage: SystemStage) -> Vec<SystemStage> {
+        let mut stages = vec![];
+
+        for &(custom, after) in &self.custom_stages {
+            if after == stage {
+                stages.push(custom);
+                stages.extend(self.stages_after(custom));
+            }
+        }
+
+        stages
+    }
+
+    /// Build schedule of the stage, followed by the custom stages after it. Systems are
+    /// moved into the schedule, like with [`ScheduleBuilder::build`]. The schedule's
+    /// [`CommandBuffer`] is applied after each of the stages
+    pub fn build_stage(&mut self, system_stage: SystemStage) -> Schedule {
+        let followers = self.stages_after(system_stage);
+        let mut builder = self.get_systems(system_stage)
+            .map(std::mem::take)
+            .unwrap_or_else(|| panic!("Stage `{system_stage:?}` doesn't exist"));
+
+        for stage in followers {
+            builder.flush().barrier();
+            builder.append(self.schedules.get_mut(&stage).unwrap());
+        }
+
+        builder.build()
     }
 
     pub fn get_systems(&mut self, system_stage: SystemStage) -> Option<&mut ScheduleBuilder> {
@@ -0,0 +1,199 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use parking_lot::{
+    lock_api::{ArcRwLockReadGuard, ArcRwLockWriteGuard},
+    RawRwLock, RwLock,
+};
+
+use crate::{
+    Context, IntoAccess, Read,
+    borrow::{Borrows, ComponentBorrow, ContextBorrow, IntoBorrow},
+    error::{Error, Result},
+};
+
+/// Any type, which can be stored as a resource
+pub trait Resource: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Resource for T {}
+
+/// Type map of global values, which aren't bound to entities, e.g. settings or caches.
+/// Each resource is locked separately, so systems, borrowing different resources
+/// with [`Res`]/[`ResMut`], can run in parallel
+#[derive(Default)]
+pub struct Resources {
+    resources: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Resources::default()
+    }
+
+    /// Insert resource, replacing the previous one of the same type
+    pub fn insert<R: Resource>(&mut self, resource: R) {
+        self.resources.insert(TypeId::of::<R>(), Box::new(Arc::new(RwLock::new(resource))));
+    }
+
+    /// Remove resource. Returns `None`, if there's no such resource or it's still borrowed
+    pub fn remove<R: Resource>(&mut self) -> Option<R> {
+        let lock = self.lock::<R>()?;
+        if Arc::strong_count(&lock) > 2 {
+            return None;
+        }
+
+        drop(lock);
+
+        let lock = self.resources.remove(&TypeId::of::<R>())?
+            .downcast::<Arc<RwLock<R>>>()
+            .ok()?;
+
+        Arc::into_inner(*lock).map(RwLock::into_inner)
+    }
+
+    pub fn contains<R: Resource>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    /// Borrow resource. Returns `None`, if there's no such resource or it's mutably borrowed
+    pub fn get<R: Resource>(&self) -> Option<Res<R>> {
+        Some(Res(self.lock::<R>()?.try_read_arc()?))
+    }
+
+    /// Mutably borrow resource. Returns `None`, if there's no such resource or it's borrowed
+    pub fn get_mut<R: Resource>(&self) -> Option<ResMut<R>> {
+        Some(ResMut(self.lock::<R>()?.try_write_arc()?))
+    }
+
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.resources.clear();
+    }
+
+    fn lock<R: Resource>(&self) -> Option<Arc<RwLock<R>>> {
+        self.resources
+            .get(&TypeId::of::<R>())?
+            .downcast_ref::<Arc<RwLock<R>>>()
+            .cloned()
+    }
+}
+
+/// Shared borrow of resource `R`. As a system parameter it's borrowed from the
+/// app's [`Resources`] and only conflicts with systems, which mutably borrow `R`
+pub struct Res<R: Resource>(ArcRwLockReadGuard<RawRwLock, R>);
+
+impl<R: Resource> Deref for Res<R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.0
+    }
+}
+
+/// Exclusive borrow of resource `R`. As a system parameter it's borrowed from the
+/// app's [`Resources`] and conflicts with systems, which borrow `R`
+pub struct ResMut<R: Resource>(ArcRwLockWriteGuard<RawRwLock, R>);
+
+impl<R: Resource> Deref for ResMut<R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.0
+    }
+}
+
+impl<R: Resource> DerefMut for ResMut<R> {
+    fn deref_mut(&mut self) -> &mut R {
+        &mut self.0
+    }
+}
+
+/// Type, which stands for the resource in the schedule's borrows, so resources
+/// don't conflict with components or context data of the same type
+struct ResourceMarker<R>(PhantomData<R>);
+
+fn borrow_resource<R: Resource>(context: &Context) -> Result<Arc<RwLock<R>>> {
+    <Read<Resources> as ContextBorrow>::borrow(context)?
+        .lock::<R>()
+        .ok_or(Error::MissingData(type_name::<R>()))
+}
+
+fn resource_borrows<A: IntoAccess>() -> Borrows {
+    let mut borrows = <Read<Resources> as ComponentBorrow>::borrows();
+    borrows.push(A::access());
+    borrows
+}
+
+#[doc(hidden)]
+pub struct ResBorrow<R: Resource>(PhantomData<R>);
+
+impl<R: Resource> IntoBorrow for Res<R> {
+    type Borrow = ResBorrow<R>;
+}
+
+impl<'a, R: Resource> ContextBorrow<'a> for ResBorrow<R> {
+    type Target = Res<R>;
+
+    fn borrow(context: &'a Context) -> Result<Self::Target> {
+        borrow_resource::<R>(context)?
+            .try_read_arc()
+            .map(Res)
+            .ok_or(Error::Borrow(type_name::<R>()))
+    }
+}
+
+impl<R: Resource> ComponentBorrow for Res<R> {
+    fn borrows() -> Borrows {
+        resource_borrows::<&ResourceMarker<R>>()
+    }
+
+    fn has<U: IntoAccess>() -> bool {
+        <Read<Resources> as ComponentBorrow>::has::<U>()
+    }
+
+    fn has_dynamic(id: TypeId, exclusive: bool) -> bool {
+        <Read<Resources> as ComponentBorrow>::has_dynamic(id, exclusive)
+    }
+}
+
+#[doc(hidden)]
+pub struct ResMutBorrow<R: Resource>(PhantomData<R>);
+
+impl<R: Resource> IntoBorrow for ResMut<R> {
+    type Borrow = ResMutBorrow<R>;
+}
+
+impl<'a, R: Resource> ContextBorrow<'a> for ResMutBorrow<R> {
+    type Target = ResMut<R>;
+
+    fn borrow(context: &'a Context) -> Result<Self::Target> {
+        borrow_resource::<R>(context)?
+            .try_write_arc()
+            .map(ResMut)
+            .ok_or(Error::BorrowMut(type_name::<R>()))
+    }
+}
+
+impl<R: Resource> ComponentBorrow for ResMut<R> {
+    fn borrows() -> Borrows {
+        resource_borrows::<&mut ResourceMarker<R>>()
+    }
+
+    fn has<U: IntoAccess>() -> bool {
+        <Read<Resources> as ComponentBorrow>::has::<U>()
+    }
+
+    fn has_dynamic(id: TypeId, exclusive: bool) -> bool {
+        <Read<Resources> as ComponentBorrow>::has_dynamic(id, exclusive)
+    }
+}
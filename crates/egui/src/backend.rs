@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use parking_lot::Mutex;
 
 use flatbox_render::{
@@ -13,26 +13,44 @@ pub struct EguiBackend {
     pub state: Arc<Mutex<egui_winit::State>>,
     pub painter: Painter,
 
+    display: Display,
     shapes: Vec<egui::epaint::ClippedShape>,
     textures_delta: egui::TexturesDelta,
+    pixels_per_point: f32,
 }
 
 impl EguiBackend {
     pub fn new(context: &Context) -> Self {
         let painter = Painter::new().expect("Cannot initialize egui backend");
 
-        let mut state = egui_winit::State::new(context.event_loop_target());
-        state.set_max_texture_side(2048);
+        let egui_ctx = egui::Context::default();
+        let display = context.display();
 
-        let pixels_per_point = context.display().lock().window().scale_factor() as f32;
-        state.set_pixels_per_point(pixels_per_point);
+        let (state, pixels_per_point) = {
+            let gl_context = display.lock();
+            let window = gl_context.window();
+            let pixels_per_point = window.scale_factor() as f32;
+
+            let state = egui_winit::State::new(
+                egui_ctx.clone(),
+                egui::ViewportId::ROOT,
+                window,
+                Some(pixels_per_point),
+                None,
+                Some(2048),
+            );
+
+            (state, pixels_per_point)
+        };
 
         Self {
-            egui_ctx: egui::Context::default(),
+            egui_ctx,
             state: Arc::new(Mutex::new(state)),
             painter,
+            display,
             shapes: Default::default(),
             textures_delta: Default::default(),
+            pixels_per_point,
         }
     }
 
@@ -40,38 +58,45 @@ impl EguiBackend {
         &self.egui_ctx
     }
 
-    pub fn on_event(&mut self, event: &WindowEvent<'_>) -> bool {
-        self.state.lock().on_event(&self.egui_ctx, event)
+    /// Handle the window event. Returns `true`, if egui wants exclusive use of it, e.g. typing into a text field
+    pub fn on_event(&mut self, event: &WindowEvent) -> bool {
+        self.state.lock()
+            .on_window_event(self.display.lock().window(), event)
+            .consumed
     }
 
     pub fn run(
         &mut self,
         display: Display,
         run_ui: impl FnMut(&egui::Context),
-    ) -> std::time::Duration {
+    ) -> Duration {
         let raw_input = self.state.lock().take_egui_input(display.lock().window());
         let egui::FullOutput {
             platform_output,
-            repaint_after,
             textures_delta,
             shapes,
+            pixels_per_point,
+            viewport_output,
         } = self.egui_ctx.run(raw_input, run_ui);
 
         self.state.lock()
-            .handle_platform_output(display.lock().window(), &self.egui_ctx, platform_output);
+            .handle_platform_output(display.lock().window(), platform_output);
 
         self.shapes = shapes;
+        self.pixels_per_point = pixels_per_point;
         self.textures_delta.append(textures_delta);
 
-        repaint_after
+        viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .map_or(Duration::ZERO, |viewport| viewport.repaint_delay)
     }
 
     pub fn paint(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
         let shapes = std::mem::take(&mut self.shapes);
         let textures_delta = std::mem::take(&mut self.textures_delta);
-        let clipped_primitives = self.egui_ctx.tessellate(shapes);
+        let clipped_primitives = self.egui_ctx.tessellate(shapes, self.pixels_per_point);
 
-        let pixels_per_point = self.egui_ctx.pixels_per_point();
+        let pixels_per_point = self.pixels_per_point;
         let screen_size_px = renderer.extent().into();
 
         self.painter.paint_and_update_textures(
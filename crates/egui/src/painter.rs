@@ -15,7 +15,7 @@ use flatbox_render::{
         buffer::{Buffer, BufferTarget, BufferUsage, VertexArray, AttributeType}
     }, 
     error::RenderError, 
-    pbr::texture::{Filter, Texture, TextureDescriptor, WrapMode, ColorMode, ImageType, Order}, renderer::{Renderer, Capability, WindowExtent, EnableCommand, DisableCommand, ColorMaskCommand, BlendEquationSeparateCommand, ColorBlendMode, BlendFuncSeparateCommand, ColorBlendEquation, ScissorCommand, ActivateTextureRawCommand, DrawTrianglesCommand}
+    pbr::texture::{Filter, Texture, TextureDescriptor, WrapMode, ColorMode, ColorSpace, ImageType, Order}, renderer::{Renderer, Capability, WindowExtent, EnableCommand, DisableCommand, ColorMaskCommand, BlendEquationSeparateCommand, ColorBlendMode, BlendFuncSeparateCommand, ColorBlendEquation, ScissorCommand, ActivateTextureRawCommand, DrawTrianglesCommand}
 };
 
 const VERT_SRC: &str = include_str!("shaders/egui.vs");
@@ -224,7 +224,7 @@ impl Painter {
         if let Some(texture) = self.texture(mesh.texture_id) {
             self.vertex_buffer.fill(&mesh.vertices);
             self.index_buffer.fill(&mesh.indices);
-            texture.bind();
+            texture.activate(Order::Texture0);
 
             unsafe { renderer.execute(&mut DrawTrianglesCommand::new(mesh.indices.len()))?; }
         } else {
@@ -239,7 +239,7 @@ impl Painter {
         tex_id: egui::TextureId, 
         delta: &egui::epaint::ImageDelta
     ) -> Result<(), RenderError> {
-        let texture = match &delta.image {
+        let (w, h, data): (usize, usize, Vec<u8>) = match &delta.image {
             egui::ImageData::Color(image) => {
                 let (w, h) = (image.width(), image.height());
 
@@ -250,35 +250,13 @@ impl Painter {
                     image.pixels.len(),
                     "Mismatch between texture size and texel count"
                 );
-                assert_eq!(data.len(), w * h * 4);
-                assert!(
-                    w <= self.max_texture_side && h <= self.max_texture_side,
-                    "Got a texture image of size {}x{}, but the maximum supported texture side is only {}",
-                    w,
-                    h,
-                    self.max_texture_side
-                );
-
-                Texture::new_from_raw(
-                    data, 
-                    image.width() as u32, 
-                    image.height() as u32, 
-                    Some(TextureDescriptor {
-                        filter: delta.filter.to_native(),
-                        wrap_mode: WrapMode::ClampToEdge,
-                        color_mode: ColorMode::Srgb8Alpha8,
-                        image_type: match delta.pos {
-                            Some([x, y]) => ImageType::SubImage2D([x, y]),
-                            None => ImageType::Image2D,
-                        },
-                    })
-                )?
 
+                (w, h, data.to_vec())
             }
             egui::ImageData::Font(image) => {
                 let (w, h) = (image.width(), image.height());
 
-                let gamma = 1.0;
+                let gamma = Some(1.0);
                 let data: Vec<u8> = image
                     .srgba_pixels(gamma)
                     .flat_map(|a| a.to_array())
@@ -289,32 +267,38 @@ impl Painter {
                     image.pixels.len(),
                     "Mismatch between texture size and texel count"
                 );
-                assert_eq!(data.len(), w * h * 4);
-                assert!(
-                    w <= self.max_texture_side && h <= self.max_texture_side,
-                    "Got a texture image of size {}x{}, but the maximum supported texture side is only {}",
-                    w,
-                    h,
-                    self.max_texture_side
-                );
 
-                Texture::new_from_raw(
-                    &data, 
-                    image.width() as u32, 
-                    image.height() as u32, 
-                    Some(TextureDescriptor {
-                        filter: delta.filter.to_native(),
-                        wrap_mode: WrapMode::ClampToEdge,
-                        color_mode: ColorMode::Srgb8Alpha8,
-                        image_type: match delta.pos {
-                            Some(coords) => ImageType::SubImage2D(coords),
-                            None => ImageType::Image2D,
-                        },
-                    })
-                )?
+                (w, h, data)
             }
         };
 
+        assert_eq!(data.len(), w * h * 4);
+        assert!(
+            w <= self.max_texture_side && h <= self.max_texture_side,
+            "Got a texture image of size {}x{}, but the maximum supported texture side is only {}",
+            w,
+            h,
+            self.max_texture_side
+        );
+
+        // Partial updates, e.g. of the font atlas, are written into the existing texture
+        if let (Some([x, y]), Some(texture)) = (delta.pos, self.textures.get(&tex_id)) {
+            return texture.write_region(x as u32, y as u32, w as u32, h as u32, &data);
+        }
+
+        let texture = Texture::new_from_raw(
+            &data, 
+            w as u32, 
+            h as u32, 
+            Some(TextureDescriptor {
+                filter: delta.options.magnification.to_native(),
+                wrap_mode: WrapMode::ClampToEdge,
+                color_mode: ColorMode::Srgb8Alpha8,
+                color_space: ColorSpace::Srgb,
+                image_type: ImageType::Image2D,
+            })
+        )?;
+
         self.textures.insert(tex_id, texture);
 
         Ok(())
@@ -336,6 +320,31 @@ impl Painter {
             self.textures_to_destroy.push(old_tex);
         }
     }
+
+    /// Render into a texture with [`Renderer::render_to_texture`] and register it, or replace
+    /// the texture with `id` if provided, e.g. for a viewport updated every frame. The texture
+    /// is upside down in egui, so it should be shown with flipped UV, e.g.
+    /// `egui::Image::new(id, size).uv(egui::Rect::from_min_max(egui::pos2(0.0, 1.0), egui::pos2(1.0, 0.0)))`
+    pub fn render_to_native_texture<F>(
+        &mut self,
+        renderer: &mut Renderer,
+        id: Option<TextureId>,
+        extent: WindowExtent,
+        render: F,
+    ) -> Result<TextureId, RenderError>
+    where
+        F: FnOnce(&mut Renderer) -> Result<(), RenderError>,
+    {
+        let texture = renderer.render_to_texture(extent, render)?;
+
+        Ok(match id {
+            Some(id) => {
+                self.replace_native_texture(id, texture);
+                id
+            },
+            None => self.register_native_texture(texture),
+        })
+    }
 }
 
 fn set_clip_rect(
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+use crate::transport::ClientId;
+
+#[derive(Debug, Error)]
+pub enum NetError {
+    #[error("Network I/O error")]
+    IoError(#[from] std::io::Error),
+    #[error("Cannot serialize message: {0}")]
+    SerializeError(#[from] flatbox_assets::ron::Error),
+    #[error("Message of {0} bytes doesn't fit into a packet")]
+    MessageTooLarge(usize),
+    #[error("Client isn't connected to the server")]
+    NotConnected,
+    #[error("Client {0:?} isn't connected")]
+    UnknownClient(ClientId),
+}
@@ -0,0 +1,20 @@
+//! Client/server networking over UDP with replication of components. Messages are delivered
+//! reliably and in order: each of them is resent, until the peer acknowledges it.
+//!
+//! Components of entities with [`Replicated`] marker, which are registered in
+//! [`ReplicationRegistry`], are sent by [`NetServer`] as periodic diffs and applied to the world
+//! by [`NetClient`]. Clients send inputs upstream as components, which are added to the
+//! [`RemoteClient`] entity of the client on the server. Components are serialized as
+//! [`SerializableComponent`]s, so both sides must register them with `impl_ser_component!`
+//!
+//! [`SerializableComponent`]: flatbox_assets::ser_component::SerializableComponent
+
+pub mod error;
+pub mod protocol;
+pub mod replication;
+pub mod transport;
+
+pub use error::*;
+pub use protocol::*;
+pub use replication::*;
+pub use transport::*;
@@ -0,0 +1,58 @@
+use serde::{Serialize, Deserialize};
+
+/// Version of the protocol. Clients of other versions are rejected
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Message, which is delivered reliably and in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Message {
+    /// Changes of the replicated entities, sent by the server
+    Replication(ReplicationUpdate),
+    /// Components in RON, sent by the client and added to its entity on the server
+    Input(Vec<String>),
+}
+
+/// Changes of the replicated entities since the previous update. Large updates
+/// are split into several ones with the same tick
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplicationUpdate {
+    pub tick: u64,
+    pub entities: Vec<EntityUpdate>,
+    /// Ids of the entities, which are despawned or aren't replicated anymore
+    pub despawned: Vec<u64>,
+}
+
+impl ReplicationUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty() && self.despawned.is_empty()
+    }
+}
+
+/// Changes of a single replicated entity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityUpdate {
+    /// Id of the entity on the server
+    pub id: u64,
+    /// Added or changed components in RON
+    pub components: Vec<String>,
+    /// Names of the removed components
+    pub removed: Vec<String>,
+}
+
+impl EntityUpdate {
+    /// Approximate size of the update in a message
+    pub(crate) fn size(&self) -> usize {
+        self.components.iter().chain(&self.removed).map(String::len).sum::<usize>() + 32
+    }
+}
+
+/// Datagram, sent between the client and the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Packet {
+    Connect { version: u32 },
+    Accept { client_id: u64 },
+    Disconnect,
+    Heartbeat,
+    Message { id: u64, message: Message },
+    Ack { id: u64 },
+}
@@ -0,0 +1,492 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::net::ToSocketAddrs;
+use std::time::{Duration, Instant};
+
+use flatbox_assets::{impl_ser_component, ron, ser_component::SerializableComponent};
+use flatbox_core::{logger::{error, warn}, math::transform::Transform};
+use flatbox_ecs::{Entity, EntityBuilder, World, event::Events};
+use pretty_type_name::pretty_type_name;
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    error::NetError,
+    protocol::{EntityUpdate, Message, ReplicationUpdate},
+    transport::{Client, ClientEvent, ClientId, Server, ServerEvent},
+};
+
+/// Approximate size of the replication message, which larger updates are split by
+pub const MAX_MESSAGE_SIZE: usize = 1024;
+
+/// Marker of the server entities, whose registered components are replicated to clients
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replicated;
+
+/// Id of the server entity, which the client entity replicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NetworkId(pub u64);
+
+/// Entity of the connected client on the server, which its inputs are added to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RemoteClient(pub ClientId);
+
+impl_ser_component!(Replicated);
+
+/// ECS event of the connection state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetEvent {
+    /// Client has connected to the server. The entity has [`RemoteClient`] component
+    ClientConnected(ClientId, Entity),
+    ClientDisconnected(ClientId),
+    /// This client has connected to the server
+    Connected(ClientId),
+    /// This client has disconnected from the server
+    Disconnected,
+}
+
+#[derive(Clone, Copy)]
+struct ReplicatedType {
+    get: fn(&World, Entity) -> Option<Box<dyn SerializableComponent>>,
+    remove: fn(&mut World, Entity),
+}
+
+/// Components, which are replicated. Both the server and the clients must register
+/// the same components. [`Transform`] is registered by default
+#[derive(Clone)]
+pub struct ReplicationRegistry {
+    types: BTreeMap<String, ReplicatedType>,
+}
+
+impl ReplicationRegistry {
+    pub fn new() -> Self {
+        ReplicationRegistry::default()
+    }
+
+    /// Registry without any components
+    pub fn empty() -> Self {
+        ReplicationRegistry {
+            types: BTreeMap::new(),
+        }
+    }
+
+    pub fn register<T: SerializableComponent + Clone>(&mut self) {
+        self.types.insert(pretty_type_name::<T>(), ReplicatedType {
+            get: get_component::<T>,
+            remove: remove_component::<T>,
+        });
+    }
+
+    pub fn with_component<T: SerializableComponent + Clone>(mut self) -> Self {
+        self.register::<T>();
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.types.keys().map(String::as_str)
+    }
+
+    /// Registered components of the entity in RON by their names
+    fn serialize(&self, world: &World, entity: Entity) -> BTreeMap<String, String> {
+        self.types
+            .iter()
+            .filter_map(|(name, replicated)| {
+                let component = (replicated.get)(world, entity)?;
+                match ron::to_string(&component) {
+                    Ok(component) => Some((name.clone(), component)),
+                    Err(e) => {
+                        error!("Cannot serialize replicated component `{name}`: {e}");
+                        None
+                    },
+                }
+            })
+            .collect()
+    }
+
+    fn remove(&self, world: &mut World, entity: Entity, name: &str) {
+        match self.types.get(name) {
+            Some(replicated) => (replicated.remove)(world, entity),
+            None => warn!("Replicated component `{name}` isn't registered"),
+        }
+    }
+}
+
+impl Default for ReplicationRegistry {
+    fn default() -> Self {
+        ReplicationRegistry::empty().with_component::<Transform>()
+    }
+}
+
+impl Debug for ReplicationRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+fn get_component<T: SerializableComponent + Clone>(world: &World, entity: Entity) -> Option<Box<dyn SerializableComponent>> {
+    let component = world.get::<&T>(entity).ok()?;
+    Some(Box::new((*component).clone()))
+}
+
+fn remove_component<T: SerializableComponent>(world: &mut World, entity: Entity) {
+    let _ = world.remove_one::<T>(entity);
+}
+
+/// Add the components in RON to the entity
+fn insert_components(world: &mut World, entity: Entity, components: &[String]) {
+    let mut builder = EntityBuilder::new();
+
+    for component in components {
+        match ron::from_str::<Box<dyn SerializableComponent>>(component) {
+            Ok(component) => component.add_into(&mut builder),
+            Err(e) => error!("Cannot deserialize replicated component: {e}"),
+        }
+    }
+
+    if let Err(e) = world.insert(entity, builder.build()) {
+        warn!("Cannot insert replicated components: {e}");
+    }
+}
+
+/// Server, which replicates entities with [`Replicated`] marker to the clients every send
+/// interval. Only the components, which have changed since the previous update, are sent,
+/// while connected clients receive the whole state. It's available as a resource
+#[derive(Debug)]
+pub struct NetServer {
+    server: Server,
+    registry: ReplicationRegistry,
+    /// Components in RON, which have been sent to the clients
+    baseline: HashMap<Entity, BTreeMap<String, String>>,
+    clients: HashMap<ClientId, Entity>,
+    tick: u64,
+    send_interval: Duration,
+    last_send: Instant,
+}
+
+impl NetServer {
+    pub fn bind<A: ToSocketAddrs>(addr: A, registry: ReplicationRegistry, send_interval: Duration) -> Result<Self, NetError> {
+        Ok(NetServer {
+            server: Server::bind(addr)?,
+            registry,
+            baseline: HashMap::new(),
+            clients: HashMap::new(),
+            tick: 0,
+            send_interval,
+            last_send: Instant::now(),
+        })
+    }
+
+    pub fn transport(&self) -> &Server {
+        &self.server
+    }
+
+    pub fn transport_mut(&mut self) -> &mut Server {
+        &mut self.server
+    }
+
+    /// Entity of the connected client
+    pub fn client_entity(&self, client: ClientId) -> Option<Entity> {
+        self.clients.get(&client).copied()
+    }
+
+    /// Handle connections and inputs of the clients and send changes of the replicated
+    /// entities, if the send interval has passed
+    pub fn update(&mut self, world: &mut World, events: &mut Events) {
+        for event in self.server.update() {
+            match event {
+                ServerEvent::Connected(client) => {
+                    // Changes are sent first, so the new client gets the same state as the others
+                    self.send_changes(world);
+
+                    let entity = world.spawn((RemoteClient(client),));
+                    self.clients.insert(client, entity);
+                    self.send_state(client);
+
+                    events.send(NetEvent::ClientConnected(client, entity));
+                },
+                ServerEvent::Disconnected(client) => {
+                    if let Some(entity) = self.clients.remove(&client) {
+                        let _ = world.despawn(entity);
+                    }
+
+                    events.send(NetEvent::ClientDisconnected(client));
+                },
+                ServerEvent::Message(client, Message::Input(components)) => {
+                    if let Some(&entity) = self.clients.get(&client) {
+                        insert_components(world, entity, &components);
+                    }
+                },
+                ServerEvent::Message(client, _) => {
+                    warn!("Client {client:?} has sent unexpected message");
+                },
+            }
+        }
+
+        if self.last_send.elapsed() >= self.send_interval {
+            self.send_changes(world);
+        }
+    }
+
+    /// Send changes of the replicated entities since the previous update to the clients
+    fn send_changes(&mut self, world: &World) {
+        self.last_send = Instant::now();
+
+        let current: HashMap<Entity, BTreeMap<String, String>> = world
+            .query::<()>()
+            .with::<&Replicated>()
+            .iter()
+            .map(|(entity, ())| (entity, self.registry.serialize(world, entity)))
+            .collect();
+
+        let despawned = self.baseline
+            .keys()
+            .filter(|entity| !current.contains_key(entity))
+            .map(|entity| entity.to_bits().get())
+            .collect();
+
+        let entities = current
+            .iter()
+            .filter_map(|(entity, components)| {
+                let previous = self.baseline.get(entity);
+                let update = EntityUpdate {
+                    id: entity.to_bits().get(),
+                    components: components
+                        .iter()
+                        .filter(|(name, component)| previous.and_then(|previous| previous.get(*name)) != Some(component))
+                        .map(|(_, component)| component.clone())
+                        .collect(),
+                    removed: previous
+                        .into_iter()
+                        .flat_map(|previous| previous.keys())
+                        .filter(|name| !components.contains_key(*name))
+                        .cloned()
+                        .collect(),
+                };
+
+                // New entities are sent even without components, so clients spawn them
+                let changed = previous.is_none() || !update.components.is_empty() || !update.removed.is_empty();
+                changed.then_some(update)
+            })
+            .collect();
+
+        self.baseline = current;
+        self.tick += 1;
+
+        for update in split_update(self.tick, entities, despawned) {
+            let clients: Vec<ClientId> = self.clients.keys().copied().collect();
+            for client in clients {
+                self.send(client, &update);
+            }
+        }
+    }
+
+    /// Send the replicated state to the client
+    fn send_state(&mut self, client: ClientId) {
+        let entities = self.baseline
+            .iter()
+            .map(|(entity, components)| EntityUpdate {
+                id: entity.to_bits().get(),
+                components: components.values().cloned().collect(),
+                removed: vec![],
+            })
+            .collect();
+
+        for update in split_update(self.tick, entities, vec![]) {
+            self.send(client, &update);
+        }
+    }
+
+    fn send(&mut self, client: ClientId, update: &ReplicationUpdate) {
+        if let Err(e) = self.server.send(client, &Message::Replication(update.clone())) {
+            error!("Cannot send replication update to client {client:?}: {e}");
+        }
+    }
+}
+
+/// Split the changes into updates of about [`MAX_MESSAGE_SIZE`]
+fn split_update(tick: u64, entities: Vec<EntityUpdate>, despawned: Vec<u64>) -> Vec<ReplicationUpdate> {
+    let mut updates = vec![ReplicationUpdate { tick, entities: vec![], despawned }];
+    let mut size = 0;
+
+    for entity in entities {
+        if size > 0 && size + entity.size() > MAX_MESSAGE_SIZE {
+            updates.push(ReplicationUpdate { tick, ..Default::default() });
+            size = 0;
+        }
+
+        size += entity.size();
+        updates.last_mut().unwrap().entities.push(entity);
+    }
+
+    updates.retain(|update| !update.is_empty());
+    updates
+}
+
+/// Client, which applies replicated entities of the server to the world. Replicated
+/// entities are spawned with [`NetworkId`]. It's available as a resource
+#[derive(Debug)]
+pub struct NetClient {
+    client: Client,
+    registry: ReplicationRegistry,
+    entities: HashMap<u64, Entity>,
+    tick: u64,
+}
+
+impl NetClient {
+    pub fn connect<A: ToSocketAddrs>(server_addr: A, registry: ReplicationRegistry) -> Result<Self, NetError> {
+        Ok(NetClient {
+            client: Client::connect(server_addr)?,
+            registry,
+            entities: HashMap::new(),
+            tick: 0,
+        })
+    }
+
+    pub fn transport(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn transport_mut(&mut self) -> &mut Client {
+        &mut self.client
+    }
+
+    /// Tick of the latest applied update
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Client entity, which replicates the server one with the id
+    pub fn entity(&self, id: NetworkId) -> Option<Entity> {
+        self.entities.get(&id.0).copied()
+    }
+
+    /// Send components to the server, which adds them to the entity of this client
+    pub fn send_input(&mut self, components: &[&dyn SerializableComponent]) -> Result<(), NetError> {
+        let components = components
+            .iter()
+            .map(ron::to_string)
+            .collect::<Result<_, _>>()?;
+
+        self.client.send(&Message::Input(components))
+    }
+
+    /// Receive and apply changes of the replicated entities
+    pub fn update(&mut self, world: &mut World, events: &mut Events) {
+        for event in self.client.update() {
+            match event {
+                ClientEvent::Connected(id) => events.send(NetEvent::Connected(id)),
+                ClientEvent::Disconnected => events.send(NetEvent::Disconnected),
+                ClientEvent::Message(Message::Replication(update)) => self.apply(world, update),
+                ClientEvent::Message(_) => warn!("Server has sent unexpected message"),
+            }
+        }
+    }
+
+    fn apply(&mut self, world: &mut World, update: ReplicationUpdate) {
+        self.tick = update.tick;
+
+        for id in update.despawned {
+            if let Some(entity) = self.entities.remove(&id) {
+                let _ = world.despawn(entity);
+            }
+        }
+
+        for update in update.entities {
+            let entity = *self.entities
+                .entry(update.id)
+                .or_insert_with(|| world.spawn((NetworkId(update.id),)));
+
+            insert_components(world, entity, &update.components);
+
+            for name in &update.removed {
+                self.registry.remove(world, entity, name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flatbox_core::math::glm;
+
+    use super::*;
+
+    fn update_until(
+        server: &mut (NetServer, World),
+        client: &mut (NetClient, World),
+        mut condition: impl FnMut(&NetClient, &World) -> bool,
+    ) {
+        let mut events = Events::new();
+        events.push_handler::<NetEvent>();
+
+        let started = Instant::now();
+
+        while !condition(&client.0, &client.1) {
+            assert!(started.elapsed() < Duration::from_secs(5), "Replication has timed out");
+
+            server.0.update(&mut server.1, &mut events);
+            client.0.update(&mut client.1, &mut events);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn changes_are_replicated() {
+        let mut server = (NetServer::bind("127.0.0.1:0", ReplicationRegistry::new(), Duration::ZERO).unwrap(), World::new());
+        let addr = server.0.transport().local_addr().unwrap();
+        let mut client = (NetClient::connect(addr, ReplicationRegistry::new()).unwrap(), World::new());
+
+        let entity = server.1.spawn((Replicated, Transform::identity(), 5u32));
+        let id = NetworkId(entity.to_bits().get());
+
+        let has_transform = |client: &NetClient, world: &World, scale: f32| {
+            client.entity(id).is_some_and(|entity| {
+                world.get::<&Transform>(entity).is_ok_and(|transform| transform.scale == scale)
+            })
+        };
+
+        update_until(&mut server, &mut client, |client, world| has_transform(client, world, 1.0));
+        assert!(client.1.get::<&u32>(client.0.entity(id).unwrap()).is_err());
+
+        server.1.get::<&mut Transform>(entity).unwrap().translation = glm::vec3(1.0, 2.0, 3.0);
+        server.1.get::<&mut Transform>(entity).unwrap().scale = 2.0;
+        update_until(&mut server, &mut client, |client, world| has_transform(client, world, 2.0));
+
+        server.1.remove_one::<Transform>(entity).unwrap();
+        update_until(&mut server, &mut client, |client, world| {
+            world.get::<&Transform>(client.entity(id).unwrap()).is_err()
+        });
+
+        server.1.despawn(entity).unwrap();
+        update_until(&mut server, &mut client, |client, _| client.entity(id).is_none());
+    }
+
+    #[test]
+    fn inputs_are_added_to_client_entity() {
+        let mut server = (NetServer::bind("127.0.0.1:0", ReplicationRegistry::new(), Duration::ZERO).unwrap(), World::new());
+        let addr = server.0.transport().local_addr().unwrap();
+        let mut client = (NetClient::connect(addr, ReplicationRegistry::new()).unwrap(), World::new());
+
+        update_until(&mut server, &mut client, |client, _| client.transport().is_connected());
+        client.0.send_input(&[&7u32]).unwrap();
+
+        let client_id = client.0.transport().id().unwrap();
+        let mut events = Events::new();
+        events.push_handler::<NetEvent>();
+
+        let started = Instant::now();
+        loop {
+            server.0.update(&mut server.1, &mut events);
+            client.0.update(&mut client.1, &mut events);
+
+            let input = server.0.client_entity(client_id)
+                .and_then(|entity| server.1.get::<&u32>(entity).ok().map(|input| *input));
+
+            if input == Some(7) {
+                break;
+            }
+
+            assert!(started.elapsed() < Duration::from_secs(5), "Input hasn't been received");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,489 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use flatbox_assets::ron;
+use flatbox_core::logger::warn;
+use serde::{Serialize, Deserialize};
+
+use crate::{error::NetError, protocol::{Message, Packet, PROTOCOL_VERSION}};
+
+/// Maximum size of a datagram, which can be sent over UDP
+pub const MAX_PACKET_SIZE: usize = 65507;
+/// Interval of resending unacknowledged messages and connection requests
+pub const RESEND_INTERVAL: Duration = Duration::from_millis(100);
+/// Interval of sending heartbeats to idle peers, so the connection doesn't time out
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+/// Time without packets from the peer, after which it's disconnected
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of messages, which can be received ahead of the missing one
+const MAX_BUFFERED_MESSAGES: u64 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ClientId(pub u64);
+
+/// Reliable ordered channel to a single peer
+#[derive(Debug)]
+pub(crate) struct Connection {
+    addr: SocketAddr,
+    next_send_id: u64,
+    /// Sent packets along with the time of the latest sending
+    unacked: BTreeMap<u64, (Vec<u8>, Instant)>,
+    next_receive_id: u64,
+    /// Messages, received ahead of the missing ones
+    buffered: BTreeMap<u64, Message>,
+    last_received: Instant,
+    last_sent: Instant,
+}
+
+impl Connection {
+    pub fn new(addr: SocketAddr) -> Self {
+        Connection {
+            addr,
+            next_send_id: 0,
+            unacked: BTreeMap::new(),
+            next_receive_id: 0,
+            buffered: BTreeMap::new(),
+            last_received: Instant::now(),
+            last_sent: Instant::now(),
+        }
+    }
+
+    pub fn send(&mut self, socket: &UdpSocket, message: &Message) -> Result<(), NetError> {
+        let id = self.next_send_id;
+        let packet = encode(&Packet::Message { id, message: message.clone() })?;
+
+        self.next_send_id += 1;
+        self.send_raw(socket, &packet);
+        self.unacked.insert(id, (packet, Instant::now()));
+
+        Ok(())
+    }
+
+    pub fn send_packet(&mut self, socket: &UdpSocket, packet: &Packet) {
+        match encode(packet) {
+            Ok(packet) => self.send_raw(socket, &packet),
+            Err(e) => warn!("Cannot send packet to {}: {e}", self.addr),
+        }
+    }
+
+    /// Handle the packet and return messages, which can be delivered in order
+    pub fn receive(&mut self, socket: &UdpSocket, packet: Packet) -> Vec<Message> {
+        self.last_received = Instant::now();
+
+        match packet {
+            Packet::Message { id, message } => {
+                // Acknowledge duplicates too, since the previous ack may be lost
+                self.send_packet(socket, &Packet::Ack { id });
+
+                if id >= self.next_receive_id && id < self.next_receive_id + MAX_BUFFERED_MESSAGES {
+                    self.buffered.insert(id, message);
+                }
+
+                let mut messages = vec![];
+                while let Some(message) = self.buffered.remove(&self.next_receive_id) {
+                    messages.push(message);
+                    self.next_receive_id += 1;
+                }
+
+                messages
+            },
+            Packet::Ack { id } => {
+                self.unacked.remove(&id);
+                vec![]
+            },
+            _ => vec![],
+        }
+    }
+
+    /// Resend unacknowledged messages and send a heartbeat, if the connection is idle
+    pub fn update(&mut self, socket: &UdpSocket) {
+        let now = Instant::now();
+        let mut resent = vec![];
+
+        for (packet, sent) in self.unacked.values_mut() {
+            if now.duration_since(*sent) >= RESEND_INTERVAL {
+                *sent = now;
+                resent.push(packet.clone());
+            }
+        }
+
+        for packet in resent {
+            self.send_raw(socket, &packet);
+        }
+
+        if now.duration_since(self.last_sent) >= HEARTBEAT_INTERVAL {
+            self.send_packet(socket, &Packet::Heartbeat);
+        }
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.last_received.elapsed() >= TIMEOUT
+    }
+
+    fn send_raw(&mut self, socket: &UdpSocket, packet: &[u8]) {
+        self.last_sent = Instant::now();
+
+        if let Err(e) = socket.send_to(packet, self.addr) {
+            warn!("Cannot send packet to {}: {e}", self.addr);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerEvent {
+    Connected(ClientId),
+    /// Client has disconnected or timed out
+    Disconnected(ClientId),
+    Message(ClientId, Message),
+}
+
+/// Server side of the transport. It's non-blocking, so [`Server::update`] must be called
+/// regularly to receive packets and keep connections alive
+#[derive(Debug)]
+pub struct Server {
+    socket: UdpSocket,
+    clients: HashMap<SocketAddr, (ClientId, Connection)>,
+    next_client_id: u64,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self, NetError> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Server {
+            socket,
+            clients: HashMap::new(),
+            next_client_id: 0,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    pub fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.clients.values().map(|(id, _)| *id)
+    }
+
+    pub fn send(&mut self, client: ClientId, message: &Message) -> Result<(), NetError> {
+        let (_, connection) = self.clients
+            .values_mut()
+            .find(|(id, _)| *id == client)
+            .ok_or(NetError::UnknownClient(client))?;
+
+        connection.send(&self.socket, message)
+    }
+
+    pub fn broadcast(&mut self, message: &Message) -> Result<(), NetError> {
+        for (_, connection) in self.clients.values_mut() {
+            connection.send(&self.socket, message)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self, client: ClientId) {
+        self.clients.retain(|_, (id, connection)| {
+            if *id == client {
+                connection.send_packet(&self.socket, &Packet::Disconnect);
+            }
+
+            *id != client
+        });
+    }
+
+    /// Receive packets, resend unacknowledged messages and drop timed out clients
+    pub fn update(&mut self) -> Vec<ServerEvent> {
+        let mut events = vec![];
+
+        for (addr, packet) in receive_packets(&self.socket) {
+            match packet {
+                Packet::Connect { version } => {
+                    if version != PROTOCOL_VERSION {
+                        warn!("Client {addr} has protocol version {version}, while {PROTOCOL_VERSION} is expected");
+                        send_packet(&self.socket, addr, &Packet::Disconnect);
+                        continue;
+                    }
+
+                    let (id, connection) = self.clients.entry(addr).or_insert_with(|| {
+                        let id = ClientId(self.next_client_id);
+                        self.next_client_id += 1;
+                        events.push(ServerEvent::Connected(id));
+
+                        (id, Connection::new(addr))
+                    });
+
+                    // Requests are repeated, until the client is accepted
+                    connection.send_packet(&self.socket, &Packet::Accept { client_id: id.0 });
+                },
+                Packet::Disconnect => {
+                    if let Some((id, _)) = self.clients.remove(&addr) {
+                        events.push(ServerEvent::Disconnected(id));
+                    }
+                },
+                packet => {
+                    if let Some((id, connection)) = self.clients.get_mut(&addr) {
+                        events.extend(connection
+                            .receive(&self.socket, packet)
+                            .into_iter()
+                            .map(|message| ServerEvent::Message(*id, message)));
+                    }
+                },
+            }
+        }
+
+        self.clients.retain(|_, (id, connection)| {
+            if connection.is_timed_out() {
+                events.push(ServerEvent::Disconnected(*id));
+                return false;
+            }
+
+            connection.update(&self.socket);
+            true
+        });
+
+        events
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        for (_, connection) in self.clients.values_mut() {
+            connection.send_packet(&self.socket, &Packet::Disconnect);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientEvent {
+    Connected(ClientId),
+    /// Server has rejected or closed the connection, or it has timed out
+    Disconnected,
+    Message(Message),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClientState {
+    Connecting { since: Instant, last_request: Instant },
+    Connected(ClientId),
+    Disconnected,
+}
+
+/// Client side of the transport. It's non-blocking, so [`Client::update`] must be called
+/// regularly to connect, receive packets and keep the connection alive
+#[derive(Debug)]
+pub struct Client {
+    socket: UdpSocket,
+    connection: Connection,
+    state: ClientState,
+}
+
+impl Client {
+    /// Start connecting to the server. [`ClientEvent::Connected`] is returned by
+    /// [`Client::update`], when the server accepts the connection
+    pub fn connect<A: ToSocketAddrs>(server_addr: A) -> Result<Self, NetError> {
+        let server_addr = server_addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| std::io::Error::new(ErrorKind::InvalidInput, "Server address is empty"))?;
+
+        let local_addr: SocketAddr = match server_addr {
+            SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+            SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+        };
+
+        let socket = UdpSocket::bind(local_addr)?;
+        socket.set_nonblocking(true)?;
+
+        let mut connection = Connection::new(server_addr);
+        connection.send_packet(&socket, &Packet::Connect { version: PROTOCOL_VERSION });
+
+        Ok(Client {
+            socket,
+            connection,
+            state: ClientState::Connecting {
+                since: Instant::now(),
+                last_request: Instant::now(),
+            },
+        })
+    }
+
+    pub fn server_addr(&self) -> SocketAddr {
+        self.connection.addr
+    }
+
+    /// Id of the client, which is assigned by the server on connection
+    pub fn id(&self) -> Option<ClientId> {
+        match self.state {
+            ClientState::Connected(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.id().is_some()
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError> {
+        if !self.is_connected() {
+            return Err(NetError::NotConnected);
+        }
+
+        self.connection.send(&self.socket, message)
+    }
+
+    pub fn disconnect(&mut self) {
+        if self.state != ClientState::Disconnected {
+            self.connection.send_packet(&self.socket, &Packet::Disconnect);
+            self.state = ClientState::Disconnected;
+        }
+    }
+
+    /// Receive packets, resend unacknowledged messages and connection requests
+    pub fn update(&mut self) -> Vec<ClientEvent> {
+        let mut events = vec![];
+
+        for (addr, packet) in receive_packets(&self.socket) {
+            if addr != self.connection.addr || self.state == ClientState::Disconnected {
+                continue;
+            }
+
+            match packet {
+                Packet::Accept { client_id } => {
+                    if let ClientState::Connecting { .. } = self.state {
+                        let id = ClientId(client_id);
+                        self.state = ClientState::Connected(id);
+                        events.push(ClientEvent::Connected(id));
+                    }
+                },
+                Packet::Disconnect => {
+                    self.state = ClientState::Disconnected;
+                    events.push(ClientEvent::Disconnected);
+                },
+                packet => {
+                    if self.is_connected() {
+                        events.extend(self.connection
+                            .receive(&self.socket, packet)
+                            .into_iter()
+                            .map(ClientEvent::Message));
+                    }
+                },
+            }
+        }
+
+        match &mut self.state {
+            ClientState::Connecting { since, last_request } => {
+                if since.elapsed() >= TIMEOUT {
+                    self.state = ClientState::Disconnected;
+                    events.push(ClientEvent::Disconnected);
+                } else if last_request.elapsed() >= RESEND_INTERVAL {
+                    *last_request = Instant::now();
+                    self.connection.send_packet(&self.socket, &Packet::Connect { version: PROTOCOL_VERSION });
+                }
+            },
+            ClientState::Connected(_) => {
+                if self.connection.is_timed_out() {
+                    self.state = ClientState::Disconnected;
+                    events.push(ClientEvent::Disconnected);
+                } else {
+                    self.connection.update(&self.socket);
+                }
+            },
+            ClientState::Disconnected => {},
+        }
+
+        events
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+fn encode(packet: &Packet) -> Result<Vec<u8>, NetError> {
+    let packet = ron::to_string(packet)?.into_bytes();
+
+    if packet.len() > MAX_PACKET_SIZE {
+        return Err(NetError::MessageTooLarge(packet.len()));
+    }
+
+    Ok(packet)
+}
+
+fn send_packet(socket: &UdpSocket, addr: SocketAddr, packet: &Packet) {
+    if let Err(e) = encode(packet).and_then(|packet| Ok(socket.send_to(&packet, addr)?)) {
+        warn!("Cannot send packet to {addr}: {e}");
+    }
+}
+
+/// Receive all pending packets. Malformed ones are dropped
+fn receive_packets(socket: &UdpSocket) -> Vec<(SocketAddr, Packet)> {
+    let mut packets = vec![];
+    let mut buffer = vec![0; MAX_PACKET_SIZE];
+
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((size, addr)) => {
+                if let Ok(packet) = ron::de::from_bytes(&buffer[..size]) {
+                    packets.push((addr, packet));
+                }
+            },
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            // Unreachable peers are reported on some platforms, e.g. by ICMP on Windows
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+            Err(e) => {
+                warn!("Cannot receive packets: {e}");
+                break;
+            },
+        }
+    }
+
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(text: &str) -> Message {
+        Message::Input(vec![text.to_owned()])
+    }
+
+    #[test]
+    fn messages_are_delivered_in_order() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut connection = Connection::new(socket.local_addr().unwrap());
+
+        assert!(connection.receive(&socket, Packet::Message { id: 1, message: input("b") }).is_empty());
+        assert_eq!(connection.receive(&socket, Packet::Message { id: 0, message: input("a") }), [input("a"), input("b")]);
+        assert!(connection.receive(&socket, Packet::Message { id: 1, message: input("b") }).is_empty());
+    }
+
+    #[test]
+    fn client_connects_and_sends_messages() {
+        let mut server = Server::bind("127.0.0.1:0").unwrap();
+        let mut client = Client::connect(server.local_addr().unwrap()).unwrap();
+
+        let mut server_events = vec![];
+        let started = Instant::now();
+
+        while server_events.len() < 2 && started.elapsed() < Duration::from_secs(5) {
+            if client.update().contains(&ClientEvent::Connected(ClientId(0))) {
+                client.send(&input("a")).unwrap();
+            }
+
+            server_events.extend(server.update());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(server_events, [
+            ServerEvent::Connected(ClientId(0)),
+            ServerEvent::Message(ClientId(0), input("a")),
+        ]);
+    }
+}
@@ -0,0 +1,410 @@
+use flatbox_core::math::{glm, transform::Transform};
+#[cfg(feature = "render")]
+use flatbox_render::pbr::mesh::Mesh;
+use rapier3d::{na::UnitQuaternion, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Descriptor of the rigid body, which is created for the entity with [`ColliderComponent`]
+/// and [`Transform`] on spawn. Changes of the descriptor after that aren't applied to the body;
+/// use [`PhysicsHandler::body_mut`](crate::PhysicsHandler::body_mut) instead
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RigidBodyComponent {
+    pub body_type: RigidBodyType,
+    pub linear_velocity: glm::Vec3,
+    pub angular_velocity: glm::Vec3,
+    pub gravity_scale: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// Continuous collision detection, which prevents fast bodies from tunneling through thin ones
+    pub ccd_enabled: bool,
+    /// Whether the body falls asleep, when it stays slower than [`SleepThresholds`],
+    /// so it isn't simulated until something wakes it up
+    pub can_sleep: bool,
+    /// Whether the body is created asleep, e.g. for the resting parts of large scenes
+    pub sleeping: bool,
+    pub sleep_thresholds: SleepThresholds,
+}
+
+impl RigidBodyComponent {
+    pub fn new(body_type: RigidBodyType) -> Self {
+        RigidBodyComponent {
+            body_type,
+            linear_velocity: glm::Vec3::zeros(),
+            angular_velocity: glm::Vec3::zeros(),
+            gravity_scale: 1.0,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            ccd_enabled: false,
+            can_sleep: true,
+            sleeping: false,
+            sleep_thresholds: SleepThresholds::default(),
+        }
+    }
+
+    /// Body, which is moved by forces and contacts
+    pub fn dynamic() -> Self {
+        RigidBodyComponent::new(RigidBodyType::Dynamic)
+    }
+
+    /// Body, which never moves by itself
+    pub fn fixed() -> Self {
+        RigidBodyComponent::new(RigidBodyType::Fixed)
+    }
+
+    /// Body, which follows its [`Transform`] and pushes dynamic bodies away
+    pub fn kinematic() -> Self {
+        RigidBodyComponent::new(RigidBodyType::KinematicPositionBased)
+    }
+
+    pub fn with_linear_velocity(mut self, linear_velocity: glm::Vec3) -> Self {
+        self.linear_velocity = linear_velocity;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, angular_velocity: glm::Vec3) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    pub fn with_gravity_scale(mut self, gravity_scale: f32) -> Self {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    pub fn with_damping(mut self, linear_damping: f32, angular_damping: f32) -> Self {
+        self.linear_damping = linear_damping;
+        self.angular_damping = angular_damping;
+        self
+    }
+
+    pub fn with_ccd(mut self, ccd_enabled: bool) -> Self {
+        self.ccd_enabled = ccd_enabled;
+        self
+    }
+
+    pub fn with_can_sleep(mut self, can_sleep: bool) -> Self {
+        self.can_sleep = can_sleep;
+        self
+    }
+
+    pub fn with_sleeping(mut self, sleeping: bool) -> Self {
+        self.sleeping = sleeping;
+        self
+    }
+
+    pub fn with_sleep_thresholds(mut self, sleep_thresholds: SleepThresholds) -> Self {
+        self.sleep_thresholds = sleep_thresholds;
+        self
+    }
+
+    /// Build rapier body, placed at the transform
+    pub fn build(&self, transform: &Transform) -> RigidBody {
+        let mut body = RigidBodyBuilder::new(self.body_type)
+            .position(to_isometry(transform))
+            .linvel(self.linear_velocity)
+            .angvel(self.angular_velocity)
+            .gravity_scale(self.gravity_scale)
+            .linear_damping(self.linear_damping)
+            .angular_damping(self.angular_damping)
+            .ccd_enabled(self.ccd_enabled)
+            .can_sleep(self.can_sleep)
+            .sleeping(self.sleeping)
+            .build();
+
+        if self.can_sleep {
+            self.sleep_thresholds.apply(body.activation_mut());
+        }
+
+        body
+    }
+}
+
+impl Default for RigidBodyComponent {
+    fn default() -> Self {
+        RigidBodyComponent::dynamic()
+    }
+}
+
+/// Velocities, which the body must stay below for `time_until_sleep` seconds to fall asleep
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SleepThresholds {
+    pub linear: f32,
+    pub angular: f32,
+    pub time_until_sleep: f32,
+}
+
+impl SleepThresholds {
+    pub fn new(linear: f32, angular: f32, time_until_sleep: f32) -> Self {
+        SleepThresholds { linear, angular, time_until_sleep }
+    }
+
+    pub fn apply(&self, activation: &mut RigidBodyActivation) {
+        activation.linear_threshold = self.linear;
+        activation.angular_threshold = self.angular;
+        activation.time_until_sleep = self.time_until_sleep;
+    }
+}
+
+impl Default for SleepThresholds {
+    fn default() -> Self {
+        SleepThresholds::new(
+            RigidBodyActivation::default_linear_threshold(),
+            RigidBodyActivation::default_angular_threshold(),
+            RigidBodyActivation::default_time_until_sleep(),
+        )
+    }
+}
+
+/// Velocity of the kinematic body, which moves it every fixed step, e.g. for moving platforms.
+/// Position-based bodies are moved along with their [`Transform`], so that the bodies
+/// standing on them are carried
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct KinematicVelocity {
+    pub linear: glm::Vec3,
+    /// Rotation axis scaled by the angular speed in radians per second
+    pub angular: glm::Vec3,
+}
+
+impl KinematicVelocity {
+    pub fn new(linear: glm::Vec3, angular: glm::Vec3) -> Self {
+        KinematicVelocity { linear, angular }
+    }
+
+    pub fn linear(linear: glm::Vec3) -> Self {
+        KinematicVelocity::new(linear, glm::Vec3::zeros())
+    }
+
+    pub fn angular(angular: glm::Vec3) -> Self {
+        KinematicVelocity::new(glm::Vec3::zeros(), angular)
+    }
+
+    /// Position, which is reached from `position` in `delta_time` seconds
+    pub fn integrate(&self, position: &Isometry<Real>, delta_time: Real) -> Isometry<Real> {
+        Isometry::from_parts(
+            (position.translation.vector + self.linear * delta_time).into(),
+            UnitQuaternion::new(self.angular * delta_time) * position.rotation,
+        )
+    }
+}
+
+/// Forces, applied to the dynamic body of the entity every fixed step. Impulses are applied
+/// once and cleared, so they can be accumulated by gameplay systems, e.g. jumps and explosions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExternalForces {
+    /// Force at the center of mass, applied until it's changed
+    pub force: glm::Vec3,
+    /// Torque, applied until it's changed
+    pub torque: glm::Vec3,
+    /// Impulse at the center of mass, applied at the next step
+    pub impulse: glm::Vec3,
+    /// Angular impulse, applied at the next step
+    pub torque_impulse: glm::Vec3,
+}
+
+impl ExternalForces {
+    pub fn new() -> Self {
+        ExternalForces::default()
+    }
+
+    pub fn with_force(mut self, force: glm::Vec3) -> Self {
+        self.force = force;
+        self
+    }
+
+    pub fn with_torque(mut self, torque: glm::Vec3) -> Self {
+        self.torque = torque;
+        self
+    }
+
+    pub fn add_force(&mut self, force: glm::Vec3) {
+        self.force += force;
+    }
+
+    pub fn add_torque(&mut self, torque: glm::Vec3) {
+        self.torque += torque;
+    }
+
+    pub fn apply_impulse(&mut self, impulse: glm::Vec3) {
+        self.impulse += impulse;
+    }
+
+    pub fn apply_torque_impulse(&mut self, torque_impulse: glm::Vec3) {
+        self.torque_impulse += torque_impulse;
+    }
+
+    /// Stop applying forces and drop the pending impulses
+    pub fn clear(&mut self) {
+        *self = ExternalForces::default();
+    }
+
+    /// Replace the forces of the body with the external ones and apply the pending impulses
+    pub fn apply(&mut self, body: &mut RigidBody) {
+        body.reset_forces(false);
+        body.reset_torques(false);
+
+        let wake_up = self.force != glm::Vec3::zeros() || self.torque != glm::Vec3::zeros();
+        body.add_force(self.force, wake_up);
+        body.add_torque(self.torque, wake_up);
+
+        if self.impulse != glm::Vec3::zeros() {
+            body.apply_impulse(self.impulse, true);
+        }
+
+        if self.torque_impulse != glm::Vec3::zeros() {
+            body.apply_torque_impulse(self.torque_impulse, true);
+        }
+
+        self.impulse = glm::Vec3::zeros();
+        self.torque_impulse = glm::Vec3::zeros();
+    }
+}
+
+/// Descriptor of the collider, which is attached to the body of [`RigidBodyComponent`].
+/// The shape isn't scaled by the [`Transform`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColliderComponent {
+    pub shape: SharedShape,
+    pub density: f32,
+    pub friction: f32,
+    pub restitution: f32,
+    /// Sensor detects intersections without contact forces, which are sent as
+    /// `TriggerEvents`, e.g. for pickups, zones and checkpoints
+    #[serde(default)]
+    pub sensor: bool,
+    /// Callbacks of [`PhysicsHandler::set_hooks`](crate::PhysicsHandler::set_hooks), which are called
+    /// for the contacts of the collider
+    #[serde(default)]
+    pub active_hooks: ActiveHooks,
+}
+
+impl ColliderComponent {
+    pub fn new(shape: SharedShape) -> Self {
+        ColliderComponent {
+            shape,
+            density: 1.0,
+            friction: 0.5,
+            restitution: 0.0,
+            sensor: false,
+            active_hooks: ActiveHooks::empty(),
+        }
+    }
+
+    pub fn ball(radius: f32) -> Self {
+        ColliderComponent::new(SharedShape::ball(radius))
+    }
+
+    pub fn cuboid(half_extents: glm::Vec3) -> Self {
+        ColliderComponent::new(SharedShape::cuboid(half_extents.x, half_extents.y, half_extents.z))
+    }
+
+    /// Capsule along Y axis
+    pub fn capsule(half_height: f32, radius: f32) -> Self {
+        ColliderComponent::new(SharedShape::capsule_y(half_height, radius))
+    }
+
+    /// Cylinder along Y axis
+    pub fn cylinder(half_height: f32, radius: f32) -> Self {
+        ColliderComponent::new(SharedShape::cylinder(half_height, radius))
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_sensor(mut self, sensor: bool) -> Self {
+        self.sensor = sensor;
+        self
+    }
+
+    pub fn with_active_hooks(mut self, active_hooks: ActiveHooks) -> Self {
+        self.active_hooks = active_hooks;
+        self
+    }
+
+    pub fn build(&self) -> Collider {
+        ColliderBuilder::new(self.shape.clone())
+            .density(self.density)
+            .friction(self.friction)
+            .restitution(self.restitution)
+            .sensor(self.sensor)
+            .active_hooks(self.active_hooks)
+            .active_events(ActiveEvents::COLLISION_EVENTS)
+            .build()
+    }
+}
+
+/// Colliders, built from meshes
+#[cfg(feature = "render")]
+impl ColliderComponent {
+    /// Triangle mesh with vertices and indices of the mesh, e.g. for static level geometry.
+    /// Returns `None`, if the mesh has no triangles
+    pub fn trimesh(mesh: &Mesh) -> Option<Self> {
+        let triangles = mesh_triangles(mesh);
+        if triangles.is_empty() {
+            return None;
+        }
+
+        Some(ColliderComponent::new(SharedShape::trimesh(mesh_vertices(mesh), triangles)))
+    }
+
+    /// Smallest convex shape, containing all vertices of the mesh.
+    /// Returns `None`, if the vertices are degenerate, e.g. lie in a plane
+    pub fn convex_hull(mesh: &Mesh) -> Option<Self> {
+        SharedShape::convex_hull(&mesh_vertices(mesh)).map(ColliderComponent::new)
+    }
+
+    /// Compound of convex parts, approximating the mesh, e.g. for concave dynamic bodies.
+    /// Decomposition is slow, so it should be done while loading. Returns `None`, if the mesh has no triangles
+    pub fn convex_decomposition(mesh: &Mesh) -> Option<Self> {
+        let triangles = mesh_triangles(mesh);
+        if triangles.is_empty() {
+            return None;
+        }
+
+        Some(ColliderComponent::new(SharedShape::convex_decomposition(&mesh_vertices(mesh), &triangles)))
+    }
+}
+
+#[cfg(feature = "render")]
+fn mesh_vertices(mesh: &Mesh) -> Vec<Point<Real>> {
+    mesh.vertex_data
+        .iter()
+        .map(|vertex| Point::from(vertex.position))
+        .collect()
+}
+
+/// Triangles of the mesh, skipping ones with invalid indices
+#[cfg(feature = "render")]
+fn mesh_triangles(mesh: &Mesh) -> Vec<[u32; 3]> {
+    mesh.index_data
+        .chunks_exact(3)
+        .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+        .filter(|triangle| triangle.iter().all(|&index| (index as usize) < mesh.vertex_data.len()))
+        .collect()
+}
+
+/// Position of the transform in the physics world. Physics is simulated in `f32`
+pub fn to_isometry(transform: &Transform) -> Isometry<Real> {
+    Isometry::from_parts(
+        transform.translation_f32().into(),
+        UnitQuaternion::new_normalize(transform.rotation_f32()),
+    )
+}
+
+/// Move the transform to the position of the physics world, keeping its scale
+pub fn apply_isometry(transform: &mut Transform, isometry: &Isometry<Real>) {
+    transform.translation = glm::convert(isometry.translation.vector);
+    transform.rotation = glm::convert(*isometry.rotation.quaternion());
+}
@@ -0,0 +1,509 @@
+use std::collections::HashMap;
+
+use flatbox_core::math::{glm, ray::Ray as EntityRay};
+use flatbox_ecs::{Entity, World};
+use rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{CollisionEvent as EntityCollisionEvent, CollisionKind, EventCollector, TriggerEnter, TriggerEvents, TriggerExit},
+    joints::PhysicsJointHandle,
+    query::{PointHit, RayHit},
+};
+
+/// Handles of the rigid body and its collider, inserted into [`PhysicsHandler`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PhysicsBodyHandle(pub RigidBodyHandle, pub ColliderHandle);
+
+/// Numbers of bodies in the physics world by their activity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IslandStats {
+    pub bodies: usize,
+    /// Dynamic bodies, which are simulated
+    pub active_dynamic_bodies: usize,
+    /// Kinematic bodies, which moved since the previous step
+    pub active_kinematic_bodies: usize,
+    pub sleeping_bodies: usize,
+}
+
+/// Singleton component with the physics world. `PhysicsExtension` of the app steps it
+/// every fixed update. It's serialized along with bodies, colliders and joints; after loading
+/// use [`PhysicsHandler::remap_entities`], if the entities were respawned with other ids,
+/// and [`PhysicsHandler::restore_handles`] to keep the loaded bodies instead of recreating them
+#[derive(Serialize, Deserialize)]
+pub struct PhysicsHandler {
+    pub gravity: Vector<Real>,
+    pub integration_parameters: IntegrationParameters,
+    /// Number of steps, which the fixed time step is divided into
+    pub substeps: u32,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+    /// Entities of the joints, stored like `user_data` of bodies
+    joint_entities: HashMap<ImpulseJointHandle, u128>,
+    #[serde(skip)]
+    physics_pipeline: PhysicsPipeline,
+    #[serde(skip)]
+    debug_render_pipeline: Option<DebugRenderPipeline>,
+    #[serde(skip)]
+    hooks: Option<Box<dyn PhysicsHooks>>,
+    #[serde(skip)]
+    event_handler: EventCollector,
+    #[serde(skip)]
+    collision_events: Vec<EntityCollisionEvent>,
+    #[serde(skip)]
+    trigger_events: TriggerEvents,
+    /// Entities and sensor flags of the colliders removed since the latest step, whose
+    /// contacts are stopped during the next step
+    #[serde(skip)]
+    removed_colliders: HashMap<ColliderHandle, (Entity, bool)>,
+}
+
+impl PhysicsHandler {
+    pub fn new() -> Self {
+        PhysicsHandler::default()
+    }
+
+    /// Advance the simulation by `delta_time` seconds
+    pub fn step(&mut self, delta_time: Real) {
+        let substeps = self.substeps.max(1);
+        self.integration_parameters.dt = delta_time / substeps as Real;
+
+        for _ in 0..substeps {
+            self.physics_pipeline.step(
+                &self.gravity,
+                &self.integration_parameters,
+                &mut self.islands,
+                &mut self.broad_phase,
+                &mut self.narrow_phase,
+                &mut self.bodies,
+                &mut self.colliders,
+                &mut self.impulse_joints,
+                &mut self.multibody_joints,
+                &mut self.ccd_solver,
+                Some(&mut self.query_pipeline),
+                self.hooks.as_deref().unwrap_or(&()),
+                &self.event_handler.collector,
+            );
+        }
+
+        while let Ok(event) = self.event_handler.collisions.try_recv() {
+            let kind = match (event.started(), event.sensor()) {
+                (true, false) => CollisionKind::Started,
+                (false, false) => CollisionKind::Stopped,
+                (true, true) => CollisionKind::IntersectionStarted,
+                (false, true) => CollisionKind::IntersectionStopped,
+            };
+
+            let (Some((a, a_sensor)), Some((b, b_sensor))) = (
+                self.collider_owner(event.collider1()),
+                self.collider_owner(event.collider2()),
+            ) else { continue };
+
+            self.collision_events.push(EntityCollisionEvent { a, b, kind });
+
+            let sensors = [(a, b, a_sensor), (b, a, b_sensor)];
+            for (sensor, other, _) in sensors.into_iter().filter(|&(_, _, is_sensor)| is_sensor) {
+                if event.started() {
+                    self.trigger_events.entered.push(TriggerEnter { sensor, other });
+                } else {
+                    self.trigger_events.exited.push(TriggerExit { sensor, other });
+                }
+            }
+        }
+
+        self.removed_colliders.clear();
+    }
+
+    /// Take collisions between entities, which occurred since the previous call
+    pub fn drain_collision_events(&mut self) -> Vec<EntityCollisionEvent> {
+        std::mem::take(&mut self.collision_events)
+    }
+
+    /// Take sensors, which were entered and left since the previous call
+    pub fn drain_trigger_events(&mut self) -> TriggerEvents {
+        std::mem::take(&mut self.trigger_events)
+    }
+
+    /// Entity of the existing or just removed collider and whether the collider is a sensor
+    fn collider_owner(&self, handle: ColliderHandle) -> Option<(Entity, bool)> {
+        match self.colliders.get(handle) {
+            Some(collider) => Some((entity_from_user_data(collider.user_data)?, collider.is_sensor())),
+            None => self.removed_colliders.get(&handle).copied(),
+        }
+    }
+
+    /// Set callbacks, which filter and modify contacts of the colliders with active hooks,
+    /// e.g. for one-way platforms or conveyor belts. Hooks aren't serialized
+    pub fn set_hooks(&mut self, hooks: Option<Box<dyn PhysicsHooks>>) {
+        self.hooks = hooks;
+    }
+
+    pub fn hooks(&self) -> Option<&dyn PhysicsHooks> {
+        self.hooks.as_deref()
+    }
+
+    /// Insert rigid body with the collider attached to it
+    pub fn insert(&mut self, body: impl Into<RigidBody>, collider: impl Into<Collider>) -> PhysicsBodyHandle {
+        let body_handle = self.bodies.insert(body);
+        let collider_handle = self.colliders.insert_with_parent(collider, body_handle, &mut self.bodies);
+
+        PhysicsBodyHandle(body_handle, collider_handle)
+    }
+
+    /// Insert rigid body with the collider, which belong to the entity. The entity is stored
+    /// in `user_data` of both, so it can be found by [`PhysicsHandler::entity`]
+    pub fn insert_entity(
+        &mut self,
+        entity: Entity,
+        body: impl Into<RigidBody>,
+        collider: impl Into<Collider>,
+    ) -> PhysicsBodyHandle {
+        let mut body = body.into();
+        let mut collider = collider.into();
+        body.user_data = entity_to_user_data(entity);
+        collider.user_data = entity_to_user_data(entity);
+
+        self.insert(body, collider)
+    }
+
+    /// Entity, which the collider belongs to
+    pub fn entity(&self, collider: ColliderHandle) -> Option<Entity> {
+        self.colliders.get(collider).and_then(|collider| entity_from_user_data(collider.user_data))
+    }
+
+    /// Entity, which the body belongs to
+    pub fn body_entity(&self, body: RigidBodyHandle) -> Option<Entity> {
+        self.bodies.get(body).and_then(|body| entity_from_user_data(body.user_data))
+    }
+
+    /// Remove rigid body along with its colliders and joints
+    pub fn remove(&mut self, handle: PhysicsBodyHandle) -> Option<RigidBody> {
+        self.remove_body(handle.0)
+    }
+
+    pub fn remove_body(&mut self, handle: RigidBodyHandle) -> Option<RigidBody> {
+        for &collider in self.bodies.get(handle)?.colliders() {
+            if let Some(owner) = self.collider_owner(collider) {
+                self.removed_colliders.insert(collider, owner);
+            }
+        }
+
+        let body = self.bodies.remove(
+            handle,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        );
+
+        let joints = &self.impulse_joints;
+        self.joint_entities.retain(|&joint, _| joints.contains(joint));
+
+        body
+    }
+
+    /// Replace entities of the bodies, colliders and joints, e.g. after a scene load, which respawned
+    /// them with other ids. Objects of the entities, which `remap` returns `None` for, are removed
+    /// without collision events. Objects, which were inserted manually, are kept
+    pub fn remap_entities(&mut self, mut remap: impl FnMut(Entity) -> Option<Entity>) {
+        let mut removed_colliders = vec![];
+        for (handle, collider) in self.colliders.iter_mut() {
+            match entity_from_user_data(collider.user_data).map(&mut remap) {
+                Some(Some(entity)) => collider.user_data = entity_to_user_data(entity),
+                Some(None) => {
+                    collider.user_data = 0;
+                    removed_colliders.push(handle);
+                },
+                None => {},
+            }
+        }
+
+        let mut removed_bodies = vec![];
+        for (handle, body) in self.bodies.iter_mut() {
+            match entity_from_user_data(body.user_data).map(&mut remap) {
+                Some(Some(entity)) => body.user_data = entity_to_user_data(entity),
+                Some(None) => removed_bodies.push(handle),
+                None => {},
+            }
+        }
+
+        let mut removed_joints = vec![];
+        for (&handle, user_data) in self.joint_entities.iter_mut() {
+            match entity_from_user_data(*user_data).map(&mut remap) {
+                Some(Some(entity)) => *user_data = entity_to_user_data(entity),
+                _ => removed_joints.push(handle),
+            }
+        }
+
+        for handle in removed_colliders {
+            self.colliders.remove(handle, &mut self.islands, &mut self.bodies, true);
+        }
+
+        for handle in removed_bodies {
+            self.remove_body(handle);
+        }
+
+        for handle in removed_joints {
+            self.remove_joint(handle);
+        }
+    }
+
+    /// Insert [`PhysicsBodyHandle`] and [`PhysicsJointHandle`] into the existing entities of the bodies
+    /// and joints, e.g. after loading the handler, so that the systems don't recreate them
+    pub fn restore_handles(&self, world: &mut World) {
+        for (handle, collider) in self.colliders.iter() {
+            let (Some(entity), Some(body)) = (entity_from_user_data(collider.user_data), collider.parent()) else {
+                continue;
+            };
+
+            let _ = world.insert_one(entity, PhysicsBodyHandle(body, handle));
+        }
+
+        for (&handle, &user_data) in &self.joint_entities {
+            let Some(entity) = entity_from_user_data(user_data) else { continue };
+            let _ = world.insert_one(entity, PhysicsJointHandle(handle));
+        }
+    }
+
+    /// Connect bodies with the joint, which belongs to the entity
+    pub fn insert_joint(
+        &mut self,
+        entity: Entity,
+        body1: RigidBodyHandle,
+        body2: RigidBodyHandle,
+        joint: impl Into<GenericJoint>,
+    ) -> ImpulseJointHandle {
+        let handle = self.impulse_joints.insert(body1, body2, joint, true);
+        self.joint_entities.insert(handle, entity_to_user_data(entity));
+
+        handle
+    }
+
+    pub fn remove_joint(&mut self, handle: ImpulseJointHandle) -> Option<ImpulseJoint> {
+        self.joint_entities.remove(&handle);
+        self.impulse_joints.remove(handle, true)
+    }
+
+    /// Entity, which the joint belongs to
+    pub fn joint_entity(&self, joint: ImpulseJointHandle) -> Option<Entity> {
+        self.joint_entities.get(&joint).and_then(|&user_data| entity_from_user_data(user_data))
+    }
+
+    pub fn joint(&self, handle: ImpulseJointHandle) -> Option<&ImpulseJoint> {
+        self.impulse_joints.get(handle)
+    }
+
+    pub fn joint_mut(&mut self, handle: ImpulseJointHandle) -> Option<&mut ImpulseJoint> {
+        self.impulse_joints.get_mut(handle)
+    }
+
+    /// Put the body to sleep, so it isn't simulated until it's woken up, e.g. by a contact
+    pub fn sleep(&mut self, handle: PhysicsBodyHandle) {
+        if let Some(body) = self.bodies.get_mut(handle.0) {
+            body.sleep();
+        }
+    }
+
+    /// Wake the body up. `strong` wakeup keeps it awake for some time, even if it doesn't move
+    pub fn wake_up(&mut self, handle: PhysicsBodyHandle, strong: bool) {
+        if self.bodies.contains(handle.0) {
+            self.islands.wake_up(&mut self.bodies, handle.0, strong);
+        }
+    }
+
+    pub fn is_sleeping(&self, handle: PhysicsBodyHandle) -> bool {
+        self.bodies.get(handle.0).is_some_and(|body| body.is_sleeping())
+    }
+
+    /// Numbers of active and sleeping bodies, e.g. to check that resting bodies don't cost simulation time
+    pub fn island_stats(&self) -> IslandStats {
+        IslandStats {
+            bodies: self.bodies.len(),
+            active_dynamic_bodies: self.islands.active_dynamic_bodies().len(),
+            active_kinematic_bodies: self.islands.active_kinematic_bodies().len(),
+            sleeping_bodies: self.bodies.iter().filter(|(_, body)| body.is_sleeping()).count(),
+        }
+    }
+
+    pub fn body(&self, handle: PhysicsBodyHandle) -> Option<&RigidBody> {
+        self.bodies.get(handle.0)
+    }
+
+    pub fn body_mut(&mut self, handle: PhysicsBodyHandle) -> Option<&mut RigidBody> {
+        self.bodies.get_mut(handle.0)
+    }
+
+    pub fn collider(&self, handle: PhysicsBodyHandle) -> Option<&Collider> {
+        self.colliders.get(handle.1)
+    }
+
+    pub fn collider_mut(&mut self, handle: PhysicsBodyHandle) -> Option<&mut Collider> {
+        self.colliders.get_mut(handle.1)
+    }
+
+    /// Find the nearest collider of an entity, hit by the ray within `max_distance`.
+    /// Colliders are tested at their positions after the latest step. Ray for mouse picking
+    /// can be made with `Camera::screen_to_ray`
+    pub fn cast_ray(&self, ray: &EntityRay, max_distance: f32, filter: QueryFilter) -> Option<RayHit> {
+        let user_predicate = filter.predicate;
+        let predicate = |handle, collider: &Collider| {
+            collider.user_data != 0 && user_predicate.is_none_or(|predicate| predicate(handle, collider))
+        };
+
+        let (collider, intersection) = self.query_pipeline.cast_ray_and_get_normal(
+            &self.bodies,
+            &self.colliders,
+            &Ray::new(ray.origin.into(), ray.direction),
+            max_distance,
+            true,
+            QueryFilter { predicate: Some(&predicate), ..filter },
+        )?;
+
+        Some(RayHit {
+            entity: self.entity(collider)?,
+            collider,
+            distance: intersection.toi,
+            point: ray.at(intersection.toi),
+            normal: intersection.normal,
+        })
+    }
+
+    /// Call `callback` with every collider of an entity, hit by the ray within `max_distance`,
+    /// in arbitrary order, until it returns `false`
+    pub fn intersections_with_ray(
+        &self,
+        ray: &EntityRay,
+        max_distance: f32,
+        filter: QueryFilter,
+        mut callback: impl FnMut(RayHit) -> bool,
+    ) {
+        self.query_pipeline.intersections_with_ray(
+            &self.bodies,
+            &self.colliders,
+            &Ray::new(ray.origin.into(), ray.direction),
+            max_distance,
+            true,
+            filter,
+            |collider, intersection| {
+                let Some(entity) = self.entity(collider) else { return true };
+
+                callback(RayHit {
+                    entity,
+                    collider,
+                    distance: intersection.toi,
+                    point: ray.at(intersection.toi),
+                    normal: intersection.normal,
+                })
+            },
+        );
+    }
+
+    /// Project the point on the nearest collider of an entity. If `solid` is `true`,
+    /// points inside colliders are projected onto themselves
+    pub fn project_point(&self, point: &glm::Vec3, solid: bool, filter: QueryFilter) -> Option<PointHit> {
+        let user_predicate = filter.predicate;
+        let predicate = |handle, collider: &Collider| {
+            collider.user_data != 0 && user_predicate.is_none_or(|predicate| predicate(handle, collider))
+        };
+
+        let (collider, projection) = self.query_pipeline.project_point(
+            &self.bodies,
+            &self.colliders,
+            &(*point).into(),
+            solid,
+            QueryFilter { predicate: Some(&predicate), ..filter },
+        )?;
+
+        Some(PointHit {
+            entity: self.entity(collider)?,
+            collider,
+            point: projection.point.coords,
+            is_inside: projection.is_inside,
+        })
+    }
+
+    /// Set pipeline, which draws debug shapes with [`PhysicsHandler::debug_render`]. `None` disables it
+    pub fn set_debug_renderer(&mut self, pipeline: Option<DebugRenderPipeline>) {
+        self.debug_render_pipeline = pipeline;
+    }
+
+    pub fn debug_renderer(&self) -> Option<&DebugRenderPipeline> {
+        self.debug_render_pipeline.as_ref()
+    }
+
+    /// Draw colliders, bodies, joints and contacts with the backend, if the debug renderer is set
+    pub fn debug_render(&mut self, backend: &mut impl DebugRenderBackend) {
+        let Some(pipeline) = self.debug_render_pipeline.as_mut() else { return };
+
+        pipeline.render(
+            backend,
+            &self.bodies,
+            &self.colliders,
+            &self.impulse_joints,
+            &self.multibody_joints,
+            &self.narrow_phase,
+        );
+    }
+
+    pub fn bodies(&self) -> &RigidBodySet {
+        &self.bodies
+    }
+
+    pub fn colliders(&self) -> &ColliderSet {
+        &self.colliders
+    }
+
+    pub fn impulse_joints(&self) -> &ImpulseJointSet {
+        &self.impulse_joints
+    }
+}
+
+impl Default for PhysicsHandler {
+    fn default() -> Self {
+        PhysicsHandler {
+            gravity: vector![0.0, -9.81, 0.0],
+            integration_parameters: IntegrationParameters::default(),
+            substeps: 1,
+            islands: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            joint_entities: HashMap::new(),
+            physics_pipeline: PhysicsPipeline::new(),
+            debug_render_pipeline: None,
+            hooks: None,
+            event_handler: EventCollector::default(),
+            collision_events: vec![],
+            trigger_events: TriggerEvents::default(),
+            removed_colliders: HashMap::new(),
+        }
+    }
+}
+
+/// Bodies and colliders of entities store `Entity::to_bits`, which is never zero,
+/// so `user_data` of the ones inserted manually stays zero
+fn entity_to_user_data(entity: Entity) -> u128 {
+    entity.to_bits().get() as u128
+}
+
+fn entity_from_user_data(user_data: u128) -> Option<Entity> {
+    Entity::from_bits(user_data as u64)
+}
+
+/// Entity, which the collider belongs to, e.g. for the contexts of [`PhysicsHooks`]
+pub fn collider_entity(collider: &Collider) -> Option<Entity> {
+    entity_from_user_data(collider.user_data)
+}
@@ -0,0 +1,311 @@
+use flatbox_core::math::glm;
+use flatbox_ecs::Entity;
+use rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Handle of the joint, which is created by the app for the entity with a [`JointComponent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PhysicsJointHandle(pub ImpulseJointHandle);
+
+/// Component, which connects rigid bodies of two entities. The joint is created, when both
+/// entities have bodies, and removed with the entity, its [`PhysicsJointHandle`] or any of the bodies
+pub trait JointComponent: Send + Sync + 'static {
+    /// Entities, whose bodies are connected
+    fn bodies(&self) -> (Entity, Entity);
+
+    fn build(&self) -> GenericJoint;
+}
+
+/// Motor, which drives the joint along its axis towards the target position and velocity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MotorSettings {
+    pub target_position: f32,
+    pub target_velocity: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub max_force: f32,
+}
+
+impl MotorSettings {
+    /// Motor, which keeps the velocity
+    pub fn velocity(target_velocity: f32, damping: f32) -> Self {
+        MotorSettings {
+            target_velocity,
+            damping,
+            ..Default::default()
+        }
+    }
+
+    /// Spring, which pulls the joint to the position
+    pub fn position(target_position: f32, stiffness: f32, damping: f32) -> Self {
+        MotorSettings {
+            target_position,
+            stiffness,
+            damping,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_force(mut self, max_force: f32) -> Self {
+        self.max_force = max_force;
+        self
+    }
+
+    fn apply(&self, joint: &mut GenericJoint, axis: JointAxis) {
+        joint
+            .set_motor(axis, self.target_position, self.target_velocity, self.stiffness, self.damping)
+            .set_motor_max_force(axis, self.max_force);
+    }
+}
+
+impl Default for MotorSettings {
+    fn default() -> Self {
+        MotorSettings {
+            target_position: 0.0,
+            target_velocity: 0.0,
+            stiffness: 0.0,
+            damping: 0.0,
+            max_force: f32::MAX,
+        }
+    }
+}
+
+/// Joint, which keeps the bodies at the anchors without relative motion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedJoint {
+    pub body1: Entity,
+    pub body2: Entity,
+    /// Anchors in local space of the bodies
+    pub anchor1: glm::Vec3,
+    pub anchor2: glm::Vec3,
+}
+
+impl FixedJoint {
+    pub fn new(body1: Entity, body2: Entity) -> Self {
+        FixedJoint {
+            body1,
+            body2,
+            anchor1: glm::Vec3::zeros(),
+            anchor2: glm::Vec3::zeros(),
+        }
+    }
+
+    pub fn with_anchors(mut self, anchor1: glm::Vec3, anchor2: glm::Vec3) -> Self {
+        self.anchor1 = anchor1;
+        self.anchor2 = anchor2;
+        self
+    }
+}
+
+impl JointComponent for FixedJoint {
+    fn bodies(&self) -> (Entity, Entity) {
+        (self.body1, self.body2)
+    }
+
+    fn build(&self) -> GenericJoint {
+        FixedJointBuilder::new()
+            .local_anchor1(self.anchor1.into())
+            .local_anchor2(self.anchor2.into())
+            .build()
+            .into()
+    }
+}
+
+/// Hinge, which lets the bodies rotate around the axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RevoluteJoint {
+    pub body1: Entity,
+    pub body2: Entity,
+    pub anchor1: glm::Vec3,
+    pub anchor2: glm::Vec3,
+    pub axis: glm::Vec3,
+    /// Minimum and maximum angle in radians
+    pub limits: Option<[f32; 2]>,
+    pub motor: Option<MotorSettings>,
+}
+
+impl RevoluteJoint {
+    pub fn new(body1: Entity, body2: Entity, axis: glm::Vec3) -> Self {
+        RevoluteJoint {
+            body1,
+            body2,
+            anchor1: glm::Vec3::zeros(),
+            anchor2: glm::Vec3::zeros(),
+            axis,
+            limits: None,
+            motor: None,
+        }
+    }
+
+    pub fn with_anchors(mut self, anchor1: glm::Vec3, anchor2: glm::Vec3) -> Self {
+        self.anchor1 = anchor1;
+        self.anchor2 = anchor2;
+        self
+    }
+
+    pub fn with_limits(mut self, min: f32, max: f32) -> Self {
+        self.limits = Some([min, max]);
+        self
+    }
+
+    pub fn with_motor(mut self, motor: MotorSettings) -> Self {
+        self.motor = Some(motor);
+        self
+    }
+}
+
+impl JointComponent for RevoluteJoint {
+    fn bodies(&self) -> (Entity, Entity) {
+        (self.body1, self.body2)
+    }
+
+    fn build(&self) -> GenericJoint {
+        let mut joint: GenericJoint = RevoluteJointBuilder::new(UnitVector::new_normalize(self.axis))
+            .local_anchor1(self.anchor1.into())
+            .local_anchor2(self.anchor2.into())
+            .build()
+            .into();
+
+        if let Some(limits) = self.limits {
+            joint.set_limits(JointAxis::AngX, limits);
+        }
+
+        if let Some(motor) = self.motor {
+            motor.apply(&mut joint, JointAxis::AngX);
+        }
+
+        joint
+    }
+}
+
+/// Slider, which lets the bodies move along the axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrismaticJoint {
+    pub body1: Entity,
+    pub body2: Entity,
+    pub anchor1: glm::Vec3,
+    pub anchor2: glm::Vec3,
+    pub axis: glm::Vec3,
+    /// Minimum and maximum distance
+    pub limits: Option<[f32; 2]>,
+    pub motor: Option<MotorSettings>,
+}
+
+impl PrismaticJoint {
+    pub fn new(body1: Entity, body2: Entity, axis: glm::Vec3) -> Self {
+        PrismaticJoint {
+            body1,
+            body2,
+            anchor1: glm::Vec3::zeros(),
+            anchor2: glm::Vec3::zeros(),
+            axis,
+            limits: None,
+            motor: None,
+        }
+    }
+
+    pub fn with_anchors(mut self, anchor1: glm::Vec3, anchor2: glm::Vec3) -> Self {
+        self.anchor1 = anchor1;
+        self.anchor2 = anchor2;
+        self
+    }
+
+    pub fn with_limits(mut self, min: f32, max: f32) -> Self {
+        self.limits = Some([min, max]);
+        self
+    }
+
+    pub fn with_motor(mut self, motor: MotorSettings) -> Self {
+        self.motor = Some(motor);
+        self
+    }
+}
+
+impl JointComponent for PrismaticJoint {
+    fn bodies(&self) -> (Entity, Entity) {
+        (self.body1, self.body2)
+    }
+
+    fn build(&self) -> GenericJoint {
+        let mut joint: GenericJoint = PrismaticJointBuilder::new(UnitVector::new_normalize(self.axis))
+            .local_anchor1(self.anchor1.into())
+            .local_anchor2(self.anchor2.into())
+            .build()
+            .into();
+
+        if let Some(limits) = self.limits {
+            joint.set_limits(JointAxis::X, limits);
+        }
+
+        if let Some(motor) = self.motor {
+            motor.apply(&mut joint, JointAxis::X);
+        }
+
+        joint
+    }
+}
+
+/// Ball joint, which lets the bodies rotate freely around the anchors
+#[derive(Debug, Clone, PartialEq)]
+pub struct SphericalJoint {
+    pub body1: Entity,
+    pub body2: Entity,
+    pub anchor1: glm::Vec3,
+    pub anchor2: glm::Vec3,
+    /// Minimum and maximum angles around angular axes
+    pub limits: Vec<(JointAxis, [f32; 2])>,
+    pub motors: Vec<(JointAxis, MotorSettings)>,
+}
+
+impl SphericalJoint {
+    pub fn new(body1: Entity, body2: Entity) -> Self {
+        SphericalJoint {
+            body1,
+            body2,
+            anchor1: glm::Vec3::zeros(),
+            anchor2: glm::Vec3::zeros(),
+            limits: vec![],
+            motors: vec![],
+        }
+    }
+
+    pub fn with_anchors(mut self, anchor1: glm::Vec3, anchor2: glm::Vec3) -> Self {
+        self.anchor1 = anchor1;
+        self.anchor2 = anchor2;
+        self
+    }
+
+    pub fn with_limits(mut self, axis: JointAxis, min: f32, max: f32) -> Self {
+        self.limits.push((axis, [min, max]));
+        self
+    }
+
+    pub fn with_motor(mut self, axis: JointAxis, motor: MotorSettings) -> Self {
+        self.motors.push((axis, motor));
+        self
+    }
+}
+
+impl JointComponent for SphericalJoint {
+    fn bodies(&self) -> (Entity, Entity) {
+        (self.body1, self.body2)
+    }
+
+    fn build(&self) -> GenericJoint {
+        let mut joint: GenericJoint = SphericalJointBuilder::new()
+            .local_anchor1(self.anchor1.into())
+            .local_anchor2(self.anchor2.into())
+            .build()
+            .into();
+
+        for &(axis, limits) in &self.limits {
+            joint.set_limits(axis, limits);
+        }
+
+        for (axis, motor) in &self.motors {
+            motor.apply(&mut joint, *axis);
+        }
+
+        joint
+    }
+}
@@ -0,0 +1,121 @@
+use rapier3d::prelude::{Group, InteractionGroups};
+use serde::{Deserialize, Serialize};
+
+/// Maximal number of collision layers, one per bit of the mask
+pub const MAX_COLLISION_LAYERS: usize = 32;
+
+/// Singleton component with names of collision layers. Each name is given the next bit
+/// of the layer mask, e.g. `player`, `enemy`, `bullet`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollisionLayerNames {
+    names: Vec<String>,
+}
+
+impl CollisionLayerNames {
+    pub fn new() -> Self {
+        CollisionLayerNames::default()
+    }
+
+    /// Add named layer. Panics, if there are already [`MAX_COLLISION_LAYERS`] layers
+    pub fn with_layer(mut self, name: impl Into<String>) -> Self {
+        self.add_layer(name).expect("Too many collision layers");
+        self
+    }
+
+    /// Add named layer and get its bit mask. Existing layer keeps its bit.
+    /// Returns `None`, if there are already [`MAX_COLLISION_LAYERS`] layers
+    pub fn add_layer(&mut self, name: impl Into<String>) -> Option<u32> {
+        let name = name.into();
+
+        if let Some(layer) = self.layer(&name) {
+            return Some(layer);
+        }
+
+        if self.names.len() == MAX_COLLISION_LAYERS {
+            return None;
+        }
+
+        self.names.push(name);
+        Some(1 << (self.names.len() - 1))
+    }
+
+    /// Bit mask of the named layer
+    pub fn layer(&self, name: &str) -> Option<u32> {
+        self.names
+            .iter()
+            .position(|layer| layer == name)
+            .map(|index| 1 << index)
+    }
+
+    /// Bit mask of all the named layers. Returns `None`, if any of them doesn't exist
+    pub fn mask(&self, names: &[&str]) -> Option<u32> {
+        names
+            .iter()
+            .try_fold(0, |mask, name| Some(mask | self.layer(name)?))
+    }
+
+    /// Names of the layers in order of their bits
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+/// Layers, which the collider belongs to (`memberships`) and layers, which it collides
+/// with (`filter`). Two colliders interact only if each of them is in the filter of the other one,
+/// e.g. bullets don't collide with each other, when `bullet` isn't in their filter.
+/// Colliders without the component belong to and collide with all layers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CollisionLayers {
+    pub memberships: u32,
+    pub filter: u32,
+}
+
+impl CollisionLayers {
+    pub fn new(memberships: u32, filter: u32) -> Self {
+        CollisionLayers { memberships, filter }
+    }
+
+    /// Belong to and collide with all layers
+    pub fn all() -> Self {
+        CollisionLayers::new(u32::MAX, u32::MAX)
+    }
+
+    /// Collide with nothing
+    pub fn none() -> Self {
+        CollisionLayers::new(0, 0)
+    }
+
+    /// Layers of the names from [`CollisionLayerNames`]. Returns `None`, if any of them doesn't exist
+    pub fn named(names: &CollisionLayerNames, memberships: &[&str], filter: &[&str]) -> Option<Self> {
+        Some(CollisionLayers::new(names.mask(memberships)?, names.mask(filter)?))
+    }
+
+    pub fn with_memberships(mut self, memberships: u32) -> Self {
+        self.memberships = memberships;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: u32) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn interaction_groups(&self) -> InteractionGroups {
+        InteractionGroups::new(
+            Group::from_bits_truncate(self.memberships),
+            Group::from_bits_truncate(self.filter),
+        )
+    }
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        CollisionLayers::all()
+    }
+}
+
+impl From<CollisionLayers> for InteractionGroups {
+    fn from(layers: CollisionLayers) -> Self {
+        layers.interaction_groups()
+    }
+}
@@ -0,0 +1,14 @@
+pub mod components;
+pub mod events;
+pub mod handler;
+pub mod joints;
+pub mod layers;
+pub mod query;
+
+pub use components::*;
+pub use events::*;
+pub use handler::*;
+pub use joints::*;
+pub use layers::*;
+pub use query::*;
+pub use rapier3d;
@@ -0,0 +1,24 @@
+use flatbox_ecs::Entity;
+use rapier3d::prelude::ColliderHandle;
+use flatbox_core::math::glm;
+
+/// Intersection of the ray with the collider of the entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    pub collider: ColliderHandle,
+    /// Distance along the ray
+    pub distance: f32,
+    pub point: glm::Vec3,
+    pub normal: glm::Vec3,
+}
+
+/// Projection of the point on the nearest collider of the entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointHit {
+    pub entity: Entity,
+    pub collider: ColliderHandle,
+    pub point: glm::Vec3,
+    /// Whether the projected point is inside the collider
+    pub is_inside: bool,
+}
@@ -0,0 +1,169 @@
+use flatbox_core::math::{glm, transform::{Real as TransformReal, Transform}};
+use rapier2d::{na::UnitQuaternion, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Descriptor of the 2D rigid body, which is created for the entity with [`ColliderComponent`]
+/// and [`Transform`] on spawn. Changes of the descriptor after that aren't applied to the body;
+/// use [`PhysicsHandler::body_mut`](crate::PhysicsHandler::body_mut) instead
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RigidBodyComponent {
+    pub body_type: RigidBodyType,
+    pub linear_velocity: glm::Vec2,
+    /// Angular velocity around Z axis in radians per second
+    pub angular_velocity: f32,
+    pub gravity_scale: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// Continuous collision detection, which prevents fast bodies from tunneling through thin ones
+    pub ccd_enabled: bool,
+}
+
+impl RigidBodyComponent {
+    pub fn new(body_type: RigidBodyType) -> Self {
+        RigidBodyComponent {
+            body_type,
+            linear_velocity: glm::Vec2::zeros(),
+            angular_velocity: 0.0,
+            gravity_scale: 1.0,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            ccd_enabled: false,
+        }
+    }
+
+    /// Body, which is moved by forces and contacts
+    pub fn dynamic() -> Self {
+        RigidBodyComponent::new(RigidBodyType::Dynamic)
+    }
+
+    /// Body, which never moves by itself
+    pub fn fixed() -> Self {
+        RigidBodyComponent::new(RigidBodyType::Fixed)
+    }
+
+    /// Body, which follows its [`Transform`] and pushes dynamic bodies away
+    pub fn kinematic() -> Self {
+        RigidBodyComponent::new(RigidBodyType::KinematicPositionBased)
+    }
+
+    pub fn with_linear_velocity(mut self, linear_velocity: glm::Vec2) -> Self {
+        self.linear_velocity = linear_velocity;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, angular_velocity: f32) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    pub fn with_gravity_scale(mut self, gravity_scale: f32) -> Self {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    pub fn with_damping(mut self, linear_damping: f32, angular_damping: f32) -> Self {
+        self.linear_damping = linear_damping;
+        self.angular_damping = angular_damping;
+        self
+    }
+
+    pub fn with_ccd(mut self, ccd_enabled: bool) -> Self {
+        self.ccd_enabled = ccd_enabled;
+        self
+    }
+
+    /// Build rapier body, placed at the transform
+    pub fn build(&self, transform: &Transform) -> RigidBody {
+        RigidBodyBuilder::new(self.body_type)
+            .position(to_isometry(transform))
+            .linvel(self.linear_velocity)
+            .angvel(self.angular_velocity)
+            .gravity_scale(self.gravity_scale)
+            .linear_damping(self.linear_damping)
+            .angular_damping(self.angular_damping)
+            .ccd_enabled(self.ccd_enabled)
+            .build()
+    }
+}
+
+impl Default for RigidBodyComponent {
+    fn default() -> Self {
+        RigidBodyComponent::dynamic()
+    }
+}
+
+/// Descriptor of the 2D collider, which is attached to the body of [`RigidBodyComponent`].
+/// The shape isn't scaled by the [`Transform`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColliderComponent {
+    pub shape: SharedShape,
+    pub density: f32,
+    pub friction: f32,
+    pub restitution: f32,
+}
+
+impl ColliderComponent {
+    pub fn new(shape: SharedShape) -> Self {
+        ColliderComponent {
+            shape,
+            density: 1.0,
+            friction: 0.5,
+            restitution: 0.0,
+        }
+    }
+
+    pub fn ball(radius: f32) -> Self {
+        ColliderComponent::new(SharedShape::ball(radius))
+    }
+
+    pub fn cuboid(half_extents: glm::Vec2) -> Self {
+        ColliderComponent::new(SharedShape::cuboid(half_extents.x, half_extents.y))
+    }
+
+    /// Capsule along Y axis
+    pub fn capsule(half_height: f32, radius: f32) -> Self {
+        ColliderComponent::new(SharedShape::capsule_y(half_height, radius))
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn build(&self) -> Collider {
+        ColliderBuilder::new(self.shape.clone())
+            .density(self.density)
+            .friction(self.friction)
+            .restitution(self.restitution)
+            .active_events(ActiveEvents::COLLISION_EVENTS)
+            .build()
+    }
+}
+
+/// Position of the transform in XY plane. Rotation is projected on Z axis
+pub fn to_isometry(transform: &Transform) -> Isometry<Real> {
+    let translation = transform.translation_f32();
+    let (_, _, angle) = UnitQuaternion::new_normalize(transform.rotation_f32()).euler_angles();
+
+    Isometry::new(translation.xy(), angle)
+}
+
+/// Move the transform to the position in XY plane, keeping its Z coordinate and scale
+pub fn apply_isometry(transform: &mut Transform, isometry: &Isometry<Real>) {
+    let translation: glm::TVec2<TransformReal> = glm::convert(isometry.translation.vector);
+    let rotation = glm::quat_angle_axis(isometry.rotation.angle(), &glm::Vec3::z());
+
+    transform.translation.x = translation.x;
+    transform.translation.y = translation.y;
+    transform.rotation = glm::convert(rotation);
+}
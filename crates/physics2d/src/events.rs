@@ -0,0 +1,81 @@
+use flatbox_ecs::Entity;
+use rapier2d::{
+    crossbeam::channel::{self, Receiver},
+    prelude::{ChannelEventCollector, CollisionEvent as RapierCollisionEvent, ContactForceEvent},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CollisionKind {
+    /// Colliders started touching
+    Started,
+    /// Colliders stopped touching, or one of them was removed
+    Stopped,
+    /// Colliders, at least one of which is a sensor, started overlapping
+    IntersectionStarted,
+    /// Colliders, at least one of which is a sensor, stopped overlapping
+    IntersectionStopped,
+}
+
+/// Contact or intersection of colliders of two entities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollisionEvent {
+    pub a: Entity,
+    pub b: Entity,
+    pub kind: CollisionKind,
+}
+
+impl CollisionEvent {
+    /// Whether the entity takes part in the collision
+    pub fn involves(&self, entity: Entity) -> bool {
+        self.a == entity || self.b == entity
+    }
+
+    /// The other entity of the collision, if `entity` takes part in it
+    pub fn other(&self, entity: Entity) -> Option<Entity> {
+        if self.a == entity {
+            Some(self.b)
+        } else if self.b == entity {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+}
+
+/// ECS event with collisions of a fixed update in order of occurrence
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollisionEvents(pub Vec<CollisionEvent>);
+
+impl CollisionEvents {
+    pub fn iter(&self) -> impl Iterator<Item = &CollisionEvent> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Rapier event handler, which collects collision events into a channel
+pub(crate) struct EventCollector {
+    pub collector: ChannelEventCollector,
+    pub collisions: Receiver<RapierCollisionEvent>,
+    _contact_forces: Receiver<ContactForceEvent>,
+}
+
+impl Default for EventCollector {
+    fn default() -> Self {
+        let (collision_sender, collisions) = channel::unbounded();
+        let (contact_force_sender, contact_forces) = channel::unbounded();
+
+        EventCollector {
+            collector: ChannelEventCollector::new(collision_sender, contact_force_sender),
+            collisions,
+            _contact_forces: contact_forces,
+        }
+    }
+}
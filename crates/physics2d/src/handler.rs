@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use flatbox_core::math::glm;
+use flatbox_ecs::Entity;
+use rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{CollisionEvent as EntityCollisionEvent, CollisionKind, EventCollector},
+    query::{PointHit, RayHit},
+};
+
+/// Handles of the rigid body and its collider, inserted into [`PhysicsHandler`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PhysicsBodyHandle(pub RigidBodyHandle, pub ColliderHandle);
+
+/// Singleton component with the 2D physics world. `Physics2dExtension` of the app steps it
+/// every fixed update
+#[derive(Serialize, Deserialize)]
+pub struct PhysicsHandler {
+    pub gravity: Vector<Real>,
+    pub integration_parameters: IntegrationParameters,
+    /// Number of steps, which the fixed time step is divided into
+    pub substeps: u32,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+    #[serde(skip)]
+    physics_pipeline: PhysicsPipeline,
+    #[serde(skip)]
+    debug_render_pipeline: Option<DebugRenderPipeline>,
+    #[serde(skip)]
+    event_handler: EventCollector,
+    #[serde(skip)]
+    collision_events: Vec<EntityCollisionEvent>,
+    /// Entities of the colliders removed since the latest step, whose contacts are
+    /// stopped during the next step
+    #[serde(skip)]
+    removed_colliders: HashMap<ColliderHandle, Entity>,
+}
+
+impl PhysicsHandler {
+    pub fn new() -> Self {
+        PhysicsHandler::default()
+    }
+
+    /// Advance the simulation by `delta_time` seconds
+    pub fn step(&mut self, delta_time: Real) {
+        let substeps = self.substeps.max(1);
+        self.integration_parameters.dt = delta_time / substeps as Real;
+
+        for _ in 0..substeps {
+            self.physics_pipeline.step(
+                &self.gravity,
+                &self.integration_parameters,
+                &mut self.islands,
+                &mut self.broad_phase,
+                &mut self.narrow_phase,
+                &mut self.bodies,
+                &mut self.colliders,
+                &mut self.impulse_joints,
+                &mut self.multibody_joints,
+                &mut self.ccd_solver,
+                Some(&mut self.query_pipeline),
+                &(),
+                &self.event_handler.collector,
+            );
+        }
+
+        while let Ok(event) = self.event_handler.collisions.try_recv() {
+            let kind = match (event.started(), event.sensor()) {
+                (true, false) => CollisionKind::Started,
+                (false, false) => CollisionKind::Stopped,
+                (true, true) => CollisionKind::IntersectionStarted,
+                (false, true) => CollisionKind::IntersectionStopped,
+            };
+
+            let a = self.entity(event.collider1()).or_else(|| self.removed_colliders.get(&event.collider1()).copied());
+            let b = self.entity(event.collider2()).or_else(|| self.removed_colliders.get(&event.collider2()).copied());
+
+            if let (Some(a), Some(b)) = (a, b) {
+                self.collision_events.push(EntityCollisionEvent { a, b, kind });
+            }
+        }
+
+        self.removed_colliders.clear();
+    }
+
+    /// Take collisions between entities, which occurred since the previous call
+    pub fn drain_collision_events(&mut self) -> Vec<EntityCollisionEvent> {
+        std::mem::take(&mut self.collision_events)
+    }
+
+    /// Insert rigid body with the collider attached to it
+    pub fn insert(&mut self, body: impl Into<RigidBody>, collider: impl Into<Collider>) -> PhysicsBodyHandle {
+        let body_handle = self.bodies.insert(body);
+        let collider_handle = self.colliders.insert_with_parent(collider, body_handle, &mut self.bodies);
+
+        PhysicsBodyHandle(body_handle, collider_handle)
+    }
+
+    /// Insert rigid body with the collider, which belong to the entity. The entity is stored
+    /// in `user_data` of both, so it can be found by [`PhysicsHandler::entity`]
+    pub fn insert_entity(
+        &mut self,
+        entity: Entity,
+        body: impl Into<RigidBody>,
+        collider: impl Into<Collider>,
+    ) -> PhysicsBodyHandle {
+        let mut body = body.into();
+        let mut collider = collider.into();
+        body.user_data = entity_to_user_data(entity);
+        collider.user_data = entity_to_user_data(entity);
+
+        self.insert(body, collider)
+    }
+
+    /// Entity, which the collider belongs to
+    pub fn entity(&self, collider: ColliderHandle) -> Option<Entity> {
+        self.colliders.get(collider).and_then(|collider| entity_from_user_data(collider.user_data))
+    }
+
+    /// Entity, which the body belongs to
+    pub fn body_entity(&self, body: RigidBodyHandle) -> Option<Entity> {
+        self.bodies.get(body).and_then(|body| entity_from_user_data(body.user_data))
+    }
+
+    /// Remove rigid body along with its colliders
+    pub fn remove(&mut self, handle: PhysicsBodyHandle) -> Option<RigidBody> {
+        self.remove_body(handle.0)
+    }
+
+    pub fn remove_body(&mut self, handle: RigidBodyHandle) -> Option<RigidBody> {
+        for &collider in self.bodies.get(handle)?.colliders() {
+            if let Some(entity) = self.entity(collider) {
+                self.removed_colliders.insert(collider, entity);
+            }
+        }
+
+        self.bodies.remove(
+            handle,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        )
+    }
+
+    pub fn body(&self, handle: PhysicsBodyHandle) -> Option<&RigidBody> {
+        self.bodies.get(handle.0)
+    }
+
+    pub fn body_mut(&mut self, handle: PhysicsBodyHandle) -> Option<&mut RigidBody> {
+        self.bodies.get_mut(handle.0)
+    }
+
+    pub fn collider(&self, handle: PhysicsBodyHandle) -> Option<&Collider> {
+        self.colliders.get(handle.1)
+    }
+
+    pub fn collider_mut(&mut self, handle: PhysicsBodyHandle) -> Option<&mut Collider> {
+        self.colliders.get_mut(handle.1)
+    }
+
+    /// Find the nearest collider of an entity, hit by the ray from `origin` along `direction`
+    /// within `max_distance`. Colliders are tested at their positions after the latest step
+    pub fn cast_ray(
+        &self,
+        origin: glm::Vec2,
+        direction: glm::Vec2,
+        max_distance: f32,
+        filter: QueryFilter,
+    ) -> Option<RayHit> {
+        let user_predicate = filter.predicate;
+        let predicate = |handle, collider: &Collider| {
+            collider.user_data != 0 && user_predicate.is_none_or(|predicate| predicate(handle, collider))
+        };
+
+        let ray = Ray::new(origin.into(), glm::normalize(&direction));
+        let (collider, intersection) = self.query_pipeline.cast_ray_and_get_normal(
+            &self.bodies,
+            &self.colliders,
+            &ray,
+            max_distance,
+            true,
+            QueryFilter { predicate: Some(&predicate), ..filter },
+        )?;
+
+        Some(RayHit {
+            entity: self.entity(collider)?,
+            collider,
+            distance: intersection.toi,
+            point: ray.point_at(intersection.toi).coords,
+            normal: intersection.normal,
+        })
+    }
+
+    /// Call `callback` with every collider of an entity, hit by the ray within `max_distance`,
+    /// in arbitrary order, until it returns `false`
+    pub fn intersections_with_ray(
+        &self,
+        origin: glm::Vec2,
+        direction: glm::Vec2,
+        max_distance: f32,
+        filter: QueryFilter,
+        mut callback: impl FnMut(RayHit) -> bool,
+    ) {
+        let ray = Ray::new(origin.into(), glm::normalize(&direction));
+
+        self.query_pipeline.intersections_with_ray(
+            &self.bodies,
+            &self.colliders,
+            &ray,
+            max_distance,
+            true,
+            filter,
+            |collider, intersection| {
+                let Some(entity) = self.entity(collider) else { return true };
+
+                callback(RayHit {
+                    entity,
+                    collider,
+                    distance: intersection.toi,
+                    point: ray.point_at(intersection.toi).coords,
+                    normal: intersection.normal,
+                })
+            },
+        );
+    }
+
+    /// Project the point on the nearest collider of an entity. If `solid` is `true`,
+    /// points inside colliders are projected onto themselves
+    pub fn project_point(&self, point: &glm::Vec2, solid: bool, filter: QueryFilter) -> Option<PointHit> {
+        let user_predicate = filter.predicate;
+        let predicate = |handle, collider: &Collider| {
+            collider.user_data != 0 && user_predicate.is_none_or(|predicate| predicate(handle, collider))
+        };
+
+        let (collider, projection) = self.query_pipeline.project_point(
+            &self.bodies,
+            &self.colliders,
+            &(*point).into(),
+            solid,
+            QueryFilter { predicate: Some(&predicate), ..filter },
+        )?;
+
+        Some(PointHit {
+            entity: self.entity(collider)?,
+            collider,
+            point: projection.point.coords,
+            is_inside: projection.is_inside,
+        })
+    }
+
+    /// Set pipeline, which draws debug shapes with [`PhysicsHandler::debug_render`]. `None` disables it
+    pub fn set_debug_renderer(&mut self, pipeline: Option<DebugRenderPipeline>) {
+        self.debug_render_pipeline = pipeline;
+    }
+
+    pub fn debug_renderer(&self) -> Option<&DebugRenderPipeline> {
+        self.debug_render_pipeline.as_ref()
+    }
+
+    /// Draw colliders, bodies and contacts with the backend, if the debug renderer is set
+    pub fn debug_render(&mut self, backend: &mut impl DebugRenderBackend) {
+        let Some(pipeline) = self.debug_render_pipeline.as_mut() else { return };
+
+        pipeline.render(
+            backend,
+            &self.bodies,
+            &self.colliders,
+            &self.impulse_joints,
+            &self.multibody_joints,
+            &self.narrow_phase,
+        );
+    }
+
+    pub fn bodies(&self) -> &RigidBodySet {
+        &self.bodies
+    }
+
+    pub fn colliders(&self) -> &ColliderSet {
+        &self.colliders
+    }
+
+    pub fn impulse_joints(&self) -> &ImpulseJointSet {
+        &self.impulse_joints
+    }
+
+    /// Joints between bodies. Joints aren't removed with entities, unlike bodies
+    pub fn impulse_joints_mut(&mut self) -> &mut ImpulseJointSet {
+        &mut self.impulse_joints
+    }
+}
+
+impl Default for PhysicsHandler {
+    fn default() -> Self {
+        PhysicsHandler {
+            gravity: vector![0.0, -9.81],
+            integration_parameters: IntegrationParameters::default(),
+            substeps: 1,
+            islands: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            physics_pipeline: PhysicsPipeline::new(),
+            debug_render_pipeline: None,
+            event_handler: EventCollector::default(),
+            collision_events: vec![],
+            removed_colliders: HashMap::new(),
+        }
+    }
+}
+
+/// Bodies and colliders of entities store `Entity::to_bits`, which is never zero,
+/// so `user_data` of the ones inserted manually stays zero
+fn entity_to_user_data(entity: Entity) -> u128 {
+    entity.to_bits().get() as u128
+}
+
+fn entity_from_user_data(user_data: u128) -> Option<Entity> {
+    Entity::from_bits(user_data as u64)
+}
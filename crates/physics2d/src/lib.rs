@@ -0,0 +1,16 @@
+//! 2D counterpart of `flatbox_physics`, which simulates bodies in XY plane. Bodies are synced
+//! with X and Y of the translation and rotation around Z axis of the [`Transform`], so it works
+//! with orthographic cameras looking along Z axis
+//!
+//! [`Transform`]: flatbox_core::math::transform::Transform
+
+pub mod components;
+pub mod events;
+pub mod handler;
+pub mod query;
+
+pub use components::*;
+pub use events::*;
+pub use handler::*;
+pub use query::*;
+pub use rapier2d;
@@ -0,0 +1,24 @@
+use flatbox_ecs::Entity;
+use rapier2d::prelude::ColliderHandle;
+use flatbox_core::math::glm;
+
+/// Intersection of the ray with the collider of the entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    pub collider: ColliderHandle,
+    /// Distance along the ray
+    pub distance: f32,
+    pub point: glm::Vec2,
+    pub normal: glm::Vec2,
+}
+
+/// Projection of the point on the nearest collider of the entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointHit {
+    pub entity: Entity,
+    pub collider: ColliderHandle,
+    pub point: glm::Vec2,
+    /// Whether the projected point is inside the collider
+    pub is_inside: bool,
+}
@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use flatbox_core::math::glm;
+use serde::{Serialize, Deserialize};
+use winit::{
+    event::{ElementState, KeyEvent, MouseScrollDelta, WindowEvent},
+    keyboard::PhysicalKey,
+};
+
+pub use winit::event::MouseButton;
+
+use super::KeyCode;
+
+/// Number of pixels, which correspond to a single line of scrolling
+const PIXELS_PER_LINE: f32 = 16.0;
+
+/// Keyboard and mouse state, maintained by the app from window events.
+/// It's available to all stages as `Read<Input>`. "Just pressed" and
+/// "just released" states, mouse motion and scrolling are reset at the end of each frame,
+/// while `Update` and `Paused` stages see the ones gathered since the previous update
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Input {
+    pressed_keys: HashSet<KeyCode>,
+    just_pressed_keys: HashSet<KeyCode>,
+    just_released_keys: HashSet<KeyCode>,
+    pressed_buttons: HashSet<MouseButton>,
+    just_pressed_buttons: HashSet<MouseButton>,
+    just_released_buttons: HashSet<MouseButton>,
+    cursor_position: Option<glm::Vec2>,
+    mouse_motion: glm::Vec2,
+    scroll: glm::Vec2,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Input::default()
+    }
+
+    pub fn key_pressed(&self, key: KeyCode) -> bool {
+        self.pressed_keys.contains(&key)
+    }
+
+    pub fn key_just_pressed(&self, key: KeyCode) -> bool {
+        self.just_pressed_keys.contains(&key)
+    }
+
+    pub fn key_just_released(&self, key: KeyCode) -> bool {
+        self.just_released_keys.contains(&key)
+    }
+
+    pub fn pressed_keys(&self) -> impl Iterator<Item = &KeyCode> {
+        self.pressed_keys.iter()
+    }
+
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_buttons.contains(&button)
+    }
+
+    pub fn mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.just_pressed_buttons.contains(&button)
+    }
+
+    pub fn mouse_just_released(&self, button: MouseButton) -> bool {
+        self.just_released_buttons.contains(&button)
+    }
+
+    /// Cursor position in physical pixels relative to the top-left corner
+    /// of the window. It's `None` if the cursor is outside of the window
+    pub fn cursor_position(&self) -> Option<glm::Vec2> {
+        self.cursor_position
+    }
+
+    /// Raw mouse movement during the frame. It isn't limited by the window
+    /// borders, so it's suitable for camera control
+    pub fn mouse_motion(&self) -> glm::Vec2 {
+        self.mouse_motion
+    }
+
+    /// Scrolling during the frame in lines
+    pub fn scroll(&self) -> glm::Vec2 {
+        self.scroll
+    }
+
+    /// Keys are identified by their physical location, so the bindings don't depend on the keyboard layout
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput { event: KeyEvent { physical_key: PhysicalKey::Code(key), state, .. }, .. } => {
+                match state {
+                    ElementState::Pressed => {
+                        if self.pressed_keys.insert(*key) {
+                            self.just_pressed_keys.insert(*key);
+                        }
+                    },
+                    ElementState::Released => {
+                        if self.pressed_keys.remove(key) {
+                            self.just_released_keys.insert(*key);
+                        }
+                    },
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } => {
+                match state {
+                    ElementState::Pressed => {
+                        if self.pressed_buttons.insert(*button) {
+                            self.just_pressed_buttons.insert(*button);
+                        }
+                    },
+                    ElementState::Released => {
+                        if self.pressed_buttons.remove(button) {
+                            self.just_released_buttons.insert(*button);
+                        }
+                    },
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Some(glm::vec2(position.x as f32, position.y as f32));
+            },
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor_position = None;
+            },
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll += match delta {
+                    MouseScrollDelta::LineDelta(x, y) => glm::vec2(*x, *y),
+                    MouseScrollDelta::PixelDelta(position) => {
+                        glm::vec2(position.x as f32, position.y as f32) / PIXELS_PER_LINE
+                    },
+                };
+            },
+            // Release events are lost, when the window isn't focused
+            WindowEvent::Focused(false) => {
+                self.just_released_keys.extend(self.pressed_keys.drain());
+                self.just_released_buttons.extend(self.pressed_buttons.drain());
+            },
+            _ => {},
+        }
+    }
+
+    pub fn handle_mouse_motion(&mut self, delta: (f64, f64)) {
+        self.mouse_motion += glm::vec2(delta.0 as f32, delta.1 as f32);
+    }
+
+    /// Reset per-frame state
+    pub fn clear(&mut self) {
+        self.just_pressed_keys.clear();
+        self.just_released_keys.clear();
+        self.just_pressed_buttons.clear();
+        self.just_released_buttons.clear();
+        self.mouse_motion = glm::Vec2::zeros();
+        self.scroll = glm::Vec2::zeros();
+    }
+}
@@ -1,21 +1,77 @@
-use std::{time::{Instant, Duration}, sync::Arc, fmt::Debug};
-use flatbox_core::logger::LoggerLevel;
+use std::{time::{Instant, Duration}, sync::{Arc, atomic::{AtomicBool, Ordering}}, fmt::Debug, any::Any, ffi::{c_void, CString}, num::NonZeroU32, path::PathBuf, cmp::Reverse};
+use flatbox_core::{logger::{LoggerLevel, LogFileConfig, warn, error}, time::{Time, TimeScale}};
 use glutin::{
-    platform::run_return::EventLoopExtRunReturn,
-    event_loop::{EventLoop, ControlFlow as WinitControlFlow, EventLoopWindowTarget}, 
-    window::{Window, Icon, WindowBuilder as GlutinWindowBuilder},
-    dpi::{Size, LogicalSize, PhysicalSize},
-    event::Event,
-    ContextWrapper, PossiblyCurrent, ContextBuilder, GlRequest, Api, 
+    config::{Config, ConfigTemplateBuilder, GlConfig},
+    context::{ContextApi, ContextAttributesBuilder, GlProfile as GlutinProfile, NotCurrentGlContext, PossiblyCurrentContext, Version},
+    display::{GetGlDisplay, GlDisplay},
+    surface::{GlSurface, Surface, SurfaceAttributesBuilder, SwapInterval, WindowSurface},
+};
+use glutin_winit::{DisplayBuilder, GlWindow};
+use raw_window_handle::HasWindowHandle;
+use winit::{
+    application::ApplicationHandler,
+    event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy, ControlFlow as WinitControlFlow},
+    window::{Window, WindowId, Icon, Fullscreen},
+    monitor::{MonitorHandle, VideoModeHandle},
+    dpi::{Size, LogicalSize, LogicalPosition, PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, DeviceId},
 };
 use parking_lot::{Mutex, MutexGuard};
-use crate::renderer::WindowExtent;
+use crate::{renderer::WindowExtent, error::RenderError};
+
+pub mod input;
+pub mod text;
+pub mod touch;
+
+pub use input::*;
+pub use text::*;
+pub use touch::*;
+pub use winit::event::WindowEvent;
+pub use winit::keyboard::KeyCode;
+pub use winit::event::ElementState;
+#[cfg(target_os = "android")]
+pub use winit::platform::android::activity::AndroidApp;
+
+/// App, passed to `android_main`, which the event loop is created with
+#[cfg(target_os = "android")]
+static ANDROID_APP: std::sync::OnceLock<AndroidApp> = std::sync::OnceLock::new();
+
+/// Set the app, passed to `android_main`. It must be done before the context is created
+#[cfg(target_os = "android")]
+pub fn set_android_app(app: AndroidApp) {
+    if ANDROID_APP.set(app).is_err() {
+        warn!("Android app is already set");
+    }
+}
+
+/// Window along with its OpenGL surface and context, which is current on the main thread
+pub struct GlContext {
+    window: Window,
+    surface: Surface<WindowSurface>,
+    context: PossiblyCurrentContext,
+}
+
+impl GlContext {
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
 
-pub use glutin::event::WindowEvent;
-pub use glutin::event::VirtualKeyCode;
-pub use glutin::event::ElementState;
+    /// Resize the surface, e.g. after the window is resized. Zero sizes are ignored
+    pub fn resize(&self, size: PhysicalSize<u32>) {
+        if let (Some(width), Some(height)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) {
+            self.surface.resize(&self.context, width, height);
+        }
+    }
+
+    pub fn swap_buffers(&self) -> Result<(), RenderError> {
+        Ok(self.surface.swap_buffers(&self.context)?)
+    }
 
-pub type GlContext = ContextWrapper<PossiblyCurrent, Window>;
+    pub fn get_proc_address(&self, addr: &str) -> *const c_void {
+        let addr = CString::new(addr).expect("Function name must not contain null bytes");
+        self.context.display().get_proc_address(&addr)
+    }
+}
 
 #[derive(Clone)]
 pub struct Display(Arc<Mutex<GlContext>>);
@@ -27,9 +83,46 @@ impl Display {
         Display(Arc::new(Mutex::new(context)))
     }
 
-    pub fn lock(&self) -> MutexGuard<GlContext> {
+    pub fn lock(&self) -> MutexGuard<'_, GlContext> {
         self.0.lock()
     }
+
+    /// Change swap interval of the window at runtime. Adaptive vsync is supported by WGL
+    /// and GLX with `GLX_MESA_swap_control`
+    pub fn set_vsync(&self, vsync: VsyncMode) -> Result<(), RenderError> {
+        type SwapIntervalFn = extern "system" fn(i32) -> i32;
+
+        let context = self.lock();
+        let interval = vsync.swap_interval();
+
+        if let Some(interval) = vsync.glutin_interval() {
+            return context.surface
+                .set_swap_interval(&context.context, interval)
+                .map_err(|_| RenderError::UnsupportedVsyncMode(vsync));
+        }
+
+        let wgl_swap_interval = context.get_proc_address("wglSwapIntervalEXT");
+        if !wgl_swap_interval.is_null() {
+            let wgl_swap_interval = unsafe { std::mem::transmute::<*const c_void, SwapIntervalFn>(wgl_swap_interval) };
+
+            return match wgl_swap_interval(interval) {
+                0 => Err(RenderError::UnsupportedVsyncMode(vsync)),
+                _ => Ok(()),
+            };
+        }
+
+        let glx_swap_interval = context.get_proc_address("glXSwapIntervalMESA");
+        if !glx_swap_interval.is_null() && interval >= 0 {
+            let glx_swap_interval = unsafe { std::mem::transmute::<*const c_void, SwapIntervalFn>(glx_swap_interval) };
+
+            return match glx_swap_interval(interval) {
+                0 => Ok(()),
+                _ => Err(RenderError::UnsupportedVsyncMode(vsync)),
+            };
+        }
+
+        Err(RenderError::UnsupportedVsyncMode(vsync))
+    }
 }
 
 unsafe impl Send for Display {}
@@ -49,6 +142,7 @@ impl From<PhysicalSize<u32>> for WindowExtent {
 #[derive(Default, Clone)]
 pub struct ControlFlow {
     inner: Arc<Mutex<WinitControlFlow>>,
+    exit: Arc<AtomicBool>,
     repaint_after: Duration,
 }  
 
@@ -78,7 +172,11 @@ impl ControlFlow {
     }
 
     pub fn exit(&self) {
-        *(self.inner.lock()) = WinitControlFlow::Exit;
+        self.exit.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_exiting(&self) -> bool {
+        self.exit.load(Ordering::Relaxed)
     }
 }
 
@@ -88,34 +186,422 @@ impl Debug for ControlFlow {
     }
 }
 
+/// Video mode of a monitor, which can be used for exclusive fullscreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VideoModeInfo {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate_millihertz: u32,
+    pub bit_depth: u16,
+}
+
+impl VideoModeInfo {
+    pub fn refresh_rate(&self) -> f32 {
+        self.refresh_rate_millihertz as f32 / 1000.0
+    }
+
+    /// Key to sort modes from the worst to the best one
+    fn quality(&self) -> (u32, u32, u16) {
+        (self.width * self.height, self.refresh_rate_millihertz, self.bit_depth)
+    }
+}
+
+impl From<&VideoModeHandle> for VideoModeInfo {
+    fn from(mode: &VideoModeHandle) -> Self {
+        VideoModeInfo {
+            width: mode.size().width,
+            height: mode.size().height,
+            refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+            bit_depth: mode.bit_depth(),
+        }
+    }
+}
+
+/// Kind of fullscreen, which is used when the window is fullscreen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FullscreenMode {
+    /// Borderless window covering the whole monitor
+    #[default]
+    Borderless,
+    /// Exclusive fullscreen with the given video mode. If it's `None` or the monitor
+    /// doesn't support the mode, the one with the highest resolution and refresh rate is used
+    Exclusive(Option<VideoModeInfo>),
+}
+
+impl FullscreenMode {
+    fn to_winit(self, monitor: Option<MonitorHandle>) -> Option<Fullscreen> {
+        match self {
+            FullscreenMode::Borderless => Some(Fullscreen::Borderless(monitor)),
+            FullscreenMode::Exclusive(requested) => {
+                let Some(monitor) = monitor else {
+                    warn!("No monitor available for exclusive fullscreen, using borderless one");
+                    return Some(Fullscreen::Borderless(None));
+                };
+
+                let mut modes: Vec<VideoModeHandle> = monitor.video_modes().collect();
+                modes.sort_by_key(|mode| Reverse(VideoModeInfo::from(mode).quality()));
+
+                let mode = match requested {
+                    Some(requested) => modes
+                        .iter()
+                        .position(|mode| VideoModeInfo::from(mode) == requested)
+                        .or_else(|| {
+                            warn!("Video mode {requested:?} is not supported by the monitor, using the best one");
+                            (!modes.is_empty()).then_some(0)
+                        }),
+                    None => (!modes.is_empty()).then_some(0),
+                };
+
+                match mode {
+                    Some(index) => Some(Fullscreen::Exclusive(modes.swap_remove(index))),
+                    None => {
+                        warn!("Monitor doesn't provide any video modes, using borderless fullscreen");
+                        Some(Fullscreen::Borderless(Some(monitor)))
+                    },
+                }
+            },
+        }
+    }
+}
+
+/// Information about a monitor
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    /// Size of the monitor in physical pixels
+    pub width: u32,
+    pub height: u32,
+    /// Position of the monitor's top-left corner on the desktop in physical pixels
+    pub x: i32,
+    pub y: i32,
+    pub scale_factor: f64,
+    pub refresh_rate_millihertz: Option<u32>,
+}
+
+impl From<&MonitorHandle> for MonitorInfo {
+    fn from(monitor: &MonitorHandle) -> Self {
+        let size = monitor.size();
+        let position = monitor.position();
+
+        MonitorInfo {
+            name: monitor.name(),
+            width: size.width,
+            height: size.height,
+            x: position.x,
+            y: position.y,
+            scale_factor: monitor.scale_factor(),
+            refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+        }
+    }
+}
+
+/// Information about the window's position, scale factor and available monitors. It's
+/// available to systems as `Read<WindowInfo>` and updated, when the window is
+/// moved or its scale factor is changed. Monitors are enumerated again only, when the
+/// window is moved to another monitor
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    /// Ratio between physical and logical pixels of the window
+    pub scale_factor: f64,
+    /// Position of the window's top-left corner on the desktop in physical pixels.
+    /// It's `None`, if the platform doesn't report it, e.g. on Wayland
+    pub position: Option<(i32, i32)>,
+    pub monitors: Vec<MonitorInfo>,
+    /// Monitor, which contains the window
+    pub current_monitor: Option<MonitorInfo>,
+    pub primary_monitor: Option<MonitorInfo>,
+}
+
+impl WindowInfo {
+    pub fn new(window: &Window) -> WindowInfo {
+        WindowInfo {
+            scale_factor: window.scale_factor(),
+            position: window.outer_position().ok().map(|position| (position.x, position.y)),
+            monitors: window.available_monitors().map(|monitor| MonitorInfo::from(&monitor)).collect(),
+            current_monitor: window.current_monitor().map(|monitor| MonitorInfo::from(&monitor)),
+            primary_monitor: window.primary_monitor().map(|monitor| MonitorInfo::from(&monitor)),
+        }
+    }
+}
+
+impl Default for WindowInfo {
+    fn default() -> Self {
+        WindowInfo {
+            scale_factor: 1.0,
+            position: None,
+            monitors: vec![],
+            current_monitor: None,
+            primary_monitor: None,
+        }
+    }
+}
+
+/// ECS event, which is sent when the window's scale factor is changed, e.g.
+/// when it's moved to a monitor with different DPI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleFactorChanged {
+    pub scale_factor: f64,
+}
+
+/// ECS event, which is sent when the user tries to close the window. Unless it's
+/// vetoed by a system (e.g. to show "unsaved changes" dialog), `AppExit` is spawned
+/// at the end of the frame. If there are no fixed updates during the frame, the request
+/// is kept until the next frame with them. The system vetoes it through `Write<Events>`,
+/// and while the context is paused, it has to be added to `SystemStage::Paused` as well:
+///
+/// ```rust,ignore
+/// fn confirm_exit(mut events: Write<Events>) {
+///     if let Some(close) = events.read_mut::<CloseRequested>() {
+///         close.veto();
+///     }
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CloseRequested {
+    vetoed: bool,
+}
+
+impl CloseRequested {
+    pub fn new() -> CloseRequested {
+        CloseRequested::default()
+    }
+
+    /// Prevent the app from exiting
+    pub fn veto(&mut self) {
+        self.vetoed = true;
+    }
+
+    pub fn is_vetoed(&self) -> bool {
+        self.vetoed
+    }
+}
+
+/// Get video modes of the monitor, sorted from the best to the worst one
+pub fn video_modes(monitor: &MonitorHandle) -> Vec<VideoModeInfo> {
+    let mut modes: Vec<VideoModeInfo> = monitor.video_modes().map(|mode| VideoModeInfo::from(&mode)).collect();
+    modes.sort_by_key(|mode| Reverse(mode.quality()));
+    modes.dedup();
+    modes
+}
+
+/// Image, which is used as the window icon
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IconSource {
+    /// Path to the image file
+    Path(PathBuf),
+    /// Encoded image data (e.g. PNG, included with `include_bytes!`)
+    Bytes(&'static [u8]),
+}
+
+impl IconSource {
+    /// Decode the image into the window icon
+    pub fn load(&self) -> Result<Icon, RenderError> {
+        let image = match self {
+            IconSource::Path(path) => image::open(path)?,
+            IconSource::Bytes(bytes) => image::load_from_memory(bytes)?,
+        }.into_rgba8();
+
+        let (width, height) = image.dimensions();
+
+        Ok(Icon::from_rgba(image.into_raw(), width, height)?)
+    }
+
+    fn load_or_log(&self) -> Option<Icon> {
+        self.load()
+            .map_err(|e| error!("Cannot load window icon from {self:?}: {e}"))
+            .ok()
+    }
+}
+
+impl<P: Into<PathBuf>> From<P> for IconSource {
+    fn from(path: P) -> Self {
+        IconSource::Path(path.into())
+    }
+}
+
+/// Change of window's parameters, requested by [`WindowCommands`]
+#[derive(Debug, Clone)]
+pub enum WindowCommand {
+    SetTitle(String),
+    /// Set inner size of the window in logical pixels
+    SetSize { width: u32, height: u32 },
+    SetResizable(bool),
+    SetFullscreen(bool),
+    SetFullscreenMode(FullscreenMode),
+    SetMaximized(bool),
+    SetIcon(Option<IconSource>),
+    SetImeAllowed(bool),
+    /// Set position of IME candidate box in logical pixels
+    SetImePosition { x: f32, y: f32 },
+}
+
+/// Queue of window changes, which can be used by systems as `Write<WindowCommands>`.
+/// Queued commands are applied by the context at the end of each frame
+#[derive(Default, Clone)]
+pub struct WindowCommands {
+    inner: Arc<Mutex<Vec<WindowCommand>>>,
+}
+
+impl WindowCommands {
+    pub fn new() -> WindowCommands {
+        WindowCommands::default()
+    }
+
+    pub fn push(&self, command: WindowCommand) {
+        self.inner.lock().push(command);
+    }
+
+    pub fn set_title<S: Into<String>>(&self, title: S) {
+        self.push(WindowCommand::SetTitle(title.into()));
+    }
+
+    pub fn set_size(&self, width: u32, height: u32) {
+        self.push(WindowCommand::SetSize { width, height });
+    }
+
+    pub fn set_resizable(&self, resizable: bool) {
+        self.push(WindowCommand::SetResizable(resizable));
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        self.push(WindowCommand::SetFullscreen(fullscreen));
+    }
+
+    /// Switch window to the given fullscreen mode
+    pub fn set_fullscreen_mode(&self, mode: FullscreenMode) {
+        self.push(WindowCommand::SetFullscreenMode(mode));
+    }
+
+    pub fn set_maximized(&self, maximized: bool) {
+        self.push(WindowCommand::SetMaximized(maximized));
+    }
+
+    pub fn set_icon(&self, icon: Option<IconSource>) {
+        self.push(WindowCommand::SetIcon(icon));
+    }
+
+    /// Allow IME input, which is disabled by default
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.push(WindowCommand::SetImeAllowed(allowed));
+    }
+
+    /// Set position of IME candidate box (e.g. under the text field) in logical pixels
+    pub fn set_ime_position(&self, x: f32, y: f32) {
+        self.push(WindowCommand::SetImePosition { x, y });
+    }
+
+    /// Apply all the queued commands to the window
+    pub fn apply(&self, window: &Window) {
+        for command in self.inner.lock().drain(..) {
+            match command {
+                WindowCommand::SetTitle(title) => window.set_title(&title),
+                WindowCommand::SetSize { width, height } => {
+                    let _ = window.request_inner_size(LogicalSize::new(width, height));
+                },
+                WindowCommand::SetResizable(resizable) => window.set_resizable(resizable),
+                WindowCommand::SetFullscreen(fullscreen) => window.set_fullscreen(match fullscreen {
+                    true => Some(Fullscreen::Borderless(None)),
+                    false => None,
+                }),
+                WindowCommand::SetFullscreenMode(mode) => {
+                    window.set_fullscreen(mode.to_winit(window.current_monitor()));
+                },
+                WindowCommand::SetMaximized(maximized) => window.set_maximized(maximized),
+                WindowCommand::SetIcon(icon) => window.set_window_icon(icon.and_then(|icon| icon.load_or_log())),
+                WindowCommand::SetImeAllowed(allowed) => window.set_ime_allowed(allowed),
+                WindowCommand::SetImePosition { x, y } => {
+                    window.set_ime_cursor_area(LogicalPosition::new(x, y), LogicalSize::new(0, 0));
+                },
+            }
+        }
+    }
+}
+
+impl Debug for WindowCommands {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+/// Custom event, sent to the event loop with [`ContextProxy`]
+pub struct UserEvent(Box<dyn Any + Send + Sync>);
+
+impl UserEvent {
+    pub fn new<T: Any + Send + Sync>(event: T) -> UserEvent {
+        UserEvent(Box::new(event))
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    /// Take the inner event out, if it has type `T`. Otherwise the user event is returned back
+    pub fn downcast<T: Any>(self) -> Result<T, UserEvent> {
+        self.0.downcast().map(|event| *event).map_err(UserEvent)
+    }
+}
+
+impl Debug for UserEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserEvent").finish_non_exhaustive()
+    }
+}
+
+/// Handle to the event loop, which can be cloned and moved to other threads
+/// (e.g. asset loaders or network sockets) to wake the loop with [`UserEvent`]s.
+/// They are delivered as [`ContextEvent::User`]
+#[derive(Clone)]
+pub struct ContextProxy {
+    inner: Arc<Mutex<EventLoopProxy<UserEvent>>>,
+}
+
+impl ContextProxy {
+    pub fn send<T: Any + Send + Sync>(&self, event: T) -> Result<(), RenderError> {
+        self.inner
+            .lock()
+            .send_event(UserEvent::new(event))
+            .map_err(|_| RenderError::EventLoopClosed)
+    }
+}
+
+impl Debug for ContextProxy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextProxy").finish_non_exhaustive()
+    }
+}
+
 #[derive(Default)]
 pub enum EventLoopWrapper {
-    Present(EventLoop<()>),
+    Present(Box<EventLoop<UserEvent>>),
     #[default]
     NotPresent,
 }
 
 impl EventLoopWrapper {
-    pub fn new(event_loop: EventLoop<()>) -> EventLoopWrapper {
-        EventLoopWrapper::Present(event_loop)
+    pub fn new(event_loop: EventLoop<UserEvent>) -> EventLoopWrapper {
+        EventLoopWrapper::Present(Box::new(event_loop))
     }
 
     pub fn new_not_present() -> EventLoopWrapper {
         EventLoopWrapper::NotPresent
     }
 
-    pub fn take(&mut self) -> EventLoop<()> {
+    pub fn take(&mut self) -> EventLoop<UserEvent> {
         let event_loop = std::mem::take(self);
         *self = EventLoopWrapper::NotPresent;
         match event_loop {
             Self::NotPresent => panic!("EventLoop is not present"),
-            Self::Present(e) => e,
+            Self::Present(e) => *e,
         }
     }
 }
 
-impl AsRef<EventLoop<()>> for EventLoopWrapper {
-    fn as_ref(&self) -> &EventLoop<()> {
+impl AsRef<EventLoop<UserEvent>> for EventLoopWrapper {
+    fn as_ref(&self) -> &EventLoop<UserEvent> {
         match self {
             Self::NotPresent => panic!("EventLoop is not present"),
             Self::Present(e) => e,
@@ -123,20 +609,159 @@ impl AsRef<EventLoop<()>> for EventLoopWrapper {
     }
 }
 
+/// Graphics API of the context
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlApi {
+    /// OpenGL 4.1
+    OpenGl,
+    /// OpenGL ES 3.0, e.g. for Android. Shaders are converted to GLSL ES automatically
+    OpenGlEs,
+}
+
+impl Default for GlApi {
+    fn default() -> Self {
+        if cfg!(target_os = "android") {
+            GlApi::OpenGlEs
+        } else {
+            GlApi::OpenGl
+        }
+    }
+}
+
+impl GlApi {
+    /// Default context version of the API
+    pub fn default_version(&self) -> (u8, u8) {
+        match self {
+            GlApi::OpenGl => (4, 1),
+            GlApi::OpenGlEs => (3, 0),
+        }
+    }
+
+    /// Request of the given version (or the default one, if `None`)
+    pub fn request(&self, version: Option<(u8, u8)>) -> ContextApi {
+        let (major, minor) = version.unwrap_or(self.default_version());
+        let version = Some(Version::new(major, minor));
+
+        match self {
+            GlApi::OpenGl => ContextApi::OpenGl(version),
+            GlApi::OpenGlEs => ContextApi::Gles(version),
+        }
+    }
+}
+
+impl From<GlApi> for ContextApi {
+    fn from(api: GlApi) -> Self {
+        api.request(None)
+    }
+}
+
+/// OpenGL context profile. It's ignored by OpenGL ES
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlProfile {
+    /// Only non-deprecated functionality
+    #[default]
+    Core,
+    /// Core functionality along with the deprecated one
+    Compatibility,
+}
+
+impl From<GlProfile> for GlutinProfile {
+    fn from(profile: GlProfile) -> Self {
+        match profile {
+            GlProfile::Core => GlutinProfile::Core,
+            GlProfile::Compatibility => GlutinProfile::Compatibility,
+        }
+    }
+}
+
+/// Synchronization of buffer swaps with the display's refresh rate
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VsyncMode {
+    /// Wait for vertical blank, which prevents tearing
+    #[default]
+    On,
+    /// Swap immediately. Gives the lowest latency and uncapped frame rate
+    Off,
+    /// Wait for vertical blank only if the frame is in time, otherwise swap immediately
+    Adaptive,
+}
+
+impl VsyncMode {
+    pub fn swap_interval(&self) -> i32 {
+        match self {
+            VsyncMode::On => 1,
+            VsyncMode::Off => 0,
+            VsyncMode::Adaptive => -1,
+        }
+    }
+
+    /// Swap interval, which can be set with glutin. Adaptive vsync isn't supported by it
+    fn glutin_interval(&self) -> Option<SwapInterval> {
+        match self {
+            VsyncMode::On => Some(SwapInterval::Wait(NonZeroU32::MIN)),
+            VsyncMode::Off => Some(SwapInterval::DontWait),
+            VsyncMode::Adaptive => None,
+        }
+    }
+}
+
+/// Behavior of the context, when the window is occluded or minimized
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OcclusionPolicy {
+    /// Keep running fixed updates, but skip rendering
+    #[default]
+    KeepUpdating,
+    /// Keep running fixed updates, but wake up only the given number of times per second
+    Throttle(u32),
+    /// Stop time and updates completely until the window is visible again.
+    /// [`ContextEvent::PauseEvent`] and [`ContextEvent::ResumeEvent`] are sent on transitions.
+    /// [`ContextEvent::PausedUpdateEvent`] is sent on pausing and after window events
+    /// instead of updates, so the events can be handled without advancing time
+    Pause,
+}
+
+/// ECS event, which is sent when the context is paused due to [`OcclusionPolicy::Pause`].
+/// It can be handled by systems of `SystemStage::Paused`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowPaused;
+
+/// ECS event, which is sent when the paused context is resumed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowResumed;
+
 pub enum ContextEvent {
     ResizeEvent(WindowExtent),
-    UpdateEvent,
-    RenderEvent(Display, ControlFlow),
-    WindowEvent(Display, WindowEvent<'static>),
+    UpdateEvent(Time),
+    RenderEvent(Display, ControlFlow, Time),
+    /// End of the frame. It's sent even if the window is occluded and nothing is rendered
+    FrameEndEvent(ControlFlow),
+    PauseEvent,
+    ResumeEvent,
+    /// Update of the paused context. Time isn't advanced
+    PausedUpdateEvent(Time),
+    WindowInfoChanged(WindowInfo),
+    WindowEvent(Display, WindowEvent),
+    /// Raw mouse movement
+    MouseMotion((f64, f64)),
+    /// Custom event, sent with [`ContextProxy`]
+    User(UserEvent),
 }
 
 pub struct Context {
     event_loop: EventLoopWrapper,
     display: Display,
     control_flow: ControlFlow,
+    window_commands: WindowCommands,
+    window_info: WindowInfo,
+    time: Time,
+    time_scale: TimeScale,
     max_frame_time: Duration,
+    min_frame_time: Option<Duration>,
     exit_next_iteration: bool,
     window_occluded: bool,
+    occlusion_policy: OcclusionPolicy,
+    paused: bool,
+    paused_update: bool,
     fixed_time_step: f64,
     number_of_updates: u32,
     number_of_renders: u32,
@@ -150,36 +775,96 @@ pub struct Context {
 
 impl Context {
     pub fn new(builder: &WindowBuilder) -> Context {
-        let event_loop = EventLoop::new();
+        #[allow(unused_mut)]
+        let mut event_loop_builder = EventLoop::with_user_event();
+
+        #[cfg(target_os = "android")]
+        {
+            use winit::platform::android::EventLoopBuilderExtAndroid;
 
-        let window = GlutinWindowBuilder::new()
+            let app = ANDROID_APP.get().expect("Android app must be set with `set_android_app` before creating the context");
+            event_loop_builder.with_android_app(app.clone());
+        }
+
+        let event_loop = event_loop_builder
+            .build()
+            .expect("Cannot create event loop");
+
+        let window_attributes = Window::default_attributes()
             .with_inner_size(Size::from(LogicalSize::new(builder.width, builder.height)))
             .with_title(builder.title)
             .with_maximized(builder.maximized)
             .with_resizable(builder.resizable)
-            .with_window_icon(builder.icon.clone())
-            .with_fullscreen(match builder.fullscreen {
-                true => Some(glutin::window::Fullscreen::Borderless(None)),
-                false => None,
-            });
-
-        let gl_context = ContextBuilder::new()
-            .with_gl(GlRequest::Specific(Api::OpenGl, (4, 1)))
-            .build_windowed(window, &event_loop)
-            .expect("Cannot create windowed context");
-
-        let gl_context = unsafe {
-            gl_context
-                .make_current()
-                .expect("Failed to make context current")
+            .with_window_icon(builder.icon.as_ref().and_then(|icon| icon.load_or_log()));
+
+        let (window, config) = DisplayBuilder::new()
+            .with_window_attributes(Some(window_attributes))
+            .build(&event_loop, ConfigTemplateBuilder::new(), |configs| pick_config(configs, builder.srgb))
+            .expect("Cannot create window");
+
+        let window = window.expect("Window has just been requested");
+
+        // Monitors are available only through the window before the event loop is run
+        if builder.fullscreen {
+            window.set_fullscreen(builder.fullscreen_mode.to_winit(window.primary_monitor()));
+        }
+
+        let context_attributes = ContextAttributesBuilder::new()
+            .with_context_api(builder.gl_api.request(builder.gl_version))
+            .with_profile(builder.gl_profile.into())
+            .with_debug(builder.gl_debug)
+            .build(window.window_handle().ok().map(|handle| handle.as_raw()));
+
+        let surface_attributes = window
+            .build_surface_attributes(SurfaceAttributesBuilder::new().with_srgb(Some(builder.srgb)))
+            .expect("Cannot get window handle");
+
+        let gl_display = config.display();
+        let (context, surface) = unsafe {
+            (
+                gl_display
+                    .create_context(&config, &context_attributes)
+                    .expect("Cannot create OpenGL context"),
+                gl_display
+                    .create_window_surface(&config, &surface_attributes)
+                    .expect("Cannot create window surface"),
+            )
         };
 
+        let context = context
+            .make_current(&surface)
+            .expect("Failed to make context current");
+
+        let window_info = WindowInfo::new(&window);
+        let display = Display::new(GlContext { window, surface, context });
+
+        if let Err(e) = display.set_vsync(builder.vsync) {
+            if builder.vsync == VsyncMode::Adaptive {
+                warn!("{e}, falling back to `VsyncMode::On`");
+                display.set_vsync(VsyncMode::On).unwrap_or_else(|e| warn!("{e}"));
+            } else {
+                warn!("{e}");
+            }
+        }
+
         Context {
             event_loop: EventLoopWrapper::new(event_loop),
-            display: Display::new(gl_context),
+            display,
             control_flow: ControlFlow::default(),
+            window_commands: WindowCommands::default(),
+            window_info,
+            time_scale: TimeScale::default(),
+            time: {
+                let mut time = Time::new();
+                time.set_fixed_delta_time(Duration::from_secs_f64(1.0 / builder.updates_per_second as f64));
+                time
+            },
             max_frame_time: Duration::from_secs_f64(builder.max_frame_time),
+            min_frame_time: min_frame_time(builder.max_fps),
             window_occluded: false,
+            occlusion_policy: builder.occlusion_policy,
+            paused: false,
+            paused_update: false,
             exit_next_iteration: false,
             fixed_time_step: 1.0 / builder.updates_per_second as f64,
             number_of_updates: 0,
@@ -193,12 +878,58 @@ impl Context {
         }
     }
 
+    pub fn time(&self) -> Time {
+        self.time
+    }
+
     pub fn display(&self) -> Display {
         self.display.clone()
     }
 
-    pub fn event_loop_target(&self) -> &EventLoopWindowTarget<()> {
-        self.event_loop.as_ref()
+    /// Get the shared speed of the game time
+    pub fn time_scale(&self) -> TimeScale {
+        self.time_scale.clone()
+    }
+
+    /// Get the shared control flow of the event loop, e.g. to exit it
+    pub fn control_flow(&self) -> ControlFlow {
+        self.control_flow.clone()
+    }
+
+    /// Get the queue of window changes, which are applied at the end of each frame
+    pub fn window_commands(&self) -> WindowCommands {
+        self.window_commands.clone()
+    }
+
+    /// Limit the frame rate. `None` means the frame rate is unlimited
+    /// (except for the vsync)
+    pub fn set_max_fps(&mut self, max_fps: Option<u32>) {
+        self.min_frame_time = min_frame_time(max_fps);
+    }
+
+    pub fn set_occlusion_policy(&mut self, occlusion_policy: OcclusionPolicy) {
+        self.occlusion_policy = occlusion_policy;
+    }
+
+    pub fn window_info(&self) -> WindowInfo {
+        self.window_info.clone()
+    }
+
+    /// Get video modes of the monitor, which contains the window
+    pub fn video_modes(&self) -> Vec<VideoModeInfo> {
+        self.display
+            .lock()
+            .window()
+            .current_monitor()
+            .map(|monitor| video_modes(&monitor))
+            .unwrap_or_default()
+    }
+
+    /// Create handle, which can wake the event loop with custom events from other threads
+    pub fn create_proxy(&self) -> ContextProxy {
+        ContextProxy {
+            inner: Arc::new(Mutex::new(self.event_loop.as_ref().create_proxy())),
+        }
     }
 
     pub fn get_proc_address(&self, addr: &str) -> *const core::ffi::c_void {
@@ -210,69 +941,207 @@ impl Context {
 
         self.current_instant = Instant::now();
 
+        if self.window_occluded && self.occlusion_policy == OcclusionPolicy::Pause {
+            if !self.paused {
+                self.paused = true;
+                self.paused_update = true;
+                (runner)(ContextEvent::PauseEvent);
+            }
+
+            if std::mem::take(&mut self.paused_update) {
+                (runner)(ContextEvent::PausedUpdateEvent(self.time));
+            }
+
+            std::thread::sleep(Duration::from_secs_f64(self.fixed_time_step));
+            (runner)(ContextEvent::FrameEndEvent(self.control_flow.clone()));
+
+            // Time doesn't pass while paused
+            self.previous_instant = Instant::now();
+            return;
+        }
+
+        if self.paused {
+            self.paused = false;
+            (runner)(ContextEvent::ResumeEvent);
+        }
+
         let mut elapsed = self.current_instant.duration_since(self.previous_instant);
         if elapsed > self.max_frame_time { elapsed = self.max_frame_time; }
 
+        let time_scale = self.time_scale.scale();
+
         self.last_frame_time = elapsed.as_secs_f64();
         self.running_time += elapsed.as_secs_f64();
-        self.accumulated_time += elapsed.as_secs_f64();
+        self.accumulated_time += elapsed.as_secs_f64() * time_scale;
+        self.time.set_time_scale(time_scale);
+        self.time.advance(elapsed);
 
         while self.accumulated_time >= self.fixed_time_step {
-            (runner)(ContextEvent::UpdateEvent);
+            (runner)(ContextEvent::UpdateEvent(self.time));
 
             self.accumulated_time -= self.fixed_time_step;
             self.number_of_updates += 1;
+            self.time.advance_fixed();
         }
 
         self.blending_factor = self.accumulated_time / self.fixed_time_step;
+        self.time.set_overstep_fraction(self.blending_factor);
 
         if self.window_occluded {
-            std::thread::sleep(Duration::from_secs_f64(self.fixed_time_step));
+            let sleep_time = match self.occlusion_policy {
+                OcclusionPolicy::Throttle(rate) if rate > 0 => 1.0 / rate as f64,
+                _ => self.fixed_time_step,
+            };
+
+            std::thread::sleep(Duration::from_secs_f64(sleep_time));
         } else {
             (runner)(ContextEvent::RenderEvent(
                 self.display.clone(), 
                 self.control_flow.clone(),
+                self.time,
             ));
 
             self.number_of_renders += 1;
         }
 
+        (runner)(ContextEvent::FrameEndEvent(self.control_flow.clone()));
+
+        if let Some(min_frame_time) = self.min_frame_time {
+            sleep_until(self.current_instant + min_frame_time);
+        }
+
         self.previous_instant = self.current_instant;        
     }
 
-    pub fn run<F: FnMut(ContextEvent)>(&mut self, mut runner: F) {
-        self.event_loop.take().run_return(move |event, _, control_flow|{
-            match event {
-                Event::LoopDestroyed => (),
-                Event::WindowEvent { event, .. } => {
-                    match event {
-                        WindowEvent::CloseRequested => *control_flow = WinitControlFlow::Exit,
-                        WindowEvent::Resized(physical_size) => {
-                            let size = WindowExtent::from(physical_size);
-                            (runner)(ContextEvent::ResizeEvent(size));
-                            self.display.lock().resize(physical_size);
-                        },
-                        WindowEvent::Occluded(occluded) => self.window_occluded = occluded,
-                        _ => {},
-                    }
-
-                    (runner)(ContextEvent::WindowEvent(
-                        self.display.clone(),
-                        event.to_static().unwrap_or(WindowEvent::Focused(true)), 
-                    ));
-                },
-                Event::RedrawRequested(_) => {
-                    self.next_frame(&mut runner);
-                    
-                    *control_flow = *(self.control_flow.inner.lock());
-                    self.display.lock().swap_buffers().unwrap();
-                },
-                Event::MainEventsCleared => {
-                    self.display.lock().window().request_redraw();
-                },
-                _ => {},
-            }
-        });
+    fn update_window_info<F: FnMut(ContextEvent)>(&mut self, runner: &mut F) {
+        let window_info = WindowInfo::new(self.display.lock().window());
+
+        if window_info != self.window_info {
+            self.window_info = window_info.clone();
+            (runner)(ContextEvent::WindowInfoChanged(window_info));
+        }
+    }
+
+    /// Update the position of the window and rebuild the whole info only, when it's
+    /// moved to another monitor, because enumerating monitors is expensive
+    fn window_moved<F: FnMut(ContextEvent)>(&mut self, position: PhysicalPosition<i32>, runner: &mut F) {
+        let current_monitor = self.display
+            .lock()
+            .window()
+            .current_monitor()
+            .map(|monitor| MonitorInfo::from(&monitor));
+
+        if current_monitor != self.window_info.current_monitor {
+            self.update_window_info(runner);
+        } else {
+            self.window_info.position = Some((position.x, position.y));
+            (runner)(ContextEvent::WindowInfoChanged(self.window_info.clone()));
+        }
+    }
+
+    /// Run the event loop, passing events to the runner, until the control flow is exited
+    pub fn run<F: FnMut(ContextEvent)>(&mut self, runner: F) -> Result<(), RenderError> {
+        let event_loop = self.event_loop.take();
+        event_loop.run_app(&mut ContextHandler { context: self, runner })?;
+
+        Ok(())
+    }
+}
+
+/// Handler of the event loop, which advances the context's frames and passes events to the runner
+struct ContextHandler<'a, F> {
+    context: &'a mut Context,
+    runner: F,
+}
+
+impl<F: FnMut(ContextEvent)> ApplicationHandler<UserEvent> for ContextHandler<'_, F> {
+    // The window is created along with the context
+    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _window_id: WindowId, event: WindowEvent) {
+        let context = &mut *self.context;
+        let runner = &mut self.runner;
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                context.next_frame(&mut *runner);
+
+                if context.control_flow.is_exiting() {
+                    event_loop.exit();
+                } else {
+                    event_loop.set_control_flow(*(context.control_flow.inner.lock()));
+                }
+
+                let display = context.display.lock();
+                display.swap_buffers().unwrap();
+                context.window_commands.apply(display.window());
+
+                return;
+            },
+            WindowEvent::Resized(physical_size) => {
+                (runner)(ContextEvent::ResizeEvent(WindowExtent::from(physical_size)));
+                context.display.lock().resize(physical_size);
+            },
+            // The window is resized afterwards with `Resized` event
+            WindowEvent::ScaleFactorChanged { .. } => context.update_window_info(runner),
+            WindowEvent::Occluded(occluded) => context.window_occluded = occluded,
+            WindowEvent::Moved(position) => context.window_moved(position, runner),
+            _ => {},
+        }
+
+        // Let systems handle the event, e.g. veto closing, on the next paused frame
+        if context.paused {
+            context.paused_update = true;
+        }
+
+        (runner)(ContextEvent::WindowEvent(context.display.clone(), event));
+    }
+
+    fn device_event(&mut self, _event_loop: &ActiveEventLoop, _device_id: DeviceId, event: DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta } = event {
+            (self.runner)(ContextEvent::MouseMotion(delta));
+        }
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+        (self.runner)(ContextEvent::User(event));
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        self.context.display.lock().window().request_redraw();
+    }
+}
+
+/// Prefer hardware accelerated configs, which support sRGB if it's requested
+fn pick_config(configs: Box<dyn Iterator<Item = Config> + '_>, srgb: bool) -> Config {
+    configs
+        .max_by_key(|config| (config.hardware_accelerated(), config.srgb_capable() == srgb))
+        .expect("No suitable OpenGL config")
+}
+
+fn min_frame_time(max_fps: Option<u32>) -> Option<Duration> {
+    max_fps
+        .filter(|&fps| fps > 0)
+        .map(|fps| Duration::from_secs_f64(1.0 / fps as f64))
+}
+
+/// Sleep until `deadline`. The thread sleeps for the most of the time and spins
+/// for the rest, because OS sleep is not precise enough for frame pacing
+fn sleep_until(deadline: Instant) {
+    const SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+
+        let remaining = deadline - now;
+        if remaining > SPIN_THRESHOLD {
+            std::thread::sleep(remaining - SPIN_THRESHOLD);
+        } else {
+            std::hint::spin_loop();
+        }
     }
 }
 
@@ -286,18 +1155,46 @@ pub struct WindowBuilder {
     pub height: u32,
     /// Specifies whether the window should be fullscreen or windowed
     pub fullscreen: bool,
+    /// Kind of fullscreen, used if `fullscreen` is `true`. Available video modes
+    /// can be obtained with [`Context::video_modes`]
+    pub fullscreen_mode: FullscreenMode,
     /// Specifies whether the window is maximized on startup
     pub maximized: bool,
     /// Specifies whether the window should be resizable
     pub resizable: bool,
-    /// Icon of the winit window. Requires feature `render` enabled
-    pub icon: Option<Icon>,
+    /// Run the app without window and rendering, executing only `Setup`, `Update`
+    /// and `Paused` stages (e.g. for dedicated servers and tests)
+    pub headless: bool,
+    /// Graphics API of the context. It's OpenGL ES on Android and OpenGL otherwise
+    pub gl_api: GlApi,
+    /// `(major, minor)` version of the context. `None` means the default
+    /// version of `gl_api` (OpenGL 4.1 or OpenGL ES 3.0)
+    pub gl_version: Option<(u8, u8)>,
+    /// OpenGL context profile
+    pub gl_profile: GlProfile,
+    /// Request debug context, which enables driver diagnostics
+    pub gl_debug: bool,
+    /// Request sRGB-capable default framebuffer. Shading is done in linear space, so without it
+    /// the output isn't gamma-corrected. See [`Renderer::set_srgb_output`](crate::renderer::Renderer::set_srgb_output)
+    pub srgb: bool,
+    /// Behavior of the context, when the window is occluded or minimized
+    pub occlusion_policy: OcclusionPolicy,
+    /// Exit, when closing the window is requested and it's not vetoed
+    pub exit_on_close: bool,
+    /// Vertical synchronization mode. Can be changed at runtime with [`Display::set_vsync`]
+    pub vsync: VsyncMode,
+    /// Icon of the window. Requires feature `render` enabled
+    pub icon: Option<IconSource>,
     /// Specifies logger level and whether it must be initialized
     pub logger_level: LoggerLevel,
-    /// 
+    /// Optional rotating log file, which is written along with the console output
+    pub log_file: Option<LogFileConfig>,
+    /// Number of fixed updates per second
     pub updates_per_second: u32, 
-    ///
-    pub max_frame_time: f64
+    /// Maximum time of a single frame (in seconds), which is taken into account
+    pub max_frame_time: f64,
+    /// Frame rate limit, independent of vsync. `None` means unlimited frame rate
+    pub max_fps: Option<u32>,
 }
 
 impl Default for WindowBuilder {
@@ -307,15 +1204,27 @@ impl Default for WindowBuilder {
             width: 800, 
             height: 600, 
             fullscreen: false, 
+            fullscreen_mode: FullscreenMode::default(),
             maximized: false, 
             resizable: true, 
+            headless: false,
+            gl_api: GlApi::default(),
+            gl_version: None,
+            gl_profile: GlProfile::default(),
+            gl_debug: cfg!(debug_assertions),
+            srgb: true,
+            occlusion_policy: OcclusionPolicy::default(),
+            exit_on_close: true,
+            vsync: VsyncMode::default(),
             icon: None, 
             #[cfg(not(debug_assertions))]
             logger_level: LoggerLevel::Info, 
             #[cfg(debug_assertions)]
             logger_level: LoggerLevel::Debug,
+            log_file: None,
             updates_per_second: 240,
             max_frame_time: 0.1,
+            max_fps: None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,41 @@
+use winit::event::{ElementState, Ime, KeyEvent, WindowEvent};
+
+/// ECS event, which is sent on text input. It's independent of the keyboard
+/// layout, so it must be used for chat boxes, name entry fields etc.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextInputEvent {
+    /// Printable character is typed
+    Character(char),
+    /// IME is enabled. IME must be allowed with [`WindowCommands::set_ime_allowed`]
+    ///
+    /// [`WindowCommands::set_ime_allowed`]: super::WindowCommands::set_ime_allowed
+    ImeEnabled,
+    /// Text is being composed with IME. Cursor range is byte-wise indexed
+    /// and `None` if the cursor must be hidden. Empty text means the composition is cleared
+    Preedit { text: String, cursor: Option<(usize, usize)> },
+    /// Composed text must be inserted
+    Commit(String),
+    ImeDisabled,
+}
+
+impl TextInputEvent {
+    /// Text input of the window event. A key press can type several characters, e.g. with dead keys
+    pub fn from_window_event(event: &WindowEvent) -> Vec<TextInputEvent> {
+        match event {
+            WindowEvent::KeyboardInput { event: KeyEvent { text: Some(text), state: ElementState::Pressed, .. }, .. } => {
+                text.chars()
+                    .filter(|c| !c.is_control())
+                    .map(TextInputEvent::Character)
+                    .collect()
+            },
+            WindowEvent::Ime(Ime::Enabled) => vec![TextInputEvent::ImeEnabled],
+            WindowEvent::Ime(Ime::Preedit(text, cursor)) => vec![TextInputEvent::Preedit {
+                text: text.clone(),
+                cursor: *cursor,
+            }],
+            WindowEvent::Ime(Ime::Commit(text)) => vec![TextInputEvent::Commit(text.clone())],
+            WindowEvent::Ime(Ime::Disabled) => vec![TextInputEvent::ImeDisabled],
+            _ => vec![],
+        }
+    }
+}
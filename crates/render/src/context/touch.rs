@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use flatbox_core::math::glm;
+use winit::event::{Touch, TouchPhase as WinitTouchPhase};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TouchPhase {
+    Started,
+    Moved,
+    Ended,
+    Cancelled,
+}
+
+impl From<WinitTouchPhase> for TouchPhase {
+    fn from(phase: WinitTouchPhase) -> Self {
+        match phase {
+            WinitTouchPhase::Started => TouchPhase::Started,
+            WinitTouchPhase::Moved => TouchPhase::Moved,
+            WinitTouchPhase::Ended => TouchPhase::Ended,
+            WinitTouchPhase::Cancelled => TouchPhase::Cancelled,
+        }
+    }
+}
+
+/// ECS event, which is sent for each touch of the touch screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchEvent {
+    /// Identifier of the finger, unique while it's touching the screen
+    pub id: u64,
+    pub phase: TouchPhase,
+    /// Position in physical pixels relative to the top-left corner of the window
+    pub position: glm::Vec2,
+    /// Normalized pressure (`0.0..=1.0`) if it's supported by the device
+    pub force: Option<f32>,
+}
+
+impl From<&Touch> for TouchEvent {
+    fn from(touch: &Touch) -> Self {
+        TouchEvent {
+            id: touch.id,
+            phase: touch.phase.into(),
+            position: glm::vec2(touch.location.x as f32, touch.location.y as f32),
+            force: touch.force.map(|force| force.normalized() as f32),
+        }
+    }
+}
+
+/// ECS event, which is sent when [`GestureRecognizer`] detects a gesture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// Short touch without movement
+    Tap { position: glm::Vec2 },
+    /// Single finger movement
+    Drag { position: glm::Vec2, delta: glm::Vec2 },
+    /// Two fingers movement. `scale` is the ratio of the current distance
+    /// between the fingers to the previous one
+    Pinch { center: glm::Vec2, scale: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TouchState {
+    start_position: glm::Vec2,
+    start_time: Instant,
+    position: glm::Vec2,
+    dragging: bool,
+}
+
+/// Simple recognizer of tap, drag and pinch gestures from [`TouchEvent`]s
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    /// Maximal duration of a tap
+    pub tap_time: Duration,
+    /// Distance (in physical pixels), after which the touch is considered as drag
+    pub drag_threshold: f32,
+    touches: HashMap<u64, TouchState>,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> GestureRecognizer {
+        GestureRecognizer::default()
+    }
+
+    /// Number of fingers touching the screen
+    pub fn touch_count(&self) -> usize {
+        self.touches.len()
+    }
+
+    pub fn handle(&mut self, event: &TouchEvent) -> Option<Gesture> {
+        match event.phase {
+            TouchPhase::Started => {
+                self.touches.insert(event.id, TouchState {
+                    start_position: event.position,
+                    start_time: Instant::now(),
+                    position: event.position,
+                    dragging: false,
+                });
+
+                None
+            },
+            TouchPhase::Moved => {
+                let previous = self.touches.get(&event.id)?.position;
+
+                if self.touches.len() == 2 {
+                    let other = self.touches
+                        .iter()
+                        .find(|(&id, _)| id != event.id)
+                        .map(|(_, touch)| touch.position)?;
+
+                    self.touches.get_mut(&event.id)?.position = event.position;
+
+                    let previous_distance = glm::distance(&previous, &other);
+                    if previous_distance <= f32::EPSILON {
+                        return None;
+                    }
+
+                    return Some(Gesture::Pinch {
+                        center: (event.position + other) * 0.5,
+                        scale: glm::distance(&event.position, &other) / previous_distance,
+                    });
+                }
+
+                let touch = self.touches.get_mut(&event.id)?;
+                touch.position = event.position;
+
+                if !touch.dragging && glm::distance(&touch.start_position, &event.position) > self.drag_threshold {
+                    touch.dragging = true;
+                }
+
+                if touch.dragging && self.touches.len() == 1 {
+                    Some(Gesture::Drag {
+                        position: event.position,
+                        delta: event.position - previous,
+                    })
+                } else {
+                    None
+                }
+            },
+            TouchPhase::Ended => {
+                let single = self.touches.len() == 1;
+                let touch = self.touches.remove(&event.id)?;
+
+                if single && !touch.dragging && touch.start_time.elapsed() <= self.tap_time {
+                    Some(Gesture::Tap { position: event.position })
+                } else {
+                    None
+                }
+            },
+            TouchPhase::Cancelled => {
+                self.touches.remove(&event.id);
+                None
+            },
+        }
+    }
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        GestureRecognizer {
+            tap_time: Duration::from_millis(300),
+            drag_threshold: 10.0,
+            touches: HashMap::new(),
+        }
+    }
+}
@@ -2,6 +2,10 @@ use image::ImageError;
 use thiserror::Error;
 
 use crate::hal::shader::ShaderError;
+#[cfg(feature = "context")]
+use crate::context::VsyncMode;
+#[cfg(feature = "context")]
+use winit::{error::EventLoopError, window::BadIcon};
 
 #[derive(Debug, Error)]
 pub enum RenderError {
@@ -9,10 +13,39 @@ pub enum RenderError {
     ImageProcessing(#[from] ImageError),
     #[error("Error processing shaders")]
     ShaderProcessing(#[from] ShaderError),
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid font data")]
+    InvalidFont(#[from] ab_glyph::InvalidFont),
     #[error("Material not bound: {0}")]
     MaterialNotBound(String),
     #[error("Model is not prepared for drawing. Before `DrawModelCommand` call `PrepareModelCommand` first")]
     ModelNotPrepared,
+    #[error("Cubemap faces must be square and have the same size")]
+    InvalidCubemap,
+    #[error("Invalid texture region: {0}")]
+    InvalidTextureRegion(String),
+    #[error("Framebuffer is incomplete: {0}")]
+    IncompleteFramebuffer(String),
+    #[error("Render target not found: {0}")]
+    RenderTargetNotFound(String),
+    #[error("Render passes depend on each other: {0}")]
+    RenderGraphCycle(String),
     #[error("There can be only one active camera at once")]
     MultipleActiveCameras,
+    #[cfg(feature = "context")]
+    #[error("Swap interval `{0:?}` is not supported by the platform")]
+    UnsupportedVsyncMode(VsyncMode),
+    #[cfg(feature = "context")]
+    #[error("Invalid window icon")]
+    InvalidIcon(#[from] BadIcon),
+    #[cfg(feature = "context")]
+    #[error("Event loop is closed")]
+    EventLoopClosed,
+    #[cfg(feature = "context")]
+    #[error("Event loop error")]
+    EventLoop(#[from] EventLoopError),
+    #[cfg(feature = "context")]
+    #[error("OpenGL context error")]
+    Context(#[from] glutin::error::Error),
 }
\ No newline at end of file
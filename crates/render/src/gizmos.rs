@@ -0,0 +1,226 @@
+use serde::{Serialize, Deserialize};
+use flatbox_assets::typetag;
+use flatbox_core::{
+    color::Color,
+    math::{bounds::Aabb, glm, transform::Transform},
+};
+
+use crate::{
+    error::RenderError,
+    macros::vertex_attribute,
+    hal::buffer::{AttributeType, Buffer, BufferTarget, BufferUsage, VertexArray, VertexAttribute, VertexLayout},
+    pbr::material::Material,
+    renderer::{Capability, DisableCommand, EnableCommand, RenderCommand, Renderer},
+};
+
+/// Number of segments of circles, which make up spheres
+const CIRCLE_SEGMENTS: usize = 32;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineVertex {
+    pub position: glm::Vec3,
+    pub color: glm::Vec4,
+}
+
+impl LineVertex {
+    pub fn new(position: glm::Vec3, color: &Color) -> Self {
+        LineVertex {
+            position,
+            color: glm::vec4(color.r, color.g, color.b, color.a),
+        }
+    }
+}
+
+impl VertexLayout for LineVertex {
+    fn attributes() -> Vec<VertexAttribute> {
+        vec![
+            vertex_attribute!("position", LineVertex::position, AttributeType::Float),
+            vertex_attribute!("color", LineVertex::color, AttributeType::Float),
+        ]
+    }
+}
+
+/// Singleton component, which collects debug lines in world space during the frame.
+/// They are drawn at once with [`DrawLinesCommand`] and cleared afterwards
+pub struct Gizmos {
+    /// Whether lines are hidden by the scene geometry
+    pub depth_test: bool,
+    vertices: Vec<LineVertex>,
+    vertex_array: Option<VertexArray>,
+    vertex_buffer: Option<Buffer>,
+}
+
+impl Gizmos {
+    pub fn new() -> Self {
+        Gizmos::default()
+    }
+
+    pub fn line(&mut self, start: glm::Vec3, end: glm::Vec3, color: Color) {
+        self.vertices.push(LineVertex::new(start, &color));
+        self.vertices.push(LineVertex::new(end, &color));
+    }
+
+    /// Line from `origin` to `origin + direction`
+    pub fn ray(&mut self, origin: glm::Vec3, direction: glm::Vec3, color: Color) {
+        self.line(origin, origin + direction, color);
+    }
+
+    /// Edges of the box
+    pub fn aabb(&mut self, aabb: &Aabb, color: Color) {
+        let corners = aabb.corners();
+
+        // Corners are ordered by X, then Y, then Z, so edges connect indices, differing in one bit
+        for (a, b) in [
+            (0, 1), (2, 3), (4, 5), (6, 7),
+            (0, 2), (1, 3), (4, 6), (5, 7),
+            (0, 4), (1, 5), (2, 6), (3, 7),
+        ] {
+            self.line(corners[a], corners[b], color);
+        }
+    }
+
+    /// Circle around `normal` axis
+    pub fn circle(&mut self, center: glm::Vec3, normal: glm::Vec3, radius: f32, color: Color) {
+        let normal = normal.try_normalize(f32::EPSILON).unwrap_or_else(glm::Vec3::y);
+        let helper = if normal.x.abs() < 0.9 { glm::Vec3::x() } else { glm::Vec3::y() };
+        let u = normal.cross(&helper).normalize() * radius;
+        let v = normal.cross(&u);
+
+        let point = |i: usize| {
+            let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + u * angle.cos() + v * angle.sin()
+        };
+
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), color);
+        }
+    }
+
+    /// Three circles around the coordinate axes
+    pub fn sphere(&mut self, center: glm::Vec3, radius: f32, color: Color) {
+        for axis in [glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z()] {
+            self.circle(center, axis, radius, color);
+        }
+    }
+
+    /// Local X, Y and Z axes of the transform as red, green and blue lines
+    pub fn axes(&mut self, transform: &Transform, length: f32) {
+        let origin = transform.translation_f32();
+        let rotation = transform.rotation_f32();
+
+        for (axis, color) in [
+            (glm::Vec3::x(), Color::rgb(1.0, 0.0, 0.0)),
+            (glm::Vec3::y(), Color::rgb(0.0, 1.0, 0.0)),
+            (glm::Vec3::z(), Color::rgb(0.0, 0.0, 1.0)),
+        ] {
+            self.ray(origin, glm::quat_rotate_vec3(&rotation, &axis) * length, color);
+        }
+    }
+
+    /// Number of lines, collected since the last draw
+    pub fn len(&self) -> usize {
+        self.vertices.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+}
+
+impl Default for Gizmos {
+    fn default() -> Self {
+        Gizmos {
+            depth_test: true,
+            vertices: vec![],
+            vertex_array: None,
+            vertex_buffer: None,
+        }
+    }
+}
+
+/// Material of debug lines. Lines don't need any configuration, so it's only used
+/// to bind the pipeline and to set the camera with `RenderCameraCommand::<LineMaterial>`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct LineMaterial;
+
+#[typetag::serde]
+impl Material for LineMaterial {
+    fn vertex_shader() -> &'static str {
+        include_str!("shaders/lines.vs")
+    }
+
+    fn fragment_shader() -> &'static str {
+        include_str!("shaders/lines.fs")
+    }
+}
+
+/// Draw all lines of the [`Gizmos`] with a single draw call and clear them. Only color is drawn,
+/// auxiliary outputs (e.g. velocity) are kept. `RenderCameraCommand::<LineMaterial>` must be executed first
+pub struct DrawLinesCommand<'a> {
+    gizmos: &'a mut Gizmos,
+}
+
+impl<'a> DrawLinesCommand<'a> {
+    pub fn new(gizmos: &'a mut Gizmos) -> DrawLinesCommand<'a> {
+        DrawLinesCommand { gizmos }
+    }
+}
+
+impl<'a> RenderCommand for DrawLinesCommand<'a> {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        if self.gizmos.is_empty() {
+            return Ok(());
+        }
+
+        let pipeline = renderer.get_pipeline::<LineMaterial>()?;
+        pipeline.apply();
+
+        if self.gizmos.vertex_array.is_none() {
+            let vertex_array = VertexArray::new();
+            let vertex_buffer = Buffer::new(BufferTarget::ArrayBuffer, BufferUsage::DynamicDraw);
+
+            vertex_array.bind();
+            vertex_buffer.bind();
+
+            vertex_array.set_layout::<LineVertex>(pipeline);
+
+            self.gizmos.vertex_array = Some(vertex_array);
+            self.gizmos.vertex_buffer = Some(vertex_buffer);
+        }
+
+        self.gizmos.vertex_array.as_ref().unwrap().bind();
+        self.gizmos.vertex_buffer.as_ref().unwrap().fill(&self.gizmos.vertices);
+
+        if !self.gizmos.depth_test {
+            renderer.execute(&mut DisableCommand(Capability::DepthTest))?;
+        }
+
+        unsafe {
+            for i in 1..renderer.draw_buffers() {
+                gl::ColorMaski(i as u32, gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            }
+        }
+
+        renderer.record_draw_call(0);
+        unsafe { gl::DrawArrays(gl::LINES, 0, self.gizmos.vertices.len() as i32); }
+
+        unsafe {
+            for i in 1..renderer.draw_buffers() {
+                gl::ColorMaski(i as u32, gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+            }
+        }
+
+        if !self.gizmos.depth_test {
+            renderer.execute(&mut EnableCommand(Capability::DepthTest))?;
+        }
+
+        self.gizmos.clear();
+
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use std::fmt::Debug;
-use gl::types::{GLuint, GLsizeiptr, GLint};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use gl::types::{GLuint, GLsizeiptr, GLint, GLintptr};
 
 use crate::macros::glenum_wrapper;
+use crate::hal::shader::GraphicsPipeline;
 
 glenum_wrapper! {
     wrapper: BufferTarget,
@@ -20,11 +22,16 @@ glenum_wrapper! {
     variants: [StreamDraw, StaticDraw, DynamicDraw]
 }
 
+/// GPU buffer. Buffers with dynamic or stream usage keep their storage between fills:
+/// if the data fits, the storage is orphaned and updated in place, otherwise it grows
+/// to the next power of two, so buffers, refilled every frame, stop reallocating
 #[readonly::make]
 pub struct Buffer {
     id: GLuint,
     target: GLuint,
     usage: GLuint,
+    /// Size of the allocated storage in bytes
+    capacity: AtomicUsize,
 
     __debug_target: BufferTarget,
     __debug_usage: BufferUsage,
@@ -39,18 +46,74 @@ impl Buffer {
         &self,
         data: &[T],
     ){
+        let bytes = as_bytes(data);
+
+        if self.usage == gl::STATIC_DRAW {
+            self.bind();
+            self.allocate(bytes.len(), bytes.as_ptr());
+            return;
+        }
+
+        if bytes.len() > self.capacity() {
+            self.bind();
+            self.allocate(bytes.len().next_power_of_two(), std::ptr::null());
+        } else {
+            self.orphan();
+        }
+
+        self.fill_sub(0, data);
+    }
+
+    /// Update part of the storage, starting at `offset` in bytes, without reallocation
+    /// 
+    /// ## Panics
+    /// 
+    /// Panics, if the data doesn't fit into the allocated storage
+    pub fn fill_sub<T: Sized>(&self, offset: usize, data: &[T]) {
+        let bytes = as_bytes(data);
+        assert!(
+            offset + bytes.len() <= self.capacity(),
+            "Buffer update of {} bytes at offset {offset} exceeds capacity of {} bytes",
+            bytes.len(), self.capacity(),
+        );
+
+        if bytes.is_empty() {
+            return;
+        }
+
         self.bind();
-        let (_, bytes, _) = unsafe { data.align_to::<u8>() };
         unsafe {
-            gl::BufferData(
+            gl::BufferSubData(
                 self.target,
+                offset as GLintptr,
                 bytes.len() as GLsizeiptr,
                 bytes.as_ptr() as *const _,
-                self.usage,
             );
         }
     }
 
+    /// Make sure the storage can hold `len` elements of `T`. The contents are undefined, if the storage grows
+    pub fn reserve<T: Sized>(&self, len: usize) {
+        let size = len * std::mem::size_of::<T>();
+
+        if size > self.capacity() {
+            self.bind();
+            self.allocate(size, std::ptr::null());
+        }
+    }
+
+    /// Detach the current storage, so the driver can give a fresh one of the same size
+    /// instead of waiting for draw calls, which still read the old data
+    pub fn orphan(&self) {
+        self.bind();
+        self.allocate(self.capacity(), std::ptr::null());
+    }
+
+    /// Size of the allocated storage in bytes
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
     pub fn bind(&self) {
         unsafe { gl::BindBuffer(self.target, self.id); }
     }
@@ -59,6 +122,20 @@ impl Buffer {
         unsafe { gl::BindBuffer(self.target, 0); }
     }
 
+    /// Allocate storage of the bound buffer. `data` may be null
+    fn allocate(&self, size: usize, data: *const u8) {
+        unsafe {
+            gl::BufferData(
+                self.target,
+                size as GLsizeiptr,
+                data as *const _,
+                self.usage,
+            );
+        }
+
+        self.capacity.store(size, Ordering::Relaxed);
+    }
+
     unsafe fn new_internal(target: BufferTarget, usage: BufferUsage) -> Buffer {
         let mut id: GLuint = 0;
         gl::GenBuffers(1, &mut id);
@@ -67,6 +144,7 @@ impl Buffer {
             id, 
             target: target as u32, 
             usage: usage as u32,
+            capacity: AtomicUsize::new(0),
             __debug_target: target,
             __debug_usage: usage,
         }
@@ -85,6 +163,7 @@ impl Debug for Buffer {
             .field("id", &self.id)
             .field("target", &self.__debug_target)
             .field("usage", &self.__debug_usage)
+            .field("capacity", &self.capacity())
             .finish()
     }
 }
@@ -95,6 +174,91 @@ impl Drop for Buffer {
     }
 }
 
+/// Buffer with immutable storage, which stays mapped to the client memory, so it's written
+/// without any GL calls. Writes become visible to subsequent draw calls, but the data,
+/// which is still read by the GPU, mustn't be overwritten, e.g. write different regions
+/// in consecutive frames. Requires OpenGL 4.4 or `GL_ARB_buffer_storage`
+pub struct MappedBuffer {
+    buffer: Buffer,
+    size: usize,
+    ptr: *mut u8,
+}
+
+impl MappedBuffer {
+    /// Allocate and map `size` bytes. Returns `None`, if persistent mapping isn't supported
+    pub fn new(target: BufferTarget, size: usize) -> Option<MappedBuffer> {
+        if !gl::BufferStorage::is_loaded() || !gl::MapBufferRange::is_loaded() {
+            return None;
+        }
+
+        let buffer = Buffer::new(target, BufferUsage::DynamicDraw);
+        let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+
+        buffer.bind();
+        let ptr = unsafe {
+            gl::BufferStorage(buffer.target, size as GLsizeiptr, std::ptr::null(), flags);
+            gl::MapBufferRange(buffer.target, 0, size as GLsizeiptr, flags) as *mut u8
+        };
+
+        if ptr.is_null() {
+            return None;
+        }
+
+        buffer.capacity.store(size, Ordering::Relaxed);
+
+        Some(MappedBuffer { buffer, size, ptr })
+    }
+
+    /// Copy data into the mapped storage, starting at `offset` in bytes
+    /// 
+    /// ## Panics
+    /// 
+    /// Panics, if the data doesn't fit into the storage
+    pub fn write<T: Sized>(&mut self, offset: usize, data: &[T]) {
+        let bytes = as_bytes(data);
+        assert!(
+            offset + bytes.len() <= self.size,
+            "Mapped buffer write of {} bytes at offset {offset} exceeds size of {} bytes",
+            bytes.len(), self.size,
+        );
+
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), self.ptr.add(offset), bytes.len()); }
+    }
+
+    pub fn bind(&self) {
+        self.buffer.bind();
+    }
+
+    pub fn unbind(&self) {
+        self.buffer.unbind();
+    }
+
+    /// Size of the storage in bytes
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Debug for MappedBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MappedBuffer")
+            .field("buffer", &self.buffer)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl Drop for MappedBuffer {
+    fn drop(&mut self) {
+        self.buffer.bind();
+        unsafe { gl::UnmapBuffer(self.buffer.target); }
+    }
+}
+
+fn as_bytes<T: Sized>(data: &[T]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
+}
+
 glenum_wrapper! {
     wrapper: AttributeType,
     variants: [
@@ -109,6 +273,48 @@ glenum_wrapper! {
     ]
 }
 
+/// Description of a single vertex attribute. It can be created
+/// from a vertex struct field with [`vertex_attribute!`](crate::vertex_attribute) macro
+#[derive(Clone, Copy, Debug)]
+pub struct VertexAttribute {
+    /// Name of the attribute in the vertex shader
+    pub name: &'static str,
+    pub attrib_type: AttributeType,
+    /// Number of components (e.g. `3` for `vec3`)
+    pub components: i32,
+    /// Offset of the attribute from the start of the vertex in bytes
+    pub offset: i32,
+}
+
+/// Vertex format, which can be bound to a vertex array with [`VertexArray::set_layout`]
+/// 
+/// ## Example
+/// ```
+/// use flatbox_render::{
+///     vertex_attribute,
+///     hal::buffer::{AttributeType, VertexAttribute, VertexLayout},
+/// };
+/// 
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct ColorVertex {
+///     position: [f32; 3],
+///     color: [f32; 4],
+/// }
+/// 
+/// impl VertexLayout for ColorVertex {
+///     fn attributes() -> Vec<VertexAttribute> {
+///         vec![
+///             vertex_attribute!("position", ColorVertex::position, AttributeType::Float),
+///             vertex_attribute!("color", ColorVertex::color, AttributeType::Float),
+///         ]
+///     }
+/// }
+/// ```
+pub trait VertexLayout: Copy + Sized + 'static {
+    fn attributes() -> Vec<VertexAttribute>;
+}
+
 #[readonly::make]
 pub struct VertexArray {
     id: GLuint,
@@ -127,6 +333,10 @@ impl VertexArray {
         unsafe { gl::BindVertexArray(0); }
     }
 
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
     /// Set attribute
     /// 
     /// ## Safety
@@ -150,6 +360,26 @@ impl VertexArray {
         gl::EnableVertexAttribArray(attrib_pos);
     }
 
+    /// Set all attributes of the vertex format, bound to the locations of the pipeline.
+    /// Attributes, which are not used by the pipeline, are skipped. Vertex buffer must be bound
+    pub fn set_layout<V: VertexLayout>(&self, pipeline: &GraphicsPipeline) {
+        for attribute in V::attributes() {
+            let location = pipeline.get_attribute_location(attribute.name) as i32;
+            if location < 0 {
+                continue;
+            }
+
+            unsafe {
+                self.set_attribute::<V>(
+                    location as u32,
+                    attribute.attrib_type,
+                    attribute.components,
+                    attribute.offset,
+                );
+            }
+        }
+    }
+
     unsafe fn new_internal() -> VertexArray {
         let mut id: GLuint = 0;
         gl::GenVertexArrays(1, &mut id);
@@ -0,0 +1,138 @@
+use std::ffi::CStr;
+use gl::types::{GLenum, GLint};
+
+/// Features of the current OpenGL context, queried once at [`Renderer`] initialization
+///
+/// [`Renderer`]: crate::renderer::Renderer
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GlCapabilities {
+    pub vendor: String,
+    pub renderer: String,
+    /// Full version string, reported by the driver
+    pub version: String,
+    pub glsl_version: String,
+    /// `(major, minor)` version of the context
+    pub version_number: (u32, u32),
+    pub is_gles: bool,
+    /// Whether the context was created with debug flag
+    pub debug_context: bool,
+    /// Whether the default framebuffer is sRGB-capable
+    pub srgb_framebuffer: bool,
+    pub max_texture_size: u32,
+    pub max_texture_units: u32,
+    pub max_samples: u32,
+    pub extensions: Vec<String>,
+}
+
+impl GlCapabilities {
+    /// Query capabilities of the current context. GL functions must be loaded
+    pub fn detect() -> GlCapabilities {
+        let version_number = (get_integer(gl::MAJOR_VERSION) as u32, get_integer(gl::MINOR_VERSION) as u32);
+        let is_gles = super::is_gles();
+
+        let extensions = (0..get_integer(gl::NUM_EXTENSIONS).max(0) as u32)
+            .filter_map(|i| unsafe { to_string(gl::GetStringi(gl::EXTENSIONS, i)) })
+            .collect::<Vec<_>>();
+
+        let debug_context = get_integer(gl::CONTEXT_FLAGS) as GLenum & gl::CONTEXT_FLAG_DEBUG_BIT != 0;
+
+        let srgb_framebuffer = unsafe {
+            let mut encoding: GLint = 0;
+            let attachment = if is_gles { gl::BACK } else { gl::BACK_LEFT };
+
+            gl::GetFramebufferAttachmentParameteriv(
+                gl::FRAMEBUFFER,
+                attachment,
+                gl::FRAMEBUFFER_ATTACHMENT_COLOR_ENCODING,
+                &mut encoding,
+            );
+
+            // Drain the error, if the query isn't supported for the default framebuffer
+            gl::GetError();
+
+            encoding as GLenum == gl::SRGB
+        };
+
+        GlCapabilities {
+            vendor: get_string(gl::VENDOR),
+            renderer: get_string(gl::RENDERER),
+            version: get_string(gl::VERSION),
+            glsl_version: get_string(gl::SHADING_LANGUAGE_VERSION),
+            version_number,
+            is_gles,
+            debug_context,
+            srgb_framebuffer,
+            max_texture_size: get_integer(gl::MAX_TEXTURE_SIZE).max(0) as u32,
+            max_texture_units: get_integer(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS).max(0) as u32,
+            max_samples: get_integer(gl::MAX_SAMPLES).max(0) as u32,
+            extensions,
+        }
+    }
+
+    /// Check whether the context version is at least `major.minor`
+    pub fn supports_version(&self, major: u32, minor: u32) -> bool {
+        self.version_number >= (major, minor)
+    }
+
+    /// Check whether the extension (e.g. `GL_KHR_debug`) is supported
+    pub fn has_extension(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|e| e == extension)
+    }
+
+    /// Query current video memory usage. It's available only with
+    /// `GL_NVX_gpu_memory_info` or `GL_ATI_meminfo` extensions
+    pub fn gpu_memory(&self) -> Option<GpuMemoryInfo> {
+        const GPU_MEMORY_INFO_TOTAL_AVAILABLE_MEMORY_NVX: GLenum = 0x9048;
+        const GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX: GLenum = 0x9049;
+        const TEXTURE_FREE_MEMORY_ATI: GLenum = 0x87FC;
+
+        if self.has_extension("GL_NVX_gpu_memory_info") {
+            Some(GpuMemoryInfo {
+                total_kb: Some(get_integer(GPU_MEMORY_INFO_TOTAL_AVAILABLE_MEMORY_NVX).max(0) as u64),
+                available_kb: get_integer(GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX).max(0) as u64,
+            })
+        } else if self.has_extension("GL_ATI_meminfo") {
+            let mut info: [GLint; 4] = [0; 4];
+            unsafe { gl::GetIntegerv(TEXTURE_FREE_MEMORY_ATI, info.as_mut_ptr()); }
+
+            Some(GpuMemoryInfo {
+                total_kb: None,
+                available_kb: info[0].max(0) as u64,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Video memory usage in kilobytes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GpuMemoryInfo {
+    /// Total dedicated memory, if reported by the driver
+    pub total_kb: Option<u64>,
+    pub available_kb: u64,
+}
+
+impl GpuMemoryInfo {
+    pub fn used_kb(&self) -> Option<u64> {
+        self.total_kb.map(|total| total.saturating_sub(self.available_kb))
+    }
+}
+
+fn get_integer(name: GLenum) -> GLint {
+    let mut value = 0;
+    unsafe { gl::GetIntegerv(name, &mut value); }
+    value
+}
+
+fn get_string(name: GLenum) -> String {
+    unsafe { to_string(gl::GetString(name)) }.unwrap_or_default()
+}
+
+unsafe fn to_string(string: *const u8) -> Option<String> {
+    if string.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(string as *const _).to_string_lossy().into_owned())
+    }
+}
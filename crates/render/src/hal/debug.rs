@@ -0,0 +1,99 @@
+use std::ffi::{c_void, CStr};
+
+use gl::types::{GLchar, GLenum, GLsizei, GLuint};
+use flatbox_core::logger::{debug, error, info, warn};
+
+use super::capabilities::GlCapabilities;
+
+/// Install callback, which routes driver messages into the logger. Returns `false`, if
+/// neither OpenGL 4.3 nor `GL_KHR_debug` is available. Messages are reported only by
+/// debug contexts (see [`GlCapabilities::debug_context`])
+pub fn install_debug_callback(capabilities: &GlCapabilities) -> bool {
+    let supported = capabilities.supports_version(4, 3) || capabilities.has_extension("GL_KHR_debug");
+
+    if !supported || !gl::DebugMessageCallback::is_loaded() {
+        warn!("GL debug output is not supported by the context");
+        return false;
+    }
+
+    if !capabilities.debug_context {
+        warn!("GL context is not a debug one, driver messages may be missing");
+    }
+
+    unsafe {
+        gl::Enable(gl::DEBUG_OUTPUT);
+        // Messages are reported from the call, which caused them
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(debug_callback), std::ptr::null());
+    }
+
+    true
+}
+
+/// Take all errors, raised since the previous check
+pub fn take_errors() -> Vec<&'static str> {
+    let mut errors = vec![];
+
+    loop {
+        match unsafe { gl::GetError() } {
+            gl::NO_ERROR => break,
+            error => errors.push(error_name(error)),
+        }
+
+        // Lost context keeps reporting errors
+        if errors.len() >= 16 {
+            break;
+        }
+    }
+
+    errors
+}
+
+extern "system" fn debug_callback(
+    source: GLenum,
+    gltype: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    _length: GLsizei,
+    message: *const GLchar,
+    _user_param: *mut c_void,
+) {
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    let source = match source {
+        gl::DEBUG_SOURCE_API => "API",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "third party",
+        gl::DEBUG_SOURCE_APPLICATION => "application",
+        _ => "other",
+    };
+    let gltype = match gltype {
+        gl::DEBUG_TYPE_ERROR => "error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+        gl::DEBUG_TYPE_PORTABILITY => "portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "performance",
+        gl::DEBUG_TYPE_MARKER => "marker",
+        _ => "other",
+    };
+
+    match severity {
+        gl::DEBUG_SEVERITY_HIGH => error!("GL {gltype} from {source} ({id}): {message}"),
+        gl::DEBUG_SEVERITY_MEDIUM => warn!("GL {gltype} from {source} ({id}): {message}"),
+        gl::DEBUG_SEVERITY_LOW => info!("GL {gltype} from {source} ({id}): {message}"),
+        _ => debug!("GL {gltype} from {source} ({id}): {message}"),
+    }
+}
+
+fn error_name(error: GLenum) -> &'static str {
+    match error {
+        gl::INVALID_ENUM => "GL_INVALID_ENUM",
+        gl::INVALID_VALUE => "GL_INVALID_VALUE",
+        gl::INVALID_OPERATION => "GL_INVALID_OPERATION",
+        gl::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+        gl::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+        gl::STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
+        gl::STACK_OVERFLOW => "GL_STACK_OVERFLOW",
+        _ => "unknown GL error",
+    }
+}
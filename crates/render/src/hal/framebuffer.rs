@@ -0,0 +1,201 @@
+use gl::types::{GLenum, GLuint};
+use flatbox_core::logger::error;
+
+use crate::{
+    error::RenderError,
+    pbr::texture::{ColorMode, Filter, Texture},
+};
+
+/// Kind of the depth attachment of a [`Framebuffer`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DepthAttachment {
+    #[default]
+    None,
+    Depth,
+    /// Combined 24-bit depth and 8-bit stencil attachment
+    DepthStencil,
+}
+
+/// Offscreen render target with texture attachments
+#[derive(Debug)]
+pub struct Framebuffer {
+    id: GLuint,
+    width: u32,
+    height: u32,
+    color_modes: Vec<ColorMode>,
+    depth: DepthAttachment,
+    color_attachments: Vec<Texture>,
+    depth_attachment: Option<Texture>,
+}
+
+impl Framebuffer {
+    /// Create framebuffer with color attachments of the given modes and optional depth/stencil attachment
+    pub fn new(width: u32, height: u32, color_modes: &[ColorMode], depth: DepthAttachment) -> Framebuffer {
+        let mut id: GLuint = 0;
+        unsafe { gl::GenFramebuffers(1, &mut id); }
+
+        let mut framebuffer = Framebuffer {
+            id,
+            width,
+            height,
+            color_modes: color_modes.to_vec(),
+            depth,
+            color_attachments: vec![],
+            depth_attachment: None,
+        };
+
+        framebuffer.create_attachments();
+        framebuffer
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn color_attachment(&self, index: usize) -> Option<&Texture> {
+        self.color_attachments.get(index)
+    }
+
+    /// Depth attachment, which also contains the stencil values for [`DepthAttachment::DepthStencil`]
+    pub fn depth_attachment(&self) -> Option<&Texture> {
+        self.depth_attachment.as_ref()
+    }
+
+    pub fn has_stencil(&self) -> bool {
+        self.depth == DepthAttachment::DepthStencil
+    }
+
+    /// Check whether the framebuffer can be rendered into with its current attachments
+    pub fn check_status(&self) -> Result<(), RenderError> {
+        let status = unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+
+        match status {
+            gl::FRAMEBUFFER_COMPLETE => Ok(()),
+            status => Err(RenderError::IncompleteFramebuffer(status_name(status).to_string())),
+        }
+    }
+
+    /// Take the color attachment out of the framebuffer, e.g. to keep the rendered image
+    pub fn into_color_attachment(mut self, index: usize) -> Option<Texture> {
+        let mut attachments = std::mem::take(&mut self.color_attachments);
+        (index < attachments.len()).then(|| attachments.swap_remove(index))
+    }
+
+    /// Number of color attachments, i.e. fragment shader outputs written by draw calls
+    pub fn draw_buffers(&self) -> usize {
+        self.color_attachments.len()
+    }
+
+    /// Recreate attachments with the new size. Their previous contents are lost
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if (self.width, self.height) != (width, height) {
+            self.width = width;
+            self.height = height;
+            self.create_attachments();
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    /// Copy the first color attachment into `target`, or into the default framebuffer
+    /// of `extent` size if `None`. Draw framebuffer binding is changed to the target
+    pub fn blit(&self, target: Option<&Framebuffer>, extent: [u32; 2]) {
+        let (target_id, [width, height]) = match target {
+            Some(target) => (target.id, [target.width, target.height]),
+            None => (0, extent),
+        };
+
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target_id);
+            gl::BlitFramebuffer(
+                0, 0, self.width as i32, self.height as i32,
+                0, 0, width as i32, height as i32,
+                gl::COLOR_BUFFER_BIT,
+                gl::LINEAR,
+            );
+        }
+    }
+
+    /// Bind the default framebuffer
+    pub fn unbind(&self) {
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, 0); }
+    }
+
+    fn create_attachments(&mut self) {
+        let (width, height) = (self.width, self.height);
+
+        self.color_attachments = self.color_modes
+            .iter()
+            .map(|&mode| Texture::new_empty(width, height, mode, Filter::Linear))
+            .collect();
+
+        self.depth_attachment = match self.depth {
+            DepthAttachment::None => None,
+            DepthAttachment::Depth => Some(Texture::new_empty(width, height, ColorMode::DepthComponent24, Filter::Nearest)),
+            DepthAttachment::DepthStencil => Some(Texture::new_empty(width, height, ColorMode::Depth24Stencil8, Filter::Nearest)),
+        };
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+
+            let mut draw_buffers = vec![];
+
+            for (i, texture) in self.color_attachments.iter().enumerate() {
+                let attachment = gl::COLOR_ATTACHMENT0 + i as u32;
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture.id(), 0);
+                draw_buffers.push(attachment);
+            }
+
+            if let Some(ref texture) = self.depth_attachment {
+                let attachment = match self.depth {
+                    DepthAttachment::DepthStencil => gl::DEPTH_STENCIL_ATTACHMENT,
+                    _ => gl::DEPTH_ATTACHMENT,
+                };
+
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture.id(), 0);
+            }
+
+            gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        if let Err(e) = self.check_status() {
+            error!("Cannot create {}x{} framebuffer: {e}", width, height);
+        }
+    }
+}
+
+pub(crate) fn status_name(status: GLenum) -> &'static str {
+    match status {
+        gl::FRAMEBUFFER_UNDEFINED => "FRAMEBUFFER_UNDEFINED",
+        gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "FRAMEBUFFER_INCOMPLETE_ATTACHMENT",
+        gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => "FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT",
+        gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => "FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER",
+        gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => "FRAMEBUFFER_INCOMPLETE_READ_BUFFER",
+        gl::FRAMEBUFFER_UNSUPPORTED => "FRAMEBUFFER_UNSUPPORTED",
+        gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "FRAMEBUFFER_INCOMPLETE_MULTISAMPLE",
+        gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => "FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS",
+        _ => "unknown framebuffer status",
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteFramebuffers(1, [self.id].as_ptr()); }
+    }
+}
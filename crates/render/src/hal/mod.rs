@@ -1,6 +1,24 @@
 pub mod buffer;
+pub mod capabilities;
+#[cfg(feature = "debug-gl")]
+pub mod debug;
+pub mod framebuffer;
+pub mod preprocessor;
+pub mod query;
+pub mod sampler;
 pub mod shader;
 
+/// Check whether the current context is OpenGL ES
+pub fn is_gles() -> bool {
+    unsafe {
+        let version = gl::GetString(gl::VERSION);
+
+        !version.is_null() && std::ffi::CStr::from_ptr(version as *const _)
+            .to_string_lossy()
+            .starts_with("OpenGL ES")
+    }
+}
+
 pub trait GlInitFunction: FnMut(&'static str) -> *const std::ffi::c_void {}
 impl<F> GlInitFunction for F
 where 
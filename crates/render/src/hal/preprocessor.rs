@@ -0,0 +1,107 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::path::Path;
+use std::sync::OnceLock;
+use parking_lot::RwLock;
+
+use crate::hal::shader::ShaderError;
+
+type ChunkRegistry = RwLock<HashMap<String, Cow<'static, str>>>;
+
+static SHADER_CHUNKS: OnceLock<ChunkRegistry> = OnceLock::new();
+
+fn shader_chunks() -> &'static ChunkRegistry {
+    SHADER_CHUNKS.get_or_init(|| {
+        RwLock::new(HashMap::from([
+            (String::from("lighting.glsl"), Cow::Borrowed(include_str!("../shaders/chunks/lighting.glsl"))),
+            (String::from("transforms.glsl"), Cow::Borrowed(include_str!("../shaders/chunks/transforms.glsl"))),
+            (String::from("shadows.glsl"), Cow::Borrowed(include_str!("../shaders/chunks/shadows.glsl"))),
+            (String::from("fog.glsl"), Cow::Borrowed(include_str!("../shaders/chunks/fog.glsl"))),
+            (String::from("environment.glsl"), Cow::Borrowed(include_str!("../shaders/chunks/environment.glsl"))),
+            (String::from("environment/cubemap.glsl"), Cow::Borrowed(include_str!("../shaders/environment/cubemap.glsl"))),
+        ]))
+    })
+}
+
+/// Register virtual shader chunk, which can be included with `#include "name"`.
+/// Chunks with the same name (including built-in `lighting.glsl`, `transforms.glsl`, `shadows.glsl`,
+/// `fog.glsl` and `environment.glsl`) are replaced
+pub fn register_shader_chunk(name: impl Into<String>, source: impl Into<Cow<'static, str>>) {
+    shader_chunks().write().insert(name.into(), source.into());
+}
+
+/// Get source of the registered shader chunk
+pub fn shader_chunk(name: &str) -> Option<Cow<'static, str>> {
+    shader_chunks().read().get(name).cloned()
+}
+
+/// Resolve `#include "file"` directives and inject `#define`s after the `#version` line.
+///
+/// Includes are looked up in the chunk registry first, then in `include_dir` (if any).
+/// Every file is included only once, so chunks don't need include guards
+pub fn preprocess(
+    source_code: &str,
+    defines: &[(&str, &str)],
+    include_dir: Option<&Path>,
+) -> Result<String, ShaderError> {
+    let mut output = String::with_capacity(source_code.len());
+    let mut included = HashSet::new();
+
+    let (version, body) = match source_code.trim_start().strip_prefix("#version") {
+        Some(rest) => {
+            let (version, body) = rest.split_once('\n').unwrap_or((rest, ""));
+            (Some(version.trim()), body)
+        },
+        None => (None, source_code),
+    };
+
+    if let Some(version) = version {
+        output.push_str(&format!("#version {version}\n"));
+    }
+
+    for (name, value) in defines {
+        output.push_str(&format!("#define {name} {value}\n"));
+    }
+
+    resolve_includes(body, include_dir, &mut included, &mut output)?;
+
+    Ok(output)
+}
+
+fn resolve_includes(
+    source_code: &str,
+    include_dir: Option<&Path>,
+    included: &mut HashSet<String>,
+    output: &mut String,
+) -> Result<(), ShaderError> {
+    for line in source_code.lines() {
+        let Some(directive) = line.trim_start().strip_prefix("#include") else {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        };
+
+        let name = directive.trim()
+            .strip_prefix('"')
+            .and_then(|name| name.strip_suffix('"'))
+            .ok_or_else(|| ShaderError::IncludeError(format!("Malformed include directive `{}`", line.trim())))?;
+
+        if !included.insert(name.to_owned()) {
+            continue;
+        }
+
+        let chunk = match shader_chunk(name) {
+            Some(chunk) => chunk.into_owned(),
+            None => match include_dir {
+                Some(dir) => read_to_string(dir.join(name))
+                    .map_err(|e| ShaderError::IncludeError(format!("Cannot include `{name}`: {e}")))?,
+                None => return Err(ShaderError::IncludeError(format!("Shader chunk `{name}` is not registered"))),
+            },
+        };
+
+        resolve_includes(&chunk, include_dir, included, output)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+use gl::types::GLuint;
+
+/// Query, whether any samples of the draw calls between [`OcclusionQuery::begin`] and
+/// [`OcclusionQuery::end`] pass the depth test. The result arrives asynchronously,
+/// usually a frame later, so it's polled with [`OcclusionQuery::try_result`]
+#[derive(Debug)]
+pub struct OcclusionQuery {
+    id: GLuint,
+    pending: bool,
+}
+
+impl OcclusionQuery {
+    pub fn new() -> OcclusionQuery {
+        let mut id: GLuint = 0;
+        unsafe { gl::GenQueries(1, &mut id); }
+
+        OcclusionQuery { id, pending: false }
+    }
+
+    /// Start the query. Only one occlusion query can be active at a time
+    pub fn begin(&mut self) {
+        unsafe { gl::BeginQuery(gl::ANY_SAMPLES_PASSED, self.id); }
+    }
+
+    pub fn end(&mut self) {
+        unsafe { gl::EndQuery(gl::ANY_SAMPLES_PASSED); }
+        self.pending = true;
+    }
+
+    /// Whether the query was issued and its result hasn't been read yet
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Get the result without waiting for the GPU. It's `None`, if the query
+    /// wasn't issued, the result was already read or it's not available yet
+    pub fn try_result(&mut self) -> Option<bool> {
+        if !self.pending {
+            return None;
+        }
+
+        let mut available = 0;
+        unsafe { gl::GetQueryObjectuiv(self.id, gl::QUERY_RESULT_AVAILABLE, &mut available); }
+
+        if available == gl::FALSE as u32 {
+            return None;
+        }
+
+        self.result()
+    }
+
+    /// Wait for the result. It's `None`, if the query wasn't issued or the result was already read
+    pub fn result(&mut self) -> Option<bool> {
+        if !self.pending {
+            return None;
+        }
+
+        let mut result = 0;
+        unsafe { gl::GetQueryObjectuiv(self.id, gl::QUERY_RESULT, &mut result); }
+        self.pending = false;
+
+        Some(result != gl::FALSE as u32)
+    }
+}
+
+impl Default for OcclusionQuery {
+    fn default() -> Self {
+        OcclusionQuery::new()
+    }
+}
+
+impl Drop for OcclusionQuery {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteQueries(1, &self.id); }
+    }
+}
+
+/// Query of GPU time, elapsed between [`TimerQuery::begin`] and [`TimerQuery::end`].
+/// Like [`OcclusionQuery`], its result arrives asynchronously
+#[derive(Debug)]
+pub struct TimerQuery {
+    id: GLuint,
+    pending: bool,
+}
+
+impl TimerQuery {
+    pub fn new() -> TimerQuery {
+        let mut id: GLuint = 0;
+        unsafe { gl::GenQueries(1, &mut id); }
+
+        TimerQuery { id, pending: false }
+    }
+
+    /// Whether timer queries are supported by the context. OpenGL ES lacks them
+    pub fn is_supported() -> bool {
+        !super::is_gles() && gl::GetQueryObjectui64v::is_loaded()
+    }
+
+    /// Start the query. Only one timer query can be active at a time
+    pub fn begin(&mut self) {
+        unsafe { gl::BeginQuery(gl::TIME_ELAPSED, self.id); }
+    }
+
+    pub fn end(&mut self) {
+        unsafe { gl::EndQuery(gl::TIME_ELAPSED); }
+        self.pending = true;
+    }
+
+    /// Whether the query was issued and its result hasn't been read yet
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Get the result without waiting for the GPU. It's `None`, if the query
+    /// wasn't issued, the result was already read or it's not available yet
+    pub fn try_result(&mut self) -> Option<Duration> {
+        if !self.pending {
+            return None;
+        }
+
+        let mut available = 0;
+        unsafe { gl::GetQueryObjectuiv(self.id, gl::QUERY_RESULT_AVAILABLE, &mut available); }
+
+        if available == gl::FALSE as u32 {
+            return None;
+        }
+
+        self.result()
+    }
+
+    /// Wait for the result. It's `None`, if the query wasn't issued or the result was already read
+    pub fn result(&mut self) -> Option<Duration> {
+        if !self.pending {
+            return None;
+        }
+
+        let mut nanoseconds = 0;
+        unsafe { gl::GetQueryObjectui64v(self.id, gl::QUERY_RESULT, &mut nanoseconds); }
+        self.pending = false;
+
+        Some(Duration::from_nanos(nanoseconds))
+    }
+}
+
+impl Default for TimerQuery {
+    fn default() -> Self {
+        TimerQuery::new()
+    }
+}
+
+impl Drop for TimerQuery {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteQueries(1, &self.id); }
+    }
+}
@@ -0,0 +1,115 @@
+use gl::types::{GLenum, GLuint};
+
+use crate::pbr::texture::{Filter, Order, WrapMode};
+
+/// `GL_TEXTURE_MAX_ANISOTROPY` of `GL_EXT_texture_filter_anisotropic`, core since OpenGL 4.6
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+/// Parameters of [`Sampler`]
+#[derive(Debug, Clone, Copy)]
+pub struct SamplerDescriptor {
+    pub min_filter: Filter,
+    pub mag_filter: Filter,
+    /// Filter between mip levels. `None` samples only the base level, so textures don't need mipmaps
+    pub mipmap_filter: Option<Filter>,
+    pub wrap_mode: WrapMode,
+    /// Number of samples of anisotropic filtering, e.g. `16.0`. It's clamped to the maximum,
+    /// supported by the driver, and `1.0` disables it
+    pub max_anisotropy: f32,
+}
+
+impl Default for SamplerDescriptor {
+    fn default() -> Self {
+        SamplerDescriptor {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmap_filter: None,
+            wrap_mode: WrapMode::Repeat,
+            max_anisotropy: 1.0,
+        }
+    }
+}
+
+/// Filter and wrap parameters, which override the parameters of any texture bound to the same unit.
+/// The same texture can be sampled differently by different materials without duplicating it.
+/// Activating a texture with [`Texture::activate`] unbinds the sampler of its unit, so samplers
+/// are bound after textures
+///
+/// [`Texture::activate`]: crate::pbr::texture::Texture::activate
+#[derive(Debug)]
+pub struct Sampler {
+    id: GLuint,
+    descriptor: SamplerDescriptor,
+}
+
+impl Sampler {
+    pub fn new(descriptor: SamplerDescriptor) -> Sampler {
+        let mut id: GLuint = 0;
+
+        let min_filter = match (descriptor.min_filter, descriptor.mipmap_filter) {
+            (filter, None) => filter as u32,
+            (Filter::Nearest, Some(Filter::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Nearest, Some(Filter::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+            (Filter::Linear, Some(Filter::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+            (Filter::Linear, Some(Filter::Linear)) => gl::LINEAR_MIPMAP_LINEAR,
+        };
+
+        unsafe {
+            gl::GenSamplers(1, &mut id);
+
+            gl::SamplerParameteri(id, gl::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl::SamplerParameteri(id, gl::TEXTURE_MAG_FILTER, descriptor.mag_filter as i32);
+            gl::SamplerParameteri(id, gl::TEXTURE_WRAP_S, descriptor.wrap_mode as i32);
+            gl::SamplerParameteri(id, gl::TEXTURE_WRAP_T, descriptor.wrap_mode as i32);
+            gl::SamplerParameteri(id, gl::TEXTURE_WRAP_R, descriptor.wrap_mode as i32);
+
+            if descriptor.max_anisotropy > 1.0 {
+                let anisotropy = descriptor.max_anisotropy.min(max_anisotropy());
+
+                if anisotropy > 1.0 {
+                    gl::SamplerParameterf(id, TEXTURE_MAX_ANISOTROPY, anisotropy);
+                }
+            }
+        }
+
+        Sampler { id, descriptor }
+    }
+
+    pub fn descriptor(&self) -> &SamplerDescriptor {
+        &self.descriptor
+    }
+
+    pub(crate) fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Sample textures of the unit with this sampler
+    pub fn bind(&self, order: Order) {
+        unsafe { gl::BindSampler(order as u32 - gl::TEXTURE0, self.id); }
+    }
+
+    /// Sample textures of the unit with their own parameters
+    pub fn unbind(order: Order) {
+        unsafe { gl::BindSampler(order as u32 - gl::TEXTURE0, 0); }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteSamplers(1, &self.id); }
+    }
+}
+
+/// Maximum anisotropy, supported by the driver. It's `1.0` without anisotropic filtering
+pub fn max_anisotropy() -> f32 {
+    let mut max_anisotropy = 1.0;
+
+    unsafe {
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy);
+        // Drain the error, if anisotropic filtering isn't supported
+        gl::GetError();
+    }
+
+    max_anisotropy.max(1.0)
+}
@@ -1,12 +1,14 @@
+use std::borrow::Cow;
 use std::fs::read_to_string;
 use std::path::Path;
 use std::ptr;
 use std::string::FromUtf8Error;
 use thiserror::Error;
 use gl::types::{GLuint, GLint};
-use flatbox_core::math::glm;
+use flatbox_core::{color::Color, math::glm};
 
 use crate::macros::*;
+use crate::hal::{is_gles, preprocessor::preprocess};
 
 #[derive(Error, Debug)]
 pub enum ShaderError {
@@ -18,6 +20,8 @@ pub enum ShaderError {
     LinkingError(String),
     #[error("Can't convert raw pointer to UTF-8 string")]
     Utf8Error(#[from] FromUtf8Error),
+    #[error("Shader include error")]
+    IncludeError(String),
 }
 
 glenum_wrapper! {
@@ -33,18 +37,34 @@ pub struct Shader {
 }
 
 impl Shader {
+    /// Load shader from file. Includes, which aren't registered shader chunks,
+    /// are resolved relative to the file's directory
     pub fn new(path: impl AsRef<Path>, shader_type: ShaderType) -> Result<Shader, ShaderError> {
+        let path = path.as_ref();
         let source_code = read_to_string(path)?;
+        let source_code = preprocess(&source_code, &[], path.parent())?;
         
-        Shader::new_from_source(&source_code, shader_type)
+        unsafe { Shader::new_internal(&source_code, shader_type as u32) }
     }
 
+    /// Compile shader from source, resolving `#include`s of registered shader chunks
     pub fn new_from_source(source_code: &str, shader_type: ShaderType) -> Result<Shader, ShaderError> {
-        unsafe { Shader::new_internal(source_code, shader_type as u32) }
+        Shader::new_with_defines(source_code, shader_type, &[])
+    }
+
+    /// Compile shader from source with `#define NAME VALUE` injected for each of `defines`
+    pub fn new_with_defines(
+        source_code: &str, 
+        shader_type: ShaderType,
+        defines: &[(&str, &str)],
+    ) -> Result<Shader, ShaderError> {
+        let source_code = preprocess(source_code, defines, None)?;
+
+        unsafe { Shader::new_internal(&source_code, shader_type as u32) }
     }
 
     unsafe fn new_internal(source_code: &str, shader_type: GLuint) -> Result<Shader, ShaderError> {
-        let source_code = c_string!(source_code);
+        let source_code = c_string!(adapt_source(source_code, shader_type).as_ref());
         let shader = Shader {
             id: gl::CreateShader(shader_type),
         };
@@ -76,12 +96,36 @@ impl Shader {
     }
 }
 
+/// Replace desktop GLSL version with GLSL ES 3.00 and add default precision
+/// qualifiers, if the current context is OpenGL ES
+fn adapt_source(source_code: &str, shader_type: GLuint) -> Cow<'_, str> {
+    if !is_gles() {
+        return Cow::Borrowed(source_code);
+    }
+
+    let body = match source_code.trim_start().strip_prefix("#version") {
+        Some(rest) => rest.split_once('\n').map(|(_, body)| body).unwrap_or(""),
+        None => source_code,
+    };
+
+    let precision = match shader_type {
+        gl::FRAGMENT_SHADER => "precision highp float;\nprecision highp int;\n",
+        _ => "",
+    };
+
+    Cow::Owned(format!("#version 300 es\n{precision}{body}"))
+}
+
 impl Drop for Shader {
     fn drop(&mut self) {
         unsafe { gl::DeleteShader(self.id); }
     }
 }
 
+/// Vertex attributes, bound to locations `0`, `1` and `2` respectively, unless
+/// the shader sets locations explicitly
+pub const STANDARD_ATTRIBUTES: [&str; 3] = ["position", "normal", "texcoord"];
+
 pub struct GraphicsPipeline {
     id: GLuint,
 }
@@ -92,6 +136,7 @@ impl GraphicsPipeline {
     }
 
     pub fn apply(&self){
+        crate::renderer::stats::count_pipeline_apply(self.id);
         unsafe { gl::UseProgram(self.id); }
     }
 
@@ -126,6 +171,11 @@ impl GraphicsPipeline {
         unsafe { gl::Uniform4fv(location, 1, glm::value_ptr(value).as_ptr()); }
     }
 
+    pub fn set_color(&self, name: &str, value: &Color) {
+        let location = self.get_uniform_location(name);
+        unsafe { gl::Uniform4f(location, value.r, value.g, value.b, value.a); }
+    }
+
     pub fn set_mat2(&self, name: &str, value: &glm::Mat2) {        
         let location = self.get_uniform_location(name);
         unsafe { gl::UniformMatrix2fv(location, 1, gl::FALSE, glm::value_ptr(value).as_ptr()); }
@@ -160,6 +210,13 @@ impl GraphicsPipeline {
             gl::AttachShader(program.id, shader.id);
         }
 
+        // Common vertex attributes have the same locations in all pipelines,
+        // so a vertex array can be drawn with any of them
+        for (location, name) in STANDARD_ATTRIBUTES.iter().enumerate() {
+            let name = c_string!(*name);
+            gl::BindAttribLocation(program.id, location as u32, name.as_ptr());
+        }
+
         gl::LinkProgram(program.id);
 
         let mut success: GLint = 0;
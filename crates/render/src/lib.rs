@@ -1,11 +1,18 @@
 #[cfg(feature = "context")]
 pub mod context;
 pub mod error;
+pub mod gizmos;
 pub mod hal;
 pub mod macros;
+pub mod occlusion;
+pub mod outline;
 pub mod pbr;
+pub mod postprocess;
 pub mod renderer;
+pub mod screenshot;
+pub mod text;
+pub mod tilemap;
 pub mod palette {
-    pub use palette::*;
+    pub use flatbox_core::color::palette::*;
 }
 pub mod prelude;
\ No newline at end of file
@@ -1,4 +1,4 @@
-pub use crate::{glenum_wrapper, set_vertex_attribute, c_string};
+pub use crate::{glenum_wrapper, vertex_attribute, set_vertex_attribute, c_string};
 
 #[macro_export]
 macro_rules! glenum_wrapper {
@@ -31,8 +31,8 @@ macro_rules! c_string {
 }
 
 #[macro_export]
-macro_rules! set_vertex_attribute {
-    ($vao:ident, $pos:tt, $t:ident :: $field:tt, $attrib_type:expr) => {
+macro_rules! vertex_attribute {
+    ($name:expr, $t:ident :: $field:tt, $attrib_type:expr) => {
         {
             use core::mem::size_of;
             use $crate::hal::buffer::AttributeType::*;
@@ -46,7 +46,9 @@ macro_rules! set_vertex_attribute {
             let member_ptr = unsafe { core::ptr::addr_of!((*dummy_ptr).$field) };
             let member_offset = member_ptr as i32 - dummy_ptr as i32;
 
-            let size = match $attrib_type {
+            let attrib_type = $attrib_type;
+
+            let size = match attrib_type {
                 Byte            => size_of::<i8>(),
                 UnsignedByte    => size_of::<u8>(),
                 Short           => size_of::<i16>(),
@@ -57,14 +59,33 @@ macro_rules! set_vertex_attribute {
                 Double          => size_of::<f64>(),
             };
 
+            $crate::hal::buffer::VertexAttribute {
+                name: $name,
+                attrib_type,
+                components: (size_of_raw(member_ptr) / size) as i32,
+                offset: member_offset,
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! set_vertex_attribute {
+    ($vao:ident, $pos:tt, $t:ident :: $field:tt, $attrib_type:expr) => {
+        {
+            let attribute = $crate::vertex_attribute!("", $t::$field, $attrib_type);
+
+            let vertex_array = &$vao;
+            let attrib_pos = $pos;
+
             unsafe { 
-                $vao.set_attribute::<$t>(
-                    $pos,
-                    $attrib_type,
-                    (size_of_raw(member_ptr) / size) as i32,
-                    member_offset,
+                vertex_array.set_attribute::<$t>(
+                    attrib_pos,
+                    attribute.attrib_type,
+                    attribute.components,
+                    attribute.offset,
                 )
             }
         }
     };
-}
\ No newline at end of file
+}
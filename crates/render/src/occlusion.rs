@@ -0,0 +1,159 @@
+use flatbox_core::math::{bounds::Aabb, glm, transform::Transform};
+
+use crate::{
+    error::RenderError,
+    hal::{
+        query::OcclusionQuery,
+        shader::{GraphicsPipeline, Shader, ShaderType},
+    },
+    pbr::{
+        camera::Camera,
+        mesh::Mesh,
+        model::Model,
+    },
+    renderer::{DrawTrianglesCommand, Renderer},
+};
+
+/// Skip drawing the model, if its bounding box was hidden by the scene in the previous frame.
+/// It pays off for complex models in dense scenes, e.g. buildings of a city.
+/// Boxes are tested by [`OcclusionCulling`] singleton
+#[derive(Debug)]
+pub struct OcclusionCulled {
+    visible: bool,
+    query: Option<OcclusionQuery>,
+    bounds: Option<Aabb>,
+}
+
+impl OcclusionCulled {
+    pub fn new() -> Self {
+        OcclusionCulled::default()
+    }
+
+    /// Whether the model was visible in the previous test. Models are visible until tested
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Forget the cached bounding box, e.g. after the mesh has been changed
+    pub fn reset_bounds(&mut self) {
+        self.bounds = None;
+    }
+}
+
+impl Default for OcclusionCulled {
+    fn default() -> Self {
+        OcclusionCulled {
+            visible: true,
+            query: None,
+            bounds: None,
+        }
+    }
+}
+
+/// Singleton component, which tests bounding boxes of [`OcclusionCulled`] models against
+/// the depth of the rendered scene. Results are used in the next frame, so models, which
+/// become visible, may appear a frame late
+#[derive(Default)]
+pub struct OcclusionCulling {
+    pipeline: Option<GraphicsPipeline>,
+    cube: Option<Mesh>,
+}
+
+impl OcclusionCulling {
+    pub fn new() -> Self {
+        OcclusionCulling::default()
+    }
+
+    /// Read results of the previous tests and test bounding boxes of `models` against the depth
+    /// of the bound framebuffer, viewed from the camera. Models must be drawn beforehand
+    pub fn render<'a>(
+        &mut self,
+        renderer: &mut Renderer,
+        camera: &Camera,
+        camera_transform: &Transform,
+        models: impl IntoIterator<Item = (&'a Model, &'a Transform, &'a mut OcclusionCulled)>,
+    ) -> Result<(), RenderError> {
+        if self.pipeline.is_none() {
+            let pipeline = GraphicsPipeline::new(&[
+                Shader::new_from_source(include_str!("shaders/occlusion/bounds.vs"), ShaderType::VertexShader)?,
+                Shader::new_from_source(include_str!("shaders/occlusion/bounds.fs"), ShaderType::FragmentShader)?,
+            ])?;
+
+            let mut cube = Mesh::cube();
+            cube.setup(&pipeline);
+
+            self.pipeline = Some(pipeline);
+            self.cube = Some(cube);
+        }
+
+        let pipeline = self.pipeline.as_ref().unwrap();
+        let cube = self.cube.as_ref().unwrap();
+
+        let origin = renderer.render_origin();
+        let camera_position: glm::Vec3 = glm::convert(camera_transform.translation - origin);
+
+        pipeline.apply();
+        pipeline.set_mat4("view", &camera.relative_view_matrix(camera_transform, &origin));
+        pipeline.set_mat4("projection", &camera.projection_matrix());
+        cube.vertex_array.bind();
+
+        unsafe {
+            gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            gl::DepthMask(gl::FALSE);
+        }
+
+        let mut result = Ok(());
+
+        for (model, transform, culled) in models {
+            let Some(ref mesh) = model.mesh else { continue };
+
+            let query = culled.query.get_or_insert_with(OcclusionQuery::new);
+            if let Some(visible) = query.try_result() {
+                culled.visible = visible;
+            }
+
+            // The previous test is still in flight
+            if query.is_pending() {
+                continue;
+            }
+
+            let bounds = culled.bounds.get_or_insert_with(|| {
+                Aabb::from_points(mesh.vertex_data.iter().map(|vertex| &vertex.position))
+                    .unwrap_or(Aabb::new(glm::Vec3::zeros(), glm::Vec3::zeros()))
+            });
+
+            let (model_matrix, inversed) = transform.to_relative_matrices(&origin);
+
+            // Box faces are clipped by the near plane, when the camera is inside the box
+            let local_camera = (inversed * camera_position.push(1.0)).xyz();
+            let margin = glm::Vec3::repeat(camera.near() / transform.scale_f32());
+            if Aabb::new(bounds.min - margin, bounds.max + margin).contains_point(&local_camera) {
+                culled.visible = true;
+                continue;
+            }
+
+            let box_matrix = model_matrix
+                * glm::translation(&bounds.center())
+                * glm::scaling(&bounds.size().map(|size| size.max(f32::EPSILON)));
+
+            pipeline.set_mat4("model", &box_matrix);
+
+            query.begin();
+            result = unsafe { renderer.execute(&mut DrawTrianglesCommand::new(cube.index_data.len())) };
+            query.end();
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        unsafe {
+            gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+            gl::DepthMask(gl::TRUE);
+        }
+
+        renderer.reset_material_state();
+
+        result
+    }
+}
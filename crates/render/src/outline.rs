@@ -0,0 +1,175 @@
+use serde::{Serialize, Deserialize};
+use flatbox_core::{
+    color::Color,
+    math::{glm, transform::Transform},
+};
+
+use crate::{
+    error::RenderError,
+    hal::{
+        buffer::VertexArray,
+        framebuffer::{DepthAttachment, Framebuffer},
+        shader::{GraphicsPipeline, Shader, ShaderType},
+    },
+    pbr::{
+        camera::Camera,
+        model::Model,
+        texture::{ColorMode, Order},
+    },
+    postprocess::FULLSCREEN_VERTEX_SHADER,
+    renderer::{Capability, DisableCommand, DrawFullscreenCommand, DrawTrianglesCommand, EnableCommand, Renderer},
+};
+
+/// Maximum width of an outline in pixels
+pub const MAX_OUTLINE_WIDTH: f32 = 32.0;
+
+/// Draw an opaque outline around the model's silhouette, e.g. to highlight the selected
+/// entity in an editor or an interactive object in a game. It's visible through other objects
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Outlined {
+    pub color: Color,
+    /// Width in pixels, up to [`MAX_OUTLINE_WIDTH`]
+    pub width: f32,
+}
+
+impl Outlined {
+    pub fn new(color: Color, width: f32) -> Self {
+        Outlined { color, width }
+    }
+}
+
+impl Default for Outlined {
+    fn default() -> Self {
+        Outlined::new(Color::rgb(1.0, 0.6, 0.0), 2.0)
+    }
+}
+
+/// Singleton component, which renders outlines of [`Outlined`] models. Silhouettes are drawn
+/// into a mask with outline colors and widths, which is then dilated over the bound framebuffer
+#[derive(Default)]
+pub struct Outlines {
+    mask: Option<Framebuffer>,
+    mask_pipeline: Option<GraphicsPipeline>,
+    composite_pipeline: Option<GraphicsPipeline>,
+    vertex_array: Option<VertexArray>,
+}
+
+impl Outlines {
+    pub fn new() -> Self {
+        Outlines::default()
+    }
+
+    /// Draw outlines of `models`, viewed from the camera, over the bound framebuffer
+    pub fn render<'a>(
+        &mut self,
+        renderer: &mut Renderer,
+        camera: &Camera,
+        camera_transform: &Transform,
+        models: impl IntoIterator<Item = (&'a Model, &'a Outlined, &'a Transform)>,
+    ) -> Result<(), RenderError> {
+        let models = models
+            .into_iter()
+            .filter(|(model, outlined, _)| outlined.width > 0.0 && model.mesh.as_ref().is_some_and(|mesh| mesh.prepared))
+            .collect::<Vec<_>>();
+
+        if models.is_empty() {
+            return Ok(());
+        }
+
+        if self.mask_pipeline.is_none() {
+            self.mask_pipeline = Some(GraphicsPipeline::new(&[
+                Shader::new_from_source(include_str!("shaders/outline/mask.vs"), ShaderType::VertexShader)?,
+                Shader::new_from_source(include_str!("shaders/outline/mask.fs"), ShaderType::FragmentShader)?,
+            ])?);
+
+            self.composite_pipeline = Some(GraphicsPipeline::new(&[
+                Shader::new_from_source(FULLSCREEN_VERTEX_SHADER, ShaderType::VertexShader)?,
+                Shader::new_from_source(include_str!("shaders/outline/composite.fs"), ShaderType::FragmentShader)?,
+            ])?);
+        }
+
+        let [width, height]: [u32; 2] = renderer.extent().into();
+        let mask = self.mask.get_or_insert_with(|| Framebuffer::new(width, height, &[ColorMode::Rgba], DepthAttachment::None));
+        mask.resize(width, height);
+
+        // The mask is drawn aside, then the bound framebuffer is restored
+        let mut bound_framebuffer = 0;
+        let mut viewport = [0; 4];
+
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut bound_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+
+        mask.bind();
+
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+
+        renderer.execute(&mut DisableCommand(Capability::DepthTest))?;
+        renderer.execute(&mut DisableCommand(Capability::Blend))?;
+
+        let origin = renderer.render_origin();
+        let mask_pipeline = self.mask_pipeline.as_ref().unwrap();
+        mask_pipeline.apply();
+        mask_pipeline.set_mat4("view", &camera.relative_view_matrix(camera_transform, &origin));
+        mask_pipeline.set_mat4("projection", &camera.projection_matrix());
+
+        let mut max_width: f32 = 0.0;
+
+        for (model, outlined, transform) in models {
+            let mesh = model.mesh.as_ref().unwrap();
+            let width = outlined.width.min(MAX_OUTLINE_WIDTH);
+            let (model_matrix, _) = transform.to_relative_matrices(&origin);
+
+            mask_pipeline.set_mat4("model", &model_matrix);
+            mask_pipeline.set_vec4("outline", &glm::vec4(
+                outlined.color.r,
+                outlined.color.g,
+                outlined.color.b,
+                width / MAX_OUTLINE_WIDTH,
+            ));
+
+            mesh.vertex_array.bind();
+            unsafe { renderer.execute(&mut DrawTrianglesCommand::new(mesh.index_data.len()))?; }
+
+            max_width = max_width.max(width);
+        }
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, bound_framebuffer as u32);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+
+            // Only color is drawn, auxiliary outputs (e.g. velocity) are kept
+            for i in 1..renderer.draw_buffers() {
+                gl::ColorMaski(i as u32, gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            }
+        }
+
+        renderer.execute(&mut EnableCommand(Capability::Blend))?;
+
+        let composite_pipeline = self.composite_pipeline.as_ref().unwrap();
+        composite_pipeline.apply();
+        composite_pipeline.set_int("mask_texture", 0);
+        composite_pipeline.set_float("max_width", MAX_OUTLINE_WIDTH);
+        composite_pipeline.set_int("radius", max_width.ceil() as i32);
+        mask.color_attachment(0).unwrap().activate(Order::Texture0);
+
+        self.vertex_array.get_or_insert_with(VertexArray::new).bind();
+        unsafe { renderer.execute(&mut DrawFullscreenCommand::new())?; }
+
+        unsafe {
+            for i in 1..renderer.draw_buffers() {
+                gl::ColorMaski(i as u32, gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+            }
+        }
+
+        renderer.execute(&mut EnableCommand(Capability::DepthTest))?;
+
+        Ok(())
+    }
+}
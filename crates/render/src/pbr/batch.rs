@@ -0,0 +1,19 @@
+use serde::{Serialize, Deserialize};
+
+#[allow(unused_imports)]
+use crate::pbr::{mesh::Mesh, model::Model};
+
+/// Marks static model to be merged with other models of the same group and material
+/// type into a single [`Model`] (see [`Mesh::merge`]), which is drawn with one draw call.
+/// All models of a group must share the same material, as only the first one is kept.
+/// Batched entities are baked once and can't be moved afterwards
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StaticBatch {
+    pub group: u32,
+}
+
+impl StaticBatch {
+    pub fn new(group: u32) -> Self {
+        StaticBatch { group }
+    }
+}
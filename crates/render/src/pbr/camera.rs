@@ -4,12 +4,15 @@ use serde::{Serialize, Deserialize};
 use flatbox_core::{
     math::{
         glm, 
-        transform::Transform,
+        frustum::Frustum,
+        ray::Ray,
+        transform::{Transform, Real},
     },
     logger::error,
 };
 
 use crate::hal::shader::GraphicsPipeline;
+use crate::renderer::WindowExtent;
 
 #[derive(Clone, Default, Debug, Hash, PartialEq, Serialize, Deserialize)]
 pub enum CameraType {
@@ -27,6 +30,16 @@ pub struct Camera {
     near: f32,
     far: f32,
     is_active: bool,
+    #[serde(default = "default_focus_distance")]
+    focus_distance: f32,
+    #[serde(default)]
+    aperture: f32,
+    #[serde(default)]
+    autofocus: Option<glm::Vec2>,
+}
+
+fn default_focus_distance() -> f32 {
+    10.0
 }
 
 impl Camera {
@@ -42,6 +55,9 @@ impl Camera {
             near: 0.1,
             far: 100.0,
             is_active: false,
+            focus_distance: default_focus_distance(),
+            aperture: 0.0,
+            autofocus: None,
         }
     }
     
@@ -56,32 +72,137 @@ impl Camera {
     pub fn camera_type(&self) -> CameraType {
         self.camera_type.clone()
     }
+
+    pub fn near(&self) -> f32 {
+        self.near
+    }
+
+    pub fn far(&self) -> f32 {
+        self.far
+    }
+
+    /// Distance to the plane in focus, used by depth of field
+    pub fn focus_distance(&self) -> f32 {
+        self.focus_distance
+    }
+
+    pub fn set_focus_distance(&mut self, focus_distance: f32) {
+        self.focus_distance = focus_distance.max(self.near);
+    }
+
+    /// Strength of the depth of field blur. It's disabled, when the aperture is zero
+    pub fn aperture(&self) -> f32 {
+        self.aperture
+    }
+
+    pub fn set_aperture(&mut self, aperture: f32) {
+        self.aperture = aperture.max(0.0);
+    }
+
+    /// Point on the screen in texture coordinates, i.e. `(0.0, 0.0)` is the bottom-left
+    /// corner, whose depth is used as the focus distance instead of the fixed one
+    pub fn autofocus(&self) -> Option<glm::Vec2> {
+        self.autofocus
+    }
+
+    /// Focus on the object at the point of the screen, e.g. `(0.5, 0.5)` for the center
+    /// or the picked object under the cursor. `None` uses [`Camera::focus_distance`]
+    pub fn set_autofocus(&mut self, autofocus: Option<glm::Vec2>) {
+        self.autofocus = autofocus;
+    }
     
     pub fn set_aspect(&mut self, aspect: f32) {
         self.aspect = aspect;
         self.update_projection_matrix();
     }
     
+    pub fn projection_matrix(&self) -> glm::Mat4 {
+        self.projection_matrix
+    }
+
+    pub fn view_matrix(&self, transform: &Transform) -> glm::Mat4 {
+        glm::convert(self.view_matrix_real(transform))
+    }
+
+    /// Get the view matrix for rendering relative to `origin`
+    /// (see [`Camera::render_origin`])
+    pub fn relative_view_matrix(&self, transform: &Transform, origin: &glm::TVec3<Real>) -> glm::Mat4 {
+        glm::convert(self.view_matrix_real(transform) * glm::translation(origin))
+    }
+
+    /// Get the origin for camera-relative rendering, i.e. the point, which is
+    /// mapped to the camera's position. Objects translated relative to it stay
+    /// close to zero, so they can be uploaded to the GPU without losing precision
+    pub fn render_origin(&self, transform: &Transform) -> glm::TVec3<Real> {
+        if self.camera_type == CameraType::FirstPerson {
+            -transform.translation
+        } else {
+            -glm::quat_rotate_vec3(&glm::quat_inverse(&transform.rotation), &transform.translation)
+        }
+    }
+
+    /// Origin, which positions are uploaded relative to: [`Camera::render_origin`]
+    /// with `f64-transforms` feature, otherwise zero
+    pub fn upload_origin(&self, transform: &Transform) -> glm::TVec3<Real> {
+        if cfg!(feature = "f64-transforms") {
+            self.render_origin(transform)
+        } else {
+            glm::TVec3::zeros()
+        }
+    }
+
+    /// Get the camera's view frustum in world space, e.g. for culling
+    pub fn frustum(&self, transform: &Transform) -> Frustum {
+        Frustum::from_view_projection(&(self.projection_matrix * self.view_matrix(transform)))
+    }
+
+    /// Cast a ray from the camera through the `cursor` position (in physical pixels,
+    /// relative to the top-left corner of the window), e.g. for mouse picking
+    pub fn screen_to_ray(&self, transform: &Transform, cursor: glm::Vec2, extent: WindowExtent) -> Ray {
+        let ndc_x = 2.0 * (cursor.x - extent.x) / extent.width - 1.0;
+        let ndc_y = 1.0 - 2.0 * (cursor.y - extent.y) / extent.height;
+
+        let inversed = glm::inverse(&(self.projection_matrix * self.view_matrix(transform)));
+        let unproject = |z: f32| {
+            let point = inversed * glm::vec4(ndc_x, ndc_y, z, 1.0);
+            point.xyz() / point.w
+        };
+
+        Ray::from_points(&unproject(-1.0), &unproject(1.0))
+    }
+    
     pub(crate) fn update_buffer(
         &self,
         pipeline: &GraphicsPipeline,
         transform: &Transform,
+        origin: &glm::TVec3<Real>,
+        previous_view_projection: &glm::TMat4<Real>,
     ) {     
-        let rotation_matrix = glm::quat_cast(&transform.rotation);
-        let translation_matrix = glm::translation(&transform.translation);
-        
-        let view_matrix = {
-            if self.camera_type == CameraType::FirstPerson {
-                rotation_matrix * translation_matrix
-            } else {
-                translation_matrix * rotation_matrix
-            }
-        };
+        let view_matrix = self.relative_view_matrix(transform, origin);
+        let previous_view_projection: glm::Mat4 = glm::convert(previous_view_projection * glm::translation(origin));
         
         pipeline.apply();
         pipeline.set_mat4("view", &view_matrix);
         pipeline.set_mat4("projection", &self.projection_matrix);
-        pipeline.set_vec3("viewPos", &transform.translation);
+        pipeline.set_mat4("previous_view_projection", &previous_view_projection);
+        pipeline.set_vec3("viewPos", &glm::convert(self.render_origin(transform) - origin));
+        pipeline.set_vec3("renderOrigin", &glm::convert(*origin));
+    }
+
+    /// Get the absolute view-projection matrix, e.g. to be kept for the next frame
+    pub(crate) fn view_projection_real(&self, transform: &Transform) -> glm::TMat4<Real> {
+        glm::convert::<glm::Mat4, glm::TMat4<Real>>(self.projection_matrix) * self.view_matrix_real(transform)
+    }
+
+    fn view_matrix_real(&self, transform: &Transform) -> glm::TMat4<Real> {
+        let rotation_matrix = glm::quat_cast(&transform.rotation);
+        let translation_matrix = glm::translation(&transform.translation);
+        
+        if self.camera_type == CameraType::FirstPerson {
+            rotation_matrix * translation_matrix
+        } else {
+            translation_matrix * rotation_matrix
+        }
     }
     
     fn update_projection_matrix(&mut self) {
@@ -102,6 +223,9 @@ pub struct CameraBuilder {
     near: f32,
     far: f32,
     is_active: bool,
+    focus_distance: f32,
+    aperture: f32,
+    autofocus: Option<glm::Vec2>,
 }
 
 impl CameraBuilder {
@@ -118,6 +242,9 @@ impl CameraBuilder {
             far: self.far,
             projection_matrix: glm::Mat4::identity(),
             is_active: self.is_active,
+            focus_distance: self.focus_distance,
+            aperture: self.aperture,
+            autofocus: self.autofocus,
         };
 
         cam.update_projection_matrix();
@@ -130,7 +257,7 @@ impl CameraBuilder {
     }
     
     pub fn fovy(mut self, fovy: f32) -> CameraBuilder {
-        self.fovy = fovy.max(0.01).min(std::f32::consts::PI - 0.01);
+        self.fovy = fovy.clamp(0.01, std::f32::consts::PI - 0.01);
         self
     }
     
@@ -159,4 +286,19 @@ impl CameraBuilder {
         self.is_active = is_active;
         self
     }
+
+    pub fn focus_distance(mut self, focus_distance: f32) -> CameraBuilder {
+        self.focus_distance = focus_distance;
+        self
+    }
+
+    pub fn aperture(mut self, aperture: f32) -> CameraBuilder {
+        self.aperture = aperture.max(0.0);
+        self
+    }
+
+    pub fn autofocus(mut self, autofocus: glm::Vec2) -> CameraBuilder {
+        self.autofocus = Some(autofocus);
+        self
+    }
 }
\ No newline at end of file
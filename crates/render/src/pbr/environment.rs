@@ -0,0 +1,189 @@
+use gl::types::GLuint;
+
+use crate::{
+    error::RenderError,
+    hal::{
+        buffer::VertexArray,
+        framebuffer::{DepthAttachment, Framebuffer},
+        shader::{GraphicsPipeline, Shader, ShaderType},
+    },
+    pbr::texture::{ColorMode, Cubemap, Order, Texture},
+    postprocess::FULLSCREEN_VERTEX_SHADER,
+    renderer::{Capability, DisableCommand, DrawFullscreenCommand, EnableCommand, Renderer},
+};
+
+/// Size of irradiance cubemap faces. Irradiance has no high frequencies, so it can be tiny
+pub const IRRADIANCE_SIZE: u32 = 32;
+/// Size of the base level of prefiltered cubemap faces
+pub const PREFILTER_SIZE: u32 = 128;
+/// Number of mip levels of prefiltered cubemap, from smooth to rough surfaces.
+/// Must match `MAX_REFLECTION_LOD` in `environment.glsl` shader chunk
+pub const PREFILTER_MIP_LEVELS: u32 = 5;
+/// Size of BRDF lookup texture
+pub const BRDF_LUT_SIZE: u32 = 256;
+
+/// Texture units, where environment maps are bound for materials
+const IRRADIANCE_ORDER: Order = Order::Texture13;
+const PREFILTER_ORDER: Order = Order::Texture14;
+const BRDF_LUT_ORDER: Order = Order::Texture15;
+
+/// Singleton component with image-based ambient lighting. Materials receive it via
+/// `environment.glsl` shader chunk. It's generated from a cubemap once, which is slow,
+/// so it should be done at `Setup` or while loading
+#[derive(Debug)]
+pub struct EnvironmentMap {
+    /// Multiplier of the ambient light
+    pub intensity: f32,
+    irradiance: Cubemap,
+    prefiltered: Cubemap,
+    brdf_lut: Texture,
+}
+
+impl EnvironmentMap {
+    /// Convolve environment cubemap into irradiance and prefiltered specular maps and integrate BRDF lookup texture
+    pub fn new(renderer: &mut Renderer, environment: &Cubemap) -> Result<EnvironmentMap, RenderError> {
+        let irradiance_pipeline = GraphicsPipeline::new(&[
+            Shader::new_from_source(FULLSCREEN_VERTEX_SHADER, ShaderType::VertexShader)?,
+            Shader::new_from_source(include_str!("../shaders/environment/irradiance.fs"), ShaderType::FragmentShader)?,
+        ])?;
+
+        let prefilter_pipeline = GraphicsPipeline::new(&[
+            Shader::new_from_source(FULLSCREEN_VERTEX_SHADER, ShaderType::VertexShader)?,
+            Shader::new_from_source(include_str!("../shaders/environment/prefilter.fs"), ShaderType::FragmentShader)?,
+        ])?;
+
+        let brdf_pipeline = GraphicsPipeline::new(&[
+            Shader::new_from_source(FULLSCREEN_VERTEX_SHADER, ShaderType::VertexShader)?,
+            Shader::new_from_source(include_str!("../shaders/environment/brdf.fs"), ShaderType::FragmentShader)?,
+        ])?;
+
+        let irradiance = Cubemap::new_empty(IRRADIANCE_SIZE, ColorMode::Rgba16f, false);
+        let prefiltered = Cubemap::new_empty(PREFILTER_SIZE, ColorMode::Rgba16f, true);
+        let brdf_lut_framebuffer = Framebuffer::new(BRDF_LUT_SIZE, BRDF_LUT_SIZE, &[ColorMode::Rg16f], DepthAttachment::None);
+        brdf_lut_framebuffer.check_status()?;
+
+        let mut previous_framebuffer = 0;
+        let mut previous_viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+        }
+
+        // Full-screen triangle doesn't use vertex buffers, but a vertex array must be bound
+        let vertex_array = VertexArray::new();
+        vertex_array.bind();
+
+        renderer.execute(&mut DisableCommand(Capability::DepthTest))?;
+        renderer.execute(&mut DisableCommand(Capability::Blend))?;
+
+        let result = (|| {
+            irradiance_pipeline.apply();
+            irradiance_pipeline.set_int("environmentMap", 0);
+            environment.activate(Order::Texture0);
+            render_cubemap(renderer, &irradiance, 0, &irradiance_pipeline)?;
+
+            prefilter_pipeline.apply();
+            prefilter_pipeline.set_int("environmentMap", 0);
+            environment.activate(Order::Texture0);
+            for mip in 0..PREFILTER_MIP_LEVELS {
+                prefilter_pipeline.set_float("roughness", mip as f32 / (PREFILTER_MIP_LEVELS - 1) as f32);
+                render_cubemap(renderer, &prefiltered, mip, &prefilter_pipeline)?;
+            }
+
+            brdf_lut_framebuffer.bind();
+            brdf_pipeline.apply();
+            unsafe { gl::Viewport(0, 0, BRDF_LUT_SIZE as i32, BRDF_LUT_SIZE as i32); }
+            unsafe { renderer.execute(&mut DrawFullscreenCommand::new()) }
+        })();
+
+        renderer.execute(&mut EnableCommand(Capability::DepthTest))?;
+        renderer.execute(&mut EnableCommand(Capability::Blend))?;
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as u32);
+            gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        }
+
+        result?;
+
+        Ok(EnvironmentMap {
+            intensity: 1.0,
+            irradiance,
+            prefiltered,
+            brdf_lut: brdf_lut_framebuffer.into_color_attachment(0).expect("Framebuffer has a color attachment"),
+        })
+    }
+
+    /// Bind maps to their texture units and set environment uniforms of the pipeline. `None` disables environment lighting
+    pub fn setup_pipeline(environment: Option<&EnvironmentMap>, pipeline: &GraphicsPipeline) {
+        pipeline.apply();
+
+        // Samplers of different types mustn't share a unit, even if the environment is disabled
+        pipeline.set_int("irradianceMap", texture_unit(IRRADIANCE_ORDER));
+        pipeline.set_int("prefilterMap", texture_unit(PREFILTER_ORDER));
+        pipeline.set_int("brdfLut", texture_unit(BRDF_LUT_ORDER));
+
+        let Some(environment) = environment else {
+            pipeline.set_bool("environment.enabled", false);
+            return;
+        };
+
+        pipeline.set_bool("environment.enabled", true);
+        pipeline.set_float("environment.intensity", environment.intensity);
+    }
+
+    /// Bind maps to the texture units, used by `environment.glsl` shader chunk
+    pub fn activate(&self) {
+        self.irradiance.activate(IRRADIANCE_ORDER);
+        self.prefiltered.activate(PREFILTER_ORDER);
+        self.brdf_lut.activate(BRDF_LUT_ORDER);
+
+        unsafe { gl::ActiveTexture(Order::Texture0 as u32); }
+    }
+}
+
+fn texture_unit(order: Order) -> i32 {
+    (order as u32 - gl::TEXTURE0) as i32
+}
+
+/// Draw each face of the cubemap's mip level with the bound pipeline, which uses `face` uniform
+fn render_cubemap(
+    renderer: &mut Renderer,
+    cubemap: &Cubemap,
+    mip: u32,
+    pipeline: &GraphicsPipeline,
+) -> Result<(), RenderError> {
+    let size = (cubemap.size() >> mip).max(1);
+
+    let mut framebuffer: GLuint = 0;
+    unsafe {
+        gl::GenFramebuffers(1, &mut framebuffer);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+        gl::Viewport(0, 0, size as i32, size as i32);
+    }
+
+    let mut result = Ok(());
+
+    for face in 0..6 {
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                cubemap.id(),
+                mip as i32,
+            );
+        }
+
+        pipeline.set_int("face", face as i32);
+
+        result = unsafe { renderer.execute(&mut DrawFullscreenCommand::new()) };
+        if result.is_err() {
+            break;
+        }
+    }
+
+    unsafe { gl::DeleteFramebuffers(1, &framebuffer); }
+
+    result
+}
@@ -0,0 +1,65 @@
+use serde::{Serialize, Deserialize};
+use flatbox_core::color::Color;
+
+use crate::hal::shader::GraphicsPipeline;
+
+/// How fog density grows with the distance from the camera
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FogFalloff {
+    /// Fog grows linearly from `start` distance to full at `end` distance
+    Linear { start: f32, end: f32 },
+    /// `1 - e^(-density * distance)`
+    Exponential { density: f32 },
+    /// `1 - e^(-(density * distance)^2)`; fog is thinner near the camera than the exponential one
+    ExponentialSquared { density: f32 },
+}
+
+/// Singleton component, which blends fragments of materials toward fog color by
+/// their distance to the camera. Materials receive it via `fog.glsl` shader chunk
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Fog {
+    pub color: Color,
+    pub falloff: FogFalloff,
+}
+
+impl Fog {
+    pub fn linear(color: Color, start: f32, end: f32) -> Self {
+        Fog { color, falloff: FogFalloff::Linear { start, end } }
+    }
+
+    pub fn exponential(color: Color, density: f32) -> Self {
+        Fog { color, falloff: FogFalloff::Exponential { density } }
+    }
+
+    pub fn exponential_squared(color: Color, density: f32) -> Self {
+        Fog { color, falloff: FogFalloff::ExponentialSquared { density } }
+    }
+
+    /// Set fog uniforms of the pipeline. `None` disables fog
+    pub fn setup_pipeline(fog: Option<&Fog>, pipeline: &GraphicsPipeline) {
+        pipeline.apply();
+
+        let Some(fog) = fog else {
+            pipeline.set_int("fog.mode", 0);
+            return;
+        };
+
+        let (mode, start, end, density) = match fog.falloff {
+            FogFalloff::Linear { start, end } => (1, start, end, 0.0),
+            FogFalloff::Exponential { density } => (2, 0.0, 0.0, density),
+            FogFalloff::ExponentialSquared { density } => (3, 0.0, 0.0, density),
+        };
+
+        pipeline.set_int("fog.mode", mode);
+        pipeline.set_color("fog.color", &fog.color);
+        pipeline.set_float("fog.start", start);
+        pipeline.set_float("fog.end", end);
+        pipeline.set_float("fog.density", density);
+    }
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Fog::linear(Color::rgb(0.5, 0.5, 0.5), 10.0, 100.0)
+    }
+}
@@ -0,0 +1,212 @@
+use serde::{Serialize, Deserialize};
+use flatbox_core::math::glm;
+
+use crate::{hal::shader::GraphicsPipeline, pbr::shadow::MAX_POINT_SHADOWS};
+
+/// Maximum numbers of lights per kind, supported by the default material shader
+pub const MAX_DIRECTIONAL_LIGHTS: usize = 4;
+pub const MAX_POINT_LIGHTS: usize = 16;
+pub const MAX_SPOT_LIGHTS: usize = 8;
+
+/// Light, which illuminates the whole scene from the same direction, e.g. the sun
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DirectionalLight {
+    pub direction: glm::Vec3,
+    pub ambient: glm::Vec3,
+    pub diffuse: glm::Vec3,
+    pub specular: glm::Vec3,
+    /// Whether the light casts shadows, when there's [`ShadowMaps`] singleton.
+    /// Only the first light, which casts shadows, is shadowed
+    ///
+    /// [`ShadowMaps`]: crate::pbr::shadow::ShadowMaps
+    #[serde(default = "default_cast_shadows")]
+    pub cast_shadows: bool,
+}
+
+fn default_cast_shadows() -> bool {
+    true
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        DirectionalLight {
+            direction: glm::vec3(-0.2, -1.0, -0.3),
+            ambient: glm::vec3(0.05, 0.05, 0.05),
+            diffuse: glm::vec3(0.4, 0.4, 0.4),
+            specular: glm::vec3(0.5, 0.5, 0.5),
+            cast_shadows: default_cast_shadows(),
+        }
+    }
+}
+
+/// Light, emitted in all directions from the position of the entity's `Transform`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PointLight {
+    pub ambient: glm::Vec3,
+    pub diffuse: glm::Vec3,
+    pub specular: glm::Vec3,
+    /// Constant, linear and quadratic attenuation factors
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+    /// Whether the light casts shadows, when there's [`ShadowMaps`] singleton. Each shadow
+    /// renders the scene six times, so only a few of the nearest lights are shadowed
+    ///
+    /// [`ShadowMaps`]: crate::pbr::shadow::ShadowMaps
+    #[serde(default)]
+    pub cast_shadows: bool,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        PointLight {
+            ambient: glm::vec3(0.05, 0.05, 0.05),
+            diffuse: glm::vec3(0.8, 0.8, 0.8),
+            specular: glm::vec3(1.0, 1.0, 1.0),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+            cast_shadows: false,
+        }
+    }
+}
+
+/// Light cone, emitted from the position of the entity's `Transform`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpotLight {
+    pub direction: glm::Vec3,
+    /// Angle of the fully lit inner cone in radians
+    pub cut_off: f32,
+    /// Angle of the outer cone in radians, where the light fades out
+    pub outer_cut_off: f32,
+    pub ambient: glm::Vec3,
+    pub diffuse: glm::Vec3,
+    pub specular: glm::Vec3,
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        SpotLight {
+            direction: glm::vec3(0.0, 0.0, -1.0),
+            cut_off: 12.5f32.to_radians(),
+            outer_cut_off: 15.0f32.to_radians(),
+            ambient: glm::vec3(0.0, 0.0, 0.0),
+            diffuse: glm::vec3(1.0, 1.0, 1.0),
+            specular: glm::vec3(1.0, 1.0, 1.0),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+        }
+    }
+}
+
+/// Singleton component, which limits the number of lights, uploaded per frame. When there
+/// are more point or spot lights, the nearest ones to the camera are used
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LightSettings {
+    pub max_directional_lights: usize,
+    pub max_point_lights: usize,
+    pub max_spot_lights: usize,
+}
+
+impl Default for LightSettings {
+    fn default() -> Self {
+        LightSettings {
+            max_directional_lights: MAX_DIRECTIONAL_LIGHTS,
+            max_point_lights: MAX_POINT_LIGHTS,
+            max_spot_lights: MAX_SPOT_LIGHTS,
+        }
+    }
+}
+
+/// Lights of the scene with positions of point and spot lights. Positions are relative
+/// to the render origin (see [`Camera::upload_origin`]), so lighting keeps its precision
+/// far from the world origin
+///
+/// [`Camera::upload_origin`]: crate::pbr::camera::Camera::upload_origin
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SceneLights {
+    pub directional: Vec<DirectionalLight>,
+    pub point: Vec<(glm::Vec3, PointLight)>,
+    pub spot: Vec<(glm::Vec3, SpotLight)>,
+}
+
+impl SceneLights {
+    /// Keep at most `settings` number of lights, preferring the ones closest to `viewer`
+    pub fn limit(&mut self, settings: &LightSettings, viewer: &glm::Vec3) {
+        let distance = |position: &glm::Vec3| glm::distance2(position, viewer);
+
+        self.directional.truncate(settings.max_directional_lights.min(MAX_DIRECTIONAL_LIGHTS));
+
+        self.point.sort_by(|(a, _), (b, _)| distance(a).total_cmp(&distance(b)));
+        self.point.truncate(settings.max_point_lights.min(MAX_POINT_LIGHTS));
+
+        self.spot.sort_by(|(a, _), (b, _)| distance(a).total_cmp(&distance(b)));
+        self.spot.truncate(settings.max_spot_lights.min(MAX_SPOT_LIGHTS));
+    }
+
+    /// Point lights, which are shadowed by [`ShadowMaps`] in this order
+    ///
+    /// [`ShadowMaps`]: crate::pbr::shadow::ShadowMaps
+    pub fn point_shadow_casters(&self) -> impl Iterator<Item = &(glm::Vec3, PointLight)> {
+        self.point
+            .iter()
+            .take(MAX_POINT_LIGHTS)
+            .filter(|(_, light)| light.cast_shadows)
+            .take(MAX_POINT_SHADOWS)
+    }
+
+    pub fn setup_pipeline(&self, pipeline: &GraphicsPipeline) {
+        pipeline.apply();
+
+        let directional = &self.directional[..self.directional.len().min(MAX_DIRECTIONAL_LIGHTS)];
+        let point = &self.point[..self.point.len().min(MAX_POINT_LIGHTS)];
+        let spot = &self.spot[..self.spot.len().min(MAX_SPOT_LIGHTS)];
+
+        pipeline.set_int("dirLightCount", directional.len() as i32);
+        pipeline.set_int("pointLightCount", point.len() as i32);
+        pipeline.set_int("spotLightCount", spot.len() as i32);
+
+        let shadow_caster = directional.iter().position(|light| light.cast_shadows);
+
+        for (i, light) in directional.iter().enumerate() {
+            pipeline.set_vec3(&format!("dirLights[{i}].direction"), &light.direction);
+            pipeline.set_bool(&format!("dirLights[{i}].castShadows"), shadow_caster == Some(i));
+            pipeline.set_vec3(&format!("dirLights[{i}].ambient"), &light.ambient);
+            pipeline.set_vec3(&format!("dirLights[{i}].diffuse"), &light.diffuse);
+            pipeline.set_vec3(&format!("dirLights[{i}].specular"), &light.specular);
+        }
+
+        let mut shadow_index = 0;
+
+        for (i, (position, light)) in point.iter().enumerate() {
+            let shadow = light.cast_shadows && shadow_index < MAX_POINT_SHADOWS;
+            pipeline.set_int(&format!("pointLights[{i}].shadowIndex"), if shadow { shadow_index as i32 } else { -1 });
+            shadow_index += shadow as usize;
+
+            pipeline.set_vec3(&format!("pointLights[{i}].position"), position);
+            pipeline.set_vec3(&format!("pointLights[{i}].ambient"), &light.ambient);
+            pipeline.set_vec3(&format!("pointLights[{i}].diffuse"), &light.diffuse);
+            pipeline.set_vec3(&format!("pointLights[{i}].specular"), &light.specular);
+            pipeline.set_float(&format!("pointLights[{i}].constant"), light.constant);
+            pipeline.set_float(&format!("pointLights[{i}].linear"), light.linear);
+            pipeline.set_float(&format!("pointLights[{i}].quadratic"), light.quadratic);
+        }
+
+        for (i, (position, light)) in spot.iter().enumerate() {
+            pipeline.set_vec3(&format!("spotLights[{i}].position"), position);
+            pipeline.set_vec3(&format!("spotLights[{i}].direction"), &light.direction);
+            pipeline.set_vec3(&format!("spotLights[{i}].ambient"), &light.ambient);
+            pipeline.set_vec3(&format!("spotLights[{i}].diffuse"), &light.diffuse);
+            pipeline.set_vec3(&format!("spotLights[{i}].specular"), &light.specular);
+            pipeline.set_float(&format!("spotLights[{i}].constant"), light.constant);
+            pipeline.set_float(&format!("spotLights[{i}].linear"), light.linear);
+            pipeline.set_float(&format!("spotLights[{i}].quadratic"), light.quadratic);
+            pipeline.set_float(&format!("spotLights[{i}].cutOff"), light.cut_off.cos());
+            pipeline.set_float(&format!("spotLights[{i}].outerCutOff"), light.outer_cut_off.cos());
+        }
+    }
+}
@@ -1,15 +1,25 @@
+use std::any::TypeId;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 use serde::{Serialize, Deserialize};
 use flatbox_assets::typetag;
-use flatbox_core::math::glm;
+#[cfg(feature = "ecs")]
+use flatbox_assets::{prefab::PrefabOverrides, ser_component::SerializableComponent};
+use flatbox_core::color::Color;
+#[cfg(feature = "ecs")]
+use flatbox_ecs::EntityBuilder;
 
-use crate::hal::shader::GraphicsPipeline;
+use crate::hal::{sampler::Sampler, shader::GraphicsPipeline};
 
 use super::texture::{Texture, Order};
 
 #[typetag::serde(tag = "material")]
 pub trait Material: Debug + Send + Sync + 'static {
+    /// Shaders may `#include` registered shader chunks, e.g. `"transforms.glsl"` 
+    /// and `"lighting.glsl"` (see [`crate::hal::preprocessor`])
     fn vertex_shader() -> &'static str
     where 
         Self: Sized;
@@ -19,23 +29,48 @@ pub trait Material: Debug + Send + Sync + 'static {
         Self: Sized;
 
     fn setup_pipeline(&self, _pipeline: &GraphicsPipeline) {}
+
+    /// Type of the material, which selects its pipeline, e.g. for mesh primitives
+    fn material_type(&self) -> TypeId {
+        TypeId::of::<Self>()
+    }
+
+    /// Key of the pipeline state, which [`Material::setup_pipeline`] sets up. Consecutive meshes
+    /// with equal keys are drawn without setting up the material again. `None` sets it up for every mesh
+    fn state_key(&self) -> Option<u64> {
+        None
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DefaultMaterial {
-    pub color: glm::Vec3,
+    pub color: Color,
+    /// Color map, which is loaded in sRGB color space
     pub diffuse_map: Texture,
+    /// Linear data map, which is loaded with [`TextureDescriptor::data`](super::texture::TextureDescriptor::data)
     pub specular_map: Texture,
+    /// Tangent-space normal map. Mesh vertices must have tangents for it to be applied.
+    /// It's linear data, which is loaded with [`TextureDescriptor::data`](super::texture::TextureDescriptor::data)
+    pub normal_map: Texture,
     pub shininess: f32,
+    /// Multiply the diffuse color by the color of mesh vertices
+    #[serde(default)]
+    pub vertex_color: bool,
+    /// Sampler of all maps, which overrides their own filter and wrap parameters
+    #[serde(skip)]
+    pub sampler: Option<Arc<Sampler>>,
 }
 
 impl Default for DefaultMaterial {
     fn default() -> Self {
         DefaultMaterial {
-            color: glm::vec3(1.0, 1.0, 1.0),
+            color: Color::WHITE,
             diffuse_map: Texture::default(),
             specular_map: Texture::default(),
+            normal_map: Texture::flat_normal(),
             shininess: 32.0,
+            vertex_color: false,
+            sampler: None,
         }
     }
 }
@@ -51,8 +86,9 @@ impl Material for DefaultMaterial {
     }
 
     fn setup_pipeline(&self, pipeline: &GraphicsPipeline) {
-        pipeline.set_vec3("material.color", &self.color);
+        pipeline.set_color("material.color", &self.color);
         pipeline.set_float("material.shininess", self.shininess);
+        pipeline.set_bool("material.vertex_color", self.vertex_color);
 
         pipeline.set_int("material.diffuse_map", 0);
         self.diffuse_map.activate(Order::Texture0);
@@ -60,66 +96,90 @@ impl Material for DefaultMaterial {
         pipeline.set_int("material.specular_map", 1);
         self.specular_map.activate(Order::Texture1);
 
-        let point_light_positions = [
-            glm::vec3( 0.7,  0.2,  2.0),
-            glm::vec3( 2.3, -3.3, -4.0),
-            glm::vec3(-4.0,  2.0, -12.0),
-            glm::vec3( 0.0,  0.0, -3.0)
-        ];
-
-        // Light
-        pipeline.set_vec3("light.position", &glm::vec3(0.0, 0.0, 0.0));
-        pipeline.set_vec3("light.ambient", &glm::vec3(0.2, 0.2, 0.2));
-        pipeline.set_vec3("light.diffuse", &glm::vec3(0.5, 0.5, 0.5));
-        pipeline.set_vec3("light.specular", &glm::vec3(1.0, 1.0, 1.0));
-        // directional light
-        pipeline.set_vec3("dirLight.direction", &glm::vec3(-0.2, -1.0, -0.3));
-        pipeline.set_vec3("dirLight.ambient", &glm::vec3(0.05, 0.05, 0.05));
-        pipeline.set_vec3("dirLight.diffuse", &glm::vec3(0.4, 0.4, 0.4));
-        pipeline.set_vec3("dirLight.specular", &glm::vec3(0.5, 0.5, 0.5));
-        // point light 1
-        pipeline.set_vec3("pointLights[0].position", &point_light_positions[0]);
-        pipeline.set_vec3("pointLights[0].ambient", &glm::vec3(0.05, 0.05, 0.05));
-        pipeline.set_vec3("pointLights[0].diffuse", &glm::vec3(0.8, 0.8, 0.8));
-        pipeline.set_vec3("pointLights[0].specular", &glm::vec3(1.0, 1.0, 1.0));
-        pipeline.set_float("pointLights[0].constant", 1.0);
-        pipeline.set_float("pointLights[0].linear", 0.09);
-        pipeline.set_float("pointLights[0].quadratic", 0.032);
-        // point light 2
-        pipeline.set_vec3("pointLights[1].position", &point_light_positions[1]);
-        pipeline.set_vec3("pointLights[1].ambient", &glm::vec3(0.05, 0.05, 0.05));
-        pipeline.set_vec3("pointLights[1].diffuse", &glm::vec3(0.8, 0.8, 0.8));
-        pipeline.set_vec3("pointLights[1].specular", &glm::vec3(1.0, 1.0, 1.0));
-        pipeline.set_float("pointLights[1].constant", 1.0);
-        pipeline.set_float("pointLights[1].linear", 0.09);
-        pipeline.set_float("pointLights[1].quadratic", 0.032);
-        // point light 3
-        pipeline.set_vec3("pointLights[2].position", &point_light_positions[2]);
-        pipeline.set_vec3("pointLights[2].ambient", &glm::vec3(0.05, 0.05, 0.05));
-        pipeline.set_vec3("pointLights[2].diffuse", &glm::vec3(0.8, 0.8, 0.8));
-        pipeline.set_vec3("pointLights[2].specular", &glm::vec3(1.0, 1.0, 1.0));
-        pipeline.set_float("pointLights[2].constant", 1.0);
-        pipeline.set_float("pointLights[2].linear", 0.09);
-        pipeline.set_float("pointLights[2].quadratic", 0.032);
-        // point light 4
-        pipeline.set_vec3("pointLights[3].position", &point_light_positions[3]);
-        pipeline.set_vec3("pointLights[3].ambient", &glm::vec3(0.05, 0.05, 0.05));
-        pipeline.set_vec3("pointLights[3].diffuse", &glm::vec3(0.8, 0.8, 0.8));
-        pipeline.set_vec3("pointLights[3].specular", &glm::vec3(1.0, 1.0, 1.0));
-        pipeline.set_float("pointLights[3].constant", 1.0);
-        pipeline.set_float("pointLights[3].linear", 0.09);
-        pipeline.set_float("pointLights[3].quadratic", 0.032);
-        // spotLight
-        pipeline.set_vec3("spotLight.position", &glm::vec3(0.0, 0.0, -3.0));
-        pipeline.set_vec3("spotLight.direction", &glm::vec3(0.0, 0.0, 0.0));
-        pipeline.set_vec3("spotLight.ambient", &glm::vec3(0.0, 0.0, 0.0));
-        pipeline.set_vec3("spotLight.diffuse", &glm::vec3(1.0, 1.0, 1.0));
-        pipeline.set_vec3("spotLight.specular", &glm::vec3(1.0, 1.0, 1.0));
-        pipeline.set_float("spotLight.constant", 1.0);
-        pipeline.set_float("spotLight.linear", 0.09);
-        pipeline.set_float("spotLight.quadratic", 0.032);
-        pipeline.set_float("spotLight.cutOff", f32::cos(15.0f32.to_radians()));
-        pipeline.set_float("spotLight.outerCutOff", f32::cos(15.0f32.to_radians()));
+        pipeline.set_int("material.normal_map", 2);
+        self.normal_map.activate(Order::Texture2);
+
+        if let Some(ref sampler) = self.sampler {
+            for order in [Order::Texture0, Order::Texture1, Order::Texture2] {
+                sampler.bind(order);
+            }
+        }
     }
+
+    fn state_key(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+
+        self.diffuse_map.id().hash(&mut hasher);
+        self.specular_map.id().hash(&mut hasher);
+        self.normal_map.id().hash(&mut hasher);
+        [self.color.r, self.color.g, self.color.b, self.color.a, self.shininess]
+            .map(f32::to_bits)
+            .hash(&mut hasher);
+        self.vertex_color.hash(&mut hasher);
+        self.sampler.as_ref().map(|sampler| sampler.id()).hash(&mut hasher);
+
+        Some(hasher.finish())
+    }
+}
+
+/// Unlit material, which draws mesh with its vertex colors, multiplied by `color`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct VertexColorMaterial {
+    pub color: Color,
+}
+
+impl Default for VertexColorMaterial {
+    fn default() -> Self {
+        VertexColorMaterial { color: Color::WHITE }
+    }
+}
+
+#[typetag::serde]
+impl Material for VertexColorMaterial {
+    fn vertex_shader() -> &'static str {
+        include_str!("../shaders/defaultmat.vs")
+    }
+
+    fn fragment_shader() -> &'static str {
+        include_str!("../shaders/vertexcolor.fs")
+    }
+
+    fn setup_pipeline(&self, pipeline: &GraphicsPipeline) {
+        pipeline.set_color("color", &self.color);
+    }
+
+    fn state_key(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        [self.color.r, self.color.g, self.color.b, self.color.a]
+            .map(f32::to_bits)
+            .hash(&mut hasher);
+
+        Some(hasher.finish())
+    }
+}
+
+/// Materials in prefabs take the color of [`PrefabOverrides`]
+macro_rules! impl_colored_ser_component {
+    ($($material:ty),+) => {
+        $(
+            #[cfg(feature = "ecs")]
+            #[typetag::serde]
+            impl SerializableComponent for $material {
+                fn add_into(&self, entity_builder: &mut EntityBuilder) {
+                    entity_builder.add(self.clone());
+                }
+
+                fn add_overridden(&self, entity_builder: &mut EntityBuilder, overrides: &PrefabOverrides) {
+                    let mut material = self.clone();
+                    if let Some(color) = overrides.color {
+                        material.color = color;
+                    }
+
+                    entity_builder.add(material);
+                }
+            }
+        )+
+    };
 }
 
+impl_colored_ser_component!(DefaultMaterial, VertexColorMaterial);
@@ -1,14 +1,15 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashMap, f32::consts::{PI, TAU}, path::PathBuf, sync::Arc};
 use parking_lot::Mutex;
 use serde::{Serialize, Deserialize};
-use flatbox_core::math::glm;
+use flatbox_core::{color::Color, math::{glm, transform::Transform}};
 
 use crate::{
-    macros::set_vertex_attribute,
+    macros::vertex_attribute,
     hal::{
-        buffer::{Buffer, VertexArray, BufferTarget, BufferUsage, AttributeType}, 
+        buffer::{Buffer, VertexArray, BufferTarget, BufferUsage, AttributeType, VertexAttribute, VertexLayout}, 
         shader::GraphicsPipeline
     }, 
+    renderer::MotionHistory,
 };
 
 #[allow(unused_imports)]
@@ -17,14 +18,35 @@ use crate::pbr::model::Model;
 use super::material::Material;
 
 #[repr(C)]
-#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct Vertex {
     pub position: glm::Vec3,
     pub normal: glm::Vec3,
     pub texcoord: glm::Vec2,
+    /// Direction of increasing U texture coordinate, used for normal mapping.
+    /// It can be calculated with [`Mesh::compute_tangents`]
+    #[serde(default)]
+    pub tangent: glm::Vec3,
+    /// Linear RGBA color, which is multiplied by materials using vertex colors. It's white by default
+    #[serde(default = "Vertex::white")]
+    pub color: glm::Vec4,
 }
 
 impl Vertex {
+    /// Create white vertex with zero tangent
+    pub fn new(position: glm::Vec3, normal: glm::Vec3, texcoord: glm::Vec2) -> Vertex {
+        Vertex { position, normal, texcoord, tangent: glm::Vec3::zeros(), color: Vertex::white() }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Vertex {
+        self.color = glm::vec4(color.r, color.g, color.b, color.a);
+        self
+    }
+
+    fn white() -> glm::Vec4 {
+        glm::Vec4::repeat(1.0)
+    }
+
     /// Get middle point between two vertices
     pub fn midpoint(a: &Vertex, b: &Vertex) -> Vertex {
         Vertex {
@@ -42,6 +64,8 @@ impl Vertex {
                 0.5 * (a.texcoord[0] + b.texcoord[0]),
                 0.5 * (a.texcoord[1] + b.texcoord[1]),
             ),
+            tangent: (a.tangent + b.tangent).try_normalize(f32::EPSILON).unwrap_or_default(),
+            color: (a.color + b.color) * 0.5,
         }
     }
     
@@ -52,6 +76,24 @@ impl Vertex {
     }
 }
 
+impl VertexLayout for Vertex {
+    fn attributes() -> Vec<VertexAttribute> {
+        vec![
+            vertex_attribute!("position", Vertex::position, AttributeType::Float),
+            vertex_attribute!("normal", Vertex::normal, AttributeType::Float),
+            vertex_attribute!("texcoord", Vertex::texcoord, AttributeType::Float),
+            vertex_attribute!("tangent", Vertex::tangent, AttributeType::Float),
+            vertex_attribute!("color", Vertex::color, AttributeType::Float),
+        ]
+    }
+}
+
+impl Default for Vertex {
+    fn default() -> Self {
+        Vertex::new(glm::Vec3::zeros(), glm::Vec3::zeros(), glm::Vec2::zeros())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Primitive {
     pub first_index: u32,
@@ -60,11 +102,17 @@ pub struct Primitive {
     pub material: Arc<Mutex<Box<dyn Material>>>,
 }
 
+/// Number of segments around the axis of round primitives
+const ROUND_SEGMENTS: u32 = 32;
+/// Number of rings from pole to pole of capsule and around the tube of torus
+const ROUND_RINGS: u32 = 16;
+
 /// Represents the type of mesh in [`Model`] struct.
 /// It indicates whether mesh must be created in runtime,
 /// loaded from file (or resource) or created manually
 /// with index and vertex buffers.
 #[derive(Clone, Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(from = "SavedMeshType")]
 pub enum MeshType {
     /// Plane mesh
     Plane,
@@ -73,9 +121,20 @@ pub enum MeshType {
     Cube,
     /// Icosphere mesh
     Icosahedron,
-    /// Refined icosphere mesh
-    Sphere,
-    /// Mesh which have been loaded from file or resource
+    /// Refined icosphere mesh with the given number of subdivisions. It's saved
+    /// as `Icosphere`, since `Sphere` without subdivisions is kept for older saves
+    #[serde(rename = "Icosphere")]
+    Sphere(u32),
+    /// Capsule mesh
+    Capsule,
+    /// Cylinder mesh
+    Cylinder,
+    /// Cone mesh
+    Cone,
+    /// Torus mesh
+    Torus,
+    /// Mesh which have been loaded from file or resource. Only the path is saved,
+    /// so the mesh must be loaded from it again after deserialization
     Loaded(PathBuf),
     /// Custom model type, which neither loaded from file, nor
     /// created in runtime. Unlike other meshes it's (de-)serialized.
@@ -83,71 +142,178 @@ pub enum MeshType {
     Generic,
 }
 
+/// Number of subdivisions of spheres in saves, made before it could be set
+const LEGACY_SPHERE_SUBDIVISIONS: u32 = 3;
+
+/// Deserialized form of [`MeshType`], which accepts all its saved versions
+#[derive(Deserialize)]
+#[serde(rename = "MeshType")]
+enum SavedMeshType {
+    Plane,
+    Cube,
+    Icosahedron,
+    Icosphere(u32),
+    Sphere,
+    Capsule,
+    Cylinder,
+    Cone,
+    Torus,
+    Loaded(PathBuf),
+    Generic,
+}
+
+impl From<SavedMeshType> for MeshType {
+    fn from(mesh_type: SavedMeshType) -> Self {
+        match mesh_type {
+            SavedMeshType::Plane => MeshType::Plane,
+            SavedMeshType::Cube => MeshType::Cube,
+            SavedMeshType::Icosahedron => MeshType::Icosahedron,
+            SavedMeshType::Icosphere(subdivisions) => MeshType::Sphere(subdivisions),
+            SavedMeshType::Sphere => MeshType::Sphere(LEGACY_SPHERE_SUBDIVISIONS),
+            SavedMeshType::Capsule => MeshType::Capsule,
+            SavedMeshType::Cylinder => MeshType::Cylinder,
+            SavedMeshType::Cone => MeshType::Cone,
+            SavedMeshType::Torus => MeshType::Torus,
+            SavedMeshType::Loaded(path) => MeshType::Loaded(path),
+            SavedMeshType::Generic => MeshType::Generic,
+        }
+    }
+}
+
+/// Indexed mesh of vertices of format `V`. Meshes with the default [`Vertex`] format
+/// are used by [`Model`], custom formats can be drawn with [`DrawMeshCommand`](crate::renderer::DrawMeshCommand)
 #[derive(Debug, Serialize, Deserialize)]
-pub struct Mesh {
-    pub vertex_data: Vec<Vertex>,
+pub struct Mesh<V = Vertex> {
+    pub vertex_data: Vec<V>,
     pub index_data: Vec<u32>,
     pub primitives: Vec<Primitive>,
 
     #[serde(skip)]
     pub(crate) prepared: bool,
     #[serde(skip)]
+    dirty: bool,
+    #[serde(skip)]
     pub(crate) vertex_array: VertexArray,
     #[serde(skip)]
     pub(crate) vertex_buffer: Option<Buffer>,
     #[serde(skip)]
     pub(crate) index_buffer: Option<Buffer>,
+    #[serde(skip)]
+    pub(crate) transform_history: Option<MotionHistory<Transform>>,
 }
 
-impl Mesh {
-    pub fn new(vertices: &[Vertex], indices: &[u32], primitives: &[Primitive]) -> Mesh {
+impl<V: VertexLayout> Mesh<V> {
+    pub fn new(vertices: &[V], indices: &[u32], primitives: &[Primitive]) -> Mesh<V> {
         Mesh {
             vertex_data: vertices.to_vec(),
             index_data: indices.to_vec(),
             primitives: primitives.to_vec(),
             prepared: false,
+            dirty: false,
             vertex_array: VertexArray::new(),
             vertex_buffer: None,
             index_buffer: None,
+            transform_history: None,
         }
     }
 
-    pub fn empty() -> Mesh {
+    pub fn empty() -> Mesh<V> {
         Mesh::new(&[], &[], &[])
     }
 
+    pub fn vertex_array(&self) -> &VertexArray {
+        &self.vertex_array
+    }
+
+    pub fn setup(&mut self, pipeline: &GraphicsPipeline) {
+        if self.vertex_buffer.is_some() && self.index_buffer.is_some() {
+            return;
+        }
+
+        self.vertex_buffer = Some(Buffer::new(BufferTarget::ArrayBuffer, BufferUsage::StaticDraw));
+        self.index_buffer = Some(Buffer::new(BufferTarget::ElementArrayBuffer, BufferUsage::StaticDraw));
+
+        self.update_vertices();
+        self.vertex_array.set_layout::<V>(pipeline);
+    }
+
+    /// Upload vertex and index data to GPU. Buffers are updated in place,
+    /// unless the data has grown. It's done for dirty meshes by [`PrepareMeshCommand`](crate::renderer::PrepareMeshCommand)
+    pub fn update_vertices(&mut self){     
+        self.vertex_array.bind();
+
+        if let (Some(ref vertex_buffer), Some(ref index_buffer)) = (&self.vertex_buffer, &self.index_buffer) {
+            update_buffer(vertex_buffer, &self.vertex_data);
+            update_buffer(index_buffer, &self.index_data);
+        }
+
+        self.dirty = false;
+    }
+
+    /// Replace vertices, which are uploaded before the next draw
+    pub fn set_vertices(&mut self, vertices: &[V]) {
+        self.vertex_data = vertices.to_vec();
+        self.mark_dirty();
+    }
+
+    /// Replace indices, which are uploaded before the next draw
+    pub fn set_indices(&mut self, indices: &[u32]) {
+        self.index_data = indices.to_vec();
+        self.mark_dirty();
+    }
+
+    /// Upload the data before the next draw, e.g. after `vertex_data` was modified in place
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether the data was changed since the last upload
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+fn update_buffer<T>(buffer: &Buffer, data: &[T]) {
+    if std::mem::size_of_val(data) <= buffer.capacity() {
+        buffer.fill_sub(0, data);
+    } else {
+        buffer.fill(data);
+    }
+}
+
+impl Mesh {
     pub fn cube() -> Mesh {
-        Mesh::new(
+        let mut mesh = Mesh::new(
             &[
-                Vertex { position: glm::vec3(-0.5,0.5,-0.5), normal: glm::vec3(0.0, 0.0, -1.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,-0.5), normal: glm::vec3(0.0, 0.0, -1.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,-0.5), normal: glm::vec3(0.0, 0.0, -1.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,0.5,-0.5), normal: glm::vec3(0.0, 0.0, -1.0), texcoord: glm::vec2(1.0, 0.0) },
-
-                Vertex { position: glm::vec3(-0.5,0.5,0.5), normal: glm::vec3(0.0, 0.0, 1.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,0.5), normal: glm::vec3(0.0, 0.0, 1.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,0.5), normal: glm::vec3(0.0, 0.0, 1.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,0.5,0.5), normal: glm::vec3(0.0, 0.0, 1.0), texcoord: glm::vec2(1.0, 0.0) },
-
-                Vertex { position: glm::vec3(0.5,0.5,-0.5), normal: glm::vec3(1.0, 0.0, 0.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,-0.5), normal: glm::vec3(1.0, 0.0, 0.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,0.5), normal: glm::vec3(1.0, 0.0, 0.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,0.5,0.5), normal: glm::vec3(1.0, 0.0, 0.0), texcoord: glm::vec2(1.0, 0.0) },
-
-                Vertex { position: glm::vec3(-0.5,0.5,-0.5), normal: glm::vec3(-1.0, 0.0, 0.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,-0.5), normal: glm::vec3(-1.0, 0.0, 0.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,0.5), normal: glm::vec3(-1.0, 0.0, 0.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(-0.5,0.5,0.5), normal: glm::vec3(-1.0, 0.0, 0.0), texcoord: glm::vec2(1.0, 0.0) },
-
-                Vertex { position: glm::vec3(-0.5,0.5,0.5), normal: glm::vec3(0.0, 1.0, 0.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(-0.5,0.5,-0.5), normal: glm::vec3(0.0, 1.0, 0.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,0.5,-0.5), normal: glm::vec3(0.0, 1.0, 0.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,0.5,0.5), normal: glm::vec3(0.0, 1.0, 0.0), texcoord: glm::vec2(1.0, 0.0) },
-
-                Vertex { position: glm::vec3(-0.5,-0.5,0.5), normal: glm::vec3(0.0, -1.0, 0.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,-0.5), normal: glm::vec3(0.0, -1.0, 0.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,-0.5), normal: glm::vec3(0.0, -1.0, 0.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,0.5), normal: glm::vec3(0.0, -1.0, 0.0), texcoord: glm::vec2(1.0, 0.0) },
+                Vertex::new(glm::vec3(-0.5,0.5,-0.5), glm::vec3(0.0, 0.0, -1.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,-0.5), glm::vec3(0.0, 0.0, -1.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,-0.5), glm::vec3(0.0, 0.0, -1.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,0.5,-0.5), glm::vec3(0.0, 0.0, -1.0), glm::vec2(1.0, 0.0)),
+
+                Vertex::new(glm::vec3(-0.5,0.5,0.5), glm::vec3(0.0, 0.0, 1.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,0.5), glm::vec3(0.0, 0.0, 1.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,0.5), glm::vec3(0.0, 0.0, 1.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,0.5,0.5), glm::vec3(0.0, 0.0, 1.0), glm::vec2(1.0, 0.0)),
+
+                Vertex::new(glm::vec3(0.5,0.5,-0.5), glm::vec3(1.0, 0.0, 0.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,-0.5), glm::vec3(1.0, 0.0, 0.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,0.5), glm::vec3(1.0, 0.0, 0.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,0.5,0.5), glm::vec3(1.0, 0.0, 0.0), glm::vec2(1.0, 0.0)),
+
+                Vertex::new(glm::vec3(-0.5,0.5,-0.5), glm::vec3(-1.0, 0.0, 0.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,-0.5), glm::vec3(-1.0, 0.0, 0.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,0.5), glm::vec3(-1.0, 0.0, 0.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(-0.5,0.5,0.5), glm::vec3(-1.0, 0.0, 0.0), glm::vec2(1.0, 0.0)),
+
+                Vertex::new(glm::vec3(-0.5,0.5,0.5), glm::vec3(0.0, 1.0, 0.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(-0.5,0.5,-0.5), glm::vec3(0.0, 1.0, 0.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,0.5,-0.5), glm::vec3(0.0, 1.0, 0.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,0.5,0.5), glm::vec3(0.0, 1.0, 0.0), glm::vec2(1.0, 0.0)),
+
+                Vertex::new(glm::vec3(-0.5,-0.5,0.5), glm::vec3(0.0, -1.0, 0.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,-0.5), glm::vec3(0.0, -1.0, 0.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,-0.5), glm::vec3(0.0, -1.0, 0.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,0.5), glm::vec3(0.0, -1.0, 0.0), glm::vec2(1.0, 0.0)),
             ],
             &[
                 0,1,3, 3,1,2,
@@ -158,65 +324,353 @@ impl Mesh {
                 20,21,23, 23,21,22
             ],
             &[],
-        )
+        );
+
+        mesh.compute_tangents();
+        mesh
     }
 
     pub fn plane() -> Mesh {
-        Mesh::new(
+        let mut mesh = Mesh::new(
             &[
-                Vertex { position: glm::vec3(-0.5,0.5,-0.5), normal: glm::vec3(0.0, 0.0, -1.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,-0.5), normal: glm::vec3(0.0, 0.0, -1.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,-0.5), normal: glm::vec3(0.0, 0.0, -1.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,0.5,-0.5), normal: glm::vec3(0.0, 0.0, -1.0), texcoord: glm::vec2(1.0, 0.0) },
-
-                Vertex { position: glm::vec3(-0.5,0.5,0.5), normal: glm::vec3(0.0, 0.0, 1.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,0.5), normal: glm::vec3(0.0, 0.0, 1.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,0.5), normal: glm::vec3(0.0, 0.0, 1.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,0.5,0.5), normal: glm::vec3(0.0, 0.0, 1.0), texcoord: glm::vec2(1.0, 0.0) },
-
-                Vertex { position: glm::vec3(0.5,0.5,-0.5), normal: glm::vec3(1.0, 0.0, 0.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,-0.5), normal: glm::vec3(1.0, 0.0, 0.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,-0.5,0.5), normal: glm::vec3(1.0, 0.0, 0.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(0.5,0.5,0.5), normal: glm::vec3(1.0, 0.0, 0.0), texcoord: glm::vec2(1.0, 0.0) },
-
-                Vertex { position: glm::vec3(-0.5,0.5,-0.5), normal: glm::vec3(-1.0, 0.0, 0.0), texcoord: glm::vec2(0.0, 0.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,-0.5), normal: glm::vec3(-1.0, 0.0, 0.0), texcoord: glm::vec2(0.0, 1.0) },
-                Vertex { position: glm::vec3(-0.5,-0.5,0.5), normal: glm::vec3(-1.0, 0.0, 0.0), texcoord: glm::vec2(1.0, 1.0) },
-                Vertex { position: glm::vec3(-0.5,0.5,0.5), normal: glm::vec3(-1.0, 0.0, 0.0), texcoord: glm::vec2(1.0, 0.0) },
+                Vertex::new(glm::vec3(-0.5,0.5,-0.5), glm::vec3(0.0, 0.0, -1.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,-0.5), glm::vec3(0.0, 0.0, -1.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,-0.5), glm::vec3(0.0, 0.0, -1.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,0.5,-0.5), glm::vec3(0.0, 0.0, -1.0), glm::vec2(1.0, 0.0)),
+
+                Vertex::new(glm::vec3(-0.5,0.5,0.5), glm::vec3(0.0, 0.0, 1.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,0.5), glm::vec3(0.0, 0.0, 1.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,0.5), glm::vec3(0.0, 0.0, 1.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,0.5,0.5), glm::vec3(0.0, 0.0, 1.0), glm::vec2(1.0, 0.0)),
+
+                Vertex::new(glm::vec3(0.5,0.5,-0.5), glm::vec3(1.0, 0.0, 0.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,-0.5), glm::vec3(1.0, 0.0, 0.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,-0.5,0.5), glm::vec3(1.0, 0.0, 0.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(0.5,0.5,0.5), glm::vec3(1.0, 0.0, 0.0), glm::vec2(1.0, 0.0)),
+
+                Vertex::new(glm::vec3(-0.5,0.5,-0.5), glm::vec3(-1.0, 0.0, 0.0), glm::vec2(0.0, 0.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,-0.5), glm::vec3(-1.0, 0.0, 0.0), glm::vec2(0.0, 1.0)),
+                Vertex::new(glm::vec3(-0.5,-0.5,0.5), glm::vec3(-1.0, 0.0, 0.0), glm::vec2(1.0, 1.0)),
+                Vertex::new(glm::vec3(-0.5,0.5,0.5), glm::vec3(-1.0, 0.0, 0.0), glm::vec2(1.0, 0.0)),
             ],
             &[0,1,3, 3,1,2],
             &[],
-        )
+        );
+
+        mesh.compute_tangents();
+        mesh
     }
-    
-    pub fn setup(&mut self, pipeline: &GraphicsPipeline) {
-        if self.vertex_buffer.is_some() && self.index_buffer.is_some() {
-            return;
+
+    /// Icosahedron with radius `0.5`, which is a sphere without subdivisions
+    pub fn icosahedron() -> Mesh {
+        Mesh::sphere(0)
+    }
+
+    /// Icosphere with radius `0.5`. Each subdivision splits every triangle into four
+    pub fn sphere(subdivisions: u32) -> Mesh {
+        let t = (1.0 + 5f32.sqrt()) / 2.0;
+
+        let mut positions: Vec<glm::Vec3> = [
+            (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+            (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+            (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+        ].iter().map(|&(x, y, z)| glm::vec3(x, y, z).normalize()).collect();
+
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let position = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(position);
+                    positions.len() as u32 - 1
+                })
+            };
+
+            triangles = triangles.into_iter().flat_map(|[a, b, c]| {
+                let ab = midpoint(a, b);
+                let bc = midpoint(b, c);
+                let ca = midpoint(c, a);
+
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            }).collect();
         }
 
-        self.vertex_buffer = Some(Buffer::new(BufferTarget::ArrayBuffer, BufferUsage::StaticDraw));
-        self.index_buffer = Some(Buffer::new(BufferTarget::ElementArrayBuffer, BufferUsage::StaticDraw));
+        let vertices: Vec<Vertex> = positions.iter().map(|&normal| {
+            let texcoord = glm::vec2(
+                0.5 + normal.z.atan2(normal.x) / TAU,
+                0.5 - normal.y.asin() / PI,
+            );
 
-        self.update_vertices();
+            Vertex::new(normal * 0.5, normal, texcoord)
+        }).collect();
 
-        let position_attribute = pipeline.get_attribute_location("position");
-        let normal_attribute = pipeline.get_attribute_location("normal");
-        let texcoord_attribute = pipeline.get_attribute_location("texcoord");
+        let indices: Vec<u32> = triangles.into_iter().flatten().collect();
 
-        let vertex_array = &self.vertex_array;
-        set_vertex_attribute!(vertex_array, position_attribute, Vertex::position, AttributeType::Float);
-        set_vertex_attribute!(vertex_array, normal_attribute, Vertex::normal, AttributeType::Float);
-        set_vertex_attribute!(vertex_array, texcoord_attribute, Vertex::texcoord, AttributeType::Float);
+        let mut mesh = Mesh::new(&vertices, &indices, &[]);
+        mesh.compute_tangents();
+        mesh
     }
 
-    pub fn update_vertices(&self){     
-        self.vertex_array.bind();
+    /// Capsule along Y axis with radius `0.5` and height `2.0`
+    pub fn capsule() -> Mesh {
+        let radius = 0.5;
+        let half_height = 0.5;
+        let half_rings = ROUND_RINGS / 2;
 
-        if let (Some(ref vertex_buffer), Some(ref index_buffer)) = (&self.vertex_buffer, &self.index_buffer) {
-            vertex_buffer.fill(&self.vertex_data);
-            index_buffer.fill(&self.index_data);
+        // The equator is doubled, so hemispheres are connected with the cylinder
+        let rows: Vec<(f32, f32)> = (0..=half_rings)
+            .map(|i| (i as f32 / ROUND_RINGS as f32 * PI, half_height))
+            .chain((half_rings..=ROUND_RINGS).map(|i| (i as f32 / ROUND_RINGS as f32 * PI, -half_height)))
+            .collect();
+
+        let mut mesh = Mesh::grid(rows.len() as u32 - 1, ROUND_SEGMENTS, |row, column| {
+            let (phi, offset) = rows[row as usize];
+            let theta = column as f32 / ROUND_SEGMENTS as f32 * TAU;
+            let normal = glm::vec3(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+
+            Vertex::new(
+                normal * radius + glm::vec3(0.0, offset, 0.0), 
+                normal, 
+                glm::vec2(column as f32 / ROUND_SEGMENTS as f32, row as f32 / (rows.len() - 1) as f32),
+            )
+        });
+
+        mesh.compute_tangents();
+        mesh
+    }
+
+    /// Cylinder along Y axis with radius `0.5` and height `1.0`
+    pub fn cylinder() -> Mesh {
+        let mut mesh = Mesh::grid(1, ROUND_SEGMENTS, |row, column| {
+            let theta = column as f32 / ROUND_SEGMENTS as f32 * TAU;
+            let normal = glm::vec3(theta.cos(), 0.0, theta.sin());
+
+            Vertex::new(
+                normal * 0.5 + glm::vec3(0.0, 0.5 - row as f32, 0.0),
+                normal,
+                glm::vec2(column as f32 / ROUND_SEGMENTS as f32, row as f32),
+            )
+        });
+
+        mesh.add_cap(0.5, glm::Vec3::y());
+        mesh.add_cap(-0.5, -glm::Vec3::y());
+        mesh.compute_tangents();
+        mesh
+    }
+
+    /// Cone along Y axis with base radius `0.5` and height `1.0`
+    pub fn cone() -> Mesh {
+        let mut mesh = Mesh::grid(1, ROUND_SEGMENTS, |row, column| {
+            let theta = column as f32 / ROUND_SEGMENTS as f32 * TAU;
+            // Slope of the side is `radius / height`, so normals lean up by the same ratio
+            let normal = glm::vec3(theta.cos(), 0.5, theta.sin()).normalize();
+            let radius = 0.5 * row as f32;
+
+            Vertex::new(
+                glm::vec3(theta.cos() * radius, 0.5 - row as f32, theta.sin() * radius),
+                normal,
+                glm::vec2(column as f32 / ROUND_SEGMENTS as f32, row as f32),
+            )
+        });
+
+        mesh.add_cap(-0.5, -glm::Vec3::y());
+        mesh.compute_tangents();
+        mesh
+    }
+
+    /// Torus around Y axis with outer radius `0.5` and tube radius `0.125`
+    pub fn torus() -> Mesh {
+        let major_radius = 0.375;
+        let minor_radius = 0.125;
+
+        let mut mesh = Mesh::grid(ROUND_RINGS, ROUND_SEGMENTS, |row, column| {
+            let theta = column as f32 / ROUND_SEGMENTS as f32 * TAU;
+            // Rings go downwards on the outer side, so that faces are wound like in other primitives
+            let phi = -(row as f32) / ROUND_RINGS as f32 * TAU;
+            let direction = glm::vec3(theta.cos(), 0.0, theta.sin());
+            let normal = direction * phi.cos() + glm::Vec3::y() * phi.sin();
+
+            Vertex::new(
+                direction * major_radius + normal * minor_radius,
+                normal,
+                glm::vec2(column as f32 / ROUND_SEGMENTS as f32, row as f32 / ROUND_RINGS as f32),
+            )
+        });
+
+        mesh.compute_tangents();
+        mesh
+    }
+
+    /// Create mesh of runtime-generated [`MeshType`]. Returns `None` for
+    /// loaded and generic meshes, which can't be created from their type only
+    pub fn from_type(mesh_type: &MeshType) -> Option<Mesh> {
+        match mesh_type {
+            MeshType::Plane => Some(Mesh::plane()),
+            MeshType::Cube => Some(Mesh::cube()),
+            MeshType::Icosahedron => Some(Mesh::icosahedron()),
+            MeshType::Sphere(subdivisions) => Some(Mesh::sphere(*subdivisions)),
+            MeshType::Capsule => Some(Mesh::capsule()),
+            MeshType::Cylinder => Some(Mesh::cylinder()),
+            MeshType::Cone => Some(Mesh::cone()),
+            MeshType::Torus => Some(Mesh::torus()),
+            MeshType::Loaded(_) | MeshType::Generic => None,
         }
     }
+
+    /// Grid of `(rows + 1) * (columns + 1)` vertices, connected with quads. The last
+    /// column duplicates the first one, so texture coordinates don't wrap on the seam
+    fn grid(rows: u32, columns: u32, vertex: impl Fn(u32, u32) -> Vertex) -> Mesh {
+        let vertices: Vec<Vertex> = (0..=rows)
+            .flat_map(|row| (0..=columns).map(move |column| (row, column)))
+            .map(|(row, column)| vertex(row, column))
+            .collect();
+
+        let mut indices = Vec::with_capacity((rows * columns * 6) as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = row * (columns + 1) + column;
+                let b = a + columns + 1;
+
+                indices.extend_from_slice(&[a, a + 1, b, a + 1, b + 1, b]);
+            }
+        }
+
+        Mesh::new(&vertices, &indices, &[])
+    }
+
+    /// Add flat disk with radius `0.5` at the given height, facing `normal`
+    fn add_cap(&mut self, height: f32, normal: glm::Vec3) {
+        let center = self.vertex_data.len() as u32;
+        self.vertex_data.push(Vertex::new(glm::vec3(0.0, height, 0.0), normal, glm::vec2(0.5, 0.5)));
+
+        for i in 0..=ROUND_SEGMENTS {
+            let theta = i as f32 / ROUND_SEGMENTS as f32 * TAU;
+            let (sin, cos) = theta.sin_cos();
+
+            self.vertex_data.push(Vertex::new(
+                glm::vec3(cos * 0.5, height, sin * 0.5),
+                normal,
+                glm::vec2(0.5 + cos * 0.5, 0.5 + sin * 0.5),
+            ));
+        }
+
+        for i in 0..ROUND_SEGMENTS {
+            let (a, b) = (center + 1 + i, center + 2 + i);
+
+            if normal.y > 0.0 {
+                self.index_data.extend_from_slice(&[center, b, a]);
+            } else {
+                self.index_data.extend_from_slice(&[center, a, b]);
+            }
+        }
+    }
+
+    /// Merge meshes into one, transforming their vertices to the common space. 
+    /// Primitives are kept with their index ranges shifted
+    pub fn merge(meshes: &[(&Mesh, &Transform)]) -> Mesh {
+        let mut merged = Mesh::empty();
+
+        for (mesh, transform) in meshes {
+            let (model, inversed) = transform.to_matrices();
+            let normal_matrix = glm::mat4_to_mat3(&inversed.transpose());
+            let tangent_matrix = glm::mat4_to_mat3(&model);
+
+            let first_vertex = merged.vertex_data.len() as u32;
+            let first_index = merged.index_data.len() as u32;
+
+            merged.vertex_data.extend(mesh.vertex_data.iter().map(|vertex| Vertex {
+                position: (model * vertex.position.push(1.0)).xyz(),
+                normal: (normal_matrix * vertex.normal).try_normalize(f32::EPSILON).unwrap_or_default(),
+                texcoord: vertex.texcoord,
+                tangent: (tangent_matrix * vertex.tangent).try_normalize(f32::EPSILON).unwrap_or_default(),
+                color: vertex.color,
+            }));
+
+            merged.index_data.extend(mesh.index_data.iter().map(|index| index + first_vertex));
+
+            merged.primitives.extend(mesh.primitives.iter().map(|primitive| Primitive {
+                first_index: primitive.first_index + first_index,
+                ..primitive.clone()
+            }));
+        }
+
+        merged
+    }
+
+    /// Calculate smooth normals of vertices from their positions. Normals of triangles,
+    /// sharing a vertex, are weighted by the triangle's angle at that vertex
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![glm::Vec3::zeros(); self.vertex_data.len()];
+
+        for (triangle, [a, b, c]) in self.triangles() {
+            let [p0, p1, p2] = triangle.map(|v| v.position);
+            let Some(normal) = (p1 - p0).cross(&(p2 - p0)).try_normalize(f32::EPSILON) else { continue };
+
+            for (i, weight) in [a, b, c].into_iter().zip(corner_angles(p0, p1, p2)) {
+                normals[i] += normal * weight;
+            }
+        }
+
+        for (vertex, normal) in self.vertex_data.iter_mut().zip(normals) {
+            vertex.normal = normal.try_normalize(f32::EPSILON).unwrap_or_else(glm::Vec3::y);
+        }
+    }
+
+    /// Calculate tangents of vertices from their positions, normals and texture coordinates
+    /// in MikkTSpace fashion: tangents of triangles, sharing a vertex, are weighted by
+    /// the triangle's angle at that vertex and orthogonalized against the vertex normal.
+    /// Normals must be valid, so call [`Mesh::compute_normals`] first, if they are missing
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![glm::Vec3::zeros(); self.vertex_data.len()];
+
+        for (triangle, [a, b, c]) in self.triangles() {
+            let [v0, v1, v2] = triangle;
+
+            let edge1 = v1.position - v0.position;
+            let edge2 = v2.position - v0.position;
+            let delta_uv1 = v1.texcoord - v0.texcoord;
+            let delta_uv2 = v2.texcoord - v0.texcoord;
+
+            let determinant = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+            if determinant.abs() <= f32::EPSILON {
+                continue;
+            }
+
+            let Some(tangent) = ((edge1 * delta_uv2.y - edge2 * delta_uv1.y) / determinant)
+                .try_normalize(f32::EPSILON) else { continue };
+
+            for (i, weight) in [a, b, c].into_iter().zip(corner_angles(v0.position, v1.position, v2.position)) {
+                tangents[i] += tangent * weight;
+            }
+        }
+
+        for (vertex, tangent) in self.vertex_data.iter_mut().zip(tangents) {
+            // Gram-Schmidt orthogonalization against the normal
+            let tangent = tangent - vertex.normal * vertex.normal.dot(&tangent);
+            vertex.tangent = tangent.try_normalize(f32::EPSILON).unwrap_or_default();
+        }
+    }
+
+    /// Iterate over triangles with valid indices, returning their vertices and indices
+    fn triangles(&self) -> impl Iterator<Item = ([Vertex; 3], [usize; 3])> + '_ {
+        self.index_data.chunks_exact(3).filter_map(|triangle| {
+            let indices = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+            let [a, b, c] = indices;
+            
+            Some(([
+                *self.vertex_data.get(a)?,
+                *self.vertex_data.get(b)?,
+                *self.vertex_data.get(c)?,
+            ], indices))
+        })
+    }
 }
 
 impl Default for Mesh {
@@ -225,16 +679,30 @@ impl Default for Mesh {
     }
 }
 
-impl Clone for Mesh {
+impl<V: Clone> Clone for Mesh<V> {
     fn clone(&self) -> Self {
         Mesh {
             vertex_data: self.vertex_data.clone(),
             index_data: self.index_data.clone(),
             primitives: self.primitives.clone(),
             prepared: false,
+            dirty: false,
             vertex_array: VertexArray::default(),
             vertex_buffer: None,
             index_buffer: None,
+            transform_history: None,
         }
     }
 }
+
+/// Interior angles of the triangle at its corners
+fn corner_angles(p0: glm::Vec3, p1: glm::Vec3, p2: glm::Vec3) -> [f32; 3] {
+    let angle = |a: glm::Vec3, b: glm::Vec3, c: glm::Vec3| {
+        match ((b - a).try_normalize(f32::EPSILON), (c - a).try_normalize(f32::EPSILON)) {
+            (Some(ab), Some(ac)) => ab.dot(&ac).clamp(-1.0, 1.0).acos(),
+            _ => 0.0,
+        }
+    };
+
+    [angle(p0, p1, p2), angle(p1, p2, p0), angle(p2, p0, p1)]
+}
@@ -1,5 +1,10 @@
+pub mod batch;
 pub mod camera;
+pub mod environment;
+pub mod fog;
+pub mod light;
 pub mod material;
 pub mod mesh;
 pub mod model;
+pub mod shadow;
 pub mod texture;
\ No newline at end of file
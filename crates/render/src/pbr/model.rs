@@ -40,9 +40,37 @@ impl Model {
     }
 
     pub fn plane() -> Model {
+        Model::from_type(MeshType::Plane)
+    }
+
+    pub fn icosahedron() -> Model {
+        Model::from_type(MeshType::Icosahedron)
+    }
+
+    pub fn sphere(subdivisions: u32) -> Model {
+        Model::from_type(MeshType::Sphere(subdivisions))
+    }
+
+    pub fn capsule() -> Model {
+        Model::from_type(MeshType::Capsule)
+    }
+
+    pub fn cylinder() -> Model {
+        Model::from_type(MeshType::Cylinder)
+    }
+
+    pub fn cone() -> Model {
+        Model::from_type(MeshType::Cone)
+    }
+
+    pub fn torus() -> Model {
+        Model::from_type(MeshType::Torus)
+    }
+
+    fn from_type(mesh_type: MeshType) -> Model {
         Model {
-            mesh_type: MeshType::Plane,
-            mesh: Some(Mesh::plane()),
+            mesh: Mesh::from_type(&mesh_type),
+            mesh_type,
         }
     }
 }
@@ -105,18 +133,11 @@ impl<'de> Deserialize<'de> for Model {
                 let mesh_type: MeshType = seq.next_element()?.ok_or_else(|| DeError::invalid_length(0, &self))?;
 
                 let mesh = match mesh_type {
-                    MeshType::Cube => { Some(Mesh::cube()) },
-                    // MeshType::Icosahedron => { Some(Mesh::icosahedron()) },
-                    // MeshType::Sphere => { Some(Mesh::sphere()) },
-                    // MeshType::Plane => { Some(Mesh::plane()) },
-                    // MeshType::Loaded(path) => {
-                        // return Ok(Model::load_obj(path)
-                            // .expect("Cannot load deserialized model from path"));
-                    // },
                     MeshType::Generic => { 
                         seq.next_element()?.ok_or_else(|| DeError::invalid_length(1, &self))? 
                     },
-                    _ => todo!("Mesh types: `icosahedron`, `sphere`, `plane` etc."),
+                    // Loaded meshes are restored from their paths afterwards
+                    _ => Mesh::from_type(&mesh_type),
                 };
 
                 Ok(Model {
@@ -152,18 +173,11 @@ impl<'de> Deserialize<'de> for Model {
                 let mesh_type = mesh_type.ok_or_else(|| DeError::missing_field("mesh_type"))?;
 
                 let mesh = match mesh_type {
-                    MeshType::Cube => { Some(Mesh::cube()) },
-                    // MeshType::Icosahedron => { Some(Mesh::icosahedron()) },
-                    // MeshType::Sphere => { Some(Mesh::sphere()) },
-                    // MeshType::Plane => { Some(Mesh::plane()) },
-                    // MeshType::Loaded(path) => {
-                        // return Ok(Model::load_obj(path)
-                            // .expect("Cannot load deserialized model from path"));
-                    // },
                     MeshType::Generic => { 
                         mesh.ok_or_else(|| DeError::missing_field("mesh"))?
                     },
-                    _ => todo!("Mesh types: `icosahedron`, `sphere`, `plane` etc."),
+                    // Loaded meshes are restored from their paths afterwards
+                    _ => Mesh::from_type(&mesh_type),
                 };
 
                 Ok(Model {
@@ -187,3 +201,28 @@ pub struct ModelBundle<M: Material> {
     pub material: M,
     pub transform: Transform,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn legacy_sphere_is_loaded() {
+        assert_eq!(ron::from_str::<MeshType>("Sphere").unwrap(), MeshType::Sphere(3));
+    }
+
+    #[test]
+    fn sphere_subdivisions_are_kept() {
+        let saved = ron::to_string(&MeshType::Sphere(2)).unwrap();
+        assert_eq!(ron::from_str::<MeshType>(&saved).unwrap(), MeshType::Sphere(2));
+    }
+
+    #[test]
+    fn loaded_model_keeps_path() {
+        let model: Model = ron::from_str(r#"(mesh_type: Loaded("models/ship.obj"), mesh: None)"#).unwrap();
+        assert_eq!(model.mesh_type, MeshType::Loaded(PathBuf::from("models/ship.obj")));
+        assert!(model.mesh.is_none());
+    }
+}
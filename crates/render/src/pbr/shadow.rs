@@ -0,0 +1,608 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_2;
+
+use gl::types::GLuint;
+use pretty_type_name::pretty_type_name;
+use serde::{Serialize, Deserialize};
+use flatbox_core::{
+    logger::error,
+    math::{glm, transform::{Transform, Real}},
+};
+
+use crate::{
+    error::RenderError,
+    hal::{
+        framebuffer::status_name,
+        shader::{GraphicsPipeline, Shader, ShaderType},
+    },
+    pbr::{
+        camera::Camera,
+        light::{DirectionalLight, SceneLights, MAX_DIRECTIONAL_LIGHTS},
+        material::Material,
+        mesh::Mesh,
+        model::Model,
+        texture::{ColorMode, Cubemap, Order},
+    },
+    renderer::{DrawTrianglesCommand, Renderer},
+};
+
+/// Maximum number of shadow cascades. Must match `MAX_SHADOW_CASCADES` in `shadows.glsl` shader chunk
+pub const MAX_SHADOW_CASCADES: usize = 4;
+/// Maximum number of point lights with shadows. Must match `MAX_POINT_SHADOWS` in `shadows.glsl` shader chunk
+pub const MAX_POINT_SHADOWS: usize = 4;
+/// Render graph target, which shadow passes write and material passes read
+pub const SHADOW_TARGET: &str = "shadows";
+
+/// Texture units, where shadow maps are bound for materials
+const SHADOW_MAP_ORDER: Order = Order::Texture12;
+const POINT_SHADOW_MAP_ORDERS: [Order; MAX_POINT_SHADOWS] = [Order::Texture8, Order::Texture9, Order::Texture10, Order::Texture11];
+
+/// Directions and up vectors of the cubemap faces, ordered as `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`
+const CUBEMAP_FACES: [([f32; 3], [f32; 3]); 6] = [
+    ([1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+    ([0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+];
+
+/// Parameters of the cascaded shadow map of the directional light and shadow cubemaps of point lights
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShadowSettings {
+    /// Number of cascades, up to [`MAX_SHADOW_CASCADES`]
+    pub cascades: usize,
+    /// Size of the shadow map of each cascade
+    pub resolution: u32,
+    /// Distance from the camera, where shadows end, if it's closer than the camera's far plane
+    pub max_distance: f32,
+    /// Blend between uniform (`0.0`) and logarithmic (`1.0`) cascade splits.
+    /// Logarithmic splits give more resolution to shadows near the camera
+    pub split_lambda: f32,
+    /// Depth bias against shadow acne
+    pub bias: f32,
+    /// Offset of the sampled position along the normal in texels of the cascade
+    pub normal_offset: f32,
+    /// Radius of PCF kernel in texels. `0` takes a single sample
+    pub pcf_radius: u32,
+    /// Size of the faces of point light shadow cubemaps
+    #[serde(default = "default_point_resolution")]
+    pub point_resolution: u32,
+    /// Distance from point lights, where their shadows end
+    #[serde(default = "default_point_range")]
+    pub point_range: f32,
+    /// Depth bias of point light shadows in world units
+    #[serde(default = "default_point_bias")]
+    pub point_bias: f32,
+}
+
+fn default_point_resolution() -> u32 {
+    512
+}
+
+fn default_point_range() -> f32 {
+    25.0
+}
+
+fn default_point_bias() -> f32 {
+    0.05
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        ShadowSettings {
+            cascades: MAX_SHADOW_CASCADES,
+            resolution: 2048,
+            max_distance: 100.0,
+            split_lambda: 0.75,
+            bias: 0.0005,
+            normal_offset: 1.5,
+            pcf_radius: 1,
+            point_resolution: default_point_resolution(),
+            point_range: default_point_range(),
+            point_bias: default_point_bias(),
+        }
+    }
+}
+
+/// Ortho projection of the light, which covers a slice of the camera's frustum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowCascade {
+    pub view: glm::Mat4,
+    pub projection: glm::Mat4,
+    /// Distance from the camera, where the cascade ends
+    pub split: f32,
+    /// Size of a shadow map texel in world units
+    pub texel_size: f32,
+}
+
+/// Singleton component with cascaded shadow map of the first [`DirectionalLight`], which casts shadows,
+/// and distance cubemaps of [`PointLight`]s, which cast shadows. Cascades are fitted to slices of the
+/// active camera's frustum by `update_shadows` system and models are drawn into the maps by shadow passes
+/// of material extensions
+pub struct ShadowMaps {
+    pub settings: ShadowSettings,
+    cascades: Vec<ShadowCascade>,
+    /// Positions of shadowed point lights relative to the render origin
+    point_lights: Vec<glm::Vec3>,
+    origin: glm::TVec3<Real>,
+    texture: GLuint,
+    framebuffer: GLuint,
+    /// Resolution and number of layers of the allocated shadow map
+    allocated: (u32, usize),
+    point_maps: Vec<Cubemap>,
+    pipelines: HashMap<TypeId, Option<GraphicsPipeline>>,
+    point_pipelines: HashMap<TypeId, Option<GraphicsPipeline>>,
+    cleared_frame: Option<u64>,
+}
+
+impl ShadowMaps {
+    pub fn new(settings: ShadowSettings) -> Self {
+        ShadowMaps {
+            settings,
+            cascades: vec![],
+            point_lights: vec![],
+            origin: glm::TVec3::zeros(),
+            texture: 0,
+            framebuffer: 0,
+            allocated: (0, 0),
+            point_maps: vec![],
+            pipelines: HashMap::new(),
+            point_pipelines: HashMap::new(),
+            cleared_frame: None,
+        }
+    }
+
+    /// Cascades of the current frame. It's empty, when shadows are disabled
+    pub fn cascades(&self) -> &[ShadowCascade] {
+        &self.cascades
+    }
+
+    /// Positions of shadowed point lights of the current frame relative to the render origin
+    pub fn point_lights(&self) -> &[glm::Vec3] {
+        &self.point_lights
+    }
+
+    /// Disable shadows until the next update, e.g. when there's no camera
+    pub fn disable(&mut self) {
+        self.cascades.clear();
+        self.point_lights.clear();
+    }
+
+    /// Fit cascades of the first directional light, which casts shadows, to the camera's frustum,
+    /// split between its near plane and the max distance, and place cubemaps at the point lights
+    /// from [`SceneLights::point_shadow_casters`]. Lights must be the ones uploaded to materials
+    pub fn update(&mut self, lights: &SceneLights, camera: &Camera, camera_transform: &Transform) {
+        self.disable();
+
+        self.origin = camera.upload_origin(camera_transform);
+        self.point_lights = lights
+            .point_shadow_casters()
+            .map(|(position, _)| *position)
+            .collect();
+
+        self.allocate_point_maps();
+
+        let light = lights.directional
+            .iter()
+            .take(MAX_DIRECTIONAL_LIGHTS)
+            .find(|light| light.cast_shadows);
+
+        if let Some(light) = light {
+            self.update_cascades(light, camera, camera_transform);
+        }
+    }
+
+    fn update_cascades(&mut self, light: &DirectionalLight, camera: &Camera, camera_transform: &Transform) {
+        let origin = self.origin;
+
+        let count = self.settings.cascades.clamp(1, MAX_SHADOW_CASCADES);
+        let resolution = self.settings.resolution.max(1);
+        self.allocate(resolution, count);
+
+        let near = camera.near();
+        let far = camera.far().min(self.settings.max_distance).max(near);
+
+        let inversed_view = glm::inverse(&camera.relative_view_matrix(camera_transform, &origin));
+        let inversed_projection = glm::inverse(&camera.projection_matrix());
+
+        // Corners of the near plane in view space, which are scaled to the slice distances
+        let near_corners: Vec<glm::Vec3> = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+            .iter()
+            .map(|&(x, y)| {
+                let corner = inversed_projection * glm::vec4(x, y, -1.0, 1.0);
+                corner.xyz() / corner.w
+            })
+            .collect();
+
+        let direction = glm::normalize(&light.direction);
+        let up = match direction.y.abs() > 0.99 {
+            true => glm::Vec3::z(),
+            false => glm::Vec3::y(),
+        };
+
+        let mut slice_near = near;
+        for index in 0..count {
+            let split = cascade_split(index + 1, count, near, far, self.settings.split_lambda);
+
+            let corners: Vec<glm::Vec3> = [slice_near, split]
+                .iter()
+                .flat_map(|&distance| near_corners.iter().map(move |corner| corner * (distance / near)))
+                .map(|corner| (inversed_view * corner.push(1.0)).xyz())
+                .collect();
+
+            // Bounding sphere keeps the cascade size, when the camera rotates
+            let center = corners.iter().sum::<glm::Vec3>() / corners.len() as f32;
+            let radius = corners.iter().map(|corner| glm::distance(corner, &center)).fold(0.0, f32::max);
+            let radius = (radius * 16.0).ceil() / 16.0;
+
+            // Casters between the light and the slice are kept by extending the depth range
+            let view = glm::look_at(&(center - direction * radius * 2.0), &center, &up);
+            let mut projection = glm::ortho(-radius, radius, -radius, radius, 0.0, radius * 3.0);
+
+            // Snap the projection to texels, so shadow edges don't shimmer, when the camera moves
+            let shadow_origin = projection * view * glm::vec4(0.0, 0.0, 0.0, 1.0);
+            let texels = shadow_origin.xy() * resolution as f32 / 2.0;
+            let offset = (texels.map(f32::round) - texels) * 2.0 / resolution as f32;
+            projection[(0, 3)] += offset.x;
+            projection[(1, 3)] += offset.y;
+
+            self.cascades.push(ShadowCascade {
+                view,
+                projection,
+                split,
+                texel_size: radius * 2.0 / resolution as f32,
+            });
+
+            slice_near = split;
+        }
+    }
+
+    /// Draw models of the material into the cascades and point light cubemaps. Meshes must be
+    /// prepared beforehand. Shadow maps are cleared by the first call in a frame
+    pub fn render<'a, M: Material>(
+        &mut self,
+        renderer: &mut Renderer,
+        models: impl IntoIterator<Item = (&'a Model, &'a Transform)>,
+    ) -> Result<(), RenderError> {
+        if self.cascades.is_empty() && self.point_lights.is_empty() {
+            return Ok(());
+        }
+
+        let models: Vec<_> = models
+            .into_iter()
+            .filter_map(|(model, transform)| {
+                let mesh = model.mesh.as_ref()?;
+                Some((mesh, transform.to_relative_matrices(&self.origin).0))
+            })
+            .collect();
+
+        let clear = self.cleared_frame != Some(renderer.frame());
+        self.cleared_frame = Some(renderer.frame());
+
+        let mut previous_framebuffer = 0;
+        let mut previous_viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+        }
+
+        let result = self.render_cascades::<M>(renderer, &models, clear)
+            .and_then(|_| self.render_point_maps::<M>(renderer, &models, clear));
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as u32);
+            gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        }
+
+        renderer.reset_material_state();
+
+        result
+    }
+
+    fn render_cascades<M: Material>(
+        &mut self,
+        renderer: &mut Renderer,
+        models: &[(&Mesh, glm::Mat4)],
+        clear: bool,
+    ) -> Result<(), RenderError> {
+        if self.cascades.is_empty() {
+            return Ok(());
+        }
+
+        let Some(pipeline) = shadow_pipeline::<M>(&mut self.pipelines, include_str!("../shaders/shadow/depth.fs")) else {
+            return Ok(());
+        };
+
+        pipeline.apply();
+        pipeline.set_vec2("jitter", &glm::Vec2::zeros());
+
+        unsafe {
+            gl::Viewport(0, 0, self.allocated.0 as i32, self.allocated.0 as i32);
+
+            // Casters in front of the near plane are flattened onto it instead of being clipped
+            if !renderer.capabilities().is_gles {
+                gl::Enable(gl::DEPTH_CLAMP);
+            }
+        }
+
+        let result = (|| {
+            for (layer, cascade) in self.cascades.iter().enumerate() {
+                unsafe {
+                    gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, self.texture, 0, layer as i32);
+
+                    if clear {
+                        gl::Clear(gl::DEPTH_BUFFER_BIT);
+                    }
+                }
+
+                pipeline.set_mat4("view", &cascade.view);
+                pipeline.set_mat4("projection", &cascade.projection);
+                draw_models(renderer, pipeline, models)?;
+            }
+
+            Ok(())
+        })();
+
+        if !renderer.capabilities().is_gles {
+            unsafe { gl::Disable(gl::DEPTH_CLAMP); }
+        }
+
+        result
+    }
+
+    fn render_point_maps<M: Material>(
+        &mut self,
+        renderer: &mut Renderer,
+        models: &[(&Mesh, glm::Mat4)],
+        clear: bool,
+    ) -> Result<(), RenderError> {
+        if self.point_lights.is_empty() {
+            return Ok(());
+        }
+
+        let Some(pipeline) = shadow_pipeline::<M>(&mut self.point_pipelines, include_str!("../shaders/shadow/distance.fs")) else {
+            return Ok(());
+        };
+
+        let range = self.settings.point_range.max(0.1);
+
+        pipeline.apply();
+        pipeline.set_vec2("jitter", &glm::Vec2::zeros());
+        pipeline.set_mat4("projection", &glm::perspective(1.0, FRAC_PI_2, 0.05, range));
+        pipeline.set_float("farPlane", range);
+
+        let size = self.point_maps.first().map_or(0, Cubemap::size);
+        unsafe { gl::Viewport(0, 0, size as i32, size as i32); }
+
+        for (position, cubemap) in self.point_lights.iter().zip(&self.point_maps) {
+            pipeline.set_vec3("lightPos", position);
+
+            for (face, (direction, up)) in CUBEMAP_FACES.iter().enumerate() {
+                unsafe {
+                    gl::FramebufferTexture2D(
+                        gl::FRAMEBUFFER,
+                        gl::DEPTH_ATTACHMENT,
+                        gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                        cubemap.id(),
+                        0,
+                    );
+
+                    if clear {
+                        gl::Clear(gl::DEPTH_BUFFER_BIT);
+                    }
+                }
+
+                let view = glm::look_at(position, &(position + glm::Vec3::from(*direction)), &glm::Vec3::from(*up));
+                pipeline.set_mat4("view", &view);
+                draw_models(renderer, pipeline, models)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set shadow uniforms of the pipeline. `None` disables shadows
+    pub fn setup_pipeline(shadows: Option<&ShadowMaps>, pipeline: &GraphicsPipeline) {
+        pipeline.apply();
+
+        // Samplers of different types mustn't share a unit, even if shadows are disabled
+        pipeline.set_int("shadowMap", texture_unit(SHADOW_MAP_ORDER));
+        for (i, order) in POINT_SHADOW_MAP_ORDERS.into_iter().enumerate() {
+            pipeline.set_int(&format!("pointShadowMaps[{i}]"), texture_unit(order));
+        }
+
+        let Some(shadows) = shadows else {
+            pipeline.set_bool("shadows.enabled", false);
+            pipeline.set_int("shadows.pointCount", 0);
+            return;
+        };
+
+        pipeline.set_bool("shadows.enabled", !shadows.cascades.is_empty());
+        pipeline.set_int("shadows.pointCount", shadows.point_lights.len() as i32);
+        pipeline.set_float("shadows.pointRange", shadows.settings.point_range.max(0.1));
+        pipeline.set_float("shadows.pointBias", shadows.settings.point_bias);
+        pipeline.set_float("shadows.pointResolution", shadows.settings.point_resolution.max(1) as f32);
+        pipeline.set_int("shadows.cascadeCount", shadows.cascades.len() as i32);
+        pipeline.set_float("shadows.bias", shadows.settings.bias);
+        pipeline.set_float("shadows.normalOffset", shadows.settings.normal_offset);
+        pipeline.set_int("shadows.pcfRadius", shadows.settings.pcf_radius as i32);
+
+        for (i, cascade) in shadows.cascades.iter().enumerate() {
+            pipeline.set_mat4(&format!("shadows.lightSpace[{i}]"), &(cascade.projection * cascade.view));
+            pipeline.set_float(&format!("shadows.texelSize[{i}]"), cascade.texel_size);
+        }
+    }
+
+    /// Bind shadow maps to the texture units, used by `shadows.glsl` shader chunk
+    pub fn activate(&self) {
+        for (cubemap, order) in self.point_maps.iter().zip(POINT_SHADOW_MAP_ORDERS) {
+            cubemap.activate(order);
+        }
+
+        unsafe {
+            gl::ActiveTexture(SHADOW_MAP_ORDER as u32);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.texture);
+            gl::ActiveTexture(Order::Texture0 as u32);
+        }
+    }
+
+    /// Allocate a cubemap for each shadowed point light. They are reallocated, when the resolution changes
+    fn allocate_point_maps(&mut self) {
+        let resolution = self.settings.point_resolution.max(1);
+
+        if self.point_maps.first().is_some_and(|cubemap| cubemap.size() != resolution) {
+            self.point_maps.clear();
+        }
+
+        while self.point_maps.len() < self.point_lights.len() {
+            self.point_maps.push(Cubemap::new_empty(resolution, ColorMode::DepthComponent24, false));
+        }
+
+        self.allocate_framebuffer();
+    }
+
+    /// (Re)allocate the depth texture array, when the resolution or number of cascades changes
+    fn allocate(&mut self, resolution: u32, layers: usize) {
+        if self.allocated == (resolution, layers) {
+            return;
+        }
+
+        self.release();
+
+        unsafe {
+            gl::GenTextures(1, &mut self.texture);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.texture);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                gl::DEPTH_COMPONENT24 as i32,
+                resolution as i32,
+                resolution as i32,
+                layers as i32,
+                0,
+                gl::DEPTH_COMPONENT,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+
+            // Hardware comparison gives bilinear PCF for each sample
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_COMPARE_FUNC, gl::LEQUAL as i32);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+
+            self.allocate_framebuffer();
+
+            let mut previous_framebuffer = 0;
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, self.texture, 0, 0);
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                error!("Shadow map framebuffer is incomplete: {}", status_name(status));
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as u32);
+        }
+
+        self.allocated = (resolution, layers);
+    }
+
+    /// Create depth-only framebuffer, which shadow maps are attached to while rendering
+    fn allocate_framebuffer(&mut self) {
+        if self.framebuffer != 0 {
+            return;
+        }
+
+        unsafe {
+            let mut previous_framebuffer = 0;
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+
+            gl::GenFramebuffers(1, &mut self.framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::DrawBuffers(1, &gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as u32);
+        }
+    }
+
+    fn release(&mut self) {
+        unsafe {
+            if self.texture != 0 {
+                gl::DeleteTextures(1, &self.texture);
+            }
+        }
+
+        self.texture = 0;
+        self.allocated = (0, 0);
+    }
+}
+
+impl Default for ShadowMaps {
+    fn default() -> Self {
+        ShadowMaps::new(ShadowSettings::default())
+    }
+}
+
+impl Drop for ShadowMaps {
+    fn drop(&mut self) {
+        self.release();
+
+        if self.framebuffer != 0 {
+            unsafe { gl::DeleteFramebuffers(1, &self.framebuffer); }
+        }
+    }
+}
+
+/// Distance of the end of the cascade, blended between logarithmic and uniform splits
+fn cascade_split(index: usize, count: usize, near: f32, far: f32, lambda: f32) -> f32 {
+    let ratio = index as f32 / count as f32;
+    let logarithmic = near * (far / near).powf(ratio);
+    let uniform = near + (far - near) * ratio;
+
+    lambda.clamp(0.0, 1.0) * logarithmic + (1.0 - lambda.clamp(0.0, 1.0)) * uniform
+}
+
+fn texture_unit(order: Order) -> i32 {
+    (order as u32 - gl::TEXTURE0) as i32
+}
+
+/// Draw meshes with their model matrices into the bound framebuffer
+fn draw_models(renderer: &mut Renderer, pipeline: &GraphicsPipeline, models: &[(&Mesh, glm::Mat4)]) -> Result<(), RenderError> {
+    for (mesh, model) in models {
+        mesh.vertex_array.bind();
+        pipeline.set_mat4("model", model);
+
+        unsafe { renderer.execute(&mut DrawTrianglesCommand::new(mesh.index_data.len()))?; }
+    }
+
+    Ok(())
+}
+
+/// Get shadow pipeline with the material's vertex shader and `fragment_shader`, compiling it on the first use
+fn shadow_pipeline<'a, M: Material>(
+    pipelines: &'a mut HashMap<TypeId, Option<GraphicsPipeline>>,
+    fragment_shader: &str,
+) -> Option<&'a GraphicsPipeline> {
+    pipelines.entry(TypeId::of::<M>()).or_insert_with(|| {
+        let pipeline = Shader::new_from_source(M::vertex_shader(), ShaderType::VertexShader)
+            .and_then(|vertex_shader| {
+                let fragment_shader = Shader::new_from_source(fragment_shader, ShaderType::FragmentShader)?;
+                GraphicsPipeline::new(&[vertex_shader, fragment_shader])
+            });
+
+        // Failure is kept, so the material doesn't cast shadows without retrying every frame
+        pipeline
+            .map_err(|e| error!("Cannot create shadow pipeline for material `{}`: {e}", pretty_type_name::<M>()))
+            .ok()
+    }).as_ref()
+}
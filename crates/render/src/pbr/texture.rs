@@ -1,16 +1,15 @@
 use std::path::Path;
 
-use flatbox_assets::{
-    // manager::Asset,
-    typetag,
-};
+// use flatbox_assets::manager::Asset;
 use gl::types::GLuint;
 use image::{EncodableLayout, ImageBuffer, Rgba};
 use serde::{Serialize, Deserialize};
 
 use crate::{
     macros::glenum_wrapper, 
-    error::RenderError
+    error::RenderError,
+    hal::sampler::Sampler,
+    renderer::stats::count_texture_bind,
 };
 
 glenum_wrapper! {
@@ -34,7 +33,11 @@ glenum_wrapper! {
     wrapper: ColorMode,
     variants: [
         Srgb8Alpha8,
-        Rgba
+        Rgba,
+        Rgba16f,
+        Rg16f,
+        DepthComponent24,
+        Depth24Stencil8
     ]
 }
 
@@ -55,22 +58,60 @@ glenum_wrapper! {
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum ImageType {
     Image2D,
+    /// Region at the offset of the created texture. Existing textures are updated with [`Texture::write_region`]
     SubImage2D([usize; 2]),
 }
 
+/// Color space of the texture data. Colors (e.g. diffuse maps) are usually stored in sRGB
+/// and must be decoded to be lit, while data maps (e.g. normal or specular maps) are linear
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+/// Parameters of texture creation. The default one is suitable for color textures,
+/// [`TextureDescriptor::data`] for data maps
 pub struct TextureDescriptor {
     pub filter: Filter,
     pub wrap_mode: WrapMode,
     pub color_mode: ColorMode,
+    /// Color space of the data. sRGB data with [`ColorMode::Rgba`] is stored as [`ColorMode::Srgb8Alpha8`],
+    /// so it's decoded to linear space when sampled
+    pub color_space: ColorSpace,
     pub image_type: ImageType,
 }
 
+impl TextureDescriptor {
+    /// Descriptor of color textures, e.g. diffuse maps
+    pub fn albedo() -> Self {
+        TextureDescriptor::default()
+    }
+
+    /// Descriptor of linear data, e.g. normal, specular or roughness maps
+    pub fn data() -> Self {
+        TextureDescriptor {
+            color_space: ColorSpace::Linear,
+            ..Default::default()
+        }
+    }
+
+    fn internal_format(&self) -> u32 {
+        match (self.color_mode, self.color_space) {
+            (ColorMode::Rgba, ColorSpace::Srgb) => ColorMode::Srgb8Alpha8 as u32,
+            (color_mode, _) => color_mode as u32,
+        }
+    }
+}
+
 impl Default for TextureDescriptor {
     fn default() -> Self {
         TextureDescriptor {
             filter: Filter::Linear,
             wrap_mode: WrapMode::Repeat,
             color_mode: ColorMode::Rgba,
+            color_space: ColorSpace::Srgb,
             image_type: ImageType::Image2D,
         }
     }
@@ -79,6 +120,8 @@ impl Default for TextureDescriptor {
 #[derive(Clone, Debug)]
 pub struct Texture {
     id: GLuint,
+    width: u32,
+    height: u32,
 }
 
 impl Serialize for Texture {
@@ -115,12 +158,103 @@ impl Texture {
         unsafe { Texture::new_internal(buf, width, height, descr) }
     }
 
+    /// Create texture without data, e.g. to be attached to a [`Framebuffer`](crate::hal::framebuffer::Framebuffer)
+    pub fn new_empty(width: u32, height: u32, color_mode: ColorMode, filter: Filter) -> Texture {
+        let (format, data_type) = match color_mode {
+            ColorMode::Rgba16f => (gl::RGBA, gl::FLOAT),
+            ColorMode::Rg16f => (gl::RG, gl::FLOAT),
+            ColorMode::DepthComponent24 => (gl::DEPTH_COMPONENT, gl::FLOAT),
+            ColorMode::Depth24Stencil8 => (gl::DEPTH_STENCIL, gl::UNSIGNED_INT_24_8),
+            _ => (gl::RGBA, gl::UNSIGNED_BYTE),
+        };
+
+        let mut id: GLuint = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D, id);
+
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, WrapMode::ClampToEdge as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, WrapMode::ClampToEdge as i32);
+
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                color_mode as i32,
+                width.max(1) as i32,
+                height.max(1) as i32,
+                0,
+                format,
+                data_type,
+                std::ptr::null(),
+            );
+        }
+
+        Texture { id, width: width.max(1), height: height.max(1) }
+    }
+
+    pub(crate) fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Replace texels of the region with RGBA data, e.g. of a dynamic atlas or a video frame.
+    /// `data` must contain `width * height * 4` bytes
+    pub fn write_region(&self, x: u32, y: u32, width: u32, height: u32, data: &[u8]) -> Result<(), RenderError> {
+        let fits = x.checked_add(width).is_some_and(|right| right <= self.width)
+            && y.checked_add(height).is_some_and(|top| top <= self.height);
+
+        if !fits {
+            return Err(RenderError::InvalidTextureRegion(format!(
+                "{width}x{height} at ({x}, {y}) is out of {}x{} texture", self.width, self.height,
+            )));
+        }
+
+        if data.len() != width as usize * height as usize * 4 {
+            return Err(RenderError::InvalidTextureRegion(format!(
+                "{width}x{height} region needs {} bytes, got {}", width as usize * height as usize * 4, data.len(),
+            )));
+        }
+
+        self.bind();
+
+        unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as *const _,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Bind the texture to the unit, which is sampled with the texture's parameters,
+    /// until a [`Sampler`] is bound to it
     pub fn activate(&self, order: Order) {
         unsafe { gl::ActiveTexture(order as u32); }
+        Sampler::unbind(order);
         self.bind();
     }
 
     pub fn bind(&self){
+        count_texture_bind();
         unsafe { gl::BindTexture(gl::TEXTURE_2D, self.id); }
     }
 
@@ -133,7 +267,7 @@ impl Texture {
         let mut id: GLuint = 0;
         gl::GenTextures(1, &mut id);
 
-        let texture = Texture { id };
+        let texture = Texture { id, width, height };
         texture.bind();
 
         let descr = descr.unwrap_or_default();
@@ -148,7 +282,7 @@ impl Texture {
             ImageType::Image2D => gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                descr.color_mode as i32,
+                descr.internal_format() as i32,
                 width as i32,
                 height as i32,
                 0,
@@ -173,6 +307,18 @@ impl Texture {
     }
 }
 
+impl Texture {
+    /// Normal map, which doesn't change normals, i.e. all texels point along the surface normal
+    pub fn flat_normal() -> Texture {
+        let img = ImageBuffer::from_fn(16, 16, |_, _| Rgba::<u8>([128, 128, 255, 255])).into_raw();
+
+        Texture::new_from_raw(&img, 16, 16, Some(TextureDescriptor {
+            filter: Filter::Nearest,
+            ..TextureDescriptor::data()
+        })).unwrap()
+    }
+}
+
 impl Default for Texture {
     fn default() -> Self {
         let img = ImageBuffer::from_fn(16, 16, |_, _| Rgba::<u8>([255, 255, 255, 255])).into_raw();
@@ -190,6 +336,131 @@ impl Drop for Texture {
     }
 }
 
+/// Cube texture of six square faces, ordered as `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`
+#[derive(Debug)]
+pub struct Cubemap {
+    id: GLuint,
+    size: u32,
+}
+
+impl Cubemap {
+    /// Load faces from sRGB image files. All faces must be square and have the same size
+    pub fn new<P: AsRef<Path>>(faces: [P; 6]) -> Result<Cubemap, RenderError> {
+        let mut images = Vec::with_capacity(6);
+        for path in faces {
+            images.push(image::open(path)?.into_rgba8());
+        }
+
+        let size = images[0].width();
+        if images.iter().any(|image| image.width() != size || image.height() != size) {
+            return Err(RenderError::InvalidCubemap);
+        }
+
+        let cubemap = Cubemap::new_empty(size, ColorMode::Srgb8Alpha8, false);
+
+        unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+
+            for (face, image) in images.iter().enumerate() {
+                gl::TexSubImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                    0,
+                    0,
+                    0,
+                    size as i32,
+                    size as i32,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    image.as_bytes().as_ptr() as *const _,
+                );
+            }
+        }
+
+        Ok(cubemap)
+    }
+
+    /// Create cubemap without data, e.g. to render into it. With `mipmaps` all mip levels are allocated
+    pub fn new_empty(size: u32, color_mode: ColorMode, mipmaps: bool) -> Cubemap {
+        let (format, data_type) = match color_mode {
+            ColorMode::Rgba16f => (gl::RGBA, gl::FLOAT),
+            ColorMode::Rg16f => (gl::RG, gl::FLOAT),
+            ColorMode::DepthComponent24 => (gl::DEPTH_COMPONENT, gl::FLOAT),
+            _ => (gl::RGBA, gl::UNSIGNED_BYTE),
+        };
+
+        let size = size.max(1);
+        let mut id: GLuint = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, id);
+
+            let min_filter = if mipmaps { gl::LINEAR_MIPMAP_LINEAR } else { gl::LINEAR };
+
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, WrapMode::ClampToEdge as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, WrapMode::ClampToEdge as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, WrapMode::ClampToEdge as i32);
+
+            for face in 0..6 {
+                gl::TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                    0,
+                    color_mode as i32,
+                    size as i32,
+                    size as i32,
+                    0,
+                    format,
+                    data_type,
+                    std::ptr::null(),
+                );
+            }
+
+            if mipmaps {
+                gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+            }
+        }
+
+        Cubemap { id, size }
+    }
+
+    pub(crate) fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Width and height of the faces
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn activate(&self, order: Order) {
+        unsafe { gl::ActiveTexture(order as u32); }
+        self.bind();
+    }
+
+    pub fn bind(&self){
+        count_texture_bind();
+        unsafe { gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id); }
+    }
+
+    /// Calculate mip levels from the base level
+    pub fn generate_mipmaps(&self) {
+        self.bind();
+
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32);
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+        }
+    }
+}
+
+impl Drop for Cubemap {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, [self.id].as_ptr()); }
+    }
+}
+
 pub fn load_image_from_memory(buf: &[u8]) -> Option<(Vec<u8>, u32, u32)> {
     match image::load_from_memory(buf) {
         Ok(img) => {
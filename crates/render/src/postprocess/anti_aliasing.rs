@@ -0,0 +1,74 @@
+use serde::{Serialize, Deserialize};
+use flatbox_core::math::glm;
+
+use crate::{hal::shader::GraphicsPipeline, renderer::WindowExtent};
+use super::PostEffect;
+
+/// Number of jitter offsets, after which the sequence repeats
+const JITTER_SAMPLES: u64 = 8;
+
+/// Post-process anti-aliasing of the camera, when MSAA isn't available or affordable.
+/// Add it to the camera entity, or spawn it as a singleton component (e.g. `AntiAliasing::Fxaa`)
+/// to apply it to any active camera without its own. Despawn the singleton to turn it off
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AntiAliasing {
+    /// Fast approximate anti-aliasing, which blurs edges found by luminance
+    #[default]
+    Fxaa,
+    /// Temporal anti-aliasing, which accumulates frames rendered with sub-pixel jitter.
+    /// `feedback` is the weight of the accumulated history in range `0.0..1.0`
+    Taa { feedback: f32 },
+}
+
+impl AntiAliasing {
+    pub fn taa() -> Self {
+        AntiAliasing::Taa { feedback: 0.9 }
+    }
+
+    /// Sub-pixel offset of the projection in normalized device coordinates for the frame
+    pub fn jitter(&self, frame: u64, extent: WindowExtent) -> glm::Vec2 {
+        match self {
+            AntiAliasing::Fxaa => glm::Vec2::zeros(),
+            AntiAliasing::Taa { .. } => {
+                let index = frame % JITTER_SAMPLES + 1;
+
+                glm::vec2(
+                    (halton(index, 2) - 0.5) * 2.0 / extent.width.max(1.0),
+                    (halton(index, 3) - 0.5) * 2.0 / extent.height.max(1.0),
+                )
+            },
+        }
+    }
+}
+
+impl PostEffect for AntiAliasing {
+    fn fragment_shader(&self) -> &'static str {
+        match self {
+            AntiAliasing::Fxaa => include_str!("../shaders/postprocess/fxaa.fs"),
+            AntiAliasing::Taa { .. } => include_str!("../shaders/postprocess/taa.fs"),
+        }
+    }
+
+    fn setup_pipeline(&self, pipeline: &GraphicsPipeline) {
+        if let AntiAliasing::Taa { feedback } = self {
+            pipeline.set_float("feedback", feedback.clamp(0.0, 0.99));
+        }
+    }
+
+    fn uses_history(&self) -> bool {
+        matches!(self, AntiAliasing::Taa { .. })
+    }
+}
+
+fn halton(mut index: u64, base: u64) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+
+    result
+}
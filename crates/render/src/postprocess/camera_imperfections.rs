@@ -0,0 +1,46 @@
+use serde::{Serialize, Deserialize};
+
+use crate::hal::shader::GraphicsPipeline;
+use super::PostEffect;
+
+/// Vignette, film grain and chromatic aberration, applied in one pass. Add it to the
+/// camera entity; each effect is disabled with zero intensity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraImperfections {
+    /// How much the corners are darkened, in range `0.0..=1.0`
+    pub vignette_intensity: f32,
+    /// Distance from the center of the screen, where darkening ends, in texture coordinates
+    pub vignette_radius: f32,
+    /// Width of the transition from the unaffected center to the dark edge
+    pub vignette_smoothness: f32,
+    /// Amplitude of the animated noise
+    pub grain_intensity: f32,
+    /// Shift of red and blue channels at the edges of the screen, in texture coordinates
+    pub chromatic_aberration: f32,
+}
+
+impl Default for CameraImperfections {
+    fn default() -> Self {
+        CameraImperfections {
+            vignette_intensity: 0.5,
+            vignette_radius: 0.75,
+            vignette_smoothness: 0.45,
+            grain_intensity: 0.05,
+            chromatic_aberration: 0.01,
+        }
+    }
+}
+
+impl PostEffect for CameraImperfections {
+    fn fragment_shader(&self) -> &'static str {
+        include_str!("../shaders/postprocess/camera_imperfections.fs")
+    }
+
+    fn setup_pipeline(&self, pipeline: &GraphicsPipeline) {
+        pipeline.set_float("vignette_intensity", self.vignette_intensity.clamp(0.0, 1.0));
+        pipeline.set_float("vignette_radius", self.vignette_radius);
+        pipeline.set_float("vignette_smoothness", self.vignette_smoothness.max(0.001));
+        pipeline.set_float("grain_intensity", self.grain_intensity.max(0.0));
+        pipeline.set_float("chromatic_aberration", self.chromatic_aberration);
+    }
+}
@@ -0,0 +1,42 @@
+use flatbox_core::math::glm;
+
+use crate::{hal::shader::GraphicsPipeline, pbr::camera::Camera};
+use super::PostEffect;
+
+/// Depth of field with bokeh-style gather of near and far blur. It's built from focus
+/// settings of the [`Camera`] and applied, when its aperture isn't zero
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthOfField {
+    pub near: f32,
+    pub far: f32,
+    pub focus_distance: f32,
+    pub aperture: f32,
+    pub autofocus: Option<glm::Vec2>,
+}
+
+impl DepthOfField {
+    pub fn from_camera(camera: &Camera) -> Option<Self> {
+        (camera.aperture() > 0.0).then(|| DepthOfField {
+            near: camera.near(),
+            far: camera.far(),
+            focus_distance: camera.focus_distance(),
+            aperture: camera.aperture(),
+            autofocus: camera.autofocus(),
+        })
+    }
+}
+
+impl PostEffect for DepthOfField {
+    fn fragment_shader(&self) -> &'static str {
+        include_str!("../shaders/postprocess/depth_of_field.fs")
+    }
+
+    fn setup_pipeline(&self, pipeline: &GraphicsPipeline) {
+        pipeline.set_float("near", self.near);
+        pipeline.set_float("far", self.far);
+        pipeline.set_float("focus_distance", self.focus_distance.max(self.near));
+        pipeline.set_float("aperture", self.aperture);
+        pipeline.set_bool("autofocus", self.autofocus.is_some());
+        pipeline.set_vec2("focus_point", &self.autofocus.unwrap_or(glm::vec2(0.5, 0.5)));
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use flatbox_core::math::glm;
+
+use crate::{
+    error::RenderError,
+    hal::{
+        buffer::VertexArray,
+        framebuffer::{DepthAttachment, Framebuffer},
+        shader::{GraphicsPipeline, Shader, ShaderType},
+    },
+    pbr::texture::{ColorMode, Order},
+    renderer::{Capability, DisableCommand, DrawFullscreenCommand, Renderer},
+};
+
+pub mod anti_aliasing;
+pub mod camera_imperfections;
+pub mod depth_of_field;
+pub mod motion_blur;
+pub mod stack;
+
+pub use anti_aliasing::AntiAliasing;
+pub use camera_imperfections::CameraImperfections;
+pub use depth_of_field::DepthOfField;
+pub use motion_blur::MotionBlur;
+pub use stack::PostProcessStack;
+
+pub(crate) const FULLSCREEN_VERTEX_SHADER: &str = include_str!("../shaders/postprocess/fullscreen.vs");
+const COPY_FRAGMENT_SHADER: &str = include_str!("../shaders/postprocess/copy.fs");
+
+/// Full-screen pass, which is applied to the rendered scene. The fragment shader
+/// receives `TexCoord` input and can sample the scene with `color_texture`,
+/// `velocity_texture` and `depth_texture` uniforms. `texel_size` uniform contains
+/// the size of a pixel in texture coordinates, `frame` contains the index of the frame
+pub trait PostEffect {
+    fn fragment_shader(&self) -> &'static str;
+
+    /// Set uniforms of the effect
+    fn setup_pipeline(&self, pipeline: &GraphicsPipeline);
+
+    /// Whether the effect samples its own output of the previous frame with `history_texture`
+    /// uniform. `has_history` uniform is false, when the history isn't available, e.g.
+    /// after resizing. Only one such effect can be applied per frame
+    fn uses_history(&self) -> bool { false }
+}
+
+struct CopyEffect;
+
+impl PostEffect for CopyEffect {
+    fn fragment_shader(&self) -> &'static str {
+        COPY_FRAGMENT_SHADER
+    }
+
+    fn setup_pipeline(&self, _: &GraphicsPipeline) {}
+}
+
+/// Singleton component with offscreen targets for post-processing. The scene is rendered into
+/// its color, velocity and depth attachments after [`PostProcessing::begin`]; then
+/// [`PostProcessing::resolve`] applies effects one after another and presents the result
+#[derive(Default)]
+pub struct PostProcessing {
+    scene: Option<Framebuffer>,
+    targets: Vec<Framebuffer>,
+    history: Vec<Framebuffer>,
+    history_frame: Option<u64>,
+    vertex_array: Option<VertexArray>,
+    pipelines: HashMap<&'static str, GraphicsPipeline>,
+}
+
+impl PostProcessing {
+    pub fn new() -> Self {
+        PostProcessing::default()
+    }
+
+    /// Framebuffer, which the scene is rendered into. It's created on the first [`PostProcessing::begin`]
+    pub fn scene(&self) -> Option<&Framebuffer> {
+        self.scene.as_ref()
+    }
+
+    /// Bind the scene framebuffer, resizing it to the window extent
+    pub fn begin(&mut self, renderer: &mut Renderer) {
+        let [width, height]: [u32; 2] = renderer.extent().into();
+
+        let scene = self.scene.get_or_insert_with(|| Framebuffer::new(
+            width,
+            height,
+            &[ColorMode::Rgba16f, ColorMode::Rg16f],
+            DepthAttachment::Depth,
+        ));
+
+        scene.resize(width, height);
+        renderer.bind_framebuffer(Some(scene));
+    }
+
+    /// Apply `effects` in order to the rendered scene and draw the result into the default framebuffer
+    pub fn resolve(&mut self, renderer: &mut Renderer, effects: &[&dyn PostEffect]) -> Result<(), RenderError> {
+        if self.scene.is_none() {
+            return Ok(());
+        }
+
+        let effects = if effects.is_empty() { &[&CopyEffect as &dyn PostEffect] } else { effects };
+
+        self.prepare(effects)?;
+
+        let scene = self.scene.as_ref().unwrap();
+        let texel_size = glm::vec2(1.0 / scene.width() as f32, 1.0 / scene.height() as f32);
+        let frame = renderer.frame();
+        let extent: [u32; 2] = renderer.extent().into();
+
+        renderer.execute(&mut DisableCommand(Capability::DepthTest))?;
+        renderer.execute(&mut DisableCommand(Capability::Blend))?;
+
+        self.vertex_array.get_or_insert_with(VertexArray::new).bind();
+
+        for (i, effect) in effects.iter().enumerate() {
+            let input = match i {
+                0 => scene,
+                _ => &self.targets[(i - 1) % 2],
+            };
+
+            let output = match i + 1 == effects.len() {
+                true => None,
+                false => Some(&self.targets[i % 2]),
+            };
+
+            let uses_history = effect.uses_history();
+            // History is written and read in turns
+            let (history_write, history_read) = match frame % 2 {
+                0 => (0, 1),
+                _ => (1, 0),
+            };
+
+            if uses_history {
+                renderer.bind_framebuffer(Some(&self.history[history_write]));
+            } else {
+                renderer.bind_framebuffer(output);
+            }
+
+            let pipeline = &self.pipelines[effect.fragment_shader()];
+            pipeline.apply();
+            pipeline.set_int("color_texture", 0);
+            pipeline.set_int("velocity_texture", 1);
+            pipeline.set_int("depth_texture", 2);
+            pipeline.set_vec2("texel_size", &texel_size);
+            pipeline.set_float("frame", frame as f32);
+
+            input.color_attachment(0).unwrap().activate(Order::Texture0);
+            scene.color_attachment(1).unwrap().activate(Order::Texture1);
+            scene.depth_attachment().unwrap().activate(Order::Texture2);
+
+            if uses_history {
+                pipeline.set_int("history_texture", 3);
+                pipeline.set_bool("has_history", self.history_frame.is_some_and(|f| f + 1 == frame));
+                self.history[history_read].color_attachment(0).unwrap().activate(Order::Texture3);
+            }
+
+            effect.setup_pipeline(pipeline);
+
+            unsafe { renderer.execute(&mut DrawFullscreenCommand::new())?; }
+
+            if uses_history {
+                self.history[history_write].blit(output, extent);
+                self.history_frame = Some(frame);
+                renderer.bind_framebuffer(output);
+            }
+        }
+
+        unsafe { gl::ActiveTexture(Order::Texture0 as u32); }
+
+        Ok(())
+    }
+
+    /// Compile pipelines of the effects and create intermediate targets
+    fn prepare(&mut self, effects: &[&dyn PostEffect]) -> Result<(), RenderError> {
+        for effect in effects {
+            let fragment_shader = effect.fragment_shader();
+
+            if !self.pipelines.contains_key(fragment_shader) {
+                let pipeline = GraphicsPipeline::new(&[
+                    Shader::new_from_source(FULLSCREEN_VERTEX_SHADER, ShaderType::VertexShader)?,
+                    Shader::new_from_source(fragment_shader, ShaderType::FragmentShader)?,
+                ])?;
+
+                self.pipelines.insert(fragment_shader, pipeline);
+            }
+        }
+
+        let scene = self.scene.as_ref().unwrap();
+        let (width, height) = (scene.width(), scene.height());
+        let count = (effects.len() - 1).min(2);
+
+        while self.targets.len() < count {
+            self.targets.push(Framebuffer::new(width, height, &[ColorMode::Rgba16f], DepthAttachment::None));
+        }
+
+        for target in &mut self.targets {
+            target.resize(width, height);
+        }
+
+        if effects.iter().any(|effect| effect.uses_history()) {
+            while self.history.len() < 2 {
+                self.history.push(Framebuffer::new(width, height, &[ColorMode::Rgba16f], DepthAttachment::None));
+            }
+
+            if (self.history[0].width(), self.history[0].height()) != (width, height) {
+                self.history_frame = None;
+
+                for history in &mut self.history {
+                    history.resize(width, height);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
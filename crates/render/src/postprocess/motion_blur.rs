@@ -0,0 +1,38 @@
+use serde::{Serialize, Deserialize};
+
+use crate::hal::shader::GraphicsPipeline;
+use super::PostEffect;
+
+/// Camera and object motion blur. Add it to the camera entity to blur the scene along
+/// the motion since the previous frame, written into the velocity buffer by materials
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MotionBlur {
+    /// Fraction of the frame time, during which the shutter is open. `1.0` blurs
+    /// along the whole motion since the previous frame
+    pub shutter: f32,
+    /// Number of texture samples per pixel
+    pub samples: u32,
+}
+
+impl MotionBlur {
+    pub fn new(shutter: f32, samples: u32) -> Self {
+        MotionBlur { shutter, samples }
+    }
+}
+
+impl Default for MotionBlur {
+    fn default() -> Self {
+        MotionBlur::new(0.5, 8)
+    }
+}
+
+impl PostEffect for MotionBlur {
+    fn fragment_shader(&self) -> &'static str {
+        include_str!("../shaders/postprocess/motion_blur.fs")
+    }
+
+    fn setup_pipeline(&self, pipeline: &GraphicsPipeline) {
+        pipeline.set_float("shutter", self.shutter.max(0.0));
+        pipeline.set_int("samples", self.samples.max(1) as i32);
+    }
+}
@@ -0,0 +1,72 @@
+use super::PostEffect;
+
+struct StackEntry {
+    effect: Box<dyn PostEffect + Send + Sync>,
+    enabled: bool,
+}
+
+/// Singleton component with user-defined full-screen effects. They are applied in order
+/// of pushing on the scene color, after the effects of the active camera
+#[derive(Default)]
+pub struct PostProcessStack {
+    entries: Vec<StackEntry>,
+}
+
+impl PostProcessStack {
+    pub fn new() -> Self {
+        PostProcessStack::default()
+    }
+
+    /// Add effect to the end of the stack and get its index
+    pub fn push<E: PostEffect + Send + Sync + 'static>(&mut self, effect: E) -> usize {
+        self.entries.push(StackEntry { effect: Box::new(effect), enabled: true });
+        self.entries.len() - 1
+    }
+
+    /// Insert effect at `index`, shifting the following ones
+    pub fn insert<E: PostEffect + Send + Sync + 'static>(&mut self, index: usize, effect: E) {
+        self.entries.insert(index, StackEntry { effect: Box::new(effect), enabled: true });
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Box<dyn PostEffect + Send + Sync>> {
+        (index < self.entries.len()).then(|| self.entries.remove(index).effect)
+    }
+
+    /// Replace effect at `index`, keeping whether it's enabled
+    pub fn replace<E: PostEffect + Send + Sync + 'static>(&mut self, index: usize, effect: E) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.effect = Box::new(effect);
+        }
+    }
+
+    /// Temporarily skip or apply the effect without removing it
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.enabled = enabled;
+        }
+    }
+
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.entries.get(index).is_some_and(|entry| entry.enabled)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over enabled effects in order of applying
+    pub fn effects(&self) -> impl Iterator<Item = &dyn PostEffect> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.effect.as_ref() as &dyn PostEffect)
+    }
+}
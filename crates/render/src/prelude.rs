@@ -1,5 +1,9 @@
 pub use crate::pbr::{
+    batch::*,
     camera::*,
+    environment::*,
+    fog::*,
+    light::*,
     material::*,
     mesh::*,
     model::*,
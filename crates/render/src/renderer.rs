@@ -4,27 +4,45 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 
 use flatbox_core::{
-    logger::{warn, error},
-    math::transform::Transform,
+    color::Color,
+    logger::{info, warn, error},
+    math::{glm, transform::{Transform, Real}},
 };
+use image::RgbaImage;
 use pretty_type_name::pretty_type_name;
+use serde::{Serialize, Deserialize};
+
+pub mod graph;
+pub mod stats;
 
 #[cfg(feature = "context")]
 use crate::context::Context;
 use crate::glenum_wrapper;
-use crate::pbr::texture::Order;
+use crate::pbr::texture::{ColorMode, Order, Texture};
 use crate::{
     error::RenderError,
-    hal::shader::{GraphicsPipeline, Shader, ShaderType},
+    hal::{
+        buffer::VertexLayout,
+        capabilities::GlCapabilities,
+        framebuffer::{DepthAttachment, Framebuffer},
+        shader::{GraphicsPipeline, Shader, ShaderType},
+    },
     pbr::{
         material::Material,
         model::Model,
+        mesh::{Mesh, Vertex},
         camera::Camera,
+        light::SceneLights,
+        fog::Fog,
+        environment::EnvironmentMap,
+        shadow::ShadowMaps,
     },
 };
 
 #[allow(unused_imports)]
 use crate::hal::buffer::VertexArray;
+use crate::hal::query::TimerQuery;
+use stats::{PassTimers, RenderStats};
 
 glenum_wrapper! {
     wrapper: Capability,
@@ -32,7 +50,8 @@ glenum_wrapper! {
         ScissorTest,
         CullFace,
         DepthTest,
-        Blend
+        Blend,
+        FramebufferSrgb
     ]
 }
 
@@ -66,6 +85,23 @@ glenum_wrapper! {
     ]
 }
 
+/// Way of drawing models, e.g. to diagnose broken meshes. Can be spawned as a singleton
+/// component to be applied by `render_material` system
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RenderMode {
+    /// Regular shading of materials
+    #[default]
+    Shaded,
+    /// Edges of triangles with material shading. Not supported on OpenGL ES
+    Wireframe,
+    /// World-space normals as colors
+    Normals,
+    /// Texture coordinates as red and green colors
+    Uvs,
+    /// Number of fragments drawn per pixel; brighter areas are drawn more times
+    Overdraw,
+}
+
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct WindowExtent {
@@ -91,12 +127,34 @@ impl From<WindowExtent> for [u32; 2] {
     }
 }
 
+const DEBUG_FRAGMENT_SHADER: &str = include_str!("shaders/debug.fs");
+
 pub type GraphicsPipelines = HashMap<TypeId, GraphicsPipeline>;
+pub type RenderTargets = HashMap<String, Framebuffer>;
 
 pub struct Renderer {
     graphics_pipelines: GraphicsPipelines,
+    debug_pipelines: HashMap<TypeId, Option<GraphicsPipeline>>,
+    render_mode: RenderMode,
+    render_targets: RenderTargets,
     extent: WindowExtent,
+    render_origin: glm::TVec3<Real>,
     commands_history: RenderCommandsHistory,
+    capabilities: GlCapabilities,
+    current_stats: RenderStats,
+    stats: RenderStats,
+    pass_timers: Option<PassTimers>,
+    frame: u64,
+    draw_buffers: usize,
+    view_projection_history: Option<MotionHistory<glm::TMat4<Real>>>,
+    projection_jitter: glm::Vec2,
+    /// Material type and its state, which is set up in the bound pipeline
+    material_state: Option<(TypeId, u64)>,
+    /// Lights, which were uploaded last
+    lights: SceneLights,
+    srgb_output: bool,
+    #[cfg(feature = "debug-gl")]
+    assert_gl_errors: bool,
 }
 
 #[cfg(not(feature = "context"))]
@@ -107,30 +165,217 @@ impl Renderer {
     pub fn init<F: GlInitFunction>(init_function: F) -> Renderer {
         gl::load_with(init_function);
 
-        Renderer {
+        let mut renderer = Renderer {
             graphics_pipelines: GraphicsPipelines::new(),
+            debug_pipelines: HashMap::new(),
+            render_mode: RenderMode::Shaded,
+            render_targets: RenderTargets::new(),
             extent: WindowExtent::new(800.0, 600.0),
+            render_origin: glm::TVec3::zeros(),
             commands_history: RenderCommandsHistory::new(50),
-        }
+            capabilities: detect_capabilities(),
+            current_stats: RenderStats::default(),
+            stats: RenderStats::default(),
+            pass_timers: None,
+            frame: 0,
+            draw_buffers: 1,
+            view_projection_history: None,
+            projection_jitter: glm::Vec2::zeros(),
+            material_state: None,
+            lights: SceneLights::default(),
+            srgb_output: false,
+            #[cfg(feature = "debug-gl")]
+            assert_gl_errors: false,
+        };
+
+        renderer.init_srgb_output();
+        renderer
     }
 
     #[cfg(feature = "context")]
     pub fn init(context: &Context) -> Result<Renderer, RenderError> {
         gl::load_with(|addr| context.get_proc_address(addr));
 
-        Ok(Renderer {
+        let mut renderer = Renderer {
             graphics_pipelines: GraphicsPipelines::new(),
+            debug_pipelines: HashMap::new(),
+            render_mode: RenderMode::Shaded,
+            render_targets: RenderTargets::new(),
             extent: WindowExtent::new(800.0, 600.0),
+            render_origin: glm::TVec3::zeros(),
             commands_history: RenderCommandsHistory::new(50),
-        })
+            capabilities: detect_capabilities(),
+            current_stats: RenderStats::default(),
+            stats: RenderStats::default(),
+            pass_timers: None,
+            frame: 0,
+            draw_buffers: 1,
+            view_projection_history: None,
+            projection_jitter: glm::Vec2::zeros(),
+            material_state: None,
+            lights: SceneLights::default(),
+            srgb_output: false,
+            #[cfg(feature = "debug-gl")]
+            assert_gl_errors: false,
+        };
+
+        renderer.init_srgb_output();
+        Ok(renderer)
+    }
+
+    fn init_srgb_output(&mut self) {
+        if !self.capabilities.srgb_framebuffer {
+            warn!("Default framebuffer isn't sRGB-capable, so the output isn't gamma-corrected");
+        }
+
+        self.set_srgb_output(self.capabilities.srgb_framebuffer);
+    }
+
+    /// Start a new frame, saving statistics of the previous one
+    pub fn begin_frame(&mut self) {
+        self.end_pass_timer();
+
+        let gpu_pass_times = self.current_stats.gpu_pass_times.clone();
+        self.stats = std::mem::take(&mut self.current_stats).with_bindings();
+        // GPU times arrive irregularly, so the latest ones are kept between frames
+        self.current_stats.gpu_pass_times = gpu_pass_times;
+        RenderStats::reset_bindings();
+        self.frame += 1;
+        self.reset_material_state();
+    }
+
+    /// Set up the material in the pipeline of `M`, unless the same state is already set up
+    fn setup_material<M: Material>(&mut self, material: &dyn Material) -> Result<(), RenderError> {
+        let material_state = material.state_key().map(|key| (TypeId::of::<M>(), key));
+        let material_changed = material_state.is_none() || material_state != self.material_state;
+        self.material_state = material_state;
+
+        if material_changed {
+            let pipeline = self.get_pipeline::<M>()?;
+            pipeline.apply();
+            material.setup_pipeline(pipeline);
+        }
+
+        Ok(())
+    }
+
+    /// Forget the material state, which the last drawn mesh has set up, so the next mesh sets up
+    /// its material anew. It must be called, when the pipeline or textures may have been changed
+    /// outside of draw commands, e.g. after drawing a batch of models
+    pub fn reset_material_state(&mut self) {
+        self.material_state = None;
+    }
+
+    /// Index of the current frame
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Number of draw calls, issued during the previous frame
+    pub fn draw_calls(&self) -> u32 {
+        self.stats.draw_calls
+    }
+
+    /// Statistics of the previous frame
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
+    /// Statistics of the current frame so far
+    pub fn current_stats(&self) -> RenderStats {
+        self.current_stats.clone().with_bindings()
+    }
+
+    /// Measure GPU time of render passes with timer queries. It's reported in [`RenderStats::gpu_pass_times`]
+    pub fn set_gpu_timing(&mut self, enabled: bool) {
+        if enabled && !TimerQuery::is_supported() {
+            warn!("GPU timing isn't supported by the context");
+            return;
+        }
+
+        if !enabled {
+            self.end_pass_timer();
+            self.current_stats.gpu_pass_times.clear();
+        }
+
+        self.pass_timers = enabled.then(PassTimers::default);
+    }
+
+    pub fn gpu_timing(&self) -> bool {
+        self.pass_timers.is_some()
+    }
+
+    /// Start measuring GPU time of the named pass until the next pass is started or
+    /// [`Renderer::end_pass_timer`] is called. It does nothing, unless GPU timing is enabled
+    pub fn begin_pass_timer(&mut self, name: &str) {
+        if let Some(ref mut pass_timers) = self.pass_timers {
+            pass_timers.begin(name, &mut self.current_stats);
+        }
+    }
+
+    pub fn end_pass_timer(&mut self) {
+        if let Some(ref mut pass_timers) = self.pass_timers {
+            pass_timers.end();
+        }
+    }
+
+    /// Count a draw call of `triangles` triangles. Custom render commands, which draw, should call it
+    pub fn record_draw_call(&mut self, triangles: u64) {
+        self.current_stats.draw_calls += 1;
+        self.current_stats.triangles += triangles;
+    }
+
+    /// Encode the output of linear shading into sRGB-capable framebuffers (`GL_FRAMEBUFFER_SRGB`).
+    /// It's enabled at initialization, if the default framebuffer is sRGB-capable
+    /// (see `WindowBuilder::srgb`). OpenGL ES always encodes into sRGB framebuffers
+    pub fn set_srgb_output(&mut self, enabled: bool) {
+        self.srgb_output = enabled;
+
+        if self.capabilities.is_gles {
+            return;
+        }
+
+        unsafe {
+            if enabled {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            } else {
+                gl::Disable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+    }
+
+    pub fn srgb_output(&self) -> bool {
+        self.srgb_output
+    }
+
+    /// Features of the OpenGL context, detected at initialization
+    pub fn capabilities(&self) -> &GlCapabilities {
+        &self.capabilities
     }
 
     pub fn extent(&self) -> WindowExtent {
         self.extent
     }
 
+    /// Origin of camera-relative rendering, set by [`RenderCameraCommand`].
+    /// It's always zero unless `f64-transforms` feature is enabled
+    pub fn render_origin(&self) -> glm::TVec3<Real> {
+        self.render_origin
+    }
+
+    pub fn set_render_origin(&mut self, render_origin: glm::TVec3<Real>) {
+        self.render_origin = render_origin;
+    }
+
+    /// Set the viewport, resizing render targets if the size has changed
     pub fn set_extent(&mut self, extent: WindowExtent) {
         self.extent = extent;
+
+        let [width, height]: [u32; 2] = extent.into();
+        for framebuffer in self.render_targets.values_mut() {
+            framebuffer.resize(width, height);
+        }
+
         unsafe { gl::Viewport(
             self.extent.x as i32, 
             self.extent.y as i32, 
@@ -139,10 +384,155 @@ impl Renderer {
         ); }
     }
 
+    /// Bind the framebuffer to draw into, or the default one if `None`
+    pub fn bind_framebuffer(&mut self, framebuffer: Option<&Framebuffer>) {
+        self.reset_material_state();
+
+        match framebuffer {
+            Some(framebuffer) => {
+                framebuffer.bind();
+                self.draw_buffers = framebuffer.draw_buffers();
+            },
+            None => {
+                unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, 0); }
+                self.set_extent(self.extent);
+                self.draw_buffers = 1;
+            },
+        }
+    }
+
+    /// Read pixels of the bound framebuffer within the extent. The first row of the image is the top one
+    pub fn capture_frame(&self) -> RgbaImage {
+        let [width, height]: [u32; 2] = self.extent.into();
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+
+        unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                self.extent.x as i32,
+                self.extent.y as i32,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _,
+            );
+        }
+
+        let mut image = RgbaImage::from_raw(width, height, pixels).expect("Pixel buffer has the size of the image");
+        image::imageops::flip_vertical_in_place(&mut image);
+        image
+    }
+
+    /// Render with `render` into a new texture of the `extent` size instead of the bound framebuffer,
+    /// e.g. to show a 3D preview in the UI. The texture has OpenGL orientation, i.e. its
+    /// first row is the bottom one. The previous framebuffer and extent are restored afterwards
+    pub fn render_to_texture<F>(&mut self, extent: WindowExtent, render: F) -> Result<Texture, RenderError>
+    where
+        F: FnOnce(&mut Renderer) -> Result<(), RenderError>,
+    {
+        let [width, height]: [u32; 2] = extent.into();
+        let framebuffer = Framebuffer::new(width, height, &[ColorMode::Rgba], DepthAttachment::DepthStencil);
+        framebuffer.check_status()?;
+
+        let mut previous_framebuffer = 0;
+        unsafe { gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer); }
+
+        let previous_extent = self.extent;
+        let previous_draw_buffers = self.draw_buffers;
+
+        // Extent is replaced without `set_extent`, so that render targets aren't resized
+        self.bind_framebuffer(Some(&framebuffer));
+        self.extent = WindowExtent::new(width as f32, height as f32);
+
+        let result = render(self);
+
+        self.extent = previous_extent;
+        self.draw_buffers = previous_draw_buffers;
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as u32); }
+        self.set_extent(previous_extent);
+
+        result?;
+
+        Ok(framebuffer.into_color_attachment(0).expect("Framebuffer has a color attachment"))
+    }
+
+    /// Add framebuffer, which is owned by the renderer and resized together with the window.
+    /// The previous render target with the same name is replaced
+    pub fn add_render_target(&mut self, name: impl Into<String>, color_modes: &[ColorMode], depth: DepthAttachment) -> &Framebuffer {
+        let [width, height]: [u32; 2] = self.extent.into();
+        let name = name.into();
+
+        self.render_targets.insert(name.clone(), Framebuffer::new(width, height, color_modes, depth));
+        &self.render_targets[&name]
+    }
+
+    pub fn render_target(&self, name: &str) -> Option<&Framebuffer> {
+        self.render_targets.get(name)
+    }
+
+    pub fn remove_render_target(&mut self, name: &str) -> Option<Framebuffer> {
+        self.render_targets.remove(name)
+    }
+
+    /// Sub-pixel offset of the projection in normalized device coordinates, e.g. for
+    /// temporal anti-aliasing. It doesn't affect the velocity buffer
+    pub fn projection_jitter(&self) -> glm::Vec2 {
+        self.projection_jitter
+    }
+
+    pub fn set_projection_jitter(&mut self, jitter: glm::Vec2) {
+        self.projection_jitter = jitter;
+    }
+
+    /// Number of color outputs of the bound framebuffer
+    pub fn draw_buffers(&self) -> usize {
+        self.draw_buffers
+    }
+
     pub fn get_pipeline<M: Material>(&self) -> Result<&GraphicsPipeline, RenderError> {
         self.graphics_pipelines.get(&TypeId::of::<M>()).ok_or(RenderError::MaterialNotBound(pretty_type_name::<M>().to_string()))
     }
 
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    pub fn set_render_mode(&mut self, render_mode: RenderMode) {
+        if render_mode != self.render_mode && render_mode == RenderMode::Wireframe && self.capabilities.is_gles {
+            warn!("Wireframe render mode is not supported on OpenGL ES, models are shaded");
+        }
+
+        self.render_mode = render_mode;
+    }
+
+    /// Get pipeline of the material with debug fragment shader for the current [`RenderMode`],
+    /// compiling it on the first use. Returns `None` if the mode doesn't need one or the
+    /// material's vertex shader doesn't output `Normal` and `TexCoord`
+    pub fn get_debug_pipeline<M: Material>(&mut self) -> Option<&GraphicsPipeline> {
+        if matches!(self.render_mode, RenderMode::Shaded | RenderMode::Wireframe) {
+            return None;
+        }
+
+        let pipeline = self.debug_pipelines.entry(TypeId::of::<M>()).or_insert_with(|| {
+            let pipeline = Shader::new_from_source(M::vertex_shader(), ShaderType::VertexShader)
+                .and_then(|vertex_shader| {
+                    let fragment_shader = Shader::new_from_source(DEBUG_FRAGMENT_SHADER, ShaderType::FragmentShader)?;
+                    GraphicsPipeline::new(&[vertex_shader, fragment_shader])
+                });
+
+            // Failure is kept, so the material is shaded as usual without retrying every frame
+            pipeline
+                .map_err(|e| error!("Cannot create debug pipeline for material `{}`: {e}", pretty_type_name::<M>()))
+                .ok()
+        }).as_ref()?;
+
+        pipeline.apply();
+        pipeline.set_int("mode", self.render_mode as i32);
+
+        Some(pipeline)
+    }
+
     pub fn bind_material<M: Material>(&mut self) {
         let material_type = TypeId::of::<M>();
         
@@ -160,9 +550,81 @@ impl Renderer {
         }
     }
 
+    /// Upload lights of the scene to all bound material pipelines
+    pub fn set_lights(&mut self, lights: &SceneLights) {
+        for pipeline in self.graphics_pipelines.values() {
+            lights.setup_pipeline(pipeline);
+        }
+
+        self.lights = lights.clone();
+    }
+
+    /// Lights, which were uploaded to material pipelines last
+    pub fn lights(&self) -> &SceneLights {
+        &self.lights
+    }
+
+    /// Bind environment maps and set environment uniforms of all bound material pipelines.
+    /// `None` disables image-based lighting
+    pub fn set_environment(&mut self, environment: Option<&EnvironmentMap>) {
+        if let Some(environment) = environment {
+            environment.activate();
+        }
+
+        for pipeline in self.graphics_pipelines.values() {
+            EnvironmentMap::setup_pipeline(environment, pipeline);
+        }
+    }
+
+    /// Bind the shadow map and set shadow uniforms of all bound material pipelines.
+    /// `None` disables shadows
+    pub fn set_shadows(&mut self, shadows: Option<&ShadowMaps>) {
+        if let Some(shadows) = shadows {
+            shadows.activate();
+        }
+
+        for pipeline in self.graphics_pipelines.values() {
+            ShadowMaps::setup_pipeline(shadows, pipeline);
+        }
+    }
+
+    /// Upload fog to all bound material pipelines. `None` disables fog
+    pub fn set_fog(&mut self, fog: Option<&Fog>) {
+        for pipeline in self.graphics_pipelines.values() {
+            Fog::setup_pipeline(fog, pipeline);
+        }
+    }
+
+    /// Panic, when a render command raises GL errors, instead of logging them
+    #[cfg(feature = "debug-gl")]
+    pub fn set_assert_gl_errors(&mut self, assert_gl_errors: bool) {
+        self.assert_gl_errors = assert_gl_errors;
+    }
+
     pub fn execute(&mut self, command: &mut dyn RenderCommand) -> Result<(), RenderError> {
         self.commands_history.push(command);
-        command.execute(self)
+        self.current_stats.commands += 1;
+
+        #[cfg(not(feature = "debug-gl"))]
+        return command.execute(self);
+
+        #[cfg(feature = "debug-gl")]
+        {
+            let result = command.execute(self);
+            let errors = crate::hal::debug::take_errors();
+
+            if !errors.is_empty() {
+                let message = format!("`{}` raised {}", command.name(), errors.join(", "));
+
+                if self.assert_gl_errors {
+                    panic!("{message}");
+                }
+
+                error!("{message}");
+            }
+
+            result
+        }
     }
 
     pub fn history(&self) -> &RenderCommandsHistory {
@@ -170,6 +632,51 @@ impl Renderer {
     }
 }
 
+/// Value of the current and the previous frame, used to calculate motion vectors
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MotionHistory<T> {
+    frame: u64,
+    current: T,
+    previous: T,
+}
+
+impl<T: Copy> MotionHistory<T> {
+    pub fn new(frame: u64, value: T) -> Self {
+        MotionHistory { frame, current: value, previous: value }
+    }
+
+    /// Set the value of the `frame` and get the value of the previous one.
+    /// If the value hasn't been set during the previous frame, there's no motion
+    pub fn update(&mut self, frame: u64, value: T) -> T {
+        if frame != self.frame {
+            self.previous = if frame == self.frame + 1 { self.current } else { value };
+            self.frame = frame;
+        }
+
+        self.current = value;
+        self.previous
+    }
+}
+
+fn detect_capabilities() -> GlCapabilities {
+    let capabilities = GlCapabilities::detect();
+
+    #[cfg(feature = "debug-gl")]
+    crate::hal::debug::install_debug_callback(&capabilities);
+
+    info!(
+        "{} ({}), GLSL {}, {} extensions{}{}",
+        capabilities.version,
+        capabilities.renderer,
+        capabilities.glsl_version,
+        capabilities.extensions.len(),
+        if capabilities.debug_context { ", debug context" } else { "" },
+        if capabilities.srgb_framebuffer { ", sRGB framebuffer" } else { "" },
+    );
+
+    capabilities
+}
+
 #[derive(Clone)]
 pub struct RenderCommandsHistory{
     cache: Vec<String>,
@@ -218,7 +725,7 @@ pub trait RenderCommand {
     fn name(&self) -> String { pretty_type_name::<Self>() }
 }
 
-pub struct ClearCommand(pub f32, pub f32, pub f32);
+pub struct ClearCommand(pub Color);
 
 impl RenderCommand for ClearCommand {
     fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
@@ -228,8 +735,13 @@ impl RenderCommand for ClearCommand {
         renderer.execute(&mut EnableCommand(Capability::DepthTest))?;
 
         unsafe {
-            gl::ClearColor(self.0, self.1, self.2, 1.0);
+            gl::ClearColor(self.0.r, self.0.g, self.0.b, self.0.a);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+            // Auxiliary outputs, e.g. velocity, are cleared with zeros
+            for i in 1..renderer.draw_buffers() {
+                gl::ClearBufferfv(gl::COLOR, i as i32, [0.0; 4].as_ptr());
+            }
         }
 
         Ok(())
@@ -306,6 +818,42 @@ impl RenderCommand for ColorMaskCommand {
     }
 }
 
+/// Bind the framebuffer to draw into, or the default one if `None`
+pub struct BindFramebufferCommand<'a>(pub Option<&'a Framebuffer>);
+
+impl<'a> RenderCommand for BindFramebufferCommand<'a> {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        renderer.bind_framebuffer(self.0);
+        Ok(())
+    }
+}
+
+/// Bind render target, added with [`Renderer::add_render_target`]
+pub struct BindRenderTargetCommand<'a>(pub &'a str);
+
+impl<'a> RenderCommand for BindRenderTargetCommand<'a> {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        let framebuffer = renderer.render_targets
+            .get(self.0)
+            .ok_or_else(|| RenderError::RenderTargetNotFound(self.0.to_string()))?;
+
+        framebuffer.bind();
+        renderer.draw_buffers = framebuffer.draw_buffers();
+
+        Ok(())
+    }
+}
+
+/// Bind the default framebuffer
+pub struct UnbindFramebufferCommand;
+
+impl RenderCommand for UnbindFramebufferCommand {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        renderer.bind_framebuffer(None);
+        Ok(())
+    }
+}
+
 pub struct ActivateTextureRawCommand(Order);
 
 impl ActivateTextureRawCommand {
@@ -324,7 +872,7 @@ impl RenderCommand for ActivateTextureRawCommand {
     }
 }
 
-pub struct DrawTrianglesCommand(usize);
+pub struct DrawTrianglesCommand(usize, usize);
 
 impl DrawTrianglesCommand {
     ///
@@ -332,22 +880,53 @@ impl DrawTrianglesCommand {
     /// A valid [`VertexArray`] has to be bound
     /// Valid index and vertex buffers have to be bound
     pub unsafe fn new(indices_count: usize) -> Self {
-        DrawTrianglesCommand(indices_count)
+        DrawTrianglesCommand(0, indices_count)
+    }
+
+    /// Draw `indices_count` indices, starting at `first_index`
+    ///
+    /// # Safety
+    /// The same as of [`DrawTrianglesCommand::new`]. The range must be within the index buffer
+    pub unsafe fn new_range(first_index: usize, indices_count: usize) -> Self {
+        DrawTrianglesCommand(first_index, indices_count)
     }
 }
 
 impl RenderCommand for DrawTrianglesCommand {
-    fn execute(&mut self, _: &mut Renderer) -> Result<(), RenderError> {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        renderer.record_draw_call(self.1 as u64 / 3);
+
         unsafe { gl::DrawElements(
             gl::TRIANGLES, 
-            self.0 as i32, 
+            self.1 as i32, 
             gl::UNSIGNED_INT, 
-            std::ptr::null()
+            (self.0 * std::mem::size_of::<u32>()) as *const _
         ); }
         Ok(())
     }
 } 
 
+pub struct DrawFullscreenCommand;
+
+impl DrawFullscreenCommand {
+    ///
+    /// # Safety
+    /// A valid [`VertexArray`] has to be bound
+    /// Bound pipeline has to generate a full-screen triangle from `gl_VertexID`
+    pub unsafe fn new() -> Self {
+        DrawFullscreenCommand
+    }
+}
+
+impl RenderCommand for DrawFullscreenCommand {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        renderer.record_draw_call(1);
+
+        unsafe { gl::DrawArrays(gl::TRIANGLES, 0, 3); }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct RenderCameraCommand<'a, M: Material> {
     camera: &'a mut Camera,
@@ -363,14 +942,33 @@ impl<'a, M: Material> RenderCameraCommand<'a, M> {
 
 impl<'a, M: Material> RenderCommand for RenderCameraCommand<'a, M> {
     fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
-        let pipeline = renderer.get_pipeline::<M>()?;
-
         if !self.camera.is_active() {
             warn!("Camera being rendered is not active");
         }
 
+        let origin = self.camera.upload_origin(self.transform);
+
         self.camera.set_aspect(renderer.extent().to_aspect());
-        self.camera.update_buffer(pipeline, self.transform);
+
+        let frame = renderer.frame;
+        let view_projection = self.camera.view_projection_real(self.transform);
+        let previous_view_projection = renderer.view_projection_history
+            .get_or_insert_with(|| MotionHistory::new(frame, view_projection))
+            .update(frame, view_projection);
+
+        let jitter = renderer.projection_jitter();
+        renderer.reset_material_state();
+
+        let pipeline = renderer.get_pipeline::<M>()?;
+        self.camera.update_buffer(pipeline, self.transform, &origin, &previous_view_projection);
+        pipeline.set_vec2("jitter", &jitter);
+
+        if let Some(pipeline) = renderer.get_debug_pipeline::<M>() {
+            self.camera.update_buffer(pipeline, self.transform, &origin, &previous_view_projection);
+            pipeline.set_vec2("jitter", &jitter);
+        }
+
+        renderer.set_render_origin(origin);
                 
         Ok(())
     }
@@ -392,17 +990,42 @@ impl<'a, M: Material> RenderCommand for PrepareModelCommand<'a, M> {
     fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
         let Some(ref mut mesh) = self.model.mesh else { return Ok(()) };
 
-        if mesh.prepared { return Ok(()); }
+        renderer.execute(&mut PrepareMeshCommand::new(mesh, self.material))
+    }
+}
+
+/// Upload mesh of any vertex format to GPU and bind its attributes to the material's pipeline.
+/// Data of prepared meshes is uploaded again, when they are marked dirty
+#[derive(Debug)]
+pub struct PrepareMeshCommand<'a, M, V = Vertex> {
+    mesh: &'a mut Mesh<V>,
+    material: &'a M,
+}
 
-        println!("not prepaired");
+impl<'a, M: Material, V: VertexLayout> PrepareMeshCommand<'a, M, V> {
+    pub fn new(mesh: &'a mut Mesh<V>, material: &'a M) -> PrepareMeshCommand<'a, M, V> {
+        Self { mesh, material }
+    }
+}
+
+impl<'a, M: Material, V: VertexLayout> RenderCommand for PrepareMeshCommand<'a, M, V> {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        if self.mesh.prepared {
+            if self.mesh.is_dirty() {
+                self.mesh.update_vertices();
+            }
+
+            return Ok(());
+        }
 
         let pipeline = renderer.get_pipeline::<M>()?;
-        mesh.setup(pipeline);
+        self.mesh.setup(pipeline);
 
         pipeline.apply();
         self.material.setup_pipeline(pipeline);
 
-        mesh.prepared = true;
+        self.mesh.prepared = true;
+        renderer.reset_material_state();
 
         Ok(())
     }
@@ -410,14 +1033,14 @@ impl<'a, M: Material> RenderCommand for PrepareModelCommand<'a, M> {
 
 #[derive(Debug)]
 pub struct DrawModelCommand<'a, M> {
-    model: &'a Model,
+    model: &'a mut Model,
     material: &'a M,
     transform: &'a Transform,
 }
 
 impl<'a, M: Material> DrawModelCommand<'a, M> {
     pub fn new(
-        model: &'a Model, 
+        model: &'a mut Model, 
         material: &'a M,
         transform: &'a Transform,
     ) -> DrawModelCommand<'a, M> {
@@ -427,26 +1050,116 @@ impl<'a, M: Material> DrawModelCommand<'a, M> {
 
 impl<'a, M: Material> RenderCommand for DrawModelCommand<'a, M> {
     fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
-        let Some(ref mesh) = self.model.mesh else { return Ok(()) };
+        let Some(ref mut mesh) = self.model.mesh else { return Ok(()) };
+
+        renderer.execute(&mut DrawMeshCommand::new(mesh, self.material, self.transform))
+    }
+}
+
+/// Draw mesh of any vertex format. It must be prepared with [`PrepareMeshCommand`] first.
+/// Mesh primitives are drawn with their materials, if they are of type `M`, and with `material` otherwise
+#[derive(Debug)]
+pub struct DrawMeshCommand<'a, M, V = Vertex> {
+    mesh: &'a mut Mesh<V>,
+    material: &'a M,
+    transform: &'a Transform,
+}
+
+impl<'a, M: Material, V: VertexLayout> DrawMeshCommand<'a, M, V> {
+    pub fn new(
+        mesh: &'a mut Mesh<V>, 
+        material: &'a M,
+        transform: &'a Transform,
+    ) -> DrawMeshCommand<'a, M, V> {
+        Self { mesh, material, transform }
+    }
+}
+
+impl<'a, M: Material, V: VertexLayout> RenderCommand for DrawMeshCommand<'a, M, V> {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        let mesh = &mut *self.mesh;
 
         if !mesh.prepared {
             return Err(RenderError::ModelNotPrepared);
         }
 
-        let pipeline = renderer.get_pipeline::<M>()?;
+        let frame = renderer.frame;
+        let previous_transform = mesh.transform_history
+            .get_or_insert_with(|| MotionHistory::new(frame, *self.transform))
+            .update(frame, *self.transform);
 
-        self.material.setup_pipeline(pipeline);
-        
-        let (model, inversed) = self.transform.to_matrices();
-        
-        pipeline.apply();
-        pipeline.set_mat4("model", &model);
-        pipeline.set_mat4("inversed", &inversed);
+        let (model, inversed) = self.transform.to_relative_matrices(&renderer.render_origin());
+        let (previous_model, _) = previous_transform.to_relative_matrices(&renderer.render_origin());
+        let render_mode = match renderer.render_mode() {
+            RenderMode::Wireframe if renderer.capabilities().is_gles => RenderMode::Shaded,
+            render_mode => render_mode,
+        };
+
+        let debug = renderer.get_debug_pipeline::<M>().is_some();
+
+        // Primitives are drawn with their materials, debug pipelines don't need any
+        let ranges = if debug || mesh.primitives.is_empty() {
+            vec![(0, mesh.index_data.len(), None)]
+        } else {
+            mesh.primitives
+                .iter()
+                .map(|primitive| (primitive.first_index as usize, primitive.index_count as usize, Some(primitive)))
+                .collect()
+        };
     
         mesh.vertex_array.bind();
 
-        unsafe { renderer.execute(&mut DrawTrianglesCommand::new(mesh.index_data.len()))?; }
+        unsafe {
+            match render_mode {
+                RenderMode::Wireframe => gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE),
+                RenderMode::Overdraw => {
+                    gl::Disable(gl::DEPTH_TEST);
+                    gl::BlendFunc(gl::ONE, gl::ONE);
+                },
+                _ => {},
+            }
+        }
 
-        Ok(())
+        let result = (|| {
+            for (index, (first_index, index_count, primitive)) in ranges.into_iter().enumerate() {
+                if !debug {
+                    match primitive.map(|primitive| primitive.material.lock()) {
+                        Some(material) if material.material_type() == TypeId::of::<M>() => {
+                            renderer.setup_material::<M>(&**material)?;
+                        },
+                        _ => renderer.setup_material::<M>(self.material)?,
+                    }
+                }
+
+                // Primitives share the pipeline, so matrices are set once
+                if index == 0 {
+                    let pipeline = match debug {
+                        true => renderer.get_debug_pipeline::<M>().expect("Debug pipeline exists"),
+                        false => renderer.get_pipeline::<M>()?,
+                    };
+
+                    pipeline.set_mat4("model", &model);
+                    pipeline.set_mat4("inversed", &inversed);
+                    pipeline.set_mat4("previous_model", &previous_model);
+                }
+
+                unsafe { renderer.execute(&mut DrawTrianglesCommand::new_range(first_index, index_count))?; }
+            }
+
+            Ok(())
+        })();
+
+        unsafe {
+            match render_mode {
+                RenderMode::Wireframe => gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL),
+                RenderMode::Overdraw => {
+                    gl::Enable(gl::DEPTH_TEST);
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                },
+                _ => {},
+            }
+        }
+
+        result
     }
 }
\ No newline at end of file
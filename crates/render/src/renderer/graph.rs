@@ -0,0 +1,215 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::RenderError;
+
+/// Target, which the scene is rendered into, e.g. by materials
+pub const SCENE_TARGET: &str = "scene";
+/// Final image, which is presented on the screen
+pub const SCREEN_TARGET: &str = "screen";
+
+/// Named pass of [`RenderGraph`] with the targets it reads and writes. Targets are
+/// just names, e.g. of render targets or textures, which are used to order passes
+#[derive(Debug)]
+pub struct RenderPass<P> {
+    name: String,
+    reads: Vec<String>,
+    writes: Vec<String>,
+    pub pass: P,
+}
+
+impl<P> RenderPass<P> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn reads(&self) -> &[String] {
+        &self.reads
+    }
+
+    pub fn writes(&self) -> &[String] {
+        &self.writes
+    }
+}
+
+/// Set of render passes, which are executed in the order of their dependencies:
+///
+/// * passes, writing the same target, are executed in the order of adding;
+/// * pass, which reads a target without writing it, is executed after all passes writing it.
+///
+/// Independent passes keep the order of adding. `P` is the payload of the pass, e.g. a system
+#[derive(Debug)]
+pub struct RenderGraph<P> {
+    passes: Vec<RenderPass<P>>,
+    order: Option<Vec<usize>>,
+}
+
+impl<P> RenderGraph<P> {
+    pub fn new() -> Self {
+        RenderGraph::default()
+    }
+
+    /// Add pass to the graph. Pass with the same name is replaced, keeping its place among writers
+    pub fn add_pass(&mut self, name: impl Into<String>, reads: &[&str], writes: &[&str], pass: P) -> &mut Self {
+        let pass = RenderPass {
+            name: name.into(),
+            reads: reads.iter().map(|target| target.to_string()).collect(),
+            writes: writes.iter().map(|target| target.to_string()).collect(),
+            pass,
+        };
+
+        match self.passes.iter_mut().find(|existing| existing.name == pass.name) {
+            Some(existing) => *existing = pass,
+            None => self.passes.push(pass),
+        }
+
+        self.order = None;
+        self
+    }
+
+    pub fn remove_pass(&mut self, name: &str) -> Option<P> {
+        let index = self.passes.iter().position(|pass| pass.name == name)?;
+        self.order = None;
+
+        Some(self.passes.remove(index).pass)
+    }
+
+    pub fn contains_pass(&self, name: &str) -> bool {
+        self.passes.iter().any(|pass| pass.name == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.passes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Names of the passes in execution order
+    pub fn order(&mut self) -> Result<Vec<&str>, RenderError> {
+        self.compile()?;
+
+        let order = self.order.as_ref().expect("Render graph is compiled");
+        Ok(order.iter().map(|&index| self.passes[index].name.as_str()).collect())
+    }
+
+    /// Call `execute` for each pass in execution order, stopping at the first error
+    pub fn execute<E: From<RenderError>>(
+        &mut self,
+        mut execute: impl FnMut(&mut RenderPass<P>) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.compile()?;
+
+        let order = self.order.as_ref().expect("Render graph is compiled");
+        for &index in order {
+            execute(&mut self.passes[index])?;
+        }
+
+        Ok(())
+    }
+
+    /// Sort passes topologically. The order is cached until the graph is changed
+    fn compile(&mut self) -> Result<(), RenderError> {
+        if self.order.is_some() {
+            return Ok(());
+        }
+
+        let mut dependencies: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); self.passes.len()];
+        let mut writers: HashMap<&str, Vec<usize>> = HashMap::new();
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            for target in &pass.writes {
+                let target_writers = writers.entry(target.as_str()).or_default();
+
+                if let Some(&previous) = target_writers.last() {
+                    dependencies[index].insert(previous);
+                }
+
+                target_writers.push(index);
+            }
+        }
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            for target in pass.reads.iter().filter(|target| !pass.writes.contains(target)) {
+                if let Some(target_writers) = writers.get(target.as_str()) {
+                    dependencies[index].extend(target_writers.iter().copied());
+                }
+            }
+        }
+
+        // Kahn's algorithm, which picks the earliest added pass among the ready ones
+        let mut order = Vec::with_capacity(self.passes.len());
+        let mut done = vec![false; self.passes.len()];
+
+        while order.len() < self.passes.len() {
+            let next = (0..self.passes.len())
+                .find(|&index| !done[index] && dependencies[index].iter().all(|&dependency| done[dependency]));
+
+            let Some(next) = next else {
+                let cycle: Vec<_> = (0..self.passes.len())
+                    .filter(|&index| !done[index])
+                    .map(|index| self.passes[index].name.as_str())
+                    .collect();
+
+                return Err(RenderError::RenderGraphCycle(cycle.join(", ")));
+            };
+
+            done[next] = true;
+            order.push(next);
+        }
+
+        self.order = Some(order);
+
+        Ok(())
+    }
+}
+
+impl<P> Default for RenderGraph<P> {
+    fn default() -> Self {
+        RenderGraph {
+            passes: vec![],
+            order: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writers_keep_order_of_adding() {
+        let mut graph = RenderGraph::new();
+        graph
+            .add_pass("first", &[], &[SCENE_TARGET], ())
+            .add_pass("second", &[], &[SCENE_TARGET], ())
+            .add_pass("third", &[SCENE_TARGET], &[SCENE_TARGET], ());
+
+        assert_eq!(graph.order().unwrap(), ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn reader_follows_all_writers() {
+        let mut graph = RenderGraph::new();
+        graph
+            .add_pass("present", &[SCENE_TARGET], &[SCREEN_TARGET], ())
+            .add_pass("opaque", &[], &[SCENE_TARGET], ())
+            .add_pass("transparent", &[], &[SCENE_TARGET], ());
+
+        assert_eq!(graph.order().unwrap(), ["opaque", "transparent", "present"]);
+    }
+
+    #[test]
+    fn cycle_is_detected() {
+        let mut graph = RenderGraph::new();
+        graph
+            .add_pass("a", &["x"], &["y"], ())
+            .add_pass("b", &["y"], &["x"], ())
+            .add_pass("c", &[], &[SCENE_TARGET], ());
+
+        match graph.order() {
+            Err(RenderError::RenderGraphCycle(passes)) => assert_eq!(passes, "a, b"),
+            order => panic!("Cycle isn't detected: {order:?}"),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use gl::types::GLuint;
+
+use crate::hal::query::TimerQuery;
+
+/// Number of timer queries of a pass, which may be in flight at once
+const TIMER_QUERIES_PER_PASS: usize = 4;
+
+static TEXTURE_BINDS: AtomicU32 = AtomicU32::new(0);
+static PIPELINE_SWITCHES: AtomicU32 = AtomicU32::new(0);
+static CURRENT_PROGRAM: AtomicU32 = AtomicU32::new(0);
+
+/// Statistics of a single frame, collected by [`Renderer`](super::Renderer)
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct RenderStats {
+    pub draw_calls: u32,
+    pub triangles: u64,
+    /// Number of texture bindings, including redundant ones
+    pub texture_binds: u32,
+    /// Number of times a different pipeline was applied
+    pub pipeline_switches: u32,
+    /// Number of executed render commands
+    pub commands: u32,
+    /// The latest measured GPU time of the render passes. Timing must be enabled
+    /// with [`Renderer::set_gpu_timing`](super::Renderer::set_gpu_timing), and it's measured a few frames late
+    pub gpu_pass_times: Vec<(String, Duration)>,
+}
+
+impl RenderStats {
+    /// GPU time of the named pass
+    pub fn gpu_time(&self, pass: &str) -> Option<Duration> {
+        self.gpu_pass_times.iter().find(|(name, _)| name == pass).map(|(_, time)| *time)
+    }
+
+    /// Total GPU time of the measured passes
+    pub fn total_gpu_time(&self) -> Duration {
+        self.gpu_pass_times.iter().map(|(_, time)| *time).sum()
+    }
+
+    fn set_gpu_time(&mut self, pass: &str, time: Duration) {
+        match self.gpu_pass_times.iter_mut().find(|(name, _)| name == pass) {
+            Some((_, pass_time)) => *pass_time = time,
+            None => self.gpu_pass_times.push((pass.to_owned(), time)),
+        }
+    }
+
+    /// Add counters of texture binds and pipeline switches, which happen outside of render commands
+    pub(crate) fn with_bindings(mut self) -> RenderStats {
+        self.texture_binds += TEXTURE_BINDS.load(Ordering::Relaxed);
+        self.pipeline_switches += PIPELINE_SWITCHES.load(Ordering::Relaxed);
+        self
+    }
+
+    pub(crate) fn reset_bindings() {
+        TEXTURE_BINDS.store(0, Ordering::Relaxed);
+        PIPELINE_SWITCHES.store(0, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn count_texture_bind() {
+    TEXTURE_BINDS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn count_pipeline_apply(program: GLuint) {
+    if CURRENT_PROGRAM.swap(program, Ordering::Relaxed) != program {
+        PIPELINE_SWITCHES.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Pools of timer queries of named passes, so results are read without stalls
+#[derive(Debug, Default)]
+pub(crate) struct PassTimers {
+    passes: Vec<(String, Vec<TimerQuery>)>,
+    /// Indices of the pass and its query, which is being measured
+    active: Option<(usize, usize)>,
+}
+
+impl PassTimers {
+    /// Read finished queries of the pass into `stats` and start a free one. The active query is ended
+    pub(crate) fn begin(&mut self, name: &str, stats: &mut RenderStats) {
+        self.end();
+
+        let pass = match self.passes.iter().position(|(pass, _)| pass == name) {
+            Some(pass) => pass,
+            None => {
+                self.passes.push((name.to_owned(), Vec::new()));
+                self.passes.len() - 1
+            },
+        };
+
+        let queries = &mut self.passes[pass].1;
+        for query in queries.iter_mut() {
+            if let Some(time) = query.try_result() {
+                stats.set_gpu_time(name, time);
+            }
+        }
+
+        let query = match queries.iter().position(|query| !query.is_pending()) {
+            Some(query) => query,
+            None if queries.len() < TIMER_QUERIES_PER_PASS => {
+                queries.push(TimerQuery::new());
+                queries.len() - 1
+            },
+            // The pass isn't measured, until the GPU catches up
+            None => return,
+        };
+
+        queries[query].begin();
+        self.active = Some((pass, query));
+    }
+
+    pub(crate) fn end(&mut self) {
+        if let Some((pass, query)) = self.active.take() {
+            self.passes[pass].1[query].end();
+        }
+    }
+}
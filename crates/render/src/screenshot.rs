@@ -0,0 +1,89 @@
+use std::{path::{Path, PathBuf}, thread::JoinHandle};
+
+use image::{ImageError, RgbaImage};
+use flatbox_core::logger::{info, error};
+
+use crate::{
+    error::RenderError,
+    renderer::{RenderCommand, Renderer},
+};
+
+/// Save the image on a separate thread, so the frame isn't stalled by encoding.
+/// The format is deduced from the extension of `path`
+pub fn save_image_async<P: Into<PathBuf>>(image: RgbaImage, path: P) -> JoinHandle<Result<(), ImageError>> {
+    let path = path.into();
+
+    std::thread::spawn(move || {
+        let result = image.save(&path);
+
+        match &result {
+            Ok(()) => info!("Saved screenshot to `{}`", path.display()),
+            Err(e) => error!("Cannot save screenshot to `{}`: {e}", path.display()),
+        }
+
+        result
+    })
+}
+
+/// Capture the bound framebuffer with [`Renderer::capture_frame`] and optionally save it
+pub struct ScreenshotCommand {
+    path: Option<PathBuf>,
+    image: Option<RgbaImage>,
+}
+
+impl ScreenshotCommand {
+    /// Capture the image, which can be taken with [`ScreenshotCommand::take_image`]
+    pub fn new() -> Self {
+        ScreenshotCommand { path: None, image: None }
+    }
+
+    /// Capture the image and save it asynchronously to `path`
+    pub fn save<P: AsRef<Path>>(path: P) -> Self {
+        ScreenshotCommand { path: Some(path.as_ref().to_path_buf()), image: None }
+    }
+
+    pub fn take_image(&mut self) -> Option<RgbaImage> {
+        self.image.take()
+    }
+}
+
+impl Default for ScreenshotCommand {
+    fn default() -> Self {
+        ScreenshotCommand::new()
+    }
+}
+
+impl RenderCommand for ScreenshotCommand {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        let image = renderer.capture_frame();
+
+        match &self.path {
+            Some(path) => { save_image_async(image, path.clone()); },
+            None => self.image = Some(image),
+        }
+
+        Ok(())
+    }
+}
+
+/// Singleton component with screenshot requests, which are captured at the end of the frame
+#[derive(Debug, Default)]
+pub struct Screenshots {
+    requests: Vec<PathBuf>,
+}
+
+impl Screenshots {
+    pub fn new() -> Self {
+        Screenshots::default()
+    }
+
+    /// Request screenshot of the current frame to be saved to `path`
+    pub fn request<P: Into<PathBuf>>(&mut self, path: P) {
+        self.requests.push(path.into());
+    }
+
+    /// Take requests to be captured
+    pub fn take_requests(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.requests)
+    }
+}
@@ -0,0 +1,40 @@
+// Image-based ambient lighting from `EnvironmentMap` singleton. Include it into a fragment
+// shader and add `CalcEnvironment` to the result of `CalcLighting`
+#include "lighting.glsl"
+
+struct Environment {
+    bool enabled;
+    float intensity;
+};
+
+uniform Environment environment;
+uniform samplerCube irradianceMap;
+uniform samplerCube prefilterMap;
+uniform sampler2D brdfLut;
+
+// Must match `PREFILTER_MIP_LEVELS - 1` in `pbr::environment` module
+#define MAX_REFLECTION_LOD 4.0
+
+// calculates ambient diffuse and specular light of the environment.
+vec3 CalcEnvironment(Surface surface, vec3 normal, vec3 viewDir)
+{
+    if (!environment.enabled)
+        return vec3(0.0);
+
+    // Roughness, which roughly matches Phong specular exponent
+    float roughness = clamp(sqrt(2.0 / (surface.shininess + 2.0)), 0.0, 1.0);
+    float NdotV = max(dot(normal, viewDir), 0.0);
+    vec3 reflectDir = reflect(-viewDir, normal);
+
+    vec3 irradiance = texture(irradianceMap, normal).rgb;
+    vec3 prefiltered = textureLod(prefilterMap, reflectDir, roughness * MAX_REFLECTION_LOD).rgb;
+    vec2 brdf = texture(brdfLut, vec2(NdotV, roughness)).rg;
+
+    // Specular map scales reflectance of a dielectric surface
+    vec3 F0 = 0.04 * surface.specular;
+
+    vec3 diffuse = irradiance * surface.diffuse;
+    vec3 specular = prefiltered * (F0 * brdf.x + brdf.y);
+
+    return (diffuse + specular) * environment.intensity;
+}
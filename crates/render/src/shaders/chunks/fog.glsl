@@ -0,0 +1,27 @@
+// Distance fog, uploaded by `update_fog` system. Include it into a fragment shader and
+// call `ApplyFog` with the lit color and the distance from the camera
+
+struct Fog {
+    // 0 - disabled, 1 - linear, 2 - exponential, 3 - exponential squared
+    int mode;
+    vec4 color;
+    float start;
+    float end;
+    float density;
+};
+
+uniform Fog fog;
+
+vec3 ApplyFog(vec3 color, float distance)
+{
+    float amount = 0.0;
+
+    if (fog.mode == 1)
+        amount = clamp((distance - fog.start) / max(fog.end - fog.start, 1e-4), 0.0, 1.0);
+    else if (fog.mode == 2)
+        amount = 1.0 - exp(-fog.density * distance);
+    else if (fog.mode == 3)
+        amount = 1.0 - exp(-pow(fog.density * distance, 2.0));
+
+    return mix(color, fog.color.rgb, amount * fog.color.a);
+}
@@ -0,0 +1,143 @@
+// Lights of the scene, uploaded by `collect_lights` system, and Phong shading.
+// Include it into a fragment shader and call `CalcLighting` with the surface properties.
+// Positions are relative to the render origin, like `FragPos` and `viewPos`
+#include "shadows.glsl"
+
+struct DirectionalLight {
+    vec3 direction;
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    // Whether the light is shaded by the shadow map
+    bool castShadows;
+};
+
+struct PointLight {
+    vec3 position;
+    float constant;
+    float linear;
+    float quadratic;
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    // Index of the shadow cubemap or -1, if the light isn't shadowed
+    int shadowIndex;
+};
+
+struct SpotLight {
+    vec3 position;
+    vec3 direction;
+    float cutOff;
+    float outerCutOff;
+    float constant;
+    float linear;
+    float quadratic;
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+// Colors of the surface at the fragment
+struct Surface {
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    float shininess;
+};
+
+// Must match the limits in `pbr::light` module
+#define MAX_DIRECTIONAL_LIGHTS 4
+#define MAX_POINT_LIGHTS 16
+#define MAX_SPOT_LIGHTS 8
+
+uniform DirectionalLight dirLights[MAX_DIRECTIONAL_LIGHTS];
+uniform PointLight pointLights[MAX_POINT_LIGHTS];
+uniform SpotLight spotLights[MAX_SPOT_LIGHTS];
+uniform int dirLightCount;
+uniform int pointLightCount;
+uniform int spotLightCount;
+
+// calculates the color when using a directional light, partially hidden by `shadow`.
+vec3 CalcDirLight(DirectionalLight light, Surface surface, vec3 normal, vec3 viewDir, float shadow)
+{
+    vec3 lightDir = normalize(-light.direction);
+    // diffuse shading
+    float diff = max(dot(normal, lightDir), 0.0);
+    // specular shading
+    vec3 reflectDir = reflect(-lightDir, normal);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), surface.shininess);
+    // combine results
+    vec3 ambient = light.ambient * surface.ambient;
+    vec3 diffuse = light.diffuse * diff * surface.diffuse;
+    vec3 specular = light.specular * spec * surface.specular;
+    return (ambient + (1.0 - shadow) * (diffuse + specular));
+}
+
+// calculates the color when using a point light, partially hidden by `shadow`.
+vec3 CalcPointLight(PointLight light, Surface surface, vec3 normal, vec3 fragPos, vec3 viewDir, float shadow)
+{
+    vec3 lightDir = normalize(light.position - fragPos);
+    // diffuse shading
+    float diff = max(dot(normal, lightDir), 0.0);
+    // specular shading
+    vec3 reflectDir = reflect(-lightDir, normal);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), surface.shininess);
+    // attenuation
+    float distance = length(light.position - fragPos);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));    
+    // combine results
+    vec3 ambient = light.ambient * surface.ambient;
+    vec3 diffuse = light.diffuse * diff * surface.diffuse;
+    vec3 specular = light.specular * spec * surface.specular;
+    ambient *= attenuation;
+    diffuse *= attenuation;
+    specular *= attenuation;
+    return (ambient + (1.0 - shadow) * (diffuse + specular));
+}
+
+// calculates the color when using a spot light.
+vec3 CalcSpotLight(SpotLight light, Surface surface, vec3 normal, vec3 fragPos, vec3 viewDir)
+{
+    vec3 lightDir = normalize(light.position - fragPos);
+    // diffuse shading
+    float diff = max(dot(normal, lightDir), 0.0);
+    // specular shading
+    vec3 reflectDir = reflect(-lightDir, normal);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), surface.shininess);
+    // attenuation
+    float distance = length(light.position - fragPos);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));    
+    // spotlight intensity
+    float theta = dot(lightDir, normalize(-light.direction)); 
+    float epsilon = light.cutOff - light.outerCutOff;
+    float intensity = clamp((theta - light.outerCutOff) / epsilon, 0.0, 1.0);
+    // combine results
+    vec3 ambient = light.ambient * surface.ambient;
+    vec3 diffuse = light.diffuse * diff * surface.diffuse;
+    vec3 specular = light.specular * spec * surface.specular;
+    ambient *= attenuation * intensity;
+    diffuse *= attenuation * intensity;
+    specular *= attenuation * intensity;
+    return (ambient + diffuse + specular);
+}
+
+// calculates the color, lit by all lights of the scene.
+vec3 CalcLighting(Surface surface, vec3 normal, vec3 fragPos, vec3 viewDir)
+{
+    vec3 result = vec3(0.0);
+
+    for(int i = 0; i < dirLightCount; i++) {
+        float shadow = dirLights[i].castShadows ? CalcDirShadow(fragPos, normal) : 0.0;
+        result += CalcDirLight(dirLights[i], surface, normal, viewDir, shadow);
+    }
+
+    for(int i = 0; i < pointLightCount; i++) {
+        float shadow = CalcPointShadow(pointLights[i].shadowIndex, fragPos, normal, pointLights[i].position);
+        result += CalcPointLight(pointLights[i], surface, normal, fragPos, viewDir, shadow);
+    }
+
+    for(int i = 0; i < spotLightCount; i++)
+        result += CalcSpotLight(spotLights[i], surface, normal, fragPos, viewDir);
+
+    return result;
+}
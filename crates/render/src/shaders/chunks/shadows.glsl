@@ -0,0 +1,107 @@
+// Cascaded shadow map of the directional light and distance cubemaps of point lights, uploaded
+// by `update_shadows` system. It's included by `lighting.glsl`, which applies them to the lights
+
+// Must match the limits in `pbr::shadow` module
+#define MAX_SHADOW_CASCADES 4
+#define MAX_POINT_SHADOWS 4
+
+struct Shadows {
+    bool enabled;
+    int cascadeCount;
+    mat4 lightSpace[MAX_SHADOW_CASCADES];
+    // Size of a texel of each cascade in world units
+    float texelSize[MAX_SHADOW_CASCADES];
+    float bias;
+    float normalOffset;
+    int pcfRadius;
+    int pointCount;
+    // Distance, which point light cubemaps are normalized to
+    float pointRange;
+    float pointBias;
+    float pointResolution;
+};
+
+uniform Shadows shadows;
+uniform sampler2DArrayShadow shadowMap;
+uniform samplerCube pointShadowMaps[MAX_POINT_SHADOWS];
+
+// Directions of PCF samples of point light shadows
+const vec3 pointSampleOffsets[20] = vec3[](
+    vec3( 1,  1,  1), vec3( 1, -1,  1), vec3(-1, -1,  1), vec3(-1,  1,  1),
+    vec3( 1,  1, -1), vec3( 1, -1, -1), vec3(-1, -1, -1), vec3(-1,  1, -1),
+    vec3( 1,  1,  0), vec3( 1, -1,  0), vec3(-1, -1,  0), vec3(-1,  1,  0),
+    vec3( 1,  0,  1), vec3(-1,  0,  1), vec3( 1,  0, -1), vec3(-1,  0, -1),
+    vec3( 0,  1,  1), vec3( 0, -1,  1), vec3( 0, -1, -1), vec3( 0,  1, -1)
+);
+
+// calculates the amount of shadow in range [0, 1] at the fragment.
+float CalcDirShadow(vec3 fragPos, vec3 normal)
+{
+    if (!shadows.enabled)
+        return 0.0;
+
+    vec2 texel = 1.0 / vec2(textureSize(shadowMap, 0).xy);
+    for (int i = 0; i < shadows.cascadeCount; i++) {
+        // offset along the normal against shadow acne
+        vec3 offset = normal * shadows.texelSize[i] * shadows.normalOffset;
+        vec4 lightPos = shadows.lightSpace[i] * vec4(fragPos + offset, 1.0);
+        vec3 coords = lightPos.xyz / lightPos.w * 0.5 + 0.5;
+
+        // the first cascade, which contains the whole kernel, is used
+        vec2 margin = texel * float(shadows.pcfRadius + 1);
+        if (any(lessThan(coords.xy, margin)) || any(greaterThan(coords.xy, 1.0 - margin)) || coords.z > 1.0)
+            continue;
+
+        // percentage-closer filtering
+        float lit = 0.0;
+        for (int x = -shadows.pcfRadius; x <= shadows.pcfRadius; x++)
+            for (int y = -shadows.pcfRadius; y <= shadows.pcfRadius; y++)
+                lit += texture(shadowMap, vec4(coords.xy + vec2(x, y) * texel, float(i), coords.z - shadows.bias));
+
+        float kernel = float(2 * shadows.pcfRadius + 1);
+        return 1.0 - lit / (kernel * kernel);
+    }
+
+    return 0.0;
+}
+
+// Samplers can only be indexed with constants in GLSL 3.30
+float SamplePointShadowMap(int index, vec3 direction)
+{
+    if (index == 0)
+        return texture(pointShadowMaps[0], direction).r;
+    else if (index == 1)
+        return texture(pointShadowMaps[1], direction).r;
+    else if (index == 2)
+        return texture(pointShadowMaps[2], direction).r;
+    else
+        return texture(pointShadowMaps[3], direction).r;
+}
+
+// calculates the amount of shadow in range [0, 1] at the fragment, lit by the point light.
+float CalcPointShadow(int index, vec3 fragPos, vec3 normal, vec3 lightPos)
+{
+    if (index < 0 || index >= shadows.pointCount)
+        return 0.0;
+
+    // size of a cubemap texel at the fragment's distance
+    float texelSize = 2.0 * length(fragPos - lightPos) / shadows.pointResolution;
+    vec3 fragToLight = fragPos + normal * texelSize * shadows.normalOffset - lightPos;
+    float current = length(fragToLight);
+
+    if (current > shadows.pointRange)
+        return 0.0;
+
+    if (shadows.pcfRadius == 0)
+        return current - shadows.pointBias > SamplePointShadowMap(index, fragToLight) * shadows.pointRange ? 1.0 : 0.0;
+
+    // percentage-closer filtering
+    float shadow = 0.0;
+    float radius = texelSize * float(shadows.pcfRadius);
+    for (int i = 0; i < 20; i++) {
+        float closest = SamplePointShadowMap(index, fragToLight + pointSampleOffsets[i] * radius) * shadows.pointRange;
+        shadow += current - shadows.pointBias > closest ? 1.0 : 0.0;
+    }
+
+    return shadow / 20.0;
+}
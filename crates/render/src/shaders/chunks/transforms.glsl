@@ -0,0 +1,21 @@
+// Matrices, set by `RenderCameraCommand` and `DrawModelCommand`. Positions are relative
+// to the render origin with camera-relative rendering
+
+uniform mat4 model;
+uniform mat4 inversed;
+uniform mat4 view;
+uniform mat4 projection;
+uniform mat4 previous_model;
+uniform mat4 previous_view_projection;
+uniform vec2 jitter;
+
+// Normal matrix of the model
+mat3 NormalMatrix() {
+    return mat3(transpose(inversed));
+}
+
+// Apply sub-pixel jitter to the clip-space position. It must be applied after
+// the velocity is calculated, so it's not treated as motion
+vec4 ApplyJitter(vec4 position) {
+    return position + vec4(jitter * position.w, 0.0, 0.0);
+}
@@ -0,0 +1,26 @@
+#version 330
+layout(location = 0) out vec4 FragColor;
+layout(location = 1) out vec2 Velocity;
+
+in vec3 Normal;
+in vec2 TexCoord;
+
+// Must match `RenderMode` variants
+#define MODE_NORMALS 2
+#define MODE_UVS 3
+#define MODE_OVERDRAW 4
+
+uniform int mode;
+
+void main() {
+    if (mode == MODE_NORMALS) {
+        FragColor = vec4(normalize(Normal) * 0.5 + 0.5, 1.0);
+    } else if (mode == MODE_UVS) {
+        FragColor = vec4(fract(TexCoord), 0.0, 1.0);
+    } else {
+        // Accumulated additively, so brighter areas are drawn more times
+        FragColor = vec4(0.1, 0.05, 0.02, 1.0);
+    }
+
+    Velocity = vec2(0.0);
+}
@@ -1,133 +1,61 @@
 #version 330
-out vec4 FragColor;
+#include "lighting.glsl"
+#include "fog.glsl"
+#include "environment.glsl"
+
+layout(location = 0) out vec4 FragColor;
+layout(location = 1) out vec2 Velocity;
 
 struct DefaultMaterial {
-    vec3 color;
+    vec4 color;
     sampler2D diffuse_map;
     sampler2D specular_map;
+    sampler2D normal_map;
     float shininess;
+    // Multiply by vertex color
+    bool vertex_color;
 };
 
-struct DirectionalLight {
-    vec3 direction;
-    vec3 ambient;
-    vec3 diffuse;
-    vec3 specular;
-};
-
-struct PointLight {
-    vec3 position;
-    float constant;
-    float linear;
-    float quadratic;
-    vec3 ambient;
-    vec3 diffuse;
-    vec3 specular;
-};
-
-struct SpotLight {
-    vec3 position;
-    vec3 direction;
-    float cutOff;
-    float outerCutOff;
-    float constant;
-    float linear;
-    float quadratic;
-    vec3 ambient;
-    vec3 diffuse;
-    vec3 specular;       
-};
-
-#define NR_POINT_LIGHTS 4
-
+// Position relative to the render origin
 in vec3 FragPos;
 in vec3 Normal;
 in vec2 TexCoord;
+in vec3 Tangent;
+in vec4 VertexColor;
+in vec4 CurrentPosition;
+in vec4 PreviousPosition;
 
+// Camera position relative to the render origin
 uniform vec3 viewPos;
-uniform DirectionalLight dirLight;
-uniform PointLight pointLights[NR_POINT_LIGHTS];
-uniform SpotLight spotLight;
 uniform DefaultMaterial material;
-
-vec3 CalcDirLight(DirectionalLight light, vec3 normal, vec3 viewDir);
-vec3 CalcPointLight(PointLight light, vec3 normal, vec3 fragPos, vec3 viewDir);
-vec3 CalcSpotLight(SpotLight light, vec3 normal, vec3 fragPos, vec3 viewDir);
     
 void main() {
     vec3 norm = normalize(Normal);
-    vec3 viewDir = normalize(viewPos - FragPos);
 
-    vec3 result = CalcDirLight(dirLight, norm, viewDir);
+    // Vertices without tangents keep their normals
+    if (dot(Tangent, Tangent) > 1e-6) {
+        vec3 T = normalize(Tangent - dot(Tangent, norm) * norm);
+        vec3 B = cross(norm, T);
+        mat3 TBN = mat3(T, B, norm);
+        norm = normalize(TBN * (texture(material.normal_map, TexCoord).rgb * 2.0 - 1.0));
+    }
 
-    for(int i = 0; i < NR_POINT_LIGHTS; i++)
-        result += CalcPointLight(pointLights[i], norm, FragPos, viewDir);    
+    vec3 viewDir = normalize(viewPos - FragPos);
 
-    result += CalcSpotLight(spotLight, norm, FragPos, viewDir);    
-    
-    FragColor = vec4(result, 1.0);
-}
+    vec4 tint = material.vertex_color ? VertexColor : vec4(1.0);
+    vec3 diffuseColor = vec3(texture(material.diffuse_map, TexCoord)) * tint.rgb;
 
-// calculates the color when using a directional light.
-vec3 CalcDirLight(DirectionalLight light, vec3 normal, vec3 viewDir)
-{
-    vec3 lightDir = normalize(-light.direction);
-    // diffuse shading
-    float diff = max(dot(normal, lightDir), 0.0);
-    // specular shading
-    vec3 reflectDir = reflect(-lightDir, normal);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
-    // combine results
-    vec3 ambient = light.ambient * material.color * vec3(texture(material.diffuse_map, TexCoord));
-    vec3 diffuse = light.diffuse * diff * vec3(texture(material.diffuse_map, TexCoord));
-    vec3 specular = light.specular * spec * vec3(texture(material.specular_map, TexCoord));
-    return (ambient + diffuse + specular);
-}
+    Surface surface;
+    surface.ambient = material.color.rgb * diffuseColor;
+    surface.diffuse = diffuseColor;
+    surface.specular = vec3(texture(material.specular_map, TexCoord));
+    surface.shininess = material.shininess;
 
-// calculates the color when using a point light.
-vec3 CalcPointLight(PointLight light, vec3 normal, vec3 fragPos, vec3 viewDir)
-{
-    vec3 lightDir = normalize(light.position - fragPos);
-    // diffuse shading
-    float diff = max(dot(normal, lightDir), 0.0);
-    // specular shading
-    vec3 reflectDir = reflect(-lightDir, normal);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
-    // attenuation
-    float distance = length(light.position - fragPos);
-    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));    
-    // combine results
-    vec3 ambient = light.ambient * material.color * vec3(texture(material.diffuse_map, TexCoord));
-    vec3 diffuse = light.diffuse * diff * vec3(texture(material.diffuse_map, TexCoord));
-    vec3 specular = light.specular * spec * vec3(texture(material.specular_map, TexCoord));
-    ambient *= attenuation;
-    diffuse *= attenuation;
-    specular *= attenuation;
-    return (ambient + diffuse + specular);
+    vec3 result = CalcLighting(surface, norm, FragPos, viewDir);
+    result += CalcEnvironment(surface, norm, viewDir);
+    result = ApplyFog(result, length(viewPos - FragPos));
+    
+    FragColor = vec4(result, material.color.a * tint.a);
+    // Screen-space motion since the previous frame in texture coordinates
+    Velocity = (CurrentPosition.xy / CurrentPosition.w - PreviousPosition.xy / PreviousPosition.w) * 0.5;
 }
-
-// calculates the color when using a spot light.
-vec3 CalcSpotLight(SpotLight light, vec3 normal, vec3 fragPos, vec3 viewDir)
-{
-    vec3 lightDir = normalize(light.position - fragPos);
-    // diffuse shading
-    float diff = max(dot(normal, lightDir), 0.0);
-    // specular shading
-    vec3 reflectDir = reflect(-lightDir, normal);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
-    // attenuation
-    float distance = length(light.position - fragPos);
-    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * (distance * distance));    
-    // spotlight intensity
-    float theta = dot(lightDir, normalize(-light.direction)); 
-    float epsilon = light.cutOff - light.outerCutOff;
-    float intensity = clamp((theta - light.outerCutOff) / epsilon, 0.0, 1.0);
-    // combine results
-    vec3 ambient = light.ambient * material.color * vec3(texture(material.diffuse_map, TexCoord));
-    vec3 diffuse = light.diffuse * diff * vec3(texture(material.diffuse_map, TexCoord));
-    vec3 specular = light.specular * spec * vec3(texture(material.specular_map, TexCoord));
-    ambient *= attenuation * intensity;
-    diffuse *= attenuation * intensity;
-    specular *= attenuation * intensity;
-    return (ambient + diffuse + specular);
-}
\ No newline at end of file
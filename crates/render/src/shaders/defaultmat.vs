@@ -1,21 +1,28 @@
 #version 330
+#include "transforms.glsl"
+
 in vec3 position;
 in vec3 normal;
 in vec2 texcoord;
+in vec3 tangent;
+in vec4 color;
 
 out vec3 FragPos;
 out vec3 Normal;
 out vec2 TexCoord;
-
-uniform mat4 model;
-uniform mat4 inversed;
-uniform mat4 view;
-uniform mat4 projection;
+out vec3 Tangent;
+out vec4 VertexColor;
+out vec4 CurrentPosition;
+out vec4 PreviousPosition;
 
 void main() {
     FragPos = vec3(model * vec4(position, 1.0));
-    Normal = mat3(transpose(inversed)) * normal;
+    Normal = NormalMatrix() * normal;
+    Tangent = mat3(model) * tangent;
     TexCoord = texcoord;
+    VertexColor = color;
     
-    gl_Position = projection * view * vec4(FragPos, 1.0);
+    CurrentPosition = projection * view * vec4(FragPos, 1.0);
+    PreviousPosition = previous_view_projection * previous_model * vec4(position, 1.0);
+    gl_Position = ApplyJitter(CurrentPosition);
 }
@@ -0,0 +1,49 @@
+#version 330
+#include "environment/cubemap.glsl"
+
+out vec2 FragColor;
+in vec2 TexCoord;
+
+const uint SAMPLE_COUNT = 512u;
+
+float GeometrySchlickGGX(float NdotV, float roughness) {
+    // k is remapped for image-based lighting
+    float k = (roughness * roughness) / 2.0;
+    return NdotV / (NdotV * (1.0 - k) + k);
+}
+
+float GeometrySmith(float NdotV, float NdotL, float roughness) {
+    return GeometrySchlickGGX(NdotV, roughness) * GeometrySchlickGGX(NdotL, roughness);
+}
+
+// Scale and bias of the Fresnel reflectance at normal incidence by (NdotV, roughness)
+void main() {
+    float NdotV = max(TexCoord.x, 1e-4);
+    float roughness = TexCoord.y;
+
+    vec3 V = vec3(sqrt(1.0 - NdotV * NdotV), 0.0, NdotV);
+    vec3 N = vec3(0.0, 0.0, 1.0);
+
+    float scale = 0.0;
+    float bias = 0.0;
+
+    for (uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec3 H = ImportanceSampleGGX(Hammersley(i, SAMPLE_COUNT), N, roughness);
+        vec3 L = normalize(2.0 * dot(V, H) * H - V);
+
+        float NdotL = max(L.z, 0.0);
+        float NdotH = max(H.z, 0.0);
+        float VdotH = max(dot(V, H), 0.0);
+
+        if (NdotL > 0.0) {
+            float G = GeometrySmith(NdotV, NdotL, roughness);
+            float visibility = (G * VdotH) / (NdotH * NdotV);
+            float fresnel = pow(1.0 - VdotH, 5.0);
+
+            scale += (1.0 - fresnel) * visibility;
+            bias += fresnel * visibility;
+        }
+    }
+
+    FragColor = vec2(scale, bias) / float(SAMPLE_COUNT);
+}
@@ -0,0 +1,31 @@
+#version 330
+#include "environment/cubemap.glsl"
+
+out vec4 FragColor;
+in vec2 TexCoord;
+
+uniform samplerCube environmentMap;
+
+// Cosine-weighted convolution of the environment over the hemisphere
+void main() {
+    vec3 N = FaceDirection(TexCoord);
+    vec3 up = abs(N.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 right = normalize(cross(up, N));
+    up = cross(N, right);
+
+    const float sampleDelta = 0.05;
+    vec3 irradiance = vec3(0.0);
+    float samples = 0.0;
+
+    for (float phi = 0.0; phi < 2.0 * PI; phi += sampleDelta) {
+        for (float theta = 0.0; theta < 0.5 * PI; theta += sampleDelta) {
+            vec3 tangentSample = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 sampleVec = tangentSample.x * right + tangentSample.y * up + tangentSample.z * N;
+
+            irradiance += texture(environmentMap, sampleVec).rgb * cos(theta) * sin(theta);
+            samples += 1.0;
+        }
+    }
+
+    FragColor = vec4(PI * irradiance / samples, 1.0);
+}
@@ -0,0 +1,32 @@
+#version 330
+#include "environment/cubemap.glsl"
+
+out vec4 FragColor;
+in vec2 TexCoord;
+
+uniform samplerCube environmentMap;
+uniform float roughness;
+
+const uint SAMPLE_COUNT = 256u;
+
+// Environment, blurred by GGX lobe of the roughness. The view direction is assumed to be the normal
+void main() {
+    vec3 N = FaceDirection(TexCoord);
+    vec3 V = N;
+
+    vec3 color = vec3(0.0);
+    float totalWeight = 0.0;
+
+    for (uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec3 H = ImportanceSampleGGX(Hammersley(i, SAMPLE_COUNT), N, roughness);
+        vec3 L = normalize(2.0 * dot(V, H) * H - V);
+
+        float NdotL = max(dot(N, L), 0.0);
+        if (NdotL > 0.0) {
+            color += texture(environmentMap, L).rgb * NdotL;
+            totalWeight += NdotL;
+        }
+    }
+
+    FragColor = vec4(color / max(totalWeight, 1e-4), 1.0);
+}
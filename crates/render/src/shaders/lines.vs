@@ -0,0 +1,15 @@
+#version 330
+in vec3 position;
+in vec4 color;
+
+out vec4 Color;
+
+uniform mat4 view;
+uniform mat4 projection;
+uniform vec3 renderOrigin;
+
+void main() {
+    Color = color;
+    // Lines are in world space, so they're moved to the render origin like models
+    gl_Position = projection * view * vec4(position - renderOrigin, 1.0);
+}
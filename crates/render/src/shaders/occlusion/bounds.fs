@@ -0,0 +1,4 @@
+#version 330
+
+// Only depth test matters, color isn't written
+void main() {}
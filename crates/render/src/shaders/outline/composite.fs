@@ -0,0 +1,40 @@
+#version 330
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D mask_texture;
+uniform float max_width;
+uniform int radius;
+
+void main() {
+    ivec2 size = textureSize(mask_texture, 0);
+    ivec2 center = ivec2(TexCoord * vec2(size));
+
+    // Outline is drawn only around silhouettes
+    if (texelFetch(mask_texture, center, 0).a > 0.0) {
+        discard;
+    }
+
+    float closest = max_width + 1.0;
+    vec3 color = vec3(0.0);
+
+    for (int x = -radius; x <= radius; x++) {
+        for (int y = -radius; y <= radius; y++) {
+            ivec2 coord = clamp(center + ivec2(x, y), ivec2(0), size - 1);
+            vec4 mask = texelFetch(mask_texture, coord, 0);
+            float distance = length(vec2(x, y));
+
+            if (mask.a > 0.0 && distance <= mask.a * max_width && distance < closest) {
+                closest = distance;
+                color = mask.rgb;
+            }
+        }
+    }
+
+    if (closest > max_width) {
+        discard;
+    }
+
+    FragColor = vec4(color, 1.0);
+}
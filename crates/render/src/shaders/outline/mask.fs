@@ -0,0 +1,9 @@
+#version 330
+out vec4 FragColor;
+
+// Outline color and width, divided by the maximum one
+uniform vec4 outline;
+
+void main() {
+    FragColor = outline;
+}
@@ -0,0 +1,34 @@
+#version 330
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D color_texture;
+uniform float frame;
+uniform float vignette_intensity;
+uniform float vignette_radius;
+uniform float vignette_smoothness;
+uniform float grain_intensity;
+uniform float chromatic_aberration;
+
+float random(vec2 co) {
+    return fract(sin(dot(co, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main() {
+    vec2 offset = TexCoord - 0.5;
+
+    // Channels are shifted radially, more at the edges of the screen
+    vec2 shift = offset * chromatic_aberration * length(offset);
+    vec4 color = texture(color_texture, TexCoord);
+    color.r = texture(color_texture, TexCoord + shift).r;
+    color.b = texture(color_texture, TexCoord - shift).b;
+
+    float vignette = smoothstep(vignette_radius, vignette_radius - vignette_smoothness, length(offset));
+    color.rgb *= mix(1.0, vignette, vignette_intensity);
+
+    float grain = random(TexCoord + fract(frame * 0.0137)) - 0.5;
+    color.rgb += grain * grain_intensity;
+
+    FragColor = color;
+}
@@ -0,0 +1,10 @@
+#version 330
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D color_texture;
+
+void main() {
+    FragColor = texture(color_texture, TexCoord);
+}
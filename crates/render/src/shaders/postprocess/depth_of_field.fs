@@ -0,0 +1,59 @@
+#version 330
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D color_texture;
+uniform sampler2D depth_texture;
+uniform vec2 texel_size;
+uniform float near;
+uniform float far;
+uniform float focus_distance;
+uniform float aperture;
+uniform bool autofocus;
+uniform vec2 focus_point;
+
+const float GOLDEN_ANGLE = 2.39996323;
+const float MAX_BLUR_SIZE = 16.0;
+const float RADIUS_SCALE = 0.5;
+
+float linear_depth(vec2 uv) {
+    float z = texture(depth_texture, uv).r * 2.0 - 1.0;
+    return 2.0 * near * far / (far + near - z * (far - near));
+}
+
+// Radius of the circle of confusion in pixels
+float blur_size(float depth, float focus) {
+    float coc = clamp((1.0 / focus - 1.0 / depth) * aperture, -1.0, 1.0);
+    return abs(coc) * MAX_BLUR_SIZE;
+}
+
+void main() {
+    float focus = autofocus ? linear_depth(focus_point) : focus_distance;
+    float center_depth = linear_depth(TexCoord);
+    float center_size = blur_size(center_depth, focus);
+
+    vec3 color = texture(color_texture, TexCoord).rgb;
+    float total = 1.0;
+    float radius = RADIUS_SCALE;
+
+    // Gather samples along the spiral. Farther samples can't bleed into closer pixels
+    // more than their own blur, so sharp foreground stays over blurred background
+    for (float angle = 0.0; radius < MAX_BLUR_SIZE; angle += GOLDEN_ANGLE) {
+        vec2 uv = TexCoord + vec2(cos(angle), sin(angle)) * texel_size * radius;
+        vec3 sample_color = texture(color_texture, uv).rgb;
+        float sample_depth = linear_depth(uv);
+        float sample_size = blur_size(sample_depth, focus);
+
+        if (sample_depth > center_depth) {
+            sample_size = clamp(sample_size, 0.0, center_size * 2.0);
+        }
+
+        float weight = smoothstep(radius - 0.5, radius + 0.5, sample_size);
+        color += mix(color / total, sample_color, weight);
+        total += 1.0;
+        radius += RADIUS_SCALE / radius;
+    }
+
+    FragColor = vec4(color / total, texture(color_texture, TexCoord).a);
+}
@@ -0,0 +1,49 @@
+#version 330
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D color_texture;
+uniform vec2 texel_size;
+
+const float SPAN_MAX = 8.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+
+float luma(vec3 color) {
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+void main() {
+    vec4 center = texture(color_texture, TexCoord);
+    float luma_nw = luma(texture(color_texture, TexCoord + vec2(-1.0, -1.0) * texel_size).rgb);
+    float luma_ne = luma(texture(color_texture, TexCoord + vec2(1.0, -1.0) * texel_size).rgb);
+    float luma_sw = luma(texture(color_texture, TexCoord + vec2(-1.0, 1.0) * texel_size).rgb);
+    float luma_se = luma(texture(color_texture, TexCoord + vec2(1.0, 1.0) * texel_size).rgb);
+    float luma_m = luma(center.rgb);
+
+    float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    // Blur along the edge, which is perpendicular to the luma gradient
+    vec2 direction = vec2(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se)
+    );
+
+    float direction_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float inverse_direction_min = 1.0 / (min(abs(direction.x), abs(direction.y)) + direction_reduce);
+    direction = clamp(direction * inverse_direction_min, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texel_size;
+
+    vec3 color_a = 0.5 * (
+        texture(color_texture, TexCoord + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(color_texture, TexCoord + direction * (2.0 / 3.0 - 0.5)).rgb
+    );
+    vec3 color_b = color_a * 0.5 + 0.25 * (
+        texture(color_texture, TexCoord - direction * 0.5).rgb +
+        texture(color_texture, TexCoord + direction * 0.5).rgb
+    );
+
+    float luma_b = luma(color_b);
+    FragColor = vec4((luma_b < luma_min || luma_b > luma_max) ? color_a : color_b, center.a);
+}
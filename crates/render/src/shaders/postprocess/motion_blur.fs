@@ -0,0 +1,23 @@
+#version 330
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D color_texture;
+uniform sampler2D velocity_texture;
+uniform float shutter;
+uniform int samples;
+
+void main() {
+    // Motion during the exposure is the frame motion scaled by the shutter
+    vec2 velocity = texture(velocity_texture, TexCoord).rg * shutter;
+    int count = max(samples, 1);
+    vec4 color = vec4(0.0);
+
+    for (int i = 0; i < count; i++) {
+        float offset = (float(i) + 0.5) / float(count) - 0.5;
+        color += texture(color_texture, TexCoord - velocity * offset);
+    }
+
+    FragColor = color / float(count);
+}
@@ -0,0 +1,47 @@
+#version 330
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D color_texture;
+uniform sampler2D velocity_texture;
+uniform sampler2D history_texture;
+uniform vec2 texel_size;
+uniform bool has_history;
+uniform float feedback;
+
+void main() {
+    vec4 current = texture(color_texture, TexCoord);
+
+    if (!has_history) {
+        FragColor = current;
+        return;
+    }
+
+    vec2 velocity = texture(velocity_texture, TexCoord).rg;
+    vec2 previous_uv = TexCoord - velocity;
+
+    // History is clamped to the neighborhood of the current pixel to avoid ghosting
+    vec3 neighborhood_min = current.rgb;
+    vec3 neighborhood_max = current.rgb;
+
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec3 color = texture(color_texture, TexCoord + vec2(x, y) * texel_size).rgb;
+            neighborhood_min = min(neighborhood_min, color);
+            neighborhood_max = max(neighborhood_max, color);
+        }
+    }
+
+    vec3 history = clamp(texture(history_texture, previous_uv).rgb, neighborhood_min, neighborhood_max);
+
+    // Fast moving and disoccluded pixels are taken from the current frame
+    float speed = length(velocity / texel_size);
+    float weight = feedback * clamp(1.0 - speed * 0.05, 0.0, 1.0);
+
+    if (any(lessThan(previous_uv, vec2(0.0))) || any(greaterThan(previous_uv, vec2(1.0)))) {
+        weight = 0.0;
+    }
+
+    FragColor = vec4(mix(current.rgb, history, weight), current.a);
+}
@@ -0,0 +1,4 @@
+#version 330
+
+// Only depth is written into shadow maps
+void main() {}
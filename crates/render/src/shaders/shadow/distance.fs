@@ -0,0 +1,11 @@
+#version 330
+
+in vec3 FragPos;
+
+uniform vec3 lightPos;
+uniform float farPlane;
+
+// Point light shadows store the distance to the light instead of the depth
+void main() {
+    gl_FragDepth = length(FragPos - lightPos) / farPlane;
+}
@@ -0,0 +1,17 @@
+#version 330
+layout(location = 0) out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D glyph_atlas;
+uniform vec4 color;
+
+void main() {
+    float coverage = texture(glyph_atlas, TexCoord).a;
+
+    if (coverage < 0.01) {
+        discard;
+    }
+
+    FragColor = vec4(color.rgb, color.a * coverage);
+}
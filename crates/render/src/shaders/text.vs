@@ -0,0 +1,12 @@
+#version 330
+in vec3 position;
+in vec2 texcoord;
+
+out vec2 TexCoord;
+
+uniform mat4 transform;
+
+void main() {
+    TexCoord = texcoord;
+    gl_Position = transform * vec4(position, 1.0);
+}
@@ -0,0 +1,25 @@
+#version 330
+layout(location = 0) out vec4 FragColor;
+layout(location = 1) out vec2 Velocity;
+
+in vec2 TexCoord;
+in vec4 CurrentPosition;
+in vec4 PreviousPosition;
+
+struct TilemapMaterial {
+    vec4 color;
+    sampler2D atlas;
+};
+
+uniform TilemapMaterial material;
+
+void main() {
+    vec4 color = texture(material.atlas, TexCoord) * material.color;
+
+    if (color.a < 0.01) {
+        discard;
+    }
+
+    FragColor = color;
+    Velocity = (CurrentPosition.xy / CurrentPosition.w - PreviousPosition.xy / PreviousPosition.w) * 0.5;
+}
@@ -0,0 +1,23 @@
+#version 330
+in vec3 position;
+in vec2 texcoord;
+
+out vec2 TexCoord;
+out vec4 CurrentPosition;
+out vec4 PreviousPosition;
+
+uniform mat4 model;
+uniform mat4 view;
+uniform mat4 projection;
+uniform mat4 previous_model;
+uniform mat4 previous_view_projection;
+uniform vec2 jitter;
+
+void main() {
+    TexCoord = texcoord;
+
+    CurrentPosition = projection * view * model * vec4(position, 1.0);
+    PreviousPosition = previous_view_projection * previous_model * vec4(position, 1.0);
+    // Jitter is applied after the velocity is calculated, so it's not treated as motion
+    gl_Position = CurrentPosition + vec4(jitter * CurrentPosition.w, 0.0, 0.0);
+}
@@ -0,0 +1,23 @@
+#version 330
+#include "fog.glsl"
+
+layout(location = 0) out vec4 FragColor;
+layout(location = 1) out vec2 Velocity;
+
+// Position relative to the render origin
+in vec3 FragPos;
+in vec4 VertexColor;
+in vec4 CurrentPosition;
+in vec4 PreviousPosition;
+
+// Camera position relative to the render origin
+uniform vec3 viewPos;
+uniform vec4 color;
+
+void main() {
+    vec4 result = color * VertexColor;
+
+    FragColor = vec4(ApplyFog(result.rgb, length(viewPos - FragPos)), result.a);
+    // Screen-space motion since the previous frame in texture coordinates
+    Velocity = (CurrentPosition.xy / CurrentPosition.w - PreviousPosition.xy / PreviousPosition.w) * 0.5;
+}
@@ -0,0 +1,414 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use ab_glyph::{Font as _, FontArc, PxScale, ScaleFont};
+use flatbox_core::{
+    color::Color,
+    math::{glm, transform::Transform},
+};
+
+use crate::{
+    error::RenderError,
+    macros::vertex_attribute,
+    hal::{
+        buffer::{AttributeType, Buffer, BufferTarget, BufferUsage, VertexArray, VertexAttribute, VertexLayout},
+        shader::{GraphicsPipeline, Shader, ShaderType},
+    },
+    pbr::{
+        camera::Camera,
+        texture::{Filter, Order, Texture, TextureDescriptor, WrapMode},
+    },
+    renderer::{Capability, DisableCommand, DrawTrianglesCommand, EnableCommand, Renderer},
+};
+
+/// Width of the glyph atlas in pixels. Its height depends on the number of baked glyphs
+const ATLAS_WIDTH: u32 = 512;
+/// Space between glyphs in the atlas, so they don't bleed into each other
+const ATLAS_PADDING: u32 = 1;
+
+/// Placement of a baked glyph in the atlas. Sizes are in pixels of the baked font size
+#[derive(Debug, Clone, Copy)]
+struct GlyphInfo {
+    uv_min: glm::Vec2,
+    uv_max: glm::Vec2,
+    /// Offset of the top-left corner from the pen position on the baseline; Y points down
+    offset: glm::Vec2,
+    size: glm::Vec2,
+}
+
+/// TrueType/OpenType font with glyphs, baked into a texture atlas at the given pixel size.
+/// Text of other sizes is scaled from the baked glyphs, so the size should be close to
+/// the largest one, which is displayed
+#[derive(Debug)]
+pub struct Font {
+    font: FontArc,
+    px_size: f32,
+    atlas: Texture,
+    glyphs: HashMap<char, GlyphInfo>,
+}
+
+impl Font {
+    /// Load font file and bake ASCII, Latin-1 and Cyrillic characters
+    pub fn new<P: AsRef<Path>>(path: P, px_size: f32) -> Result<Font, RenderError> {
+        Font::new_from_bytes(std::fs::read(path)?, px_size)
+    }
+
+    pub fn new_from_bytes(data: Vec<u8>, px_size: f32) -> Result<Font, RenderError> {
+        let chars = (' '..='~').chain('\u{a0}'..='\u{ff}').chain('\u{400}'..='\u{4ff}');
+        Font::with_chars(data, px_size, chars)
+    }
+
+    /// Load font from memory and bake only the given characters. Characters, which
+    /// aren't baked or aren't present in the font, are displayed as `?`
+    pub fn with_chars(
+        data: Vec<u8>,
+        px_size: f32,
+        chars: impl IntoIterator<Item = char>,
+    ) -> Result<Font, RenderError> {
+        let font = FontArc::try_from_vec(data)?;
+        let scale = PxScale::from(px_size);
+
+        let mut outlines = vec![];
+        let mut empty = vec![];
+
+        for c in chars {
+            let id = font.glyph_id(c);
+
+            if id.0 == 0 && c != '?' {
+                continue;
+            }
+
+            match font.outline_glyph(id.with_scale(scale)) {
+                Some(outlined) => outlines.push((c, outlined)),
+                // Whitespace has no outline, but still advances the pen
+                None => empty.push(c),
+            }
+        }
+
+        // Glyphs are packed into rows, left to right
+        let mut placements = vec![];
+        let (mut x, mut y, mut row_height) = (ATLAS_PADDING, ATLAS_PADDING, 0);
+
+        for (c, outlined) in &outlines {
+            let bounds = outlined.px_bounds();
+            let (width, height) = (bounds.width().ceil() as u32, bounds.height().ceil() as u32);
+
+            if x + width + ATLAS_PADDING > ATLAS_WIDTH {
+                x = ATLAS_PADDING;
+                y += row_height + ATLAS_PADDING;
+                row_height = 0;
+            }
+
+            placements.push((*c, x, y, width, height));
+            x += width + ATLAS_PADDING;
+            row_height = row_height.max(height);
+        }
+
+        let atlas_height = (y + row_height + ATLAS_PADDING).next_power_of_two();
+        let mut pixels = vec![0u8; (ATLAS_WIDTH * atlas_height * 4) as usize];
+        let mut glyphs = HashMap::new();
+
+        for ((_, outlined), &(c, x, y, width, height)) in outlines.iter().zip(&placements) {
+            outlined.draw(|glyph_x, glyph_y, coverage| {
+                if glyph_x >= width || glyph_y >= height {
+                    return;
+                }
+
+                let index = (((y + glyph_y) * ATLAS_WIDTH + x + glyph_x) * 4) as usize;
+                pixels[index..index + 3].fill(255);
+                pixels[index + 3] = (coverage.clamp(0.0, 1.0) * 255.0) as u8;
+            });
+
+            let bounds = outlined.px_bounds();
+            let atlas_size = glm::vec2(ATLAS_WIDTH as f32, atlas_height as f32);
+
+            glyphs.insert(c, GlyphInfo {
+                uv_min: glm::vec2(x as f32, y as f32).component_div(&atlas_size),
+                uv_max: glm::vec2((x + width) as f32, (y + height) as f32).component_div(&atlas_size),
+                offset: glm::vec2(bounds.min.x, bounds.min.y),
+                size: glm::vec2(width as f32, height as f32),
+            });
+        }
+
+        for c in empty {
+            glyphs.insert(c, GlyphInfo {
+                uv_min: glm::Vec2::zeros(),
+                uv_max: glm::Vec2::zeros(),
+                offset: glm::Vec2::zeros(),
+                size: glm::Vec2::zeros(),
+            });
+        }
+
+        let atlas = Texture::new_from_raw(&pixels, ATLAS_WIDTH, atlas_height, Some(TextureDescriptor {
+            filter: Filter::Linear,
+            wrap_mode: WrapMode::ClampToEdge,
+            ..TextureDescriptor::data()
+        }))?;
+
+        Ok(Font { font, px_size, atlas, glyphs })
+    }
+
+    /// Size of the baked glyphs in pixels
+    pub fn px_size(&self) -> f32 {
+        self.px_size
+    }
+
+    /// Texture with the baked glyphs. Their color is white, coverage is stored in alpha
+    pub fn atlas(&self) -> &Texture {
+        &self.atlas
+    }
+
+    /// Distance between baselines of two lines of text with the given size
+    pub fn line_height(&self, size: f32) -> f32 {
+        let font = self.font.as_scaled(PxScale::from(size));
+        font.height() + font.line_gap()
+    }
+
+    /// Width and height of the text with the given size
+    pub fn measure(&self, text: &str, size: f32) -> glm::Vec2 {
+        let (vertices, _) = self.layout(text, size);
+
+        let width = vertices.iter().map(|vertex| vertex.position.x).fold(0.0, f32::max);
+        let lines = text.lines().count().max(1);
+
+        glm::vec2(width, lines as f32 * self.line_height(size))
+    }
+
+    /// Build quads of the text with the origin in the top-left corner; Y points up
+    fn layout(&self, text: &str, size: f32) -> (Vec<GlyphVertex>, Vec<u32>) {
+        let font = self.font.as_scaled(PxScale::from(size));
+        let factor = size / self.px_size;
+        let line_height = self.line_height(size);
+
+        let mut vertices = vec![];
+        let mut indices = vec![];
+
+        for (line_index, line) in text.lines().enumerate() {
+            let baseline = -font.ascent() - line_index as f32 * line_height;
+            let mut pen = 0.0;
+            let mut previous = None;
+
+            for c in line.chars() {
+                let c = if self.glyphs.contains_key(&c) { c } else { '?' };
+                let Some(glyph) = self.glyphs.get(&c) else { continue };
+
+                let id = font.glyph_id(c);
+                if let Some(previous) = previous {
+                    pen += font.kern(previous, id);
+                }
+                previous = Some(id);
+
+                if glyph.size.x > 0.0 && glyph.size.y > 0.0 {
+                    let left = pen + glyph.offset.x * factor;
+                    let top = baseline - glyph.offset.y * factor;
+                    let right = left + glyph.size.x * factor;
+                    let bottom = top - glyph.size.y * factor;
+                    let first = vertices.len() as u32;
+
+                    vertices.extend([
+                        (glm::vec2(left, top), glm::vec2(glyph.uv_min.x, glyph.uv_min.y)),
+                        (glm::vec2(left, bottom), glm::vec2(glyph.uv_min.x, glyph.uv_max.y)),
+                        (glm::vec2(right, bottom), glm::vec2(glyph.uv_max.x, glyph.uv_max.y)),
+                        (glm::vec2(right, top), glm::vec2(glyph.uv_max.x, glyph.uv_min.y)),
+                    ].map(|(position, texcoord)| GlyphVertex {
+                        position: glm::vec3(position.x, position.y, 0.0),
+                        texcoord,
+                    }));
+
+                    indices.extend([0, 1, 3, 3, 1, 2].map(|index| first + index));
+                }
+
+                pen += font.h_advance(id);
+            }
+        }
+
+        (vertices, indices)
+    }
+}
+
+/// Text, placed in the world. Its top-left corner is at the origin of the entity's
+/// `Transform`; the text lies in the XY plane
+#[derive(Debug, Clone)]
+pub struct Text3d {
+    pub text: String,
+    pub font: Arc<Font>,
+    /// Height of a line in world units
+    pub size: f32,
+    pub color: Color,
+}
+
+impl Text3d {
+    pub fn new(text: impl Into<String>, font: Arc<Font>) -> Self {
+        Text3d {
+            text: text.into(),
+            font,
+            size: 1.0,
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// Text, drawn over the screen. Its top-left corner is placed at X and Y of the entity's
+/// `Transform` translation in pixels, counting from the top-left corner of the window
+#[derive(Debug, Clone)]
+pub struct Text2d {
+    pub text: String,
+    pub font: Arc<Font>,
+    /// Height of a line in pixels
+    pub size: f32,
+    pub color: Color,
+}
+
+impl Text2d {
+    pub fn new(text: impl Into<String>, font: Arc<Font>) -> Self {
+        let size = font.px_size();
+
+        Text2d {
+            text: text.into(),
+            font,
+            size,
+            color: Color::WHITE,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct GlyphVertex {
+    position: glm::Vec3,
+    texcoord: glm::Vec2,
+}
+
+impl VertexLayout for GlyphVertex {
+    fn attributes() -> Vec<VertexAttribute> {
+        vec![
+            vertex_attribute!("position", GlyphVertex::position, AttributeType::Float),
+            vertex_attribute!("texcoord", GlyphVertex::texcoord, AttributeType::Float),
+        ]
+    }
+}
+
+/// Singleton component, which draws [`Text3d`] and [`Text2d`] over the bound framebuffer.
+/// Only color is drawn, auxiliary outputs (e.g. velocity) are kept
+#[derive(Default)]
+pub struct TextRenderer {
+    pipeline: Option<GraphicsPipeline>,
+    vertex_array: Option<VertexArray>,
+    vertex_buffer: Option<Buffer>,
+    index_buffer: Option<Buffer>,
+}
+
+impl TextRenderer {
+    pub fn new() -> Self {
+        TextRenderer::default()
+    }
+
+    /// Draw world texts, viewed from the camera (if any), then screen texts on top of them
+    pub fn render<'a>(
+        &mut self,
+        renderer: &mut Renderer,
+        camera: Option<(&Camera, &Transform)>,
+        texts_3d: impl IntoIterator<Item = (&'a Text3d, &'a Transform)>,
+        texts_2d: impl IntoIterator<Item = (&'a Text2d, &'a Transform)>,
+    ) -> Result<(), RenderError> {
+        if self.pipeline.is_none() {
+            let pipeline = GraphicsPipeline::new(&[
+                Shader::new_from_source(include_str!("shaders/text.vs"), ShaderType::VertexShader)?,
+                Shader::new_from_source(include_str!("shaders/text.fs"), ShaderType::FragmentShader)?,
+            ])?;
+
+            let vertex_array = VertexArray::new();
+            let vertex_buffer = Buffer::new(BufferTarget::ArrayBuffer, BufferUsage::DynamicDraw);
+            let index_buffer = Buffer::new(BufferTarget::ElementArrayBuffer, BufferUsage::DynamicDraw);
+
+            vertex_array.bind();
+            vertex_buffer.bind();
+            index_buffer.bind();
+
+            vertex_array.set_layout::<GlyphVertex>(&pipeline);
+
+            self.pipeline = Some(pipeline);
+            self.vertex_array = Some(vertex_array);
+            self.vertex_buffer = Some(vertex_buffer);
+            self.index_buffer = Some(index_buffer);
+        }
+
+        unsafe {
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+
+            for i in 1..renderer.draw_buffers() {
+                gl::ColorMaski(i as u32, gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            }
+        }
+
+        renderer.execute(&mut EnableCommand(Capability::Blend))?;
+
+        let pipeline = self.pipeline.as_ref().unwrap();
+        pipeline.apply();
+        pipeline.set_int("glyph_atlas", 0);
+
+        if let Some((camera, camera_transform)) = camera {
+            let origin = renderer.render_origin();
+            let view_projection = camera.projection_matrix() * camera.relative_view_matrix(camera_transform, &origin);
+
+            renderer.execute(&mut EnableCommand(Capability::DepthTest))?;
+
+            for (text, transform) in texts_3d {
+                let (model, _) = transform.to_relative_matrices(&origin);
+                let scale = text.size / text.font.line_height(text.font.px_size());
+
+                self.draw(renderer, &text.font, &text.text, text.font.px_size(), &text.color, &(view_projection * model * glm::scaling(&glm::vec3(scale, scale, scale))))?;
+            }
+        }
+
+        let extent = renderer.extent();
+        let projection = glm::ortho(0.0, extent.width, 0.0, extent.height, -1.0, 1.0);
+
+        renderer.execute(&mut DisableCommand(Capability::DepthTest))?;
+
+        for (text, transform) in texts_2d {
+            let position = transform.translation_f32();
+            let model = glm::translation(&glm::vec3(position.x, extent.height - position.y, 0.0));
+
+            self.draw(renderer, &text.font, &text.text, text.size, &text.color, &(projection * model))?;
+        }
+
+        renderer.execute(&mut EnableCommand(Capability::DepthTest))?;
+
+        unsafe {
+            for i in 1..renderer.draw_buffers() {
+                gl::ColorMaski(i as u32, gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        font: &Font,
+        text: &str,
+        size: f32,
+        color: &Color,
+        transform: &glm::Mat4,
+    ) -> Result<(), RenderError> {
+        let (vertices, indices) = font.layout(text, size);
+
+        if indices.is_empty() {
+            return Ok(());
+        }
+
+        let pipeline = self.pipeline.as_ref().unwrap();
+        pipeline.set_mat4("transform", transform);
+        pipeline.set_color("color", color);
+        font.atlas.activate(Order::Texture0);
+
+        self.vertex_array.as_ref().unwrap().bind();
+        self.vertex_buffer.as_ref().unwrap().fill(&vertices);
+        self.index_buffer.as_ref().unwrap().fill(&indices);
+
+        unsafe { renderer.execute(&mut DrawTrianglesCommand::new(indices.len()))?; }
+
+        Ok(())
+    }
+}
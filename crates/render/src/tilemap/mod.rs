@@ -0,0 +1,473 @@
+pub mod tiled;
+
+use serde::{Serialize, Deserialize};
+use flatbox_assets::typetag;
+use flatbox_core::{
+    color::Color,
+    math::{glm, transform::Transform},
+};
+
+use crate::{
+    error::RenderError,
+    hal::{
+        buffer::{Buffer, BufferTarget, BufferUsage, VertexArray},
+        shader::GraphicsPipeline,
+    },
+    pbr::{
+        material::Material,
+        mesh::Vertex,
+        texture::{Texture, TextureDescriptor, Filter, Order},
+    },
+    renderer::{DrawTrianglesCommand, RenderCommand, Renderer},
+};
+
+/// Width and height of a tilemap chunk in tiles. Each chunk is drawn with a single draw call
+pub const CHUNK_SIZE: u32 = 16;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Tile {
+    /// Index of the tile in the atlas, counting from the top-left corner row by row
+    pub index: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Whether the tile generates collision shapes (see [`Tilemap::colliders`])
+    pub solid: bool,
+}
+
+impl Tile {
+    pub fn new(index: u32) -> Self {
+        Tile { index, ..Default::default() }
+    }
+
+    pub fn solid(index: u32) -> Self {
+        Tile { index, solid: true, ..Default::default() }
+    }
+}
+
+/// Grid of tiles in the atlas texture. Tile size, margin and spacing are in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TileAtlas {
+    pub columns: u32,
+    pub rows: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Space around the tiles
+    pub margin: u32,
+    /// Space between the tiles
+    pub spacing: u32,
+}
+
+impl TileAtlas {
+    /// Atlas of tightly packed tiles
+    pub fn new(columns: u32, rows: u32) -> Self {
+        TileAtlas {
+            columns: columns.max(1),
+            rows: rows.max(1),
+            tile_width: 1,
+            tile_height: 1,
+            margin: 0,
+            spacing: 0,
+        }
+    }
+
+    pub fn tile_count(&self) -> u32 {
+        self.columns * self.rows
+    }
+
+    /// Texture coordinates of the top-left and bottom-right corners of the tile
+    pub fn uv_rect(&self, index: u32) -> (glm::Vec2, glm::Vec2) {
+        let size = |count: u32, tile: u32| 2 * self.margin + count * tile + count.saturating_sub(1) * self.spacing;
+        let (width, height) = (size(self.columns, self.tile_width) as f32, size(self.rows, self.tile_height) as f32);
+
+        let column = index % self.columns;
+        let row = index / self.columns;
+        let x = (self.margin + column * (self.tile_width + self.spacing)) as f32;
+        let y = (self.margin + row * (self.tile_height + self.spacing)) as f32;
+
+        (
+            glm::vec2(x / width, y / height),
+            glm::vec2((x + self.tile_width as f32) / width, (y + self.tile_height as f32) / height),
+        )
+    }
+}
+
+impl Default for TileAtlas {
+    fn default() -> Self {
+        TileAtlas::new(1, 1)
+    }
+}
+
+/// Rectangle of tiles; `x` and `y` point to the bottom-left tile
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Box collision shape in the local space of the tilemap
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TileCollider {
+    pub center: glm::Vec2,
+    pub half_extents: glm::Vec2,
+}
+
+/// 2D grid of tiles, which reference a texture atlas of [`TilemapMaterial`]. Tile `(0, 0)`
+/// is the bottom-left one and is placed at the origin of the tilemap; tiles lie in the XY plane.
+/// The grid is split into chunks of [`CHUNK_SIZE`] tiles, which are meshed lazily and rebuilt
+/// only when their tiles change. It's rendered with [`DrawTilemapCommand`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tilemap {
+    width: u32,
+    height: u32,
+    /// Size of a tile in world units
+    pub tile_size: glm::Vec2,
+    pub atlas: TileAtlas,
+    tiles: Vec<Option<Tile>>,
+
+    #[serde(skip)]
+    chunks: Vec<Option<ChunkMesh>>,
+    #[serde(skip)]
+    dirty_chunks: Vec<bool>,
+}
+
+impl Tilemap {
+    pub fn new(width: u32, height: u32, tile_size: glm::Vec2, atlas: TileAtlas) -> Self {
+        Tilemap {
+            width,
+            height,
+            tile_size,
+            atlas,
+            tiles: vec![None; (width * height) as usize],
+            chunks: vec![],
+            dirty_chunks: vec![],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<Tile> {
+        self.index(x, y).and_then(|index| self.tiles[index])
+    }
+
+    /// Set or clear the tile. Tiles outside of the tilemap are ignored
+    pub fn set(&mut self, x: u32, y: u32, tile: Option<Tile>) {
+        let Some(index) = self.index(x, y) else { return };
+
+        if self.tiles[index] != tile {
+            self.tiles[index] = tile;
+
+            let chunk = self.chunk_index(x / CHUNK_SIZE, y / CHUNK_SIZE);
+            if let Some(dirty) = self.dirty_chunks.get_mut(chunk) {
+                *dirty = true;
+            }
+        }
+    }
+
+    pub fn fill(&mut self, tile: Option<Tile>) {
+        self.tiles.fill(tile);
+        self.dirty_chunks.fill(true);
+    }
+
+    /// Iterate over non-empty tiles with their coordinates
+    pub fn tiles(&self) -> impl Iterator<Item = (u32, u32, Tile)> + '_ {
+        self.tiles.iter().enumerate().filter_map(|(index, tile)| {
+            tile.map(|tile| (index as u32 % self.width, index as u32 / self.width, tile))
+        })
+    }
+
+    /// Get coordinates of the tile, which contains `position` in the local space of the tilemap
+    pub fn tile_at(&self, position: &glm::Vec2) -> Option<(u32, u32)> {
+        let x = (position.x / self.tile_size.x).floor();
+        let y = (position.y / self.tile_size.y).floor();
+
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+
+        Some((x as u32, y as u32))
+    }
+
+    /// Merge solid tiles into the least possible number of rectangles (greedily,
+    /// first along rows, then along columns)
+    pub fn collision_rects(&self) -> Vec<TileRect> {
+        let mut visited = vec![false; self.tiles.len()];
+        let mut rects = vec![];
+
+        let is_free = |visited: &[bool], x: u32, y: u32| {
+            let index = (y * self.width + x) as usize;
+            !visited[index] && self.tiles[index].is_some_and(|tile| tile.solid)
+        };
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !is_free(&visited, x, y) {
+                    continue;
+                }
+
+                let mut width = 1;
+                while x + width < self.width && is_free(&visited, x + width, y) {
+                    width += 1;
+                }
+
+                let mut height = 1;
+                while y + height < self.height && (x..x + width).all(|column| is_free(&visited, column, y + height)) {
+                    height += 1;
+                }
+
+                for row in y..y + height {
+                    for column in x..x + width {
+                        visited[(row * self.width + column) as usize] = true;
+                    }
+                }
+
+                rects.push(TileRect { x, y, width, height });
+            }
+        }
+
+        rects
+    }
+
+    /// Box colliders of solid tiles for the 2D physics backend
+    pub fn colliders(&self) -> Vec<TileCollider> {
+        self.collision_rects()
+            .into_iter()
+            .map(|rect| {
+                let size = glm::vec2(
+                    rect.width as f32 * self.tile_size.x,
+                    rect.height as f32 * self.tile_size.y,
+                );
+
+                TileCollider {
+                    center: glm::vec2(rect.x as f32 * self.tile_size.x, rect.y as f32 * self.tile_size.y) + size / 2.0,
+                    half_extents: size / 2.0,
+                }
+            })
+            .collect()
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y * self.width + x) as usize)
+    }
+
+    fn chunks_x(&self) -> u32 {
+        self.width.div_ceil(CHUNK_SIZE)
+    }
+
+    fn chunk_count(&self) -> usize {
+        (self.chunks_x() * self.height.div_ceil(CHUNK_SIZE)) as usize
+    }
+
+    fn chunk_index(&self, chunk_x: u32, chunk_y: u32) -> usize {
+        (chunk_y * self.chunks_x() + chunk_x) as usize
+    }
+
+    /// Rebuild meshes of the changed chunks
+    fn update_chunks(&mut self, pipeline: &GraphicsPipeline) {
+        let chunk_count = self.chunk_count();
+
+        if self.chunks.len() != chunk_count {
+            self.chunks = (0..chunk_count).map(|_| None).collect();
+            self.dirty_chunks = vec![true; chunk_count];
+        }
+
+        for chunk in 0..chunk_count {
+            if !std::mem::take(&mut self.dirty_chunks[chunk]) {
+                continue;
+            }
+
+            let (vertices, indices) = self.chunk_geometry(chunk as u32 % self.chunks_x(), chunk as u32 / self.chunks_x());
+
+            if indices.is_empty() {
+                self.chunks[chunk] = None;
+                continue;
+            }
+
+            self.chunks[chunk]
+                .get_or_insert_with(|| ChunkMesh::new(pipeline))
+                .fill(&vertices, &indices);
+        }
+    }
+
+    fn chunk_geometry(&self, chunk_x: u32, chunk_y: u32) -> (Vec<Vertex>, Vec<u32>) {
+        let mut vertices = vec![];
+        let mut indices = vec![];
+
+        let (start_x, start_y) = (chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE);
+
+        for y in start_y..(start_y + CHUNK_SIZE).min(self.height) {
+            for x in start_x..(start_x + CHUNK_SIZE).min(self.width) {
+                let Some(tile) = self.get(x, y) else { continue };
+
+                let (mut uv_min, mut uv_max) = self.atlas.uv_rect(tile.index);
+                if tile.flip_x {
+                    std::mem::swap(&mut uv_min.x, &mut uv_max.x);
+                }
+                if tile.flip_y {
+                    std::mem::swap(&mut uv_min.y, &mut uv_max.y);
+                }
+
+                let min = glm::vec2(x as f32 * self.tile_size.x, y as f32 * self.tile_size.y);
+                let max = min + self.tile_size;
+                let first = vertices.len() as u32;
+
+                // Top of the atlas tile corresponds to the top of the quad
+                vertices.extend([
+                    (glm::vec2(min.x, max.y), glm::vec2(uv_min.x, uv_min.y)),
+                    (glm::vec2(min.x, min.y), glm::vec2(uv_min.x, uv_max.y)),
+                    (glm::vec2(max.x, min.y), glm::vec2(uv_max.x, uv_max.y)),
+                    (glm::vec2(max.x, max.y), glm::vec2(uv_max.x, uv_min.y)),
+                ].map(|(position, texcoord)| Vertex {
+                    position: glm::vec3(position.x, position.y, 0.0),
+                    normal: glm::vec3(0.0, 0.0, 1.0),
+                    texcoord,
+                    tangent: glm::vec3(1.0, 0.0, 0.0),
+                    color: glm::Vec4::repeat(1.0),
+                }));
+
+                indices.extend([0, 1, 3, 3, 1, 2].map(|index| first + index));
+            }
+        }
+
+        (vertices, indices)
+    }
+}
+
+impl Clone for Tilemap {
+    fn clone(&self) -> Self {
+        Tilemap {
+            width: self.width,
+            height: self.height,
+            tile_size: self.tile_size,
+            atlas: self.atlas,
+            tiles: self.tiles.clone(),
+            chunks: vec![],
+            dirty_chunks: vec![],
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ChunkMesh {
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer,
+    index_buffer: Buffer,
+    index_count: usize,
+}
+
+impl ChunkMesh {
+    fn new(pipeline: &GraphicsPipeline) -> Self {
+        let chunk = ChunkMesh {
+            vertex_array: VertexArray::new(),
+            vertex_buffer: Buffer::new(BufferTarget::ArrayBuffer, BufferUsage::DynamicDraw),
+            index_buffer: Buffer::new(BufferTarget::ElementArrayBuffer, BufferUsage::DynamicDraw),
+            index_count: 0,
+        };
+
+        chunk.vertex_array.bind();
+        chunk.vertex_buffer.bind();
+        chunk.index_buffer.bind();
+
+        chunk.vertex_array.set_layout::<Vertex>(pipeline);
+
+        chunk
+    }
+
+    fn fill(&mut self, vertices: &[Vertex], indices: &[u32]) {
+        self.vertex_array.bind();
+        self.vertex_buffer.fill(vertices);
+        self.index_buffer.fill(indices);
+        self.index_count = indices.len();
+    }
+}
+
+/// Material of [`Tilemap`]s, which holds the atlas texture
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TilemapMaterial {
+    pub color: Color,
+    pub atlas: Texture,
+}
+
+impl TilemapMaterial {
+    pub fn new(atlas: Texture) -> Self {
+        TilemapMaterial {
+            color: Color::WHITE,
+            atlas,
+        }
+    }
+
+    /// Load the atlas texture with nearest filtering, so the tiles don't bleed into each other
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, RenderError> {
+        Ok(TilemapMaterial::new(Texture::new(path, Some(TextureDescriptor {
+            filter: Filter::Nearest,
+            ..Default::default()
+        }))?))
+    }
+}
+
+#[typetag::serde]
+impl Material for TilemapMaterial {
+    fn vertex_shader() -> &'static str {
+        include_str!("../shaders/tilemap.vs")
+    }
+
+    fn fragment_shader() -> &'static str {
+        include_str!("../shaders/tilemap.fs")
+    }
+
+    fn setup_pipeline(&self, pipeline: &GraphicsPipeline) {
+        pipeline.set_color("material.color", &self.color);
+
+        pipeline.set_int("material.atlas", 0);
+        self.atlas.activate(Order::Texture0);
+    }
+}
+
+/// Rebuild changed chunks of the tilemap and draw all non-empty ones.
+/// `RenderCameraCommand::<TilemapMaterial>` must be executed first
+#[derive(Debug)]
+pub struct DrawTilemapCommand<'a> {
+    tilemap: &'a mut Tilemap,
+    material: &'a TilemapMaterial,
+    transform: &'a Transform,
+}
+
+impl<'a> DrawTilemapCommand<'a> {
+    pub fn new(
+        tilemap: &'a mut Tilemap,
+        material: &'a TilemapMaterial,
+        transform: &'a Transform,
+    ) -> DrawTilemapCommand<'a> {
+        Self { tilemap, material, transform }
+    }
+}
+
+impl<'a> RenderCommand for DrawTilemapCommand<'a> {
+    fn execute(&mut self, renderer: &mut Renderer) -> Result<(), RenderError> {
+        let pipeline = renderer.get_pipeline::<TilemapMaterial>()?;
+
+        pipeline.apply();
+        self.tilemap.update_chunks(pipeline);
+        self.material.setup_pipeline(pipeline);
+
+        let (model, _) = self.transform.to_relative_matrices(&renderer.render_origin());
+        pipeline.set_mat4("model", &model);
+        // Tilemaps are expected to be static, so only the camera motion is taken into account
+        pipeline.set_mat4("previous_model", &model);
+
+        for chunk in self.tilemap.chunks.iter().flatten() {
+            chunk.vertex_array.bind();
+
+            unsafe { renderer.execute(&mut DrawTrianglesCommand::new(chunk.index_count))?; }
+        }
+
+        Ok(())
+    }
+}
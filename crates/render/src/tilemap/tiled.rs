@@ -0,0 +1,372 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flatbox_core::{logger::warn, math::glm};
+use thiserror::Error;
+use xml::reader::{EventReader, XmlEvent};
+
+use super::{Tile, TileAtlas, Tilemap};
+
+const FLIPPED_HORIZONTALLY: u32 = 0x80000000;
+const FLIPPED_VERTICALLY: u32 = 0x40000000;
+const FLIPPED_DIAGONALLY: u32 = 0x20000000;
+const ROTATED_HEXAGONAL: u32 = 0x10000000;
+const GID_MASK: u32 = !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL);
+
+#[derive(Error, Debug)]
+pub enum TiledError {
+    #[error("Tiled map I/O error")]
+    IoError(#[from] std::io::Error),
+    #[error("Error parsing XML: {0}")]
+    XmlError(#[from] xml::reader::Error),
+    #[error("Error decoding base64 layer data: {0}")]
+    Base64Error(#[from] base64::DecodeError),
+    #[error("Invalid Tiled map: {0}")]
+    InvalidMap(String),
+    #[error("Unsupported Tiled map format: {0}")]
+    UnsupportedFormat(String),
+}
+
+/// Tileset of Tiled map
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledTileset {
+    /// Global id of the first tile in the tileset
+    pub first_gid: u32,
+    pub name: String,
+    /// Path to the tileset image, relative to the working directory
+    pub image: PathBuf,
+    pub atlas: TileAtlas,
+    pub tile_count: u32,
+    /// Local ids of tiles, which have collision shapes or `solid`/`collision` boolean property
+    pub solid_tiles: HashSet<u32>,
+}
+
+impl TiledTileset {
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid < self.first_gid + self.tile_count
+    }
+}
+
+/// Tile layer of Tiled map. Tile ids are stored row by row from the top-left corner
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledLayer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub visible: bool,
+    pub opacity: f32,
+    /// Global tile ids with flip flags; `0` is an empty tile
+    pub data: Vec<u32>,
+}
+
+/// Orthogonal Tiled map, loaded from `.tmx` file. Group layers are flattened
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<TiledTileset>,
+    pub layers: Vec<TiledLayer>,
+}
+
+impl TiledMap {
+    /// Load map from `.tmx` file. External `.tsx` tilesets are loaded relatively to the map.
+    /// JSON maps (`.tmj`) and infinite maps aren't supported
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<TiledMap, TiledError> {
+        let path = path.as_ref();
+
+        if path.extension().is_some_and(|extension| extension == "tmj" || extension == "json") {
+            return Err(TiledError::UnsupportedFormat("JSON maps aren't supported, export the map as TMX".into()));
+        }
+
+        let root = XmlNode::load(path)?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        if root.name != "map" {
+            return Err(TiledError::InvalidMap(format!("expected `map` element, found `{}`", root.name)));
+        }
+
+        if let Some(orientation) = root.attribute("orientation") {
+            if orientation != "orthogonal" {
+                return Err(TiledError::UnsupportedFormat(format!("{orientation} orientation")));
+            }
+        }
+
+        if root.attribute("infinite") == Some("1") {
+            return Err(TiledError::UnsupportedFormat("infinite maps".into()));
+        }
+
+        let tilesets = root
+            .children_named("tileset")
+            .map(|tileset| parse_tileset(tileset, directory))
+            .collect::<Result<_, _>>()?;
+
+        let mut layers = vec![];
+        collect_layers(&root, &mut layers)?;
+
+        Ok(TiledMap {
+            width: root.parse_attribute("width")?,
+            height: root.parse_attribute("height")?,
+            tile_width: root.parse_attribute("tilewidth")?,
+            tile_height: root.parse_attribute("tileheight")?,
+            tilesets,
+            layers,
+        })
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&TiledLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Get the tileset of the layer, i.e. the one, which contains its first non-empty tile
+    pub fn layer_tileset(&self, layer: &TiledLayer) -> Option<&TiledTileset> {
+        let gid = layer.data.iter().map(|gid| gid & GID_MASK).find(|gid| *gid != 0)?;
+        self.tilesets.iter().find(|tileset| tileset.contains(gid))
+    }
+
+    /// Convert the layer into [`Tilemap`]. `pixels_per_unit` is the number of tile
+    /// pixels in one world unit. Tilemaps reference a single atlas, so tiles of
+    /// other tilesets are skipped. Diagonal flips aren't supported either
+    pub fn to_tilemap(&self, layer: &TiledLayer, pixels_per_unit: f32) -> Option<Tilemap> {
+        let tileset = self.layer_tileset(layer)?;
+        let tile_size = glm::vec2(self.tile_width as f32, self.tile_height as f32) / pixels_per_unit;
+        let mut tilemap = Tilemap::new(layer.width, layer.height, tile_size, tileset.atlas);
+        let mut skipped = 0;
+
+        for (index, gid) in layer.data.iter().enumerate() {
+            let id = gid & GID_MASK;
+
+            if id == 0 {
+                continue;
+            }
+
+            if !tileset.contains(id) {
+                skipped += 1;
+                continue;
+            }
+
+            let index = index as u32;
+            let local_id = id - tileset.first_gid;
+
+            tilemap.set(index % layer.width, layer.height - 1 - index / layer.width, Some(Tile {
+                index: local_id,
+                flip_x: gid & FLIPPED_HORIZONTALLY != 0,
+                flip_y: gid & FLIPPED_VERTICALLY != 0,
+                solid: tileset.solid_tiles.contains(&local_id),
+            }));
+        }
+
+        if skipped > 0 {
+            warn!("Skipped {skipped} tiles of layer `{}` from tilesets other than `{}`", layer.name, tileset.name);
+        }
+
+        Some(tilemap)
+    }
+}
+
+fn parse_tileset(node: &XmlNode, directory: &Path) -> Result<TiledTileset, TiledError> {
+    let first_gid = node.parse_attribute("firstgid")?;
+
+    // External tileset
+    if let Some(source) = node.attribute("source") {
+        let path = directory.join(source);
+        let root = XmlNode::load(&path)?;
+        let mut tileset = parse_tileset_data(&root, path.parent().unwrap_or(Path::new("")))?;
+        tileset.first_gid = first_gid;
+
+        return Ok(tileset);
+    }
+
+    let mut tileset = parse_tileset_data(node, directory)?;
+    tileset.first_gid = first_gid;
+
+    Ok(tileset)
+}
+
+fn parse_tileset_data(node: &XmlNode, directory: &Path) -> Result<TiledTileset, TiledError> {
+    let image = node
+        .children_named("image")
+        .next()
+        .ok_or_else(|| TiledError::UnsupportedFormat("image collection tilesets".into()))?;
+
+    let tile_count: u32 = node.parse_attribute("tilecount")?;
+    let columns: u32 = node.parse_attribute("columns")?;
+
+    let solid_tiles = node
+        .children_named("tile")
+        .filter(|tile| {
+            tile.children_named("objectgroup").next().is_some()
+                || tile.children_named("properties").flat_map(|properties| properties.children_named("property")).any(|property| {
+                    matches!(property.attribute("name"), Some("solid" | "collision"))
+                        && property.attribute("value") == Some("true")
+                })
+        })
+        .map(|tile| tile.parse_attribute("id"))
+        .collect::<Result<_, _>>()?;
+
+    Ok(TiledTileset {
+        first_gid: 1,
+        name: node.attribute("name").unwrap_or_default().to_owned(),
+        image: directory.join(image.attribute("source").unwrap_or_default()),
+        atlas: TileAtlas {
+            columns: columns.max(1),
+            rows: tile_count.div_ceil(columns.max(1)).max(1),
+            tile_width: node.parse_attribute("tilewidth")?,
+            tile_height: node.parse_attribute("tileheight")?,
+            margin: node.parse_attribute_or("margin", 0)?,
+            spacing: node.parse_attribute_or("spacing", 0)?,
+        },
+        tile_count,
+        solid_tiles,
+    })
+}
+
+fn collect_layers(node: &XmlNode, layers: &mut Vec<TiledLayer>) -> Result<(), TiledError> {
+    for child in &node.children {
+        match child.name.as_str() {
+            "layer" => layers.push(parse_layer(child)?),
+            "group" => collect_layers(child, layers)?,
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_layer(node: &XmlNode) -> Result<TiledLayer, TiledError> {
+    let width: u32 = node.parse_attribute("width")?;
+    let height: u32 = node.parse_attribute("height")?;
+
+    let data = node
+        .children_named("data")
+        .next()
+        .ok_or_else(|| TiledError::InvalidMap("layer without data".into()))?;
+
+    if data.children_named("chunk").next().is_some() {
+        return Err(TiledError::UnsupportedFormat("infinite maps".into()));
+    }
+
+    let gids = match (data.attribute("encoding"), data.attribute("compression")) {
+        (Some("csv"), _) => data.text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| gid.parse().map_err(|_| TiledError::InvalidMap(format!("invalid tile id `{gid}`"))))
+            .collect::<Result<Vec<u32>, _>>()?,
+        (Some("base64"), compression) => {
+            let bytes = base64::engine::general_purpose::STANDARD.decode(data.text.trim())?;
+            let bytes = match compression {
+                None => bytes,
+                Some("zlib") => decompress(ZlibDecoder::new(bytes.as_slice()))?,
+                Some("gzip") => decompress(GzDecoder::new(bytes.as_slice()))?,
+                Some(compression) => return Err(TiledError::UnsupportedFormat(format!("{compression} compression"))),
+            };
+
+            bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect()
+        },
+        (None, _) => data
+            .children_named("tile")
+            .map(|tile| tile.parse_attribute_or("gid", 0))
+            .collect::<Result<_, _>>()?,
+        (Some(encoding), _) => return Err(TiledError::UnsupportedFormat(format!("{encoding} encoding"))),
+    };
+
+    if gids.len() != (width * height) as usize {
+        return Err(TiledError::InvalidMap(format!(
+            "layer `{}` has {} tiles, expected {}",
+            node.attribute("name").unwrap_or_default(), gids.len(), width * height,
+        )));
+    }
+
+    Ok(TiledLayer {
+        name: node.attribute("name").unwrap_or_default().to_owned(),
+        width,
+        height,
+        visible: node.attribute("visible") != Some("0"),
+        opacity: node.parse_attribute_or("opacity", 1.0)?,
+        data: gids,
+    })
+}
+
+fn decompress(mut decoder: impl Read) -> Result<Vec<u8>, TiledError> {
+    let mut bytes = vec![];
+    decoder.read_to_end(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+/// Minimal XML element tree
+#[derive(Debug, Default)]
+struct XmlNode {
+    name: String,
+    attributes: HashMap<String, String>,
+    children: Vec<XmlNode>,
+    text: String,
+}
+
+impl XmlNode {
+    fn load(path: &Path) -> Result<XmlNode, TiledError> {
+        let mut stack: Vec<XmlNode> = vec![];
+
+        for event in EventReader::new(BufReader::new(File::open(path)?)) {
+            match event? {
+                XmlEvent::StartElement { name, attributes, .. } => {
+                    stack.push(XmlNode {
+                        name: name.local_name,
+                        attributes: attributes
+                            .into_iter()
+                            .map(|attribute| (attribute.name.local_name, attribute.value))
+                            .collect(),
+                        ..Default::default()
+                    });
+                },
+                XmlEvent::EndElement { .. } => {
+                    let node = stack.pop().ok_or_else(|| TiledError::InvalidMap("unbalanced elements".into()))?;
+
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(node),
+                        None => return Ok(node),
+                    }
+                },
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.text.push_str(&text);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        Err(TiledError::InvalidMap("empty document".into()))
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    fn parse_attribute<T: std::str::FromStr>(&self, name: &str) -> Result<T, TiledError> {
+        let value = self.attribute(name).ok_or_else(|| {
+            TiledError::InvalidMap(format!("`{}` has no attribute `{name}`", self.name))
+        })?;
+
+        value.parse().map_err(|_| TiledError::InvalidMap(format!("invalid `{name}` value `{value}`")))
+    }
+
+    fn parse_attribute_or<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, TiledError> {
+        match self.attribute(name) {
+            Some(_) => self.parse_attribute(name),
+            None => Ok(default),
+        }
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("Script I/O error")]
+    IoError(#[from] std::io::Error),
+    #[error("Cannot parse script: {0}")]
+    ParseError(#[from] rhai::ParseError),
+    #[error("Script error: {0}")]
+    RuntimeError(#[from] Box<rhai::EvalAltResult>),
+}
@@ -0,0 +1,29 @@
+//! Gameplay scripting with [rhai](https://rhai.rs). Entities with [`Script`] component run
+//! functions of the script file every update, and the file is reloaded, when it changes:
+//!
+//! ```rhai
+//! fn init(entity) {
+//!     this.speed = 2.0;
+//! }
+//!
+//! fn update(entity, dt) {
+//!     let transform = get(entity, "Transform");
+//!     if key_pressed("KeyW") {
+//!         transform.translation[2] -= this.speed * dt;
+//!     }
+//!     set(entity, "Transform", transform);
+//! }
+//! ```
+//!
+//! See [`ScriptRuntime`] for the available functions
+
+pub mod error;
+pub mod registry;
+pub mod runtime;
+pub mod script;
+
+pub use error::*;
+pub use registry::*;
+pub use runtime::*;
+pub use script::*;
+pub use rhai;
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use flatbox_core::math::transform::Transform;
+use flatbox_ecs::{Component, Entity, World};
+use rhai::{Dynamic, EvalAltResult};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::script::Script;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Component, which can be registered in [`ComponentRegistry`]
+pub trait ScriptComponent: Component + Serialize + DeserializeOwned {}
+
+impl<T: Component + Serialize + DeserializeOwned> ScriptComponent for T {}
+
+/// Functions, which access the component of a single type
+#[derive(Clone, Copy)]
+struct ComponentBinding {
+    get: fn(&World, Entity) -> Option<ScriptResult<Dynamic>>,
+    set: fn(&mut World, Entity, Dynamic) -> ScriptResult<()>,
+    remove: fn(&mut World, Entity) -> bool,
+}
+
+/// Components, which can be read and written by scripts by their names. Components are
+/// converted to rhai values with serde, so structs become object maps, vectors become arrays
+/// and unit enum variants become strings. Floats of scripts are `f32`, like the ones of `glm`.
+/// [`Transform`] and [`Script`] are registered by default
+#[derive(Clone)]
+pub struct ComponentRegistry {
+    components: BTreeMap<String, ComponentBinding>,
+}
+
+impl ComponentRegistry {
+    pub fn new() -> Self {
+        ComponentRegistry::default()
+    }
+
+    /// Registry without any components
+    pub fn empty() -> Self {
+        ComponentRegistry {
+            components: BTreeMap::new(),
+        }
+    }
+
+    pub fn register<T: ScriptComponent>(&mut self, name: impl Into<String>) {
+        self.components.insert(name.into(), ComponentBinding {
+            get: get_component::<T>,
+            set: set_component::<T>,
+            remove: remove_component::<T>,
+        });
+    }
+
+    pub fn with_component<T: ScriptComponent>(mut self, name: impl Into<String>) -> Self {
+        self.register::<T>(name);
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.components.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.components.keys().map(String::as_str)
+    }
+
+    /// Component of the entity as a rhai value. It's `None`, if the entity doesn't have it
+    pub fn get(&self, world: &World, entity: Entity, name: &str) -> ScriptResult<Option<Dynamic>> {
+        (self.binding(name)?.get)(world, entity).transpose()
+    }
+
+    /// Insert the component, converted from the rhai value, or replace the existing one
+    pub fn set(&self, world: &mut World, entity: Entity, name: &str, value: Dynamic) -> ScriptResult<()> {
+        (self.binding(name)?.set)(world, entity, value)
+    }
+
+    /// Remove the component. Returns `false`, if the entity doesn't have it
+    pub fn remove(&self, world: &mut World, entity: Entity, name: &str) -> ScriptResult<bool> {
+        Ok((self.binding(name)?.remove)(world, entity))
+    }
+
+    fn binding(&self, name: &str) -> ScriptResult<&ComponentBinding> {
+        self.components
+            .get(name)
+            .ok_or_else(|| format!("Component `{name}` isn't registered").into())
+    }
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        ComponentRegistry::empty()
+            .with_component::<Transform>("Transform")
+            .with_component::<Script>("Script")
+    }
+}
+
+impl Debug for ComponentRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+fn get_component<T: Component + Serialize>(world: &World, entity: Entity) -> Option<ScriptResult<Dynamic>> {
+    let component = world.get::<&T>(entity).ok()?;
+    Some(rhai::serde::to_dynamic(&*component))
+}
+
+fn set_component<T: Component + DeserializeOwned>(world: &mut World, entity: Entity, value: Dynamic) -> ScriptResult<()> {
+    let component: T = rhai::serde::from_dynamic(&value)?;
+    world
+        .insert_one(entity, component)
+        .map_err(|_| format!("Entity {entity:?} doesn't exist").into())
+}
+
+fn remove_component<T: Component>(world: &mut World, entity: Entity) -> bool {
+    world.remove_one::<T>(entity).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transform_round_trip() {
+        let registry = ComponentRegistry::new();
+        let mut world = World::new();
+        let entity = world.spawn((Transform::identity(),));
+
+        let mut value = registry.get(&world, entity, "Transform").unwrap().unwrap();
+        value.write_lock::<rhai::Map>().unwrap().insert("scale".into(), Dynamic::from_float(2.0));
+        registry.set(&mut world, entity, "Transform", value).unwrap();
+
+        assert_eq!(world.get::<&Transform>(entity).unwrap().scale, 2.0);
+        assert!(registry.remove(&mut world, entity, "Transform").unwrap());
+        assert!(registry.get(&world, entity, "Transform").unwrap().is_none());
+        assert!(registry.get(&world, entity, "Unknown").is_err());
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{metadata, read_to_string};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use flatbox_core::logger::{debug, error, info};
+use flatbox_ecs::{Entity, World, event::{EventCursor, Events}};
+use flatbox_render::context::{Input, KeyCode, MouseButton};
+use parking_lot::Mutex;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, ImmutableString, Map, Scope, AST, FLOAT};
+use serde::de::DeserializeOwned;
+
+use crate::{error::ScriptError, registry::ComponentRegistry, script::{Script, ScriptEvent}};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Data, which is accessed by the engine functions while scripts run
+#[derive(Default)]
+struct ScriptData {
+    world: World,
+    input: Input,
+    received: Vec<ScriptEvent>,
+    sent: Vec<ScriptEvent>,
+}
+
+/// Compiled script along with the modification time of its file
+struct LoadedScript {
+    ast: Option<AST>,
+    modified: Option<SystemTime>,
+}
+
+/// State of the scripted entity, which is `this` in the script functions
+struct EntityState {
+    path: PathBuf,
+    this: Dynamic,
+    initialized: bool,
+}
+
+/// Runs scripts of entities with [`Script`] component. Script functions are optional:
+///
+/// - `init(entity)` is called once before the first update of the entity;
+/// - `update(entity, dt)` is called every update with the time step in seconds.
+///
+/// Both get the object map of the entity as `this`, which is kept while the entity has
+/// the script, including reloads. Top-level statements of the script aren't executed.
+/// Scripts can use the following functions:
+///
+/// - `spawn()`, `despawn(entity)` and `exists(entity)`;
+/// - `get(entity, name)`, `set(entity, name, value)`, `has(entity, name)` and
+///   `remove(entity, name)` for the components of [`ComponentRegistry`]. `get` returns `()`,
+///   if the entity doesn't have the component;
+/// - `key_pressed(key)`, `key_just_pressed(key)`, `key_just_released(key)` with names of
+///   [`KeyCode`] variants, e.g. `"KeyW"`, and the same `mouse_*` functions with names of
+///   [`MouseButton`] variants, e.g. `"Left"`;
+/// - `mouse_motion()`, `scroll()` and `cursor_position()`, which return `[x, y]`
+///   (the latter returns `()` outside of the window);
+/// - `emit(name)`, `emit(name, data)` and `events(name)` for [`ScriptEvent`]s. Scripts
+///   receive the events, sent since their previous update, as an array of their data;
+/// - `print` and `debug`, which write to the log.
+///
+/// Script files are checked for changes every poll interval and reloaded
+pub struct ScriptRuntime {
+    engine: Engine,
+    data: Arc<Mutex<ScriptData>>,
+    scripts: HashMap<PathBuf, LoadedScript>,
+    states: HashMap<Entity, EntityState>,
+    cursor: EventCursor<ScriptEvent>,
+    poll_interval: Duration,
+    last_poll: Instant,
+}
+
+impl ScriptRuntime {
+    pub fn new(components: ComponentRegistry, poll_interval: Duration) -> Self {
+        let data = Arc::new(Mutex::new(ScriptData::default()));
+
+        ScriptRuntime {
+            engine: create_engine(&data, Arc::new(components)),
+            data,
+            scripts: HashMap::new(),
+            states: HashMap::new(),
+            cursor: EventCursor::new(),
+            poll_interval,
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Engine, e.g. to register custom functions. Functions, which access the world,
+    /// are registered by the runtime
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    /// Compile the script at the path, replacing the loaded one. The previous version is
+    /// kept, if the script can't be compiled
+    pub fn reload(&mut self, path: &Path) -> Result<(), ScriptError> {
+        let script = self.scripts.entry(path.to_owned()).or_insert(LoadedScript {
+            ast: None,
+            modified: None,
+        });
+
+        script.modified = metadata(path).and_then(|metadata| metadata.modified()).ok();
+        script.ast = Some(self.engine.compile(read_to_string(path)?)?);
+
+        Ok(())
+    }
+
+    /// Reload the scripts, whose files have been changed since the previous poll
+    pub fn poll(&mut self) {
+        if self.last_poll.elapsed() < self.poll_interval {
+            return;
+        }
+
+        self.last_poll = Instant::now();
+
+        let changed: Vec<PathBuf> = self.scripts
+            .iter()
+            .filter(|(path, script)| {
+                metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .is_ok_and(|modified| script.modified != Some(modified))
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in changed {
+            match self.reload(&path) {
+                Ok(()) => info!("Reloaded script `{}`", path.display()),
+                Err(e) => error!("Cannot reload script `{}`: {e}", path.display()),
+            }
+        }
+    }
+
+    /// Run scripts of the entities. The world is moved into the runtime for the time of
+    /// the update, so it must not be accessed by engine functions, registered outside
+    pub fn update(&mut self, world: &mut World, input: &Input, events: &mut Events, delta_time: f32) {
+        self.poll();
+
+        let entities: Vec<(Entity, PathBuf)> = world
+            .query_mut::<&Script>()
+            .into_iter()
+            .map(|(entity, script)| (entity, script.path.clone()))
+            .collect();
+
+        let scripted: HashSet<Entity> = entities.iter().map(|(entity, _)| *entity).collect();
+        self.states.retain(|entity, _| scripted.contains(entity));
+
+        for (_, path) in &entities {
+            if !self.scripts.contains_key(path) {
+                if let Err(e) = self.reload(path) {
+                    error!("Cannot load script `{}`: {e}", path.display());
+                }
+            }
+        }
+
+        {
+            let mut data = self.data.lock();
+            std::mem::swap(&mut data.world, world);
+            data.input.clone_from(input);
+            data.received = self.cursor.read(events).cloned().collect();
+        }
+
+        for (entity, path) in entities {
+            let Some(ast) = self.scripts.get(&path).and_then(|script| script.ast.as_ref()) else { continue };
+
+            let state = self.states.entry(entity).or_insert_with(|| EntityState {
+                path: path.clone(),
+                this: Map::new().into(),
+                initialized: false,
+            });
+
+            if state.path != path {
+                *state = EntityState {
+                    path: path.clone(),
+                    this: Map::new().into(),
+                    initialized: false,
+                };
+            }
+
+            if !state.initialized {
+                state.initialized = true;
+
+                if let Err(e) = call_script_fn(&self.engine, ast, &mut state.this, "init", (entity,)) {
+                    error!("Error in `init` of script `{}`: {e}", path.display());
+                }
+            }
+
+            // The entity may be despawned by scripts
+            if !self.data.lock().world.contains(entity) {
+                continue;
+            }
+
+            let args = (entity, delta_time as FLOAT);
+            if let Err(e) = call_script_fn(&self.engine, ast, &mut state.this, "update", args) {
+                error!("Error in `update` of script `{}`: {e}", path.display());
+            }
+        }
+
+        let mut data = self.data.lock();
+        std::mem::swap(&mut data.world, world);
+        data.received.clear();
+
+        for event in data.sent.drain(..) {
+            events.send(event);
+        }
+
+        // Scripts don't receive their own events of this update
+        self.cursor.read(events).for_each(drop);
+    }
+}
+
+/// Call the script function, if it's defined with the same number of parameters
+fn call_script_fn(
+    engine: &Engine,
+    ast: &AST,
+    this: &mut Dynamic,
+    name: &str,
+    args: impl rhai::FuncArgs,
+) -> ScriptResult<()> {
+    let mut arg_values = vec![];
+    args.parse(&mut arg_values);
+
+    let defined = ast.iter_functions().any(|function| {
+        function.name == name && function.params.len() == arg_values.len()
+    });
+
+    if !defined {
+        return Ok(());
+    }
+
+    let options = CallFnOptions::new()
+        .eval_ast(false)
+        .bind_this_ptr(this);
+
+    engine
+        .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), ast, name, arg_values)
+        .map(drop)
+}
+
+fn create_engine(data: &Arc<Mutex<ScriptData>>, components: Arc<ComponentRegistry>) -> Engine {
+    let mut engine = Engine::new();
+
+    engine.on_print(|text| info!("{text}"));
+    engine.on_debug(|text, source, position| {
+        debug!("{}{position:?}: {text}", source.map(|source| format!("{source} ")).unwrap_or_default())
+    });
+
+    engine
+        .register_type_with_name::<Entity>("Entity")
+        .register_fn("to_string", |entity: &mut Entity| format!("{entity:?}"))
+        .register_fn("to_debug", |entity: &mut Entity| format!("{entity:?}"))
+        .register_fn("==", |a: Entity, b: Entity| a == b)
+        .register_fn("!=", |a: Entity, b: Entity| a != b);
+
+    let d = data.clone();
+    engine.register_fn("spawn", move || d.lock().world.spawn(()));
+
+    let d = data.clone();
+    engine.register_fn("despawn", move |entity: Entity| d.lock().world.despawn(entity).is_ok());
+
+    let d = data.clone();
+    engine.register_fn("exists", move |entity: Entity| d.lock().world.contains(entity));
+
+    let (d, c) = (data.clone(), components.clone());
+    engine.register_fn("get", move |entity: Entity, name: &str| -> ScriptResult<Dynamic> {
+        Ok(c.get(&d.lock().world, entity, name)?.unwrap_or_default())
+    });
+
+    let (d, c) = (data.clone(), components.clone());
+    engine.register_fn("has", move |entity: Entity, name: &str| -> ScriptResult<bool> {
+        Ok(c.get(&d.lock().world, entity, name)?.is_some())
+    });
+
+    let (d, c) = (data.clone(), components.clone());
+    engine.register_fn("set", move |entity: Entity, name: &str, value: Dynamic| -> ScriptResult<()> {
+        c.set(&mut d.lock().world, entity, name, value)
+    });
+
+    let (d, c) = (data.clone(), components);
+    engine.register_fn("remove", move |entity: Entity, name: &str| -> ScriptResult<bool> {
+        c.remove(&mut d.lock().world, entity, name)
+    });
+
+    register_input_fn::<KeyCode>(&mut engine, data, "key_pressed", Input::key_pressed);
+    register_input_fn::<KeyCode>(&mut engine, data, "key_just_pressed", Input::key_just_pressed);
+    register_input_fn::<KeyCode>(&mut engine, data, "key_just_released", Input::key_just_released);
+    register_input_fn::<MouseButton>(&mut engine, data, "mouse_pressed", Input::mouse_pressed);
+    register_input_fn::<MouseButton>(&mut engine, data, "mouse_just_pressed", Input::mouse_just_pressed);
+    register_input_fn::<MouseButton>(&mut engine, data, "mouse_just_released", Input::mouse_just_released);
+
+    let d = data.clone();
+    engine.register_fn("mouse_motion", move || vec2_to_array(d.lock().input.mouse_motion()));
+
+    let d = data.clone();
+    engine.register_fn("scroll", move || vec2_to_array(d.lock().input.scroll()));
+
+    let d = data.clone();
+    engine.register_fn("cursor_position", move || {
+        d.lock().input.cursor_position().map(vec2_to_array).map(Dynamic::from_array).unwrap_or_default()
+    });
+
+    let d = data.clone();
+    engine.register_fn("emit", move |name: ImmutableString| d.lock().sent.push(ScriptEvent::new(name, ())));
+
+    let d = data.clone();
+    engine.register_fn("emit", move |name: ImmutableString, event: Dynamic| {
+        d.lock().sent.push(ScriptEvent::new(name, event))
+    });
+
+    let d = data.clone();
+    engine.register_fn("events", move |name: &str| -> Array {
+        d.lock().received
+            .iter()
+            .filter(|event| event.name == name)
+            .map(|event| event.data.clone())
+            .collect()
+    });
+
+    engine
+}
+
+/// Register function, which checks the key or button, given by the name of its variant
+fn register_input_fn<T: DeserializeOwned + 'static>(
+    engine: &mut Engine,
+    data: &Arc<Mutex<ScriptData>>,
+    name: &str,
+    check: fn(&Input, T) -> bool,
+) {
+    let data = data.clone();
+    engine.register_fn(name, move |variant: ImmutableString| -> ScriptResult<bool> {
+        let value = rhai::serde::from_dynamic(&variant.clone().into())
+            .map_err(|_| format!("Unknown key or button `{variant}`"))?;
+
+        Ok(check(&data.lock().input, value))
+    });
+}
+
+fn vec2_to_array(vector: flatbox_core::math::glm::Vec2) -> Array {
+    vec![Dynamic::from_float(vector.x as FLOAT), Dynamic::from_float(vector.y as FLOAT)]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{write, File};
+
+    use flatbox_core::math::transform::Transform;
+
+    use super::*;
+
+    const SCRIPT: &str = r#"
+        fn init(entity) {
+            this.updates = 0;
+        }
+
+        fn update(entity, dt) {
+            this.updates += 1;
+
+            let transform = get(entity, "Transform");
+            transform.scale = this.updates * 1.0;
+            set(entity, "Transform", transform);
+
+            if !key_pressed("KeyW") {
+                for data in events("ping") {
+                    emit("pong", data + this.updates);
+                }
+            }
+        }
+    "#;
+
+    fn script_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("flatbox-script-{}-{name}.rhai", std::process::id()))
+    }
+
+    #[test]
+    fn scripts_update_components_and_send_events() {
+        let path = script_path("update");
+        write(&path, SCRIPT).unwrap();
+
+        let mut runtime = ScriptRuntime::new(ComponentRegistry::new(), Duration::from_secs(60));
+        let mut world = World::new();
+        let mut events = Events::new();
+        events.push_handler::<ScriptEvent>();
+
+        let entity = world.spawn((Script::new(&path), Transform::identity()));
+
+        runtime.update(&mut world, &Input::new(), &mut events, 0.1);
+        events.send(ScriptEvent::new("ping", 10_i64));
+        runtime.update(&mut world, &Input::new(), &mut events, 0.1);
+
+        assert_eq!(world.get::<&Transform>(entity).unwrap().scale, 2.0);
+
+        let pongs: Vec<i64> = events.iter::<ScriptEvent>()
+            .filter(|event| event.name == "pong")
+            .map(|event| event.data.as_int().unwrap())
+            .collect();
+
+        assert_eq!(pongs, [12]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn changed_scripts_are_reloaded() {
+        let path = script_path("reload");
+        write(&path, "fn update(entity, dt) { set(entity, \"Transform\", get(entity, \"Transform\")); }").unwrap();
+
+        let mut runtime = ScriptRuntime::new(ComponentRegistry::new(), Duration::ZERO);
+        let mut world = World::new();
+        let mut events = Events::new();
+        let entity = world.spawn((Script::new(&path), Transform::identity()));
+
+        runtime.update(&mut world, &Input::new(), &mut events, 0.1);
+        assert_eq!(world.get::<&Transform>(entity).unwrap().scale, 1.0);
+
+        write(&path, "fn update(entity, dt) { despawn(entity); }").unwrap();
+        File::options().write(true).open(&path).unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+
+        runtime.update(&mut world, &Input::new(), &mut events, 0.1);
+        assert!(!world.contains(entity));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use flatbox_assets::impl_ser_component;
+use rhai::Dynamic;
+use serde::{Serialize, Deserialize};
+
+/// Component, which runs functions of the rhai script at the path every update.
+/// Scripts are shared by entities with the same path
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Script {
+    pub path: PathBuf,
+}
+
+impl Script {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Script { path: path.into() }
+    }
+}
+
+impl_ser_component!(Script);
+
+/// ECS event, which is sent by scripts with `emit(name, data)` and received with
+/// `events(name)`. It can be sent and read by Rust systems as well
+#[derive(Debug, Clone)]
+pub struct ScriptEvent {
+    pub name: String,
+    pub data: Dynamic,
+}
+
+impl ScriptEvent {
+    pub fn new(name: impl Into<String>, data: impl Into<Dynamic>) -> Self {
+        ScriptEvent {
+            name: name.into(),
+            data: data.into(),
+        }
+    }
+}
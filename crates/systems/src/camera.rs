@@ -0,0 +1,213 @@
+use flatbox_core::{
+    math::{glm, transform::Transform},
+    time::Time,
+};
+use flatbox_ecs::*;
+use flatbox_render::{
+    context::{Input, KeyCode, MouseButton},
+    pbr::camera::{Camera, CameraType},
+};
+
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+/// Free-flying camera controller. Moves with `WASD`, goes up and down with `Space`
+/// and `ShiftLeft`, speeds up with `ControlLeft` and looks around with the mouse.
+/// It's driven by [`fly_cameras`] system
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlyCamera {
+    /// Movement speed in units per second
+    pub speed: f32,
+    /// Speed multiplier, applied while `ControlLeft` is held
+    pub boost: f32,
+    /// Rotation in radians per pixel of mouse movement
+    pub sensitivity: f32,
+    /// Rotate only while the button is held. `None` means always rotate
+    pub look_button: Option<MouseButton>,
+    /// Rotation around the vertical axis in radians
+    pub yaw: f32,
+    /// Rotation around the horizontal axis in radians
+    pub pitch: f32,
+}
+
+impl FlyCamera {
+    pub fn new() -> Self {
+        FlyCamera::default()
+    }
+
+    /// Set the initial orientation, so the camera at `eye` looks at `target`
+    pub fn looking_at(mut self, eye: &glm::Vec3, target: &glm::Vec3) -> Self {
+        (self.yaw, self.pitch) = yaw_pitch(&(target - eye));
+        self
+    }
+
+    pub fn orientation(&self) -> glm::Quat {
+        orientation(self.yaw, self.pitch)
+    }
+}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        FlyCamera {
+            speed: 5.0,
+            boost: 3.0,
+            sensitivity: 0.003,
+            look_button: Some(MouseButton::Right),
+            yaw: 0.0,
+            pitch: 0.0,
+        }
+    }
+}
+
+/// Camera controller, which orbits around the target. Rotates while the mouse
+/// button is held and zooms with the mouse wheel. It's driven by [`orbit_cameras`] system
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitCamera {
+    pub target: glm::Vec3,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Rotation in radians per pixel of mouse movement
+    pub sensitivity: f32,
+    /// Relative change of distance per line of scrolling
+    pub zoom_speed: f32,
+    pub orbit_button: MouseButton,
+    /// Rotation around the vertical axis in radians
+    pub yaw: f32,
+    /// Rotation around the horizontal axis in radians
+    pub pitch: f32,
+}
+
+impl OrbitCamera {
+    pub fn new(target: glm::Vec3, distance: f32) -> Self {
+        OrbitCamera {
+            target,
+            distance,
+            ..Default::default()
+        }
+    }
+
+    /// Position of the camera
+    pub fn eye(&self) -> glm::Vec3 {
+        self.target + glm::quat_rotate_vec3(&orientation(self.yaw, self.pitch), &glm::vec3(0.0, 0.0, self.distance))
+    }
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        OrbitCamera {
+            target: glm::Vec3::zeros(),
+            distance: 5.0,
+            min_distance: 0.5,
+            max_distance: 100.0,
+            sensitivity: 0.005,
+            zoom_speed: 0.1,
+            orbit_button: MouseButton::Left,
+            yaw: 0.0,
+            pitch: -0.5,
+        }
+    }
+}
+
+/// Update cameras with [`FlyCamera`] controller. Must be added to `PreRender` stage
+pub fn fly_cameras(
+    camera_world: SubWorld<(&Camera, &mut FlyCamera, &mut Transform)>,
+    input: Read<Input>,
+    time: Read<Time>,
+){
+    for (_, (camera, mut controller, mut transform)) in &mut camera_world.query::<(&Camera, &mut FlyCamera, &mut Transform)>() {
+        if !camera.is_active() {
+            continue;
+        }
+
+        if controller.look_button.is_none_or(|button| input.mouse_pressed(button)) {
+            let motion = input.mouse_motion() * controller.sensitivity;
+
+            controller.yaw -= motion.x;
+            controller.pitch = (controller.pitch - motion.y).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+
+        let axis = |positive: KeyCode, negative: KeyCode| {
+            input.key_pressed(positive) as i32 as f32 - input.key_pressed(negative) as i32 as f32
+        };
+
+        let orientation = controller.orientation();
+        let direction = glm::quat_rotate_vec3(&orientation, &glm::vec3(
+            axis(KeyCode::KeyD, KeyCode::KeyA),
+            0.0,
+            axis(KeyCode::KeyS, KeyCode::KeyW),
+        )) + glm::Vec3::y() * axis(KeyCode::Space, KeyCode::ShiftLeft);
+
+        let mut eye = camera_eye(camera, &transform);
+
+        if glm::length2(&direction) > f32::EPSILON {
+            let speed = if input.key_pressed(KeyCode::ControlLeft) {
+                controller.speed * controller.boost
+            } else {
+                controller.speed
+            };
+
+            eye += glm::normalize(&direction) * speed * time.delta_seconds();
+        }
+
+        set_camera_view(camera, &mut transform, &eye, &orientation);
+    }
+}
+
+/// Update cameras with [`OrbitCamera`] controller. Must be added to `PreRender` stage
+pub fn orbit_cameras(
+    camera_world: SubWorld<(&Camera, &mut OrbitCamera, &mut Transform)>,
+    input: Read<Input>,
+){
+    for (_, (camera, mut controller, mut transform)) in &mut camera_world.query::<(&Camera, &mut OrbitCamera, &mut Transform)>() {
+        if !camera.is_active() {
+            continue;
+        }
+
+        if input.mouse_pressed(controller.orbit_button) {
+            let motion = input.mouse_motion() * controller.sensitivity;
+
+            controller.yaw -= motion.x;
+            controller.pitch = (controller.pitch - motion.y).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+
+        let zoom = (1.0 - input.scroll().y * controller.zoom_speed).max(0.0);
+        controller.distance = (controller.distance * zoom).clamp(controller.min_distance, controller.max_distance);
+
+        let orientation = orientation(controller.yaw, controller.pitch);
+        set_camera_view(camera, &mut transform, &controller.eye(), &orientation);
+    }
+}
+
+fn orientation(yaw: f32, pitch: f32) -> glm::Quat {
+    glm::quat_angle_axis(yaw, &glm::Vec3::y()) * glm::quat_angle_axis(pitch, &glm::Vec3::x())
+}
+
+fn yaw_pitch(direction: &glm::Vec3) -> (f32, f32) {
+    let direction = glm::normalize(direction);
+    let yaw = (-direction.x).atan2(-direction.z);
+    let pitch = direction.y.clamp(-1.0, 1.0).asin().clamp(-MAX_PITCH, MAX_PITCH);
+
+    (yaw, pitch)
+}
+
+/// Get the camera position from its transform, according to the camera type
+fn camera_eye(camera: &Camera, transform: &Transform) -> glm::Vec3 {
+    let translation = transform.translation_f32();
+
+    match camera.camera_type() {
+        CameraType::FirstPerson => -translation,
+        CameraType::LookAt => -glm::quat_rotate_vec3(&glm::quat_inverse(&transform.rotation_f32()), &translation),
+    }
+}
+
+/// Write the camera position and orientation into its transform, according to the camera type
+fn set_camera_view(camera: &Camera, transform: &mut Transform, eye: &glm::Vec3, orientation: &glm::Quat) {
+    let view_rotation = glm::quat_inverse(orientation);
+    let translation = match camera.camera_type() {
+        CameraType::FirstPerson => -eye,
+        CameraType::LookAt => -glm::quat_rotate_vec3(&view_rotation, eye),
+    };
+
+    transform.translation = glm::convert(translation);
+    transform.rotation = glm::convert(view_rotation);
+}
@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use flatbox_core::{logger::info, time::Time};
+use flatbox_ecs::*;
+use flatbox_render::{hal::capabilities::GpuMemoryInfo, renderer::{Renderer, stats::RenderStats}};
+
+/// Measurements of a single frame
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrameDiagnostic {
+    pub frame_time: Duration,
+    pub entity_count: u32,
+    pub draw_calls: u32,
+    pub render_stats: RenderStats,
+}
+
+/// Singleton component with runtime statistics of the app, maintained by
+/// [`update_diagnostics`] and [`update_render_diagnostics`] systems. It keeps
+/// history of the latest frames, so it can be read by debug overlays and tools
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    history: VecDeque<FrameDiagnostic>,
+    history_size: usize,
+    gpu_memory: Option<GpuMemoryInfo>,
+    /// Interval of logging the summary. `None` disables logging
+    pub log_interval: Option<Duration>,
+    last_log: Instant,
+}
+
+impl Diagnostics {
+    pub fn new(history_size: usize) -> Self {
+        Diagnostics {
+            history: VecDeque::with_capacity(history_size),
+            history_size: history_size.max(1),
+            gpu_memory: None,
+            log_interval: None,
+            last_log: Instant::now(),
+        }
+    }
+
+    pub fn with_log_interval(mut self, log_interval: Option<Duration>) -> Self {
+        self.log_interval = log_interval;
+        self
+    }
+
+    /// Frames from the oldest to the latest
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &FrameDiagnostic> {
+        self.history.iter()
+    }
+
+    pub fn latest(&self) -> Option<&FrameDiagnostic> {
+        self.history.back()
+    }
+
+    /// Average frame time over the history
+    pub fn average_frame_time(&self) -> Duration {
+        if self.history.is_empty() {
+            return Duration::ZERO;
+        }
+
+        self.history.iter().map(|frame| frame.frame_time).sum::<Duration>() / self.history.len() as u32
+    }
+
+    /// Average frames per second over the history
+    pub fn fps(&self) -> f32 {
+        let frame_time = self.average_frame_time().as_secs_f32();
+
+        if frame_time > 0.0 { 1.0 / frame_time } else { 0.0 }
+    }
+
+    /// Frame time percentile over the history, e.g. `0.99` gives the time,
+    /// which 99% of frames don't exceed
+    pub fn frame_time_percentile(&self, percentile: f32) -> Duration {
+        if self.history.is_empty() {
+            return Duration::ZERO;
+        }
+
+        let mut frame_times = self.history.iter().map(|frame| frame.frame_time).collect::<Vec<_>>();
+        frame_times.sort_unstable();
+
+        let index = (percentile.clamp(0.0, 1.0) * (frame_times.len() - 1) as f32).round() as usize;
+        frame_times[index]
+    }
+
+    pub fn entity_count(&self) -> u32 {
+        self.latest().map(|frame| frame.entity_count).unwrap_or_default()
+    }
+
+    pub fn draw_calls(&self) -> u32 {
+        self.latest().map(|frame| frame.draw_calls).unwrap_or_default()
+    }
+
+    /// Renderer statistics of the latest frame
+    pub fn render_stats(&self) -> Option<&RenderStats> {
+        self.latest().map(|frame| &frame.render_stats)
+    }
+
+    /// Video memory usage, if supported by the driver
+    pub fn gpu_memory(&self) -> Option<GpuMemoryInfo> {
+        self.gpu_memory
+    }
+
+    fn push(&mut self, frame: FrameDiagnostic) {
+        if self.history.len() >= self.history_size {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(frame);
+    }
+
+    fn log(&mut self) {
+        let Some(log_interval) = self.log_interval else { return };
+
+        if self.last_log.elapsed() < log_interval {
+            return;
+        }
+
+        self.last_log = Instant::now();
+
+        info!(
+            "FPS: {:.1}, frame time: avg {:.2?}, p99 {:.2?}, entities: {}, draw calls: {}, triangles: {}{}",
+            self.fps(),
+            self.average_frame_time(),
+            self.frame_time_percentile(0.99),
+            self.entity_count(),
+            self.draw_calls(),
+            self.render_stats().map(|stats| stats.triangles).unwrap_or_default(),
+            self.gpu_memory
+                .and_then(|memory| memory.used_kb().map(|used| format!(", GPU memory: {} MiB", used / 1024)))
+                .unwrap_or_default(),
+        );
+    }
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Diagnostics::new(120)
+    }
+}
+
+/// Record frame time and entity count. Must be added to `PostRender` stage
+/// (or to `Update` stage in headless mode)
+pub fn update_diagnostics(
+    world: Read<World>,
+    time: Read<Time>,
+){
+    let entity_count = world.len();
+
+    for (_, mut diagnostics) in &mut world.query::<&mut Diagnostics>() {
+        // Log before pushing, because the latest frame is already complete
+        diagnostics.log();
+        diagnostics.push(FrameDiagnostic {
+            frame_time: time.delta_time(),
+            entity_count,
+            draw_calls: 0,
+            render_stats: RenderStats::default(),
+        });
+    }
+}
+
+/// Record renderer statistics and video memory usage. Must be added to `PostRender` stage
+/// after [`update_diagnostics`]
+pub fn update_render_diagnostics(
+    diagnostics_world: SubWorld<&mut Diagnostics>,
+    renderer: Read<Renderer>,
+){
+    for (_, mut diagnostics) in &mut diagnostics_world.query::<&mut Diagnostics>() {
+        diagnostics.gpu_memory = renderer.capabilities().gpu_memory();
+
+        if let Some(frame) = diagnostics.history.back_mut() {
+            frame.draw_calls = renderer.draw_calls();
+            frame.render_stats = renderer.stats().clone();
+        }
+    }
+}
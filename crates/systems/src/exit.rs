@@ -0,0 +1,25 @@
+use flatbox_core::AppExit;
+use flatbox_ecs::{*, event::Events};
+use flatbox_render::context::{CloseRequested, Input, KeyCode};
+
+/// Spawn [`AppExit`], when `Escape` is pressed
+pub fn exit_on_esc(
+    input: Read<Input>,
+    mut cmd: Write<CommandBuffer>,
+){
+    if input.key_just_pressed(KeyCode::Escape) {
+        cmd.spawn((AppExit,));
+    }
+}
+
+/// Spawn [`AppExit`], when closing the window is requested and it's not vetoed.
+/// It's executed by the app at the end of each frame, unless
+/// `WindowBuilder::exit_on_close` is disabled
+pub fn exit_on_window_close(
+    events: Read<Events>,
+    mut cmd: Write<CommandBuffer>,
+){
+    if events.read::<CloseRequested>().is_some_and(|close| !close.is_vetoed()) {
+        cmd.spawn((AppExit,));
+    }
+}
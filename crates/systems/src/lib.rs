@@ -1 +1,15 @@
-pub mod rendering;
\ No newline at end of file
+pub mod camera;
+pub mod diagnostics;
+pub mod exit;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod path;
+#[cfg(feature = "physics")]
+pub mod physics;
+#[cfg(feature = "physics2d")]
+pub mod physics2d;
+pub mod postprocess;
+pub mod preload;
+pub mod rendering;
+pub mod replay;
+pub mod transition;
\ No newline at end of file
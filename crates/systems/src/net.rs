@@ -0,0 +1,20 @@
+use flatbox_ecs::{*, event::Events, resources::ResMut};
+use flatbox_net::{NetClient, NetServer};
+
+/// Handle connections and inputs of the clients and send changes of the replicated entities
+pub fn update_net_server(
+    mut world: Write<World>,
+    mut events: Write<Events>,
+    mut server: ResMut<NetServer>,
+) {
+    server.update(&mut world, &mut events);
+}
+
+/// Apply changes of the replicated entities, received from the server
+pub fn update_net_client(
+    mut world: Write<World>,
+    mut events: Write<Events>,
+    mut client: ResMut<NetClient>,
+) {
+    client.update(&mut world, &mut events);
+}
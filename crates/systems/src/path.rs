@@ -0,0 +1,31 @@
+use flatbox_core::{
+    math::{
+        curve::PathFollower,
+        glm,
+        transform::Transform,
+    },
+    time::Time,
+};
+use flatbox_ecs::*;
+
+/// Moves entities with [`PathFollower`] component along their paths.
+/// Must be added to `Update` stage
+pub fn follow_paths(
+    path_world: SubWorld<(&mut PathFollower, &mut Transform)>,
+    time: Read<Time>,
+){
+    for (_, (mut follower, mut transform)) in &mut path_world.query::<(&mut PathFollower, &mut Transform)>() {
+        follower.advance(time.fixed_delta_seconds());
+        transform.translation = glm::convert(follower.position());
+
+        if follower.align_to_path {
+            let direction = follower.direction();
+            transform.rotation = glm::convert(glm::safe_quat_look_at(
+                &glm::Vec3::zeros(),
+                &direction,
+                &glm::Vec3::y(),
+                &glm::Vec3::z(),
+            ));
+        }
+    }
+}
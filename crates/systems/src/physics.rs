@@ -0,0 +1,323 @@
+use std::collections::HashSet;
+
+use flatbox_core::{color::Color, math::transform::Transform, profile_scope, time::Time};
+use flatbox_ecs::{*, disabled::Disabled, event::Events};
+use flatbox_physics::{
+    rapier3d::prelude::{DebugColor, DebugRenderBackend, DebugRenderObject, Point, Real, RigidBodyType},
+    apply_isometry, to_isometry,
+    ColliderComponent, CollisionEvents, CollisionLayers, ExternalForces, JointComponent, KinematicVelocity, PhysicsBodyHandle, PhysicsHandler,
+    PhysicsJointHandle, RigidBodyComponent,
+};
+use flatbox_render::gizmos::Gizmos;
+
+/// Debug render backend, which draws lines of the physics debug renderer with [`Gizmos`]
+pub struct GizmosDebugBackend<'a> {
+    pub gizmos: &'a mut Gizmos,
+}
+
+impl DebugRenderBackend for GizmosDebugBackend<'_> {
+    fn draw_line(&mut self, _object: DebugRenderObject, a: Point<Real>, b: Point<Real>, color: DebugColor) {
+        // Debug colors are HSLA
+        self.gizmos.line(a.coords, b.coords, Color::hsla(color[0], color[1], color[2], color[3]));
+    }
+}
+
+/// Create bodies for the spawned entities with [`RigidBodyComponent`], [`ColliderComponent`]
+/// and [`Transform`], and remove bodies of the despawned ones. Entities receive [`PhysicsBodyHandle`]
+/// component, which is replaced, if it doesn't refer to the body of the entity. Colliders are placed in [`CollisionLayers`] of the entity, if any.
+/// Bodies of [`Disabled`] entities are created disabled. Must be added to `Update` stage before [`step_physics`]
+pub fn spawn_physics_bodies(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    body_world: SubWorld<(&RigidBodyComponent, &ColliderComponent, &Transform, &CollisionLayers, &Disabled, &PhysicsBodyHandle)>,
+    mut cmd: Write<CommandBuffer>,
+){
+    profile_scope!("spawn_physics_bodies");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    let alive: HashSet<_> = body_world
+        .query::<&PhysicsBodyHandle>()
+        .iter()
+        .map(|(_, handle)| handle.0)
+        .collect();
+
+    let despawned: Vec<_> = physics.bodies()
+        .iter()
+        .filter(|(handle, _)| !alive.contains(handle))
+        .filter(|(handle, _)| physics.body_entity(*handle).is_some())
+        .map(|(handle, _)| handle)
+        .collect();
+
+    for handle in despawned {
+        physics.remove_body(handle);
+    }
+
+    // Handles, which don't match the bodies, e.g. loaded without the handler, are dropped to recreate the bodies
+    for (entity, handle) in &mut body_world.query::<&PhysicsBodyHandle>() {
+        let stale = match physics.body_entity(handle.0) {
+            Some(owner) => owner != entity,
+            None => physics.body(*handle).is_none(),
+        };
+
+        if stale {
+            cmd.remove_one::<PhysicsBodyHandle>(entity);
+        }
+    }
+
+    let mut body_query = body_world
+        .query::<(&RigidBodyComponent, &ColliderComponent, &Transform, Option<&CollisionLayers>, Option<&Disabled>)>()
+        .without::<&PhysicsBodyHandle>();
+
+    for (entity, (body, collider, transform, layers, disabled)) in body_query.iter() {
+        let mut collider = collider.build();
+        if let Some(layers) = layers {
+            collider.set_collision_groups(layers.interaction_groups());
+        }
+
+        let mut body = body.build(transform);
+        body.set_enabled(disabled.is_none());
+
+        let handle = physics.insert_entity(entity, body, collider);
+        cmd.insert_one(entity, handle);
+    }
+}
+
+/// Apply changed [`CollisionLayers`] to the colliders of the spawned bodies.
+/// Must be added to `Update` stage before [`step_physics`]
+pub fn sync_collision_layers(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    layers_world: SubWorld<(&PhysicsBodyHandle, &CollisionLayers)>,
+){
+    profile_scope!("sync_collision_layers");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    for (_, (handle, layers)) in &mut layers_world.query::<(&PhysicsBodyHandle, &CollisionLayers)>() {
+        let Some(collider) = physics.collider_mut(*handle) else { continue };
+        let groups = layers.interaction_groups();
+
+        if collider.collision_groups() != groups {
+            collider.set_collision_groups(groups);
+        }
+    }
+}
+
+/// Disable bodies of the [`Disabled`] entities in [`PhysicsHandler`], leaving them and their colliders
+/// out of the simulation, and enable them back, when the marker is removed.
+/// Must be added to `Update` stage before [`step_physics`]
+pub fn sync_disabled_bodies(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    body_world: SubWorld<(&PhysicsBodyHandle, &Disabled)>,
+){
+    profile_scope!("sync_disabled_bodies");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    for (_, (handle, disabled)) in &mut body_world.query::<(&PhysicsBodyHandle, Option<&Disabled>)>() {
+        let Some(body) = physics.body_mut(*handle) else { continue };
+        let enabled = disabled.is_none();
+
+        if body.is_enabled() != enabled {
+            body.set_enabled(enabled);
+        }
+    }
+}
+
+/// Create joints for the entities with joint component `J`, whose both bodies exist, and recreate
+/// joints, which were removed with any of the bodies. Must be added to `Update` stage
+/// after [`spawn_physics_bodies`]
+pub fn spawn_physics_joints<J: JointComponent>(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    joint_world: SubWorld<(&J, &PhysicsJointHandle, &PhysicsBodyHandle)>,
+    mut cmd: Write<CommandBuffer>,
+){
+    profile_scope!(format!("spawn_physics_joints<{}>", std::any::type_name::<J>()));
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    for (entity, (_, handle)) in &mut joint_world.query::<(&J, &PhysicsJointHandle)>() {
+        if physics.joint(handle.0).is_none() {
+            cmd.remove_one::<PhysicsJointHandle>(entity);
+        }
+    }
+
+    for (entity, joint) in &mut joint_world.query::<&J>().without::<&PhysicsJointHandle>() {
+        let (body1, body2) = joint.bodies();
+        let (Ok(body1), Ok(body2)) = (
+            joint_world.get::<PhysicsBodyHandle>(body1),
+            joint_world.get::<PhysicsBodyHandle>(body2),
+        ) else { continue };
+
+        let handle = physics.insert_joint(entity, body1.0, body2.0, joint.build());
+        cmd.insert_one(entity, PhysicsJointHandle(handle));
+    }
+}
+
+/// Remove joints of the despawned entities and of the ones, whose [`PhysicsJointHandle`] was removed.
+/// Must be added to `Update` stage before [`step_physics`]
+pub fn remove_physics_joints(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    joint_world: SubWorld<&PhysicsJointHandle>,
+){
+    profile_scope!("remove_physics_joints");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    let alive: HashSet<_> = joint_world
+        .query::<&PhysicsJointHandle>()
+        .iter()
+        .map(|(_, handle)| handle.0)
+        .collect();
+
+    let removed: Vec<_> = physics.impulse_joints()
+        .iter()
+        .filter(|(handle, _)| !alive.contains(handle))
+        .filter(|(handle, _)| physics.joint_entity(*handle).is_some())
+        .map(|(handle, _)| handle)
+        .collect();
+
+    for handle in removed {
+        physics.remove_joint(handle);
+    }
+}
+
+/// Move kinematic bodies with [`KinematicVelocity`]: position-based ones through their transforms,
+/// velocity-based ones by setting the velocity of the body. [`Disabled`] entities are skipped. Must be added to `Update` stage
+/// before [`sync_kinematic_bodies`]
+pub fn apply_kinematic_velocities(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    body_world: SubWorld<(&PhysicsBodyHandle, &KinematicVelocity, &mut Transform)>,
+    time: Read<Time>,
+){
+    profile_scope!("apply_kinematic_velocities");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    let query = &mut body_world
+        .query::<(&PhysicsBodyHandle, &KinematicVelocity, &mut Transform)>()
+        .without::<&Disabled>();
+    for (_, (handle, velocity, mut transform)) in query {
+        let Some(body) = physics.body_mut(*handle) else { continue };
+
+        match body.body_type() {
+            RigidBodyType::KinematicPositionBased => {
+                let position = velocity.integrate(&to_isometry(&transform), time.fixed_delta_seconds());
+                apply_isometry(&mut transform, &position);
+            },
+            RigidBodyType::KinematicVelocityBased => {
+                body.set_linvel(velocity.linear, true);
+                body.set_angvel(velocity.angular, true);
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Push transforms of kinematic and fixed bodies into [`PhysicsHandler`].
+/// Must be added to `Update` stage before [`step_physics`]
+pub fn sync_kinematic_bodies(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    body_world: SubWorld<(&PhysicsBodyHandle, &Transform)>,
+){
+    profile_scope!("sync_kinematic_bodies");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    for (_, (handle, transform)) in &mut body_world.query::<(&PhysicsBodyHandle, &Transform)>() {
+        let Some(body) = physics.body_mut(*handle) else { continue };
+        let position = to_isometry(transform);
+
+        match body.body_type() {
+            RigidBodyType::KinematicPositionBased => body.set_next_kinematic_position(position),
+            RigidBodyType::Fixed if *body.position() != position => body.set_position(position, true),
+            _ => {},
+        }
+    }
+}
+
+/// Apply [`ExternalForces`] of the entities to their bodies.
+/// Must be added to `Update` stage before [`step_physics`]
+pub fn apply_external_forces(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    forces_world: SubWorld<(&PhysicsBodyHandle, &mut ExternalForces)>,
+){
+    profile_scope!("apply_external_forces");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    for (_, (handle, mut forces)) in &mut forces_world.query::<(&PhysicsBodyHandle, &mut ExternalForces)>() {
+        let Some(body) = physics.body_mut(*handle) else { continue };
+        forces.apply(body);
+    }
+}
+
+/// Advance [`PhysicsHandler`] singleton by the fixed time step and send [`CollisionEvents`]
+/// and [`TriggerEvents`] of the step as separate events. Must be added to `Update` stage
+///
+/// [`TriggerEvents`]: flatbox_physics::TriggerEvents
+pub fn step_physics(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    time: Read<Time>,
+    mut events: Write<Events>,
+){
+    profile_scope!("step_physics");
+
+    for (_, mut physics) in &mut physics_world.query::<&mut PhysicsHandler>() {
+        physics.step(time.fixed_delta_seconds());
+
+        let collisions = physics.drain_collision_events();
+        if !collisions.is_empty() {
+            events.send(CollisionEvents(collisions));
+        }
+
+        let triggers = physics.drain_trigger_events();
+        if !triggers.is_empty() {
+            events.send(triggers);
+        }
+    }
+}
+
+/// Write positions of the simulated bodies back into their transforms.
+/// Must be added to `Update` stage after [`step_physics`]
+pub fn sync_physics_transforms(
+    physics_world: SubWorld<&PhysicsHandler>,
+    body_world: SubWorld<(&PhysicsBodyHandle, &mut Transform)>,
+){
+    profile_scope!("sync_physics_transforms");
+
+    let mut physics_query = physics_world.query::<&PhysicsHandler>();
+    let Some((_, physics)) = physics_query.iter().next() else { return };
+
+    for (_, (handle, mut transform)) in &mut body_world.query::<(&PhysicsBodyHandle, &mut Transform)>() {
+        let Some(body) = physics.body(*handle) else { continue };
+
+        if body.is_dynamic() || body.body_type() == RigidBodyType::KinematicVelocityBased {
+            apply_isometry(&mut transform, body.position());
+        }
+    }
+}
+
+/// Draw debug shapes of [`PhysicsHandler`] with [`Gizmos`] singleton, if the debug renderer
+/// is set with [`PhysicsHandler::set_debug_renderer`]. Must be added to `PreRender` stage
+pub fn render_physics_debug(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    gizmos_world: SubWorld<&mut Gizmos>,
+){
+    profile_scope!("render_physics_debug");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    let mut gizmos_query = gizmos_world.query::<&mut Gizmos>();
+    let Some((_, mut gizmos)) = gizmos_query.iter().next() else { return };
+
+    physics.debug_render(&mut GizmosDebugBackend { gizmos: &mut gizmos });
+}
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use flatbox_core::{color::Color, math::{glm, transform::Transform}, profile_scope, time::Time};
+use flatbox_ecs::{*, disabled::Disabled, event::Events};
+use flatbox_physics2d::{
+    rapier2d::prelude::{DebugColor, DebugRenderBackend, DebugRenderObject, Isometry, Point, Real, RigidBodyType, SharedShape},
+    apply_isometry, to_isometry,
+    ColliderComponent, CollisionEvents, PhysicsBodyHandle, PhysicsHandler, RigidBodyComponent,
+};
+use flatbox_render::{gizmos::Gizmos, tilemap::Tilemap};
+
+/// Debug render backend, which draws lines of the 2D physics debug renderer with [`Gizmos`] at Z = 0
+pub struct GizmosDebugBackend<'a> {
+    pub gizmos: &'a mut Gizmos,
+}
+
+impl DebugRenderBackend for GizmosDebugBackend<'_> {
+    fn draw_line(&mut self, _object: DebugRenderObject, a: Point<Real>, b: Point<Real>, color: DebugColor) {
+        // Debug colors are HSLA
+        self.gizmos.line(
+            glm::vec3(a.x, a.y, 0.0),
+            glm::vec3(b.x, b.y, 0.0),
+            Color::hsla(color[0], color[1], color[2], color[3]),
+        );
+    }
+}
+
+/// Create bodies for the spawned entities with [`RigidBodyComponent`], [`ColliderComponent`]
+/// and [`Transform`], and remove bodies of the despawned ones. Entities receive [`PhysicsBodyHandle`]
+/// component. Bodies of [`Disabled`] entities are created disabled. Must be added to `Update` stage before [`step_physics`]
+pub fn spawn_physics_bodies(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    body_world: SubWorld<(&RigidBodyComponent, &ColliderComponent, &Transform, &Disabled, &PhysicsBodyHandle)>,
+    mut cmd: Write<CommandBuffer>,
+){
+    profile_scope!("spawn_physics2d_bodies");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    let alive: HashSet<_> = body_world
+        .query::<&PhysicsBodyHandle>()
+        .iter()
+        .map(|(_, handle)| handle.0)
+        .collect();
+
+    let despawned: Vec<_> = physics.bodies()
+        .iter()
+        .filter(|(handle, _)| !alive.contains(handle))
+        .filter(|(handle, _)| physics.body_entity(*handle).is_some())
+        .map(|(handle, _)| handle)
+        .collect();
+
+    for handle in despawned {
+        physics.remove_body(handle);
+    }
+
+    let mut body_query = body_world
+        .query::<(&RigidBodyComponent, &ColliderComponent, &Transform, Option<&Disabled>)>()
+        .without::<&PhysicsBodyHandle>();
+
+    for (entity, (body, collider, transform, disabled)) in body_query.iter() {
+        let mut body = body.build(transform);
+        body.set_enabled(disabled.is_none());
+
+        let handle = physics.insert_entity(entity, body, collider.build());
+        cmd.insert_one(entity, handle);
+    }
+}
+
+/// Add fixed [`RigidBodyComponent`] and compound [`ColliderComponent`] of the solid tiles
+/// to the entities with [`Tilemap`] and [`Transform`], which have no collider yet. Changes of
+/// the tiles after that aren't applied to the collider. Must be added to `Update` stage
+/// before [`spawn_physics_bodies`]
+pub fn spawn_tilemap_colliders(
+    tilemap_world: SubWorld<(&Tilemap, &Transform, &ColliderComponent)>,
+    mut cmd: Write<CommandBuffer>,
+){
+    profile_scope!("spawn_tilemap_colliders");
+
+    let mut tilemap_query = tilemap_world
+        .query::<(&Tilemap, &Transform)>()
+        .without::<&ColliderComponent>();
+
+    for (entity, (tilemap, _)) in tilemap_query.iter() {
+        let Some(collider) = tilemap_collider(tilemap) else { continue };
+        cmd.insert(entity, (RigidBodyComponent::fixed(), collider));
+    }
+}
+
+/// Compound collider of box shapes of the solid tiles. Returns `None`, if there are no solid tiles
+fn tilemap_collider(tilemap: &Tilemap) -> Option<ColliderComponent> {
+    let shapes: Vec<_> = tilemap.colliders()
+        .into_iter()
+        .map(|collider| (
+            Isometry::translation(collider.center.x, collider.center.y),
+            SharedShape::cuboid(collider.half_extents.x, collider.half_extents.y),
+        ))
+        .collect();
+
+    (!shapes.is_empty()).then(|| ColliderComponent::new(SharedShape::compound(shapes)))
+}
+
+/// Disable bodies of the [`Disabled`] entities in 2D [`PhysicsHandler`], leaving them and their colliders
+/// out of the simulation, and enable them back, when the marker is removed.
+/// Must be added to `Update` stage before [`step_physics`]
+pub fn sync_disabled_bodies(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    body_world: SubWorld<(&PhysicsBodyHandle, &Disabled)>,
+){
+    profile_scope!("sync_disabled_bodies2d");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    for (_, (handle, disabled)) in &mut body_world.query::<(&PhysicsBodyHandle, Option<&Disabled>)>() {
+        let Some(body) = physics.body_mut(*handle) else { continue };
+        let enabled = disabled.is_none();
+
+        if body.is_enabled() != enabled {
+            body.set_enabled(enabled);
+        }
+    }
+}
+
+/// Push transforms of kinematic and fixed bodies into 2D [`PhysicsHandler`].
+/// Must be added to `Update` stage before [`step_physics`]
+pub fn sync_kinematic_bodies(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    body_world: SubWorld<(&PhysicsBodyHandle, &Transform)>,
+){
+    profile_scope!("sync_kinematic_bodies2d");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    for (_, (handle, transform)) in &mut body_world.query::<(&PhysicsBodyHandle, &Transform)>() {
+        let Some(body) = physics.body_mut(*handle) else { continue };
+        let position = to_isometry(transform);
+
+        match body.body_type() {
+            RigidBodyType::KinematicPositionBased => body.set_next_kinematic_position(position),
+            RigidBodyType::Fixed if *body.position() != position => body.set_position(position, true),
+            _ => {},
+        }
+    }
+}
+
+/// Advance 2D [`PhysicsHandler`] singleton by the fixed time step and send [`CollisionEvents`]
+/// of the step as a separate event. Must be added to `Update` stage
+pub fn step_physics(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    time: Read<Time>,
+    mut events: Write<Events>,
+){
+    profile_scope!("step_physics2d");
+
+    for (_, mut physics) in &mut physics_world.query::<&mut PhysicsHandler>() {
+        physics.step(time.fixed_delta_seconds());
+
+        let collisions = physics.drain_collision_events();
+        if !collisions.is_empty() {
+            events.send(CollisionEvents(collisions));
+        }
+    }
+}
+
+/// Write positions of the simulated bodies back into their transforms.
+/// Must be added to `Update` stage after [`step_physics`]
+pub fn sync_physics_transforms(
+    physics_world: SubWorld<&PhysicsHandler>,
+    body_world: SubWorld<(&PhysicsBodyHandle, &mut Transform)>,
+){
+    profile_scope!("sync_physics2d_transforms");
+
+    let mut physics_query = physics_world.query::<&PhysicsHandler>();
+    let Some((_, physics)) = physics_query.iter().next() else { return };
+
+    for (_, (handle, mut transform)) in &mut body_world.query::<(&PhysicsBodyHandle, &mut Transform)>() {
+        let Some(body) = physics.body(*handle) else { continue };
+
+        if body.is_dynamic() || body.body_type() == RigidBodyType::KinematicVelocityBased {
+            apply_isometry(&mut transform, body.position());
+        }
+    }
+}
+
+/// Draw debug shapes of 2D [`PhysicsHandler`] with [`Gizmos`] singleton, if the debug renderer
+/// is set with [`PhysicsHandler::set_debug_renderer`]. Must be added to `PreRender` stage
+pub fn render_physics_debug(
+    physics_world: SubWorld<&mut PhysicsHandler>,
+    gizmos_world: SubWorld<&mut Gizmos>,
+){
+    profile_scope!("render_physics2d_debug");
+
+    let mut physics_query = physics_world.query::<&mut PhysicsHandler>();
+    let Some((_, mut physics)) = physics_query.iter().next() else { return };
+
+    let mut gizmos_query = gizmos_world.query::<&mut Gizmos>();
+    let Some((_, mut gizmos)) = gizmos_query.iter().next() else { return };
+
+    physics.debug_render(&mut GizmosDebugBackend { gizmos: &mut gizmos });
+}
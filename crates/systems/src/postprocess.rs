@@ -0,0 +1,106 @@
+use anyhow::Result;
+use flatbox_core::profile_scope;
+use flatbox_core::math::glm;
+use flatbox_ecs::*;
+use flatbox_render::{
+    error::RenderError,
+    pbr::camera::Camera,
+    postprocess::{AntiAliasing, CameraImperfections, DepthOfField, MotionBlur, PostEffect, PostProcessStack, PostProcessing},
+    renderer::Renderer,
+};
+
+/// Camera with optional components of post-processing effects
+type CameraEffects<'a> = (
+    &'a Camera,
+    Option<&'a AntiAliasing>,
+    Option<&'a MotionBlur>,
+    Option<&'a CameraImperfections>,
+);
+
+/// Redirect rendering of the scene into [`PostProcessing`] targets and jitter
+/// the projection for temporal anti-aliasing of the active camera
+pub fn begin_post_processing(
+    post_world: SubWorld<&mut PostProcessing>,
+    camera_world: SubWorld<(&Camera, Option<&AntiAliasing>)>,
+    anti_aliasing_world: SubWorld<&AntiAliasing>,
+    mut renderer: Write<Renderer>,
+) {
+    profile_scope!("begin_post_processing");
+
+    let mut global_query = anti_aliasing_world.query::<&AntiAliasing>().without::<&Camera>();
+    let global_anti_aliasing = global_query.iter().map(|(_, anti_aliasing)| anti_aliasing).next();
+
+    let jitter = camera_world
+        .query::<(&Camera, Option<&AntiAliasing>)>()
+        .iter()
+        .find(|(_, (camera, _))| camera.is_active())
+        .and_then(|(_, (_, anti_aliasing))| anti_aliasing.or(global_anti_aliasing))
+        .map(|anti_aliasing| anti_aliasing.jitter(renderer.frame(), renderer.extent()))
+        .unwrap_or_else(glm::Vec2::zeros);
+
+    renderer.set_projection_jitter(jitter);
+
+    if let Some((_, mut post_processing)) = post_world.query::<&mut PostProcessing>().iter().next() {
+        post_processing.begin(&mut renderer);
+    }
+}
+
+/// Apply effects of the active camera, then effects of [`PostProcessStack`], and present the scene.
+/// [`AntiAliasing`] singleton (without camera) is used, when the camera has no own anti-aliasing
+pub fn apply_post_processing(
+    post_world: SubWorld<&mut PostProcessing>,
+    camera_world: SubWorld<CameraEffects>,
+    anti_aliasing_world: SubWorld<&AntiAliasing>,
+    stack_world: SubWorld<&PostProcessStack>,
+    mut renderer: Write<Renderer>,
+) -> Result<()> {
+    profile_scope!("apply_post_processing");
+
+    let mut post_query = post_world.query::<&mut PostProcessing>();
+    let Some((_, mut post_processing)) = post_query.iter().next() else {
+        return Ok(());
+    };
+
+    let mut global_query = anti_aliasing_world.query::<&AntiAliasing>().without::<&Camera>();
+    let global_anti_aliasing = global_query.iter().map(|(_, anti_aliasing)| anti_aliasing).next();
+
+    let mut camera_query = camera_world.query::<CameraEffects>();
+    let mut active_cameras = camera_query.iter().filter(|(_, (camera, ..))| camera.is_active());
+    let depth_of_field;
+    let mut effects: Vec<&dyn PostEffect> = vec![];
+
+    if let Some((_, (camera, anti_aliasing, motion_blur, imperfections))) = active_cameras.next() {
+        if active_cameras.next().is_some() {
+            Err(RenderError::MultipleActiveCameras)?;
+        }
+
+        // Anti-aliasing is applied first, since other effects don't produce hard edges
+        if let Some(anti_aliasing) = anti_aliasing.or(global_anti_aliasing) {
+            effects.push(anti_aliasing);
+        }
+
+        depth_of_field = DepthOfField::from_camera(camera);
+
+        if let Some(ref depth_of_field) = depth_of_field {
+            effects.push(depth_of_field);
+        }
+
+        if let Some(motion_blur) = motion_blur {
+            effects.push(motion_blur);
+        }
+
+        if let Some(imperfections) = imperfections {
+            effects.push(imperfections);
+        }
+    }
+
+    let mut stack_query = stack_world.query::<&PostProcessStack>();
+
+    for (_, stack) in stack_query.iter() {
+        effects.extend(stack.effects());
+    }
+
+    post_processing.resolve(&mut renderer, &effects)?;
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use flatbox_assets::{
+    preload::{AssetManifest, AssetPreloader, PreloadedAssets},
+};
+use flatbox_core::logger::{error, info};
+use flatbox_ecs::{*, event::Events};
+use flatbox_egui::{self as egui, backend::EguiBackend};
+
+/// ECS event, which is sent once, when all assets of the manifest are preloaded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssetsPreloaded;
+
+/// Singleton component, which reports progress of assets preloading, e.g. for loading
+/// bars. The app is in loading state, until [`PreloadProgress::is_finished`] is `true`.
+/// It's driven by [`update_preload_progress`] system
+#[derive(Debug, Clone)]
+pub struct PreloadProgress {
+    preloader: AssetPreloader,
+    finished: bool,
+}
+
+impl PreloadProgress {
+    /// Start loading assets of the manifest on a background thread
+    pub fn start(manifest: &AssetManifest) -> Self {
+        PreloadProgress {
+            preloader: AssetPreloader::start(manifest),
+            finished: false,
+        }
+    }
+
+    /// Loading progress in range `0.0..=1.0`
+    pub fn progress(&self) -> f32 {
+        self.preloader.progress()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn loaded(&self) -> usize {
+        self.preloader.loaded()
+    }
+
+    pub fn total(&self) -> usize {
+        self.preloader.total()
+    }
+
+    /// Get preloaded assets. It's complete only after loading is finished
+    pub fn assets(&self) -> PreloadedAssets {
+        self.preloader.assets()
+    }
+}
+
+/// Finish loading state, when all assets are loaded. Must be added to
+/// `PreRender` stage (or to `Update` stage in headless mode)
+pub fn update_preload_progress(
+    progress_world: SubWorld<&mut PreloadProgress>,
+    mut events: Write<Events>,
+){
+    for (_, mut progress) in &mut progress_world.query::<&mut PreloadProgress>() {
+        if progress.finished || !progress.preloader.is_done() {
+            continue;
+        }
+
+        for (path, e) in progress.preloader.take_errors() {
+            error!("Cannot preload asset `{}`: {e}", path.display());
+        }
+
+        info!("Preloaded {} of {} assets", progress.assets().len(), progress.total());
+
+        progress.finished = true;
+        events.send(AssetsPreloaded);
+    }
+}
+
+/// Draw loading bar, while assets are being preloaded. Must be added to `Render` stage
+pub fn draw_preload_progress(
+    progress_world: SubWorld<&PreloadProgress>,
+    egui_world: SubWorld<&mut EguiBackend>,
+){
+    for (_, progress) in &mut progress_world.query::<&PreloadProgress>() {
+        if progress.is_finished() {
+            continue;
+        }
+
+        for (_, mut backend) in &mut egui_world.query::<&mut EguiBackend>() {
+            egui::Area::new(egui::Id::new("preload_progress"))
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .show(backend.context(), |ui| {
+                    ui.set_width(300.0);
+                    ui.add(egui::ProgressBar::new(progress.progress())
+                        .text(format!("Loading {}/{}", progress.loaded(), progress.total())));
+                });
+        }
+    }
+}
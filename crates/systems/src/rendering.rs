@@ -1,31 +1,185 @@
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
 // use flatbox_assets::resources::Resources;
-use flatbox_core::{math::transform::Transform, AppExit};
-use flatbox_ecs::*;
+use flatbox_core::{color::Color, math::{glm, transform::Transform}, profile_scope};
+use flatbox_ecs::{*, disabled::Disabled};
 use flatbox_egui::{backend::EguiBackend, command::DrawEguiCommand};
 use flatbox_render::{
     context::{ControlFlow, Display}, error::RenderError, pbr::{
-        camera::Camera, material::Material, model::Model
-    }, renderer::{ClearCommand, DrawModelCommand, PrepareModelCommand, RenderCameraCommand, Renderer}
+        batch::StaticBatch,
+        camera::Camera,
+        environment::EnvironmentMap,
+        fog::Fog,
+        light::{DirectionalLight, LightSettings, PointLight, SceneLights, SpotLight},
+        material::Material,
+        mesh::{Mesh, MeshType},
+        model::Model,
+        shadow::ShadowMaps,
+    }, renderer::{ClearCommand, DrawModelCommand, PrepareModelCommand, RenderCameraCommand, RenderMode, Renderer},
+    gizmos::{DrawLinesCommand, Gizmos, LineMaterial},
+    occlusion::{OcclusionCulled, OcclusionCulling},
+    outline::{Outlined, Outlines},
+    screenshot::{ScreenshotCommand, Screenshots},
+    text::{Text2d, Text3d, TextRenderer},
+    tilemap::{DrawTilemapCommand, Tilemap, TilemapMaterial},
 };
 
 pub fn clear_screen(mut renderer: Write<Renderer>) -> Result<()> {
-    renderer.execute(&mut ClearCommand(0.1, 0.1, 0.1))?;
+    profile_scope!("clear_screen");
+
+    renderer.execute(&mut ClearCommand(Color::rgb(0.1, 0.1, 0.1)))?;
     
     Ok(())
 }
 
+/// Upload lights of the world to material pipelines relative to the active camera's render origin.
+/// The number of lights is limited by [`LightSettings`] singleton, if present
+pub fn collect_lights(
+    directional_world: SubWorld<&DirectionalLight>,
+    point_world: SubWorld<(&PointLight, &Transform)>,
+    spot_world: SubWorld<(&SpotLight, &Transform)>,
+    camera_world: SubWorld<(&Camera, &Transform)>,
+    settings_world: SubWorld<&LightSettings>,
+    mut renderer: Write<Renderer>,
+) {
+    profile_scope!("collect_lights");
+
+    let mut directional_query = directional_world.query::<&DirectionalLight>().without::<&Disabled>();
+    let mut point_query = point_world.query::<(&PointLight, &Transform)>().without::<&Disabled>();
+    let mut spot_query = spot_world.query::<(&SpotLight, &Transform)>().without::<&Disabled>();
+
+    let (origin, viewer) = camera_world
+        .query::<(&Camera, &Transform)>()
+        .iter()
+        .find(|(_, (camera, _))| camera.is_active())
+        .map(|(_, (camera, transform))| {
+            let origin = camera.upload_origin(transform);
+            (origin, glm::convert(camera.render_origin(transform) - origin))
+        })
+        .unwrap_or_default();
+
+    let relative = |transform: &Transform| -> glm::Vec3 { glm::convert(transform.translation - origin) };
+
+    let mut lights = SceneLights {
+        directional: directional_query.iter().map(|(_, light)| *light).collect(),
+        point: point_query.iter().map(|(_, (light, transform))| (relative(transform), *light)).collect(),
+        spot: spot_query.iter().map(|(_, (light, transform))| (relative(transform), *light)).collect(),
+    };
+
+    let settings = settings_world
+        .query::<&LightSettings>()
+        .iter()
+        .map(|(_, settings)| *settings)
+        .next()
+        .unwrap_or_default();
+
+    lights.limit(&settings, &viewer);
+    renderer.set_lights(&lights);
+}
+
+/// Upload [`Fog`] singleton to material pipelines. Fog is disabled, if there is none
+pub fn update_fog(
+    fog_world: SubWorld<&Fog>,
+    mut renderer: Write<Renderer>,
+) {
+    profile_scope!("update_fog");
+
+    let mut query = fog_world.query::<&Fog>();
+    renderer.set_fog(query.iter().map(|(_, fog)| fog).next());
+}
+
+/// Bind [`EnvironmentMap`] singleton for material pipelines. Image-based lighting is disabled, if there is none
+pub fn update_environment(
+    environment_world: SubWorld<&EnvironmentMap>,
+    mut renderer: Write<Renderer>,
+) {
+    profile_scope!("update_environment");
+
+    let mut query = environment_world.query::<&EnvironmentMap>();
+    renderer.set_environment(query.iter().map(|(_, environment)| environment).next());
+}
+
+/// Fit [`ShadowMaps`] singleton to the active camera and the lights, uploaded by [`collect_lights`],
+/// so it must run afterwards. Shadows are disabled, if there is no singleton
+pub fn update_shadows(
+    shadows_world: SubWorld<&mut ShadowMaps>,
+    camera_world: SubWorld<(&Camera, &Transform)>,
+    mut renderer: Write<Renderer>,
+) {
+    profile_scope!("update_shadows");
+
+    let mut shadows_query = shadows_world.query::<&mut ShadowMaps>();
+    let Some((_, mut shadows)) = shadows_query.iter().next() else {
+        renderer.set_shadows(None);
+        return;
+    };
+
+    let mut camera_query = camera_world.query::<(&Camera, &Transform)>();
+    match camera_query.iter().find(|(_, (camera, _))| camera.is_active()) {
+        Some((_, (camera, transform))) => shadows.update(renderer.lights(), camera, transform),
+        None => shadows.disable(),
+    }
+
+    renderer.set_shadows(Some(&shadows));
+}
+
 pub fn bind_material<M: Material>(mut renderer: Write<Renderer>) {
     renderer.bind_material::<M>();
 }
 
+/// Merge models of the material, marked with [`StaticBatch`], into a single model per group.
+/// The first entity of each group receives the merged model, the others are despawned.
+/// Entities without mesh and [`Disabled`] ones are left as is
+pub fn bake_static_batches<M: Material>(
+    batch_world: SubWorld<(&Model, &M, &Transform, &StaticBatch)>,
+    mut cmd: Write<CommandBuffer>,
+) {
+    profile_scope!(format!("bake_static_batches<{}>", std::any::type_name::<M>()));
+
+    let mut query = batch_world.query::<(&Model, &M, &Transform, &StaticBatch)>().without::<&Disabled>();
+    let mut groups: HashMap<u32, Vec<(Entity, &Mesh, &Transform)>> = HashMap::new();
+
+    for (entity, (model, _, transform, batch)) in query.iter() {
+        let group = groups.entry(batch.group).or_default();
+
+        match model.mesh {
+            Some(ref mesh) => group.push((entity, mesh, transform)),
+            None => cmd.remove_one::<StaticBatch>(entity),
+        }
+    }
+
+    for batch in groups.into_values() {
+        let Some(&(target, _, _)) = batch.first() else { continue };
+
+        let meshes: Vec<_> = batch.iter().map(|(_, mesh, transform)| (*mesh, *transform)).collect();
+        let mesh = Mesh::merge(&meshes);
+
+        for &(entity, _, _) in &batch[1..] {
+            cmd.despawn(entity);
+        }
+
+        cmd.insert(target, (Model::new(MeshType::Generic, mesh), Transform::identity()));
+        cmd.remove_one::<StaticBatch>(target);
+    }
+}
+
+/// Draw models of the material with the active camera. [`RenderMode`] singleton, if present,
+/// switches the way models are drawn. Models are sorted by material state and mesh to avoid redundant state changes.
+/// [`OcclusionCulled`] models, which were hidden in the previous frame, and [`Disabled`] ones are skipped
 pub fn render_material<M: Material>(
-    model_world: SubWorld<(&mut Model, &M, &Transform)>,
+    model_world: SubWorld<(&mut Model, &M, &Transform, Option<&OcclusionCulled>)>,
     camera_world: SubWorld<(&mut Camera, &Transform)>,
+    render_mode_world: SubWorld<&RenderMode>,
     mut renderer: Write<Renderer>,
 ) -> Result<()> {
+    profile_scope!(format!("render_material<{}>", std::any::type_name::<M>()));
+
+    if let Some((_, render_mode)) = render_mode_world.query::<&RenderMode>().iter().next() {
+        renderer.set_render_mode(*render_mode);
+    }
+
     let mut found_active_camera = false;
 
     for (_, (mut camera, transform)) in &mut camera_world.query::<(&mut Camera, &Transform)>() {
@@ -36,10 +190,31 @@ pub fn render_material<M: Material>(
                 found_active_camera = true;
 
                 renderer.execute(&mut RenderCameraCommand::<M>::new(&mut camera, transform))?;
-                for (_, (mut model, material, transform)) in &mut model_world.query::<(&mut Model, &M, &Transform)>() {
+
+                let mut model_query = model_world
+                    .query::<(&mut Model, &M, &Transform, Option<&OcclusionCulled>)>()
+                    .without::<&Disabled>();
+                let mut models = Vec::new();
+
+                for (_, (mut model, material, transform, culled)) in model_query.iter() {
                     renderer.execute(&mut PrepareModelCommand::new(&mut model, material))?;
-                    renderer.execute(&mut DrawModelCommand::new(&model, material, transform))?;
+
+                    if culled.is_none_or(OcclusionCulled::is_visible) {
+                        models.push((model, material, transform));
+                    }
+                }
+
+                // Models with the same material state and mesh are drawn one after another,
+                // so the material is set up once for all of them
+                models.sort_by_key(|(model, material, _)| {
+                    (material.state_key(), model.mesh.as_ref().map(|mesh| mesh.vertex_array().id()))
+                });
+
+                for (mut model, material, transform) in models {
+                    renderer.execute(&mut DrawModelCommand::new(&mut model, material, transform))?;
                 }
+
+                renderer.reset_material_state();
             }
         }
     }
@@ -47,11 +222,212 @@ pub fn render_material<M: Material>(
     Ok(())
 }
 
+/// Draw models of the material into [`ShadowMaps`] singleton, if present
+pub fn render_shadows<M: Material>(
+    shadows_world: SubWorld<&mut ShadowMaps>,
+    model_world: SubWorld<(&mut Model, &M, &Transform)>,
+    mut renderer: Write<Renderer>,
+) -> Result<()> {
+    profile_scope!(format!("render_shadows<{}>", std::any::type_name::<M>()));
+
+    let mut shadows_query = shadows_world.query::<&mut ShadowMaps>();
+    let Some((_, mut shadows)) = shadows_query.iter().next() else { return Ok(()) };
+
+    if shadows.cascades().is_empty() && shadows.point_lights().is_empty() {
+        return Ok(());
+    }
+
+    let mut model_query = model_world.query::<(&mut Model, &M, &Transform)>().without::<&Disabled>();
+    let mut models = Vec::new();
+
+    for (_, (mut model, material, transform)) in model_query.iter() {
+        renderer.execute(&mut PrepareModelCommand::new(&mut model, material))?;
+        models.push((model, transform));
+    }
+
+    shadows.render::<M>(&mut renderer, models.iter().map(|(model, transform)| (&**model, *transform)))?;
+
+    Ok(())
+}
+
+pub fn render_tilemaps(
+    tilemap_world: SubWorld<(&mut Tilemap, &TilemapMaterial, &Transform)>,
+    camera_world: SubWorld<(&mut Camera, &Transform)>,
+    mut renderer: Write<Renderer>,
+) -> Result<()> {
+    profile_scope!("render_tilemaps");
+
+    let mut found_active_camera = false;
+
+    for (_, (mut camera, transform)) in &mut camera_world.query::<(&mut Camera, &Transform)>() {
+        if camera.is_active() {
+            if found_active_camera {
+                Err(RenderError::MultipleActiveCameras)?;
+            } else {
+                found_active_camera = true;
+
+                renderer.execute(&mut RenderCameraCommand::<TilemapMaterial>::new(&mut camera, transform))?;
+                let mut tilemap_query = tilemap_world
+                    .query::<(&mut Tilemap, &TilemapMaterial, &Transform)>()
+                    .without::<&Disabled>();
+
+                for (_, (mut tilemap, material, transform)) in tilemap_query.iter() {
+                    renderer.execute(&mut DrawTilemapCommand::new(&mut tilemap, material, transform))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn render_gizmos(
+    gizmos_world: SubWorld<&mut Gizmos>,
+    camera_world: SubWorld<(&mut Camera, &Transform)>,
+    mut renderer: Write<Renderer>,
+) -> Result<()> {
+    profile_scope!("render_gizmos");
+
+    let mut gizmos_query = gizmos_world.query::<&mut Gizmos>();
+    let Some((_, mut gizmos)) = gizmos_query.iter().next() else { return Ok(()) };
+
+    let mut found_active_camera = false;
+
+    for (_, (mut camera, transform)) in &mut camera_world.query::<(&mut Camera, &Transform)>() {
+        if camera.is_active() {
+            if found_active_camera {
+                Err(RenderError::MultipleActiveCameras)?;
+            } else {
+                found_active_camera = true;
+
+                renderer.execute(&mut RenderCameraCommand::<LineMaterial>::new(&mut camera, transform))?;
+                renderer.execute(&mut DrawLinesCommand::new(&mut gizmos))?;
+            }
+        }
+    }
+
+    // Lines are collected anew every frame, even if there's nothing to draw them with
+    gizmos.clear();
+
+    Ok(())
+}
+
+pub fn render_outlines(
+    outlines_world: SubWorld<&mut Outlines>,
+    model_world: SubWorld<(&Model, &Outlined, &Transform)>,
+    camera_world: SubWorld<(&Camera, &Transform)>,
+    mut renderer: Write<Renderer>,
+) -> Result<()> {
+    profile_scope!("render_outlines");
+
+    let mut outlines_query = outlines_world.query::<&mut Outlines>();
+    let Some((_, mut outlines)) = outlines_query.iter().next() else { return Ok(()) };
+
+    let mut camera_query = camera_world.query::<(&Camera, &Transform)>();
+    let mut active_cameras = camera_query.iter().filter(|(_, (camera, _))| camera.is_active());
+
+    let Some((_, (camera, camera_transform))) = active_cameras.next() else { return Ok(()) };
+
+    if active_cameras.next().is_some() {
+        Err(RenderError::MultipleActiveCameras)?;
+    }
+
+    let mut model_query = model_world.query::<(&Model, &Outlined, &Transform)>().without::<&Disabled>();
+    outlines.render(&mut renderer, camera, camera_transform, model_query.iter().map(|(_, components)| components))?;
+
+    Ok(())
+}
+
+/// Test bounding boxes of [`OcclusionCulled`] models against the rendered scene
+pub fn render_occlusion_queries(
+    culling_world: SubWorld<&mut OcclusionCulling>,
+    model_world: SubWorld<(&Model, &Transform, &mut OcclusionCulled)>,
+    camera_world: SubWorld<(&Camera, &Transform)>,
+    mut renderer: Write<Renderer>,
+) -> Result<()> {
+    profile_scope!("render_occlusion_queries");
+
+    let mut culling_query = culling_world.query::<&mut OcclusionCulling>();
+    let Some((_, mut culling)) = culling_query.iter().next() else { return Ok(()) };
+
+    let mut camera_query = camera_world.query::<(&Camera, &Transform)>();
+    let mut active_cameras = camera_query.iter().filter(|(_, (camera, _))| camera.is_active());
+
+    let Some((_, (camera, camera_transform))) = active_cameras.next() else { return Ok(()) };
+
+    if active_cameras.next().is_some() {
+        Err(RenderError::MultipleActiveCameras)?;
+    }
+
+    let mut model_query = model_world.query::<(&Model, &Transform, &mut OcclusionCulled)>().without::<&Disabled>();
+    let mut models: Vec<_> = model_query.iter().map(|(_, components)| components).collect();
+
+    culling.render(
+        &mut renderer,
+        camera,
+        camera_transform,
+        models.iter_mut().map(|(model, transform, culled)| (*model, *transform, &mut **culled)),
+    )?;
+
+    Ok(())
+}
+
+pub fn render_text(
+    text_renderer_world: SubWorld<&mut TextRenderer>,
+    text_3d_world: SubWorld<(&Text3d, &Transform)>,
+    text_2d_world: SubWorld<(&Text2d, &Transform)>,
+    camera_world: SubWorld<(&Camera, &Transform)>,
+    mut renderer: Write<Renderer>,
+) -> Result<()> {
+    profile_scope!("render_text");
+
+    let mut text_renderer_query = text_renderer_world.query::<&mut TextRenderer>();
+    let Some((_, mut text_renderer)) = text_renderer_query.iter().next() else { return Ok(()) };
+
+    let mut camera_query = camera_world.query::<(&Camera, &Transform)>();
+    let mut active_cameras = camera_query.iter().filter(|(_, (camera, _))| camera.is_active());
+
+    let camera = active_cameras.next().map(|(_, components)| components);
+
+    if active_cameras.next().is_some() {
+        Err(RenderError::MultipleActiveCameras)?;
+    }
+
+    let mut text_3d_query = text_3d_world.query::<(&Text3d, &Transform)>().without::<&Disabled>();
+    let mut text_2d_query = text_2d_world.query::<(&Text2d, &Transform)>().without::<&Disabled>();
+
+    text_renderer.render(
+        &mut renderer,
+        camera,
+        text_3d_query.iter().map(|(_, components)| components),
+        text_2d_query.iter().map(|(_, components)| components),
+    )?;
+
+    Ok(())
+}
+
+pub fn capture_screenshots(
+    screenshots_world: SubWorld<&mut Screenshots>,
+    mut renderer: Write<Renderer>,
+) -> Result<()> {
+    profile_scope!("capture_screenshots");
+
+    for (_, mut screenshots) in &mut screenshots_world.query::<&mut Screenshots>() {
+        for path in screenshots.take_requests() {
+            renderer.execute(&mut ScreenshotCommand::save(path))?;
+        }
+    }
+
+    Ok(())
+}
+
 pub fn run_egui_backend(
     egui_world: SubWorld<&mut EguiBackend>,
     display: Read<Display>,
     mut control_flow: Write<ControlFlow>,
 ){
+    profile_scope!("run_egui_backend");
+
     control_flow.set_repaint_after(
         egui_world
             .query::<&mut EguiBackend>()
@@ -64,12 +440,13 @@ pub fn run_egui_backend(
 }
 
 pub fn draw_ui(
-    app_exit: SubWorld<&AppExit>,
     egui_world: SubWorld<&mut EguiBackend>,
     display: Read<Display>,
     mut control_flow: Write<ControlFlow>,
     mut renderer: Write<Renderer>,
 ){
+    profile_scope!("draw_ui");
+
     let mut egui_backend_query = egui_world.query::<&mut EguiBackend>();
     let mut egui_backend = egui_backend_query
         .iter()
@@ -77,9 +454,7 @@ pub fn draw_ui(
         .next()
         .unwrap();
 
-    if app_exit.query::<&AppExit>().iter().len() > 0 {
-        control_flow.exit();
-    } else if control_flow.repaint_after().is_zero() {
+    if control_flow.repaint_after().is_zero() {
         display.lock().window().request_redraw();
         control_flow.set_poll();
     } else if let Some(repaint_after_instant) = Instant::now().checked_add(control_flow.repaint_after()) {
@@ -87,5 +462,7 @@ pub fn draw_ui(
         control_flow.set_repaint_after(Duration::ZERO);
     }
 
+    renderer.begin_pass_timer("egui");
     renderer.execute(&mut DrawEguiCommand::new(&mut egui_backend)).unwrap();    
+    renderer.end_pass_timer();
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+use std::fs::{File, read_to_string};
+use std::path::Path;
+use std::time::Duration;
+
+use flatbox_assets::{
+    error::{AssetError, RonError},
+    ron::{self, ser::PrettyConfig},
+};
+use flatbox_core::time::Time;
+use flatbox_ecs::*;
+use flatbox_render::context::Input;
+use serde::{Serialize, Deserialize};
+
+/// Input state of every fixed update, recorded by [`InputRecorder`]. Replaying it
+/// with the same seed of [`Rng`] reproduces the run, as long as gameplay logic
+/// depends only on the input, [`Time::fixed_delta_time`] and [`Rng`]
+///
+/// [`Rng`]: flatbox_core::random::Rng
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// Seed of [`Rng`](flatbox_core::random::Rng) at the start of recording
+    pub seed: u64,
+    pub fixed_delta_time: Duration,
+    /// Input of each fixed update
+    pub ticks: Vec<Input>,
+}
+
+impl InputRecording {
+    pub fn new(seed: u64) -> Self {
+        InputRecording {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.fixed_delta_time * self.ticks.len() as u32
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AssetError> {
+        Ok(ron::from_str::<InputRecording>(
+            &read_to_string(path)?
+        ).map_err(RonError::from)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AssetError> {
+        ron::ser::to_writer_pretty(File::create(path)?, self, PrettyConfig::new().compact_arrays(true))
+            .map_err(RonError::from)?;
+
+        Ok(())
+    }
+}
+
+/// Singleton component, which captures [`Input`] on every fixed update. It's driven
+/// by [`record_input`] system
+#[derive(Debug, Default, Clone)]
+pub struct InputRecorder {
+    recording: InputRecording,
+    /// Pause the recording
+    pub paused: bool,
+}
+
+impl InputRecorder {
+    pub fn new(seed: u64) -> Self {
+        InputRecorder {
+            recording: InputRecording::new(seed),
+            paused: false,
+        }
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    /// Take the recording, starting a new one with the same seed
+    pub fn take_recording(&mut self) -> InputRecording {
+        let seed = self.recording.seed;
+        std::mem::replace(&mut self.recording, InputRecording::new(seed))
+    }
+}
+
+/// Record input of the fixed update. Must be added to `Update` stage
+pub fn record_input(
+    recorder_world: SubWorld<&mut InputRecorder>,
+    input: Read<Input>,
+    time: Read<Time>,
+){
+    for (_, mut recorder) in &mut recorder_world.query::<&mut InputRecorder>() {
+        if recorder.paused {
+            continue;
+        }
+
+        recorder.recording.fixed_delta_time = time.fixed_delta_time();
+        recorder.recording.ticks.push((*input).clone());
+    }
+}
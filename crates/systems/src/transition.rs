@@ -0,0 +1,200 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+use flatbox_assets::{
+    error::AssetError,
+    parking_lot::Mutex,
+    scene::{Scene, SpawnSceneExt},
+};
+use flatbox_core::{
+    logger::{error, info},
+    time::Time,
+};
+use flatbox_ecs::*;
+use flatbox_egui::{self as egui, backend::EguiBackend};
+
+type LoadingResult = Arc<Mutex<Option<Result<Scene, AssetError>>>>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TransitionState {
+    #[default]
+    Idle,
+    /// Screen is fading to black, while the next scene is being loaded
+    FadingOut { elapsed: f32 },
+    /// Screen is black until the next scene is loaded
+    Loading { elapsed: f32 },
+    /// Screen is fading from black with the new scene
+    FadingIn { elapsed: f32 },
+}
+
+/// Singleton component, which changes scenes with a loading screen. The next scene is loaded
+/// on a background thread, while the screen fades out and holds on the loading state.
+/// Then the world is swapped at once: all entities except [`Persistent`] ones are replaced
+/// with the entities of the scene. It's driven by [`update_scene_transitions`] system
+///
+/// [`Persistent`]: flatbox_assets::scene::Persistent
+pub struct SceneTransition {
+    /// Duration of fading out and in, in seconds
+    pub fade_duration: f32,
+    /// Minimum time of the loading screen, in seconds
+    pub min_loading_time: f32,
+    state: TransitionState,
+    loading: Option<LoadingResult>,
+    pending: Option<PathBuf>,
+}
+
+impl SceneTransition {
+    pub fn new(fade_duration: f32) -> Self {
+        SceneTransition {
+            fade_duration: fade_duration.max(0.0),
+            min_loading_time: 0.0,
+            state: TransitionState::Idle,
+            loading: None,
+            pending: None,
+        }
+    }
+
+    /// Request change of the scene to the one, stored at `path`. If a transition
+    /// is already in progress, the request is executed after it
+    pub fn change_scene<P: Into<PathBuf>>(&mut self, path: P) {
+        self.pending = Some(path.into());
+    }
+
+    pub fn state(&self) -> TransitionState {
+        self.state
+    }
+
+    pub fn in_progress(&self) -> bool {
+        self.state != TransitionState::Idle || self.pending.is_some()
+    }
+
+    /// Opacity of the loading screen (`0.0..=1.0`)
+    pub fn fade_alpha(&self) -> f32 {
+        let fade = |elapsed: f32| {
+            if self.fade_duration > 0.0 { (elapsed / self.fade_duration).clamp(0.0, 1.0) } else { 1.0 }
+        };
+
+        match self.state {
+            TransitionState::Idle => 0.0,
+            TransitionState::FadingOut { elapsed } => fade(elapsed),
+            TransitionState::Loading { .. } => 1.0,
+            TransitionState::FadingIn { elapsed } => 1.0 - fade(elapsed),
+        }
+    }
+
+    fn start_loading(&mut self, path: PathBuf) {
+        info!("Loading scene `{}`", path.display());
+
+        let result = LoadingResult::default();
+        let thread_result = result.clone();
+
+        thread::spawn(move || {
+            *thread_result.lock() = Some(Scene::load(path));
+        });
+
+        self.loading = Some(result);
+        self.state = TransitionState::FadingOut { elapsed: 0.0 };
+    }
+
+    /// Advance the transition by `delta` seconds. Returns the loaded scene, when
+    /// it's time to swap the world
+    fn advance(&mut self, delta: f32) -> Option<Scene> {
+        match self.state {
+            TransitionState::Idle => {
+                if let Some(path) = self.pending.take() {
+                    self.start_loading(path);
+                }
+            },
+            TransitionState::FadingOut { elapsed } => {
+                let elapsed = elapsed + delta;
+
+                self.state = if elapsed >= self.fade_duration {
+                    TransitionState::Loading { elapsed: 0.0 }
+                } else {
+                    TransitionState::FadingOut { elapsed }
+                };
+            },
+            TransitionState::Loading { elapsed } => {
+                let elapsed = elapsed + delta;
+                self.state = TransitionState::Loading { elapsed };
+
+                if elapsed < self.min_loading_time {
+                    return None;
+                }
+
+                let result = self.loading.as_ref().and_then(|loading| loading.lock().take())?;
+                self.loading = None;
+                self.state = TransitionState::FadingIn { elapsed: 0.0 };
+
+                match result {
+                    Ok(scene) => return Some(scene),
+                    Err(e) => error!("Cannot load scene: {e}"),
+                }
+            },
+            TransitionState::FadingIn { elapsed } => {
+                let elapsed = elapsed + delta;
+
+                self.state = if elapsed >= self.fade_duration {
+                    TransitionState::Idle
+                } else {
+                    TransitionState::FadingIn { elapsed }
+                };
+            },
+        }
+
+        None
+    }
+}
+
+impl Default for SceneTransition {
+    fn default() -> Self {
+        SceneTransition::new(0.5)
+    }
+}
+
+/// Advance scene transitions and swap the world, when the next scene is loaded.
+/// Must be added to `PreRender` stage (or to `Update` stage in headless mode)
+pub fn update_scene_transitions(
+    mut world: Write<World>,
+    time: Read<Time>,
+){
+    let scene = world
+        .query_mut::<&mut SceneTransition>()
+        .into_iter()
+        .find_map(|(_, mut transition)| transition.advance(time.delta_seconds()));
+
+    if let Some(scene) = scene {
+        world.replace_scene(scene);
+    }
+}
+
+/// Draw the loading screen of scene transitions. Must be added to `Render` stage
+pub fn draw_scene_transitions(
+    transition_world: SubWorld<&SceneTransition>,
+    egui_world: SubWorld<&mut EguiBackend>,
+){
+    let alpha = transition_world
+        .query::<&SceneTransition>()
+        .iter()
+        .map(|(_, transition)| transition.fade_alpha())
+        .fold(0.0, f32::max);
+
+    if alpha <= 0.0 {
+        return;
+    }
+
+    for (_, mut backend) in &mut egui_world.query::<&mut EguiBackend>() {
+        let ctx = backend.context();
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("scene_transition"),
+        ));
+
+        painter.rect_filled(
+            ctx.screen_rect(),
+            0.0,
+            egui::Color32::from_black_alpha((alpha * 255.0).round() as u8),
+        );
+    }
+}
@@ -7,16 +7,17 @@ use flatbox::{
     egui, 
     render::{
         context::*, pbr::{
-            camera::{Camera, CameraType}, material::DefaultMaterial, model::Model, texture::Texture
+            camera::{Camera, CameraType}, light::{DirectionalLight, PointLight}, material::DefaultMaterial, model::Model, texture::Texture
         }
     }, 
     Flatbox
 };
 use flatbox_core::AppExit;
-use flatbox_ecs::{query::Mut, SubWorld, SystemStage::*};
+use flatbox_ecs::{SubWorld, SystemStage::*};
+use flatbox_systems::camera::{FlyCamera, fly_cameras};
 use flatbox_egui::backend::EguiBackend;
 
-fn main() {
+fn main() -> Result<()> {
     Flatbox::init(WindowBuilder {
         title:  "Flatbox basic example",
         width:  800,
@@ -25,8 +26,11 @@ fn main() {
     })
         .default_extensions() 
         .add_system(Setup, setup)
+        .add_system(PreRender, fly_cameras)
         .add_system(Render, set_ui)
-        .run();
+        .run()?;
+
+    Ok(())
 }
 
 fn setup(mut cmd: Write<CommandBuffer>) -> Result<()> {
@@ -70,19 +74,27 @@ fn setup(mut cmd: Write<CommandBuffer>) -> Result<()> {
         Transform::new_from_translation(glm::vec3(0.0, 0.0, -2.0)),
     ));
 
+    cmd.spawn((DirectionalLight::default(),));
+
+    cmd.spawn((
+        PointLight::default(),
+        Transform::new_from_translation(glm::vec3(0.7, 0.2, 2.0)),
+    ));
+
     cmd.spawn((
         Camera::builder()
             .camera_type(CameraType::FirstPerson)
             .is_active(true)
             .build(),
+        FlyCamera::new().looking_at(&glm::vec3(-3.0, 3.0, -3.0), &glm::Vec3::zeros()),
         Transform {
             translation: glm::vec3(3.0, -3.0, 3.0),
-            rotation: glm::safe_quat_look_at(
+            rotation: glm::convert(glm::safe_quat_look_at(
                 &glm::vec3(0.0, 0.0, 0.0), 
                 &glm::vec3(3.0, -3.0, 3.0),
                 &glm::Vec3::y_axis(), 
                 &glm::Vec3::y_axis(),
-            ),
+            )),
             scale: 1.0,
         },
     ));
@@ -93,7 +105,6 @@ fn setup(mut cmd: Write<CommandBuffer>) -> Result<()> {
 fn set_ui(
     mut cmd: Write<CommandBuffer>,
     egui_world: SubWorld<&mut EguiBackend>,
-    cam_world: SubWorld<(&Camera, &mut Transform)>,
 ){
     let mut egui_backend_query = egui_world.query::<&mut EguiBackend>();
     let mut egui_backend = egui_backend_query
@@ -109,21 +120,4 @@ fn set_ui(
             cmd.spawn((AppExit,));
         }
     });
-
-    let mut trans = <TransformFunction>::None;
-
-    if ctx.input().key_down(egui::Key::W) {
-        trans = Some(Box::new(|mut t: Mut<'_, Transform>| { t.translation.x += 1.0; println!("w pressed"); }));
-    }
-
-    if ctx.input().key_down(egui::Key::S) {
-        trans = Some(Box::new(|mut t: Mut<'_, Transform>| { t.translation.x -= 1.0; println!("s pressed"); }));
-    }
-
-    cam_world.query::<(&Camera, &mut Transform)>()
-        .into_iter()
-        .map(|(_, (_, t))| t)
-        .for_each(trans.unwrap_or(Box::new(|_|{})));  
 }
-
-type TransformFunction = Option<Box<dyn FnMut(Mut<'_, Transform>)>>;
\ No newline at end of file
@@ -0,0 +1,16 @@
+//! Android entry point glue. The game must be built as a `cdylib`, which exports
+//! `android_main` function. The app, passed to it, must be set before the game is initialized:
+//!
+//! ```rust,ignore
+//! #[no_mangle]
+//! fn android_main(app: flatbox::android::AndroidApp) {
+//!     flatbox::android::set_android_app(app);
+//!
+//!     Flatbox::init(WindowBuilder::default())
+//!         .default_extensions()
+//!         .run()
+//!         .expect("Cannot run the game");
+//! }
+//! ```
+
+pub use flatbox_render::context::{AndroidApp, set_android_app};
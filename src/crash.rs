@@ -0,0 +1,73 @@
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::fs::{create_dir_all, write};
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+use flatbox_core::logger::{file::timestamp, recent_logs};
+
+/// Folder of the latest crash report, which is not yet completed by extensions
+static CRASH_REPORT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Install panic hook, which writes `report.txt` with the panic message, backtrace and
+/// `log_records` most recent log records into a new timestamped folder inside `directory`.
+/// The previous hook is called afterwards
+pub fn install_panic_hook<P: Into<PathBuf>>(directory: P, log_records: usize) {
+    let directory = directory.into();
+    let previous_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        // Don't log through the logger here, since the panic may have happened inside of it
+        match write_panic_report(&directory, log_records, info) {
+            Ok(path) => {
+                eprintln!("Crash report is written to `{}`", path.display());
+                *CRASH_REPORT.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+            },
+            Err(e) => eprintln!("Cannot write crash report: {e}"),
+        }
+
+        previous_hook(info);
+    }));
+}
+
+/// Take the folder of the latest crash report, so more files can be added to it
+pub fn take_crash_report() -> Option<PathBuf> {
+    CRASH_REPORT.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+fn write_panic_report(directory: &Path, log_records: usize, info: &PanicHookInfo) -> io::Result<PathBuf> {
+    let time = timestamp();
+    let path = directory.join(format!("crash_{}", time.replace(' ', "_").replace(':', "-")));
+    create_dir_all(&path)?;
+
+    let message = info.payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Time: {time} UTC");
+    let _ = writeln!(report, "Engine: {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Platform: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(report, "Thread: {}", thread::current().name().unwrap_or("<unnamed>"));
+    let _ = writeln!(report, "Panic: {message}");
+
+    if let Some(location) = info.location() {
+        let _ = writeln!(report, "Location: {location}");
+    }
+
+    let _ = writeln!(report, "\nBacktrace:\n{}", Backtrace::force_capture());
+    let _ = writeln!(report, "Recent logs:");
+
+    for record in recent_logs(log_records) {
+        let _ = writeln!(report, "{} {:<5} {} > {}", record.timestamp, record.level, record.target, record.message);
+    }
+
+    write(path.join("report.txt"), report)?;
+
+    Ok(path)
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fs::{copy, metadata, read_dir, remove_file};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use flatbox_core::logger::{info, error};
+use flatbox_ecs::{IntoData, CommandBuffer, Schedule, Schedules, SystemStage::{self, *}};
+use libloading::{Library, Symbol};
+use thiserror::Error;
+
+/// Name of the function, exported by the game library with [`dynamic_game!`](crate::dynamic_game)
+pub const GAME_ENTRY_SYMBOL: &str = "flatbox_game";
+
+/// Signature of the function, exported by the game library
+pub type GameEntryFn = fn(&mut Schedules);
+
+/// Export the function, which registers systems of the game library. The library must
+/// be built as `cdylib` with the same compiler and engine version as the app
+///
+/// # Usage example
+///
+/// ```rust,ignore
+/// fn register(schedules: &mut Schedules) {
+///     schedules.add_system(SystemStage::Update, move_player);
+/// }
+///
+/// dynamic_game!(register);
+/// ```
+#[macro_export]
+macro_rules! dynamic_game {
+    ($register:path) => {
+        #[no_mangle]
+        pub fn flatbox_game(schedules: &mut $crate::ecs::Schedules) {
+            $register(schedules)
+        }
+    };
+}
+
+#[derive(Debug, Error)]
+pub enum DynamicGameError {
+    #[error("Cannot load game library: {0}")]
+    LibraryError(#[from] libloading::Error),
+    #[error("Game library I/O error")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Systems, loaded from the game library. The library is reloaded, when the file changes:
+/// its systems are registered again and replace the previous ones, while the world is
+/// kept. Previous libraries are never unloaded, since the world may still reference
+/// their code (e.g. in drop functions of components)
+pub struct DynamicGame {
+    path: PathBuf,
+    poll_interval: Duration,
+    last_poll: Instant,
+    modified: Option<SystemTime>,
+    pending: Option<SystemTime>,
+    schedules: HashMap<SystemStage, Schedule>,
+    libraries: Vec<Library>,
+}
+
+impl DynamicGame {
+    /// Create without loading; the library is loaded on the first [`DynamicGame::poll`]
+    pub fn new<P: Into<PathBuf>>(path: P, poll_interval: Duration) -> Self {
+        DynamicGame {
+            path: path.into(),
+            poll_interval,
+            last_poll: Instant::now(),
+            modified: None,
+            pending: None,
+            schedules: HashMap::new(),
+            libraries: vec![],
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of loaded versions of the library
+    pub fn generation(&self) -> usize {
+        self.libraries.len()
+    }
+
+    /// Load the library and register its systems
+    pub fn reload(&mut self) -> Result<(), DynamicGameError> {
+        self.modified = Some(metadata(&self.path)?.modified()?);
+
+        // Load a copy, so the original file can be overwritten by the compiler
+        // and the dynamic loader doesn't return the cached library
+        let extension = self.path.extension().unwrap_or_default().to_string_lossy();
+        let copy_path = std::env::temp_dir().join(format!(
+            "{}{}.{extension}",
+            copy_prefix(),
+            self.libraries.len(),
+        ));
+
+        copy(&self.path, &copy_path)?;
+
+        let library = unsafe { Library::new(&copy_path)? };
+        let mut schedules = Schedules::new();
+
+        unsafe {
+            let entry: Symbol<GameEntryFn> = library.get(GAME_ENTRY_SYMBOL.as_bytes())?;
+            entry(&mut schedules);
+        }
+
+        self.schedules = [Setup, Update, PreRender, Render, PostRender, Paused, Shutdown]
+            .into_iter()
+            .map(|stage| (stage, schedules.build_stage(stage)))
+            .collect();
+
+        self.libraries.push(library);
+
+        Ok(())
+    }
+
+    /// Reload the library, if the file has been changed. Changes are applied, when the file
+    /// stays the same during the poll interval, so partially written libraries aren't loaded
+    pub fn poll(&mut self) {
+        if !self.libraries.is_empty() && self.last_poll.elapsed() < self.poll_interval {
+            return;
+        }
+
+        self.last_poll = Instant::now();
+
+        let Ok(modified) = metadata(&self.path).and_then(|metadata| metadata.modified()) else { return };
+
+        if self.modified == Some(modified) {
+            return;
+        }
+
+        if !self.libraries.is_empty() && self.pending != Some(modified) {
+            self.pending = Some(modified);
+            return;
+        }
+
+        self.pending = None;
+
+        match self.reload() {
+            Ok(()) => info!("Loaded game library `{}` (generation {})", self.path.display(), self.generation()),
+            Err(e) => error!("{e}"),
+        }
+    }
+
+    /// Execute systems of the stage, if the library is loaded
+    pub fn execute<D: IntoData<CommandBuffer>>(&mut self, stage: SystemStage, data: D) -> Result<(), flatbox_ecs::Error> {
+        match self.schedules.get_mut(&stage) {
+            Some(schedule) => schedule.execute_seq(data),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Remove copies of the game library, loaded by this process, from the temporary directory.
+/// Copies, which can't be removed while loaded (e.g. on Windows), are left
+pub fn remove_library_copies() -> Result<(), DynamicGameError> {
+    let prefix = copy_prefix();
+
+    for entry in read_dir(std::env::temp_dir())? {
+        let path = entry?.path();
+        let is_copy = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(&prefix));
+
+        if is_copy {
+            let _ = remove_file(&path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prefix of the names of library copies, loaded by this process
+fn copy_prefix() -> String {
+    format!("flatbox-game-{}-", std::process::id())
+}
@@ -1,6 +1,7 @@
 use std::io;
 
 use flatbox_assets::error::AssetError;
+use flatbox_ecs::Error as ScheduleError;
 use flatbox_render::error::RenderError;
 use thiserror::Error;
 
@@ -10,6 +11,8 @@ pub enum FlatboxError {
     AssetError(#[from] AssetError),
     #[error("Rendering error")]
     RenderError(#[from] RenderError),
+    #[error("Error executing systems")]
+    ScheduleError(#[from] ScheduleError),
     #[error("I/O error")]
     IOError(#[from] io::Error),
 }
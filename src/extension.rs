@@ -1,29 +1,621 @@
 use std::marker::PhantomData;
 use std::any::TypeId;
 use std::fmt::Debug;
-use flatbox_render::pbr::material::Material;
-use flatbox_systems::rendering::{bind_material, clear_screen, draw_ui, render_material, run_egui_backend};
+use as_any::{AsAny, Downcast};
+use flatbox_render::{pbr::{material::Material, shadow::{ShadowMaps, ShadowSettings, SHADOW_TARGET}}, gizmos::{Gizmos, LineMaterial}, occlusion::OcclusionCulling, outline::Outlines, postprocess::{PostProcessStack, PostProcessing}, screenshot::Screenshots, text::TextRenderer, tilemap::TilemapMaterial};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use flatbox_assets::parking_lot::Mutex;
+use flatbox_core::{logger::{info, error}, random::Rng, time::{Time, TimeScale}};
+use flatbox_assets::{error::AssetError, preload::AssetManifest, save_load::SaveLoad, scene::Persistent};
+use flatbox_ecs::{World, Write, event::Events, resources::Resources};
+use flatbox_render::{context::{ControlFlow, Display, Input, WindowCommands, WindowInfo}, renderer::{Renderer, graph::{SCENE_TARGET, SCREEN_TARGET}}};
+use pretty_type_name::pretty_type_name;
+use flatbox_systems::postprocess::{begin_post_processing, apply_post_processing};
+use flatbox_systems::preload::{PreloadProgress, update_preload_progress, draw_preload_progress};
+use flatbox_systems::transition::{SceneTransition, update_scene_transitions, draw_scene_transitions};
+use flatbox_systems::replay::{InputRecorder, record_input};
+use flatbox_systems::diagnostics::{Diagnostics, update_diagnostics, update_render_diagnostics};
+use flatbox_systems::rendering::{bake_static_batches, bind_material, capture_screenshots, clear_screen, collect_lights, draw_ui, render_gizmos, render_material, render_occlusion_queries, render_outlines, render_shadows, render_text, render_tilemaps, run_egui_backend, update_environment, update_fog, update_shadows};
 
 #[cfg(feature = "egui")]
 use flatbox_egui::backend::EguiBackend;
+#[cfg(any(feature = "physics", feature = "physics2d"))]
+use flatbox_core::math::glm;
+#[cfg(feature = "physics")]
+use flatbox_physics::{CollisionLayerNames, FixedJoint, PhysicsHandler, PrismaticJoint, RevoluteJoint, SphericalJoint};
+#[cfg(feature = "physics")]
+use flatbox_systems::physics::{apply_external_forces, apply_kinematic_velocities, remove_physics_joints, render_physics_debug, spawn_physics_bodies, spawn_physics_joints, step_physics, sync_collision_layers, sync_disabled_bodies, sync_kinematic_bodies, sync_physics_transforms};
+#[cfg(feature = "physics2d")]
+use flatbox_systems::physics2d;
+#[cfg(feature = "net")]
+use std::net::SocketAddr;
+#[cfg(feature = "net")]
+use flatbox_assets::ser_component::SerializableComponent;
+#[cfg(feature = "net")]
+use flatbox_net::{NetClient, NetEvent, NetServer, ReplicationRegistry};
+#[cfg(feature = "net")]
+use flatbox_systems::net::{update_net_client, update_net_server};
+#[cfg(feature = "scripts")]
+use flatbox_scripts::{ComponentRegistry, ScriptComponent, ScriptEvent, ScriptRuntime};
 
-use crate::Flatbox;
+use crate::{Flatbox, crash, dynamic::{DynamicGame, remove_library_copies}};
 
 use flatbox_ecs::SystemStage::*;
  
-pub trait Extension: Debug {
-    fn apply(&self, app: &mut Flatbox);
+/// Set of systems and resources, added to the app at once. Extension can carry its
+/// own configuration, e.g.
+///
+/// ```ignore
+/// #[derive(Debug)]
+/// pub struct PhysicsExtension {
+///     pub gravity: glm::Vec3,
+///     pub substeps: u32,
+/// }
+/// ```
+pub trait Extension: Debug + AsAny {
+    /// Called once, when the extension is added with [`Flatbox::apply_extension`]
+    fn build(&self, app: &mut Flatbox);
+
+    /// Called on shutdown in reverse order of adding, e.g. to save state
+    /// or release resources
+    fn cleanup(&self, _app: &mut Flatbox) {}
+
+    /// Called, when the runner panics, in reverse order of adding, before the panic
+    /// is resumed. Shutdown systems and [`Extension::cleanup`] aren't executed in this case
+    fn on_crash(&self, _app: &mut Flatbox) {}
+}
+
+/// Extensions, added to the app
+#[derive(Debug, Default)]
+pub struct Extensions {
+    extensions: Vec<(TypeId, Box<dyn Extension>)>,
 }
 
-pub type Extensions = Vec<TypeId>;
+impl Extensions {
+    pub fn new() -> Self {
+        Extensions::default()
+    }
+
+    pub fn contains(&self, type_id: &TypeId) -> bool {
+        self.extensions.iter().any(|(id, _)| id == type_id)
+    }
+
+    pub fn push<E: Extension>(&mut self, extension: E) {
+        self.extensions.push((TypeId::of::<E>(), Box::new(extension)));
+    }
+
+    /// Get the added extension along with its configuration
+    pub fn get<E: Extension>(&self) -> Option<&E> {
+        self.extensions
+            .iter()
+            .find_map(|(_, extension)| extension.as_ref().downcast_ref::<E>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.extensions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &dyn Extension> {
+        self.extensions.iter().map(|(_, extension)| extension.as_ref())
+    }
+}
 
 #[derive(Default, Debug)]
 pub struct BaseRenderExtension;
 
 impl Extension for BaseRenderExtension {
-    fn apply(&self, app: &mut Flatbox) {
+    fn build(&self, app: &mut Flatbox) {
+        app
+            .add_system(PreRender, collect_lights)
+            .add_system(PreRender, update_fog)
+            .add_system(PreRender, update_environment)
+            .add_system(PreRender, update_shadows)
+            .add_render_pass("clear", &[], &[SCENE_TARGET], clear_screen);
+    }
+}
+
+/// Maintains [`Diagnostics`] singleton with frame statistics
+#[derive(Debug, Clone)]
+pub struct DiagnosticsExtension {
+    /// Number of the latest frames to keep
+    pub history_size: usize,
+    /// Interval of logging the summary. `None` disables logging
+    pub log_interval: Option<Duration>,
+    /// Measure GPU time of render passes (see [`Renderer::set_gpu_timing`])
+    pub gpu_timing: bool,
+}
+
+impl Default for DiagnosticsExtension {
+    fn default() -> Self {
+        DiagnosticsExtension {
+            history_size: 120,
+            log_interval: None,
+            gpu_timing: false,
+        }
+    }
+}
+
+impl Extension for DiagnosticsExtension {
+    fn build(&self, app: &mut Flatbox) {
+        app.world.spawn((
+            Diagnostics::new(self.history_size).with_log_interval(self.log_interval),
+            Persistent,
+        ));
+
+        if let Some(renderer) = app.renderer.as_mut() {
+            renderer.set_gpu_timing(self.gpu_timing);
+        }
+
+        if app.window_builder.headless {
+            app.add_system(Update, update_diagnostics);
+        } else {
+            app
+                .add_system(PostRender, update_diagnostics)
+                .add_system(PostRender, update_render_diagnostics);
+        }
+    }
+}
+
+/// Adds [`SceneTransition`] singleton, which changes scenes with loading screen
+#[derive(Debug, Clone)]
+pub struct SceneTransitionExtension {
+    /// Duration of fading out and in, in seconds
+    pub fade_duration: f32,
+    /// Minimum time of the loading screen, in seconds
+    pub min_loading_time: f32,
+}
+
+impl Default for SceneTransitionExtension {
+    fn default() -> Self {
+        SceneTransitionExtension {
+            fade_duration: 0.5,
+            min_loading_time: 0.0,
+        }
+    }
+}
+
+impl Extension for SceneTransitionExtension {
+    fn build(&self, app: &mut Flatbox) {
+        let mut transition = SceneTransition::new(self.fade_duration);
+        transition.min_loading_time = self.min_loading_time;
+
+        app.world.spawn((transition, Persistent));
+
+        if app.window_builder.headless {
+            app.add_system(Update, update_scene_transitions);
+        } else {
+            app.add_system(PreRender, update_scene_transitions);
+
+            #[cfg(feature = "egui")]
+            app.add_system(Render, draw_scene_transitions);
+        }
+    }
+}
+
+/// Loads all assets of the manifest into memory on startup. Progress is reported
+/// by [`PreloadProgress`] singleton
+#[derive(Debug, Clone, Default)]
+pub struct PreloadAssetsExtension {
+    pub manifest: AssetManifest,
+    /// Show loading bar while the assets are being loaded. Requires feature `egui` enabled
+    pub show_progress: bool,
+}
+
+impl PreloadAssetsExtension {
+    pub fn new(manifest: AssetManifest) -> Self {
+        PreloadAssetsExtension {
+            manifest,
+            show_progress: true,
+        }
+    }
+}
+
+impl Extension for PreloadAssetsExtension {
+    fn build(&self, app: &mut Flatbox) {
+        app.world.spawn((PreloadProgress::start(&self.manifest), Persistent));
+
+        if app.window_builder.headless {
+            app.add_system(Update, update_preload_progress);
+        } else {
+            app.add_system(PreRender, update_preload_progress);
+
+            #[cfg(feature = "egui")]
+            if self.show_progress {
+                app.add_system(Render, draw_preload_progress);
+            }
+        }
+    }
+}
+
+/// Records input of every fixed update and saves it to `path` on shutdown. The recording
+/// can be replayed with [`Flatbox::run_replay`]. Adds [`Rng`] singleton, seeded with `seed`
+/// (or with the current time), which must be used for all gameplay randomness
+#[derive(Debug, Clone)]
+pub struct InputRecordingExtension {
+    pub path: PathBuf,
+    pub seed: Option<u64>,
+}
+
+impl InputRecordingExtension {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        InputRecordingExtension {
+            path: path.into(),
+            seed: None,
+        }
+    }
+}
+
+impl Extension for InputRecordingExtension {
+    fn build(&self, app: &mut Flatbox) {
+        let rng = self.seed.map(Rng::new).unwrap_or_default();
+
+        app.world.spawn((InputRecorder::new(rng.seed()), Persistent));
+        app.world.spawn((rng, Persistent));
+        app.add_system(Update, record_input);
+    }
+
+    fn cleanup(&self, app: &mut Flatbox) {
+        for (_, mut recorder) in &mut app.world.query::<&mut InputRecorder>() {
+            let recording = recorder.take_recording();
+
+            match recording.save(&self.path) {
+                Ok(()) => info!("Saved {} ticks of input to `{}`", recording.ticks.len(), self.path.display()),
+                Err(e) => error!("Cannot save input recording to `{}`: {e}", self.path.display()),
+            }
+        }
+    }
+}
+
+type SaveWorldFn = Box<dyn Fn(&World, &Path) -> Result<(), AssetError>>;
+
+/// Writes crash reports into timestamped folders inside `directory`. On panic `report.txt`
+/// contains the panic message, backtrace and recent logs; `render_commands.txt` contains
+/// history of render commands. If the saver is set with [`CrashReportExtension::with_world_save`],
+/// the world is saved to `world` as well
+pub struct CrashReportExtension {
+    pub directory: PathBuf,
+    /// Number of the most recent log records to include
+    pub log_records: usize,
+    save_world: Option<SaveWorldFn>,
+}
+
+impl CrashReportExtension {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        CrashReportExtension {
+            directory: directory.into(),
+            log_records: 100,
+            save_world: None,
+        }
+    }
+
+    /// Make emergency save of the world with the given [`SaveLoad`]er
+    pub fn with_world_save<S: SaveLoad + Default + 'static>(mut self) -> Self {
+        self.save_world = Some(Box::new(|world, path| S::default().save(world, path)));
+        self
+    }
+}
+
+impl Debug for CrashReportExtension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CrashReportExtension")
+            .field("directory", &self.directory)
+            .field("log_records", &self.log_records)
+            .field("save_world", &self.save_world.is_some())
+            .finish()
+    }
+}
+
+impl Extension for CrashReportExtension {
+    fn build(&self, _app: &mut Flatbox) {
+        crash::install_panic_hook(&self.directory, self.log_records);
+    }
+
+    fn on_crash(&self, app: &mut Flatbox) {
+        let Some(path) = crash::take_crash_report() else { return };
+
+        if let Some(renderer) = &app.renderer {
+            let history = renderer.history();
+            let commands = (0..history.len())
+                .filter_map(|index| history.get(index))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            if let Err(e) = std::fs::write(path.join("render_commands.txt"), commands) {
+                error!("Cannot write render commands history: {e}");
+            }
+        }
+
+        if let Some(save_world) = &self.save_world {
+            // The world may be left inconsistent by the panic, so the save itself may panic
+            match panic::catch_unwind(AssertUnwindSafe(|| save_world(&app.world, &path.join("world")))) {
+                Ok(Ok(())) => info!("Emergency save of the world is written to `{}`", path.display()),
+                Ok(Err(e)) => error!("Cannot save the world: {e}"),
+                Err(_) => error!("Cannot save the world: saver panicked"),
+            }
+        }
+    }
+}
+
+/// Loads systems of the game from a `cdylib`, exported with [`dynamic_game!`](crate::dynamic_game),
+/// and reloads them, when the library file changes, keeping the world. Library systems get
+/// the same data as the app's systems of the stage. `Setup` systems are executed only once.
+/// Copies of the library are removed from the temporary directory on cleanup
+#[derive(Debug, Clone)]
+pub struct DynamicGameExtension {
+    pub path: PathBuf,
+    /// Interval of checking the library for changes
+    pub poll_interval: Duration,
+}
+
+impl DynamicGameExtension {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        DynamicGameExtension {
+            path: path.into(),
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl Extension for DynamicGameExtension {
+    fn build(&self, app: &mut Flatbox) {
+        let game = Arc::new(Mutex::new(DynamicGame::new(&self.path, self.poll_interval)));
+        game.lock().poll();
+
+        // Library systems get the same data as the app's ones, which differs without window
+        if app.window_builder.headless {
+            for stage in [Setup, Shutdown] {
+                let stage_game = game.clone();
+                app.add_system(stage, move |
+                    mut world: Write<World>,
+                    mut events: Write<Events>,
+                    mut resources: Write<Resources>,
+                | {
+                    stage_game.lock().execute(stage, (&mut *world, &mut *events, &mut *resources))
+                });
+            }
+
+            app.add_system(Update, move |
+                mut world: Write<World>,
+                mut time: Write<Time>,
+                mut events: Write<Events>,
+                mut resources: Write<Resources>,
+                mut input: Write<Input>,
+                mut time_scale: Write<TimeScale>,
+            | {
+                let mut game = game.lock();
+                game.poll();
+                game.execute(Update, (
+                    &mut *world, &mut *time, &mut *events, &mut *resources, &mut *input, &mut *time_scale,
+                ))
+            });
+
+            return;
+        }
+
+        for stage in [Setup, Shutdown] {
+            let stage_game = game.clone();
+            app.add_system(stage, move |
+                mut world: Write<World>,
+                mut renderer: Write<Renderer>,
+                mut events: Write<Events>,
+                mut resources: Write<Resources>,
+            | {
+                stage_game.lock().execute(stage, (&mut *world, &mut *renderer, &mut *events, &mut *resources))
+            });
+        }
+
+        for stage in [Update, Paused] {
+            let update_game = game.clone();
+            app.add_system(stage, move |
+                mut world: Write<World>,
+                mut renderer: Write<Renderer>,
+                mut time: Write<Time>,
+                mut window_commands: Write<WindowCommands>,
+                mut window_info: Write<WindowInfo>,
+                mut events: Write<Events>,
+                mut resources: Write<Resources>,
+                mut input: Write<Input>,
+                mut time_scale: Write<TimeScale>,
+            | {
+                let mut game = update_game.lock();
+                game.poll();
+                game.execute(stage, (
+                    &mut *world,
+                    &mut *renderer,
+                    &mut *time,
+                    &mut *window_commands,
+                    &mut *window_info,
+                    &mut *events,
+                    &mut *resources,
+                    &mut *input,
+                    &mut *time_scale,
+                ))
+            });
+        }
+
+        for stage in [PreRender, Render, PostRender] {
+            let render_game = game.clone();
+            app.add_system(stage, move |
+                mut display: Write<Display>,
+                mut control_flow: Write<ControlFlow>,
+                mut world: Write<World>,
+                mut renderer: Write<Renderer>,
+                mut time: Write<Time>,
+                mut window_commands: Write<WindowCommands>,
+                mut window_info: Write<WindowInfo>,
+                mut events: Write<Events>,
+                mut resources: Write<Resources>,
+                mut input: Write<Input>,
+                mut time_scale: Write<TimeScale>,
+            | {
+                render_game.lock().execute(stage, (
+                    &mut *display,
+                    &mut *control_flow,
+                    &mut *world,
+                    &mut *renderer,
+                    &mut *time,
+                    &mut *window_commands,
+                    &mut *window_info,
+                    &mut *events,
+                    &mut *resources,
+                    &mut *input,
+                    &mut *time_scale,
+                ))
+            });
+        }
+    }
+
+    fn cleanup(&self, _app: &mut Flatbox) {
+        if let Err(e) = remove_library_copies() {
+            error!("Cannot remove copies of the game library: {e}");
+        }
+    }
+}
+
+/// Runs rhai scripts of entities with [`Script`] component every update and reloads them,
+/// when their files change. Components of the registry are available to scripts by their
+/// names, and [`ScriptEvent`]s are sent and received by them (see [`ScriptRuntime`])
+///
+/// [`Script`]: flatbox_scripts::Script
+/// [`ScriptEvent`]: flatbox_scripts::ScriptEvent
+#[cfg(feature = "scripts")]
+#[derive(Debug, Clone)]
+pub struct ScriptExtension {
+    pub components: ComponentRegistry,
+    /// Interval of checking the scripts for changes
+    pub poll_interval: Duration,
+}
+
+#[cfg(feature = "scripts")]
+impl ScriptExtension {
+    pub fn new() -> Self {
+        ScriptExtension::default()
+    }
+
+    /// Make the component available to scripts by the name
+    pub fn with_component<T: ScriptComponent>(mut self, name: impl Into<String>) -> Self {
+        self.components.register::<T>(name);
+        self
+    }
+}
+
+#[cfg(feature = "scripts")]
+impl Default for ScriptExtension {
+    fn default() -> Self {
+        ScriptExtension {
+            components: ComponentRegistry::new(),
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+#[cfg(feature = "scripts")]
+impl Extension for ScriptExtension {
+    fn build(&self, app: &mut Flatbox) {
+        let runtime = Mutex::new(ScriptRuntime::new(self.components.clone(), self.poll_interval));
+
         app
-            .add_system(Render, clear_screen);
+            .add_event::<ScriptEvent>()
+            .add_system(Update, move |
+                mut world: Write<World>,
+                mut events: Write<Events>,
+                input: flatbox_ecs::Read<Input>,
+                time: flatbox_ecs::Read<Time>,
+            | {
+                runtime.lock().update(&mut world, &input, &mut events, time.delta_seconds());
+            });
+    }
+}
+
+/// Adds [`NetServer`] resource, which listens at the address, and replicates registered
+/// components of entities with [`Replicated`] marker to the clients every send interval.
+/// Connections are reported with [`NetEvent`]s
+///
+/// [`Replicated`]: flatbox_net::Replicated
+/// [`NetEvent`]: flatbox_net::NetEvent
+#[cfg(feature = "net")]
+#[derive(Debug, Clone)]
+pub struct NetServerExtension {
+    pub addr: SocketAddr,
+    pub registry: ReplicationRegistry,
+    pub send_interval: Duration,
+}
+
+#[cfg(feature = "net")]
+impl NetServerExtension {
+    pub fn new(addr: SocketAddr) -> Self {
+        NetServerExtension {
+            addr,
+            registry: ReplicationRegistry::new(),
+            send_interval: Duration::from_millis(50),
+        }
+    }
+
+    pub fn with_component<T: SerializableComponent + Clone>(mut self) -> Self {
+        self.registry.register::<T>();
+        self
+    }
+}
+
+#[cfg(feature = "net")]
+impl Extension for NetServerExtension {
+    fn build(&self, app: &mut Flatbox) {
+        match NetServer::bind(self.addr, self.registry.clone(), self.send_interval) {
+            Ok(server) => {
+                info!("Listening at {}", self.addr);
+                app
+                    .insert_resource(server)
+                    .add_event::<NetEvent>()
+                    .add_system(Update, update_net_server);
+            },
+            Err(e) => error!("Cannot start server at {}: {e}", self.addr),
+        }
+    }
+}
+
+/// Adds [`NetClient`] resource, which connects to the server at the address and applies
+/// its replicated entities to the world. Inputs are sent with [`NetClient::send_input`]
+#[cfg(feature = "net")]
+#[derive(Debug, Clone)]
+pub struct NetClientExtension {
+    pub server_addr: SocketAddr,
+    pub registry: ReplicationRegistry,
+}
+
+#[cfg(feature = "net")]
+impl NetClientExtension {
+    pub fn new(server_addr: SocketAddr) -> Self {
+        NetClientExtension {
+            server_addr,
+            registry: ReplicationRegistry::new(),
+        }
+    }
+
+    pub fn with_component<T: SerializableComponent + Clone>(mut self) -> Self {
+        self.registry.register::<T>();
+        self
+    }
+}
+
+#[cfg(feature = "net")]
+impl Extension for NetClientExtension {
+    fn build(&self, app: &mut Flatbox) {
+        match NetClient::connect(self.server_addr, self.registry.clone()) {
+            Ok(client) => {
+                app
+                    .insert_resource(client)
+                    .add_event::<NetEvent>()
+                    .add_system(Update, update_net_client);
+            },
+            Err(e) => error!("Cannot connect to server at {}: {e}", self.server_addr),
+        }
     }
 }
 
@@ -42,10 +634,12 @@ impl<M: Material> RenderMaterialExtension<M> {
 }
 
 impl<M: Material> Extension for RenderMaterialExtension<M> {
-    fn apply(&self, app: &mut Flatbox) {
+    fn build(&self, app: &mut Flatbox) {
         app
             .add_system(Setup, bind_material::<M>)
-            .add_system(Render, render_material::<M>);
+            .add_system(PreRender, bake_static_batches::<M>)
+            .add_render_pass(format!("shadows<{}>", pretty_type_name::<M>()), &[], &[SHADOW_TARGET], render_shadows::<M>)
+            .add_render_pass(format!("material<{}>", pretty_type_name::<M>()), &[SHADOW_TARGET], &[SCENE_TARGET], render_material::<M>);
     }
 }
 
@@ -55,13 +649,266 @@ impl<M: Material> Default for RenderMaterialExtension<M> {
     }
 }
 
+/// Renders entities with [`Tilemap`], [`TilemapMaterial`] and [`Transform`] components
+///
+/// [`Tilemap`]: flatbox_render::tilemap::Tilemap
+/// [`Transform`]: flatbox_core::math::transform::Transform
+#[derive(Debug, Default)]
+pub struct RenderTilemapExtension;
+
+impl Extension for RenderTilemapExtension {
+    fn build(&self, app: &mut Flatbox) {
+        app
+            .add_system(Setup, bind_material::<TilemapMaterial>)
+            .add_render_pass("tilemaps", &[], &[SCENE_TARGET], render_tilemaps);
+    }
+}
+
+/// Renders outlines of entities with [`Outlined`] component
+///
+/// [`Outlined`]: flatbox_render::outline::Outlined
+#[derive(Debug, Default)]
+pub struct RenderOutlinesExtension;
+
+impl Extension for RenderOutlinesExtension {
+    fn build(&self, app: &mut Flatbox) {
+        app.world.spawn((Outlines::new(), Persistent));
+        app.add_render_pass("outlines", &[SCENE_TARGET], &[SCENE_TARGET], render_outlines);
+    }
+}
+
+/// Adds [`ShadowMaps`] singleton, so the first [`DirectionalLight`] and the nearest [`PointLight`]s,
+/// which cast shadows, are shadowed by models of all materials
+///
+/// [`DirectionalLight`]: flatbox_render::pbr::light::DirectionalLight
+/// [`PointLight`]: flatbox_render::pbr::light::PointLight
+#[derive(Debug, Default, Clone)]
+pub struct ShadowsExtension {
+    pub settings: ShadowSettings,
+}
+
+impl Extension for ShadowsExtension {
+    fn build(&self, app: &mut Flatbox) {
+        app.world.spawn((ShadowMaps::new(self.settings), Persistent));
+    }
+}
+
+/// Adds [`PhysicsHandler`] singleton, which is stepped every fixed update (`Update` stage).
+/// Entities with [`RigidBodyComponent`], [`ColliderComponent`] and [`Transform`] are simulated,
+/// and their transforms are updated after each step. Collisions between them are sent
+/// as [`CollisionEvents`], and intersections with sensors as [`TriggerEvents`]. Colliders interact
+/// according to their [`CollisionLayers`], whose names are kept in [`CollisionLayerNames`] singleton.
+/// Joint components, e.g. [`RevoluteJoint`], connect their bodies, and [`ExternalForces`] push them.
+/// Kinematic bodies with [`KinematicVelocity`] move by themselves, e.g. as platforms.
+/// Bodies of [`Disabled`] entities are left out of the simulation.
+/// Debug shapes, enabled with [`PhysicsHandler::set_debug_renderer`], are drawn with [`RenderGizmosExtension`]
+///
+/// [`CollisionEvents`]: flatbox_physics::CollisionEvents
+/// [`TriggerEvents`]: flatbox_physics::TriggerEvents
+/// [`CollisionLayers`]: flatbox_physics::CollisionLayers
+/// [`RevoluteJoint`]: flatbox_physics::RevoluteJoint
+/// [`ExternalForces`]: flatbox_physics::ExternalForces
+/// [`Disabled`]: flatbox_ecs::disabled::Disabled
+/// [`KinematicVelocity`]: flatbox_physics::KinematicVelocity
+/// [`RigidBodyComponent`]: flatbox_physics::RigidBodyComponent
+/// [`ColliderComponent`]: flatbox_physics::ColliderComponent
+/// [`Transform`]: flatbox_core::math::transform::Transform
+#[cfg(feature = "physics")]
+#[derive(Debug, Clone)]
+pub struct PhysicsExtension {
+    pub gravity: glm::Vec3,
+    /// Number of steps, which the fixed time step is divided into
+    pub substeps: u32,
+    pub layers: CollisionLayerNames,
+}
+
+#[cfg(feature = "physics")]
+impl Default for PhysicsExtension {
+    fn default() -> Self {
+        PhysicsExtension {
+            gravity: glm::vec3(0.0, -9.81, 0.0),
+            substeps: 1,
+            layers: CollisionLayerNames::new(),
+        }
+    }
+}
+
+#[cfg(feature = "physics")]
+impl Extension for PhysicsExtension {
+    fn build(&self, app: &mut Flatbox) {
+        let mut physics = PhysicsHandler::new();
+        physics.gravity = self.gravity;
+        physics.substeps = self.substeps;
+
+        app.world.spawn((physics, Persistent));
+        app.world.spawn((self.layers.clone(), Persistent));
+        app
+            .add_system(Update, spawn_physics_bodies)
+            .add_system(Update, sync_collision_layers)
+            .add_system(Update, sync_disabled_bodies)
+            .add_system(Update, remove_physics_joints)
+            .add_system(Update, spawn_physics_joints::<FixedJoint>)
+            .add_system(Update, spawn_physics_joints::<RevoluteJoint>)
+            .add_system(Update, spawn_physics_joints::<PrismaticJoint>)
+            .add_system(Update, spawn_physics_joints::<SphericalJoint>)
+            .add_system(Update, apply_kinematic_velocities)
+            .add_system(Update, sync_kinematic_bodies)
+            .add_system(Update, apply_external_forces)
+            .add_system(Update, step_physics)
+            .add_system(Update, sync_physics_transforms);
+
+        if !app.window_builder.headless {
+            app.add_system(PreRender, render_physics_debug);
+        }
+    }
+}
+
+/// Adds 2D [`PhysicsHandler`] singleton, which is stepped every fixed update (`Update` stage).
+/// Entities with 2D [`RigidBodyComponent`], [`ColliderComponent`] and [`Transform`] are simulated
+/// in XY plane, and their transforms are updated after each step. Collisions between them are sent
+/// as [`CollisionEvents`]. Bodies of [`Disabled`] entities are left out of the simulation.
+/// Entities with [`Tilemap`] receive a fixed body with colliders of the solid tiles
+///
+/// [`PhysicsHandler`]: flatbox_physics2d::PhysicsHandler
+/// [`Tilemap`]: flatbox_render::tilemap::Tilemap
+/// [`Disabled`]: flatbox_ecs::disabled::Disabled
+/// [`RigidBodyComponent`]: flatbox_physics2d::RigidBodyComponent
+/// [`ColliderComponent`]: flatbox_physics2d::ColliderComponent
+/// [`CollisionEvents`]: flatbox_physics2d::CollisionEvents
+/// [`Transform`]: flatbox_core::math::transform::Transform
+#[cfg(feature = "physics2d")]
+#[derive(Debug, Clone)]
+pub struct Physics2dExtension {
+    pub gravity: glm::Vec2,
+    /// Number of steps, which the fixed time step is divided into
+    pub substeps: u32,
+}
+
+#[cfg(feature = "physics2d")]
+impl Default for Physics2dExtension {
+    fn default() -> Self {
+        Physics2dExtension {
+            gravity: glm::vec2(0.0, -9.81),
+            substeps: 1,
+        }
+    }
+}
+
+#[cfg(feature = "physics2d")]
+impl Extension for Physics2dExtension {
+    fn build(&self, app: &mut Flatbox) {
+        let mut physics = flatbox_physics2d::PhysicsHandler::new();
+        physics.gravity = self.gravity;
+        physics.substeps = self.substeps;
+
+        app.world.spawn((physics, Persistent));
+        app
+            .add_system(Update, physics2d::spawn_tilemap_colliders)
+            .add_system(Update, physics2d::spawn_physics_bodies)
+            .add_system(Update, physics2d::sync_disabled_bodies)
+            .add_system(Update, physics2d::sync_kinematic_bodies)
+            .add_system(Update, physics2d::step_physics)
+            .add_system(Update, physics2d::sync_physics_transforms);
+
+        if !app.window_builder.headless {
+            app.add_system(PreRender, physics2d::render_physics_debug);
+        }
+    }
+}
+
+/// Culls entities with [`OcclusionCulled`] component, which are hidden by the scene.
+/// It must be added after the material extensions, so that boxes are tested against the drawn scene
+///
+/// [`OcclusionCulled`]: flatbox_render::occlusion::OcclusionCulled
+#[derive(Debug, Default)]
+pub struct OcclusionCullingExtension;
+
+impl Extension for OcclusionCullingExtension {
+    fn build(&self, app: &mut Flatbox) {
+        app.world.spawn((OcclusionCulling::new(), Persistent));
+        app.add_render_pass("occlusion_queries", &[SCENE_TARGET], &[SCENE_TARGET], render_occlusion_queries);
+    }
+}
+
+/// Adds [`Gizmos`] singleton and draws its debug lines over the scene every frame
+///
+/// [`Gizmos`]: flatbox_render::gizmos::Gizmos
+#[derive(Debug, Default)]
+pub struct RenderGizmosExtension;
+
+impl Extension for RenderGizmosExtension {
+    fn build(&self, app: &mut Flatbox) {
+        app.world.spawn((Gizmos::new(), Persistent));
+        app
+            .add_system(Setup, bind_material::<LineMaterial>)
+            .add_render_pass("gizmos", &[], &[SCENE_TARGET], render_gizmos);
+    }
+}
+
+/// Renders entities with [`Text3d`] or [`Text2d`] and [`Transform`] components
+///
+/// [`Text3d`]: flatbox_render::text::Text3d
+/// [`Text2d`]: flatbox_render::text::Text2d
+/// [`Transform`]: flatbox_core::math::transform::Transform
+#[derive(Debug, Default)]
+pub struct RenderTextExtension;
+
+impl Extension for RenderTextExtension {
+    fn build(&self, app: &mut Flatbox) {
+        app.world.spawn((TextRenderer::new(), Persistent));
+        app.add_render_pass("text", &[], &[SCENE_TARGET], render_text);
+    }
+}
+
+/// Renders the scene into offscreen targets and applies effects of the active camera,
+/// e.g. [`MotionBlur`], and effects of [`PostProcessStack`] singleton before presenting it.
+/// Effects are applied at the end of [`Render`] stage, so the extension must be added after
+/// extensions rendering the scene
+///
+/// [`MotionBlur`]: flatbox_render::postprocess::MotionBlur
+/// [`PostProcessStack`]: flatbox_render::postprocess::PostProcessStack
+#[derive(Debug, Default)]
+pub struct PostProcessingExtension;
+
+impl Extension for PostProcessingExtension {
+    fn build(&self, app: &mut Flatbox) {
+        if app.window_builder.headless {
+            return;
+        }
+
+        app.world.spawn((PostProcessing::new(), Persistent));
+        app.world.spawn((PostProcessStack::new(), Persistent));
+        app
+            .add_system(PreRender, begin_post_processing)
+            .add_render_pass("post_processing", &[SCENE_TARGET], &[SCREEN_TARGET], apply_post_processing);
+    }
+}
+
+/// Adds [`Screenshots`] singleton, which captures requested screenshots at the end of
+/// [`PostRender`] stage. It must be added after [`RenderGuiExtension`] to capture the UI
+///
+/// [`Screenshots`]: flatbox_render::screenshot::Screenshots
+#[derive(Debug, Default)]
+pub struct ScreenshotExtension;
+
+impl Extension for ScreenshotExtension {
+    fn build(&self, app: &mut Flatbox) {
+        if app.window_builder.headless {
+            return;
+        }
+
+        app.world.spawn((Screenshots::new(), Persistent));
+        app.add_system(PostRender, capture_screenshots);
+    }
+}
+
 #[cfg(feature = "egui")]
 #[derive(Debug)]
 pub struct RenderGuiExtension;
 
 #[cfg(feature = "egui")]
 impl Extension for RenderGuiExtension {
-    fn apply(&self, app: &mut Flatbox) {
+    fn build(&self, app: &mut Flatbox) {
         app
             .add_system(Render, run_egui_backend)
             .add_system(PostRender, draw_ui)
@@ -1,17 +1,32 @@
 use std::any::TypeId;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
 use extension::RenderGuiExtension;
 use flatbox_egui::backend::EguiBackend;
+use flatbox_systems::{exit::exit_on_window_close, replay::InputRecording};
+use flatbox_assets::scene::Persistent;
 use pretty_type_name::pretty_type_name;
-use flatbox_core::logger::FlatboxLogger;
-use flatbox_ecs::{Schedules, System, SystemStage::{self, *}, World};
+use flatbox_core::{
+    logger::{FlatboxLogger, LoggerConfig},
+    random::Rng,
+    time::{Time, TimeScale, Pause, Resume},
+    AppExit,
+    profile_scope, profile_frame,
+};
+use flatbox_ecs::{event::{Event, Events, ReaderSystem, clear_events}, resources::{Resource, Resources}, Schedule, Schedules, System, SystemStage::{self, *}, World, execute_parallel};
 use flatbox_render::{
-    renderer::Renderer,
-    context::{Context, WindowBuilder, ContextEvent, WindowEvent}, 
+    renderer::{Renderer, graph::RenderGraph},
+    context::{Context, WindowBuilder, ContextEvent, WindowEvent, ScaleFactorChanged, TouchEvent, TextInputEvent, GestureRecognizer, CloseRequested, WindowPaused, WindowResumed, UserEvent, Input}, 
     pbr::material::DefaultMaterial,
 };
 
+use crate::error::{FlatboxError, FlatboxResult};
 use crate::extension::{Extension, Extensions, RenderMaterialExtension, BaseRenderExtension};
 
+#[cfg(all(target_os = "android", feature = "render"))]
+pub mod android;
+pub mod crash;
+pub mod dynamic;
 pub mod error;
 pub mod extension;
 pub mod prelude;
@@ -36,14 +51,30 @@ pub mod macros {
     // pub use flatbox_macros::*;
 }
 
+#[cfg(feature = "net")]
+pub mod net {
+    pub use flatbox_net::*;
+}
+
+#[cfg(feature = "physics")]
 pub mod physics {
-    // pub use flatbox_physics::*;
+    pub use flatbox_physics::*;
+}
+
+#[cfg(feature = "physics2d")]
+pub mod physics2d {
+    pub use flatbox_physics2d::*;
 }
 
 pub mod render {
     pub use flatbox_render::*;
 }
 
+#[cfg(feature = "scripts")]
+pub mod scripts {
+    pub use flatbox_scripts::*;
+}
+
 pub mod systems {
     pub use flatbox_systems::*;
 }
@@ -51,28 +82,53 @@ pub mod systems {
 pub struct Flatbox {
     pub world: World,
     pub schedules: Schedules,
+    /// Passes, which are executed at the start of `Render` stage in the order of their
+    /// dependencies. Each pass is a system, added with [`Flatbox::add_render_pass`]
+    pub render_graph: RenderGraph<Schedule>,
+    /// Events of the app, which are available to systems as `Read<Events>`/`Write<Events>`
+    /// or [`EventReader`](flatbox_ecs::event::EventReader)/[`EventWriter`](flatbox_ecs::event::EventWriter)
+    pub events: Events,
+    /// Global values, which are available to systems as [`Res`](flatbox_ecs::resources::Res)/[`ResMut`](flatbox_ecs::resources::ResMut)
+    pub resources: Resources,
     pub extensions: Extensions,
-    pub context: Context,
-    pub renderer: Renderer,
+    /// Window context. It's `None` in headless mode
+    pub context: Option<Context>,
+    /// It's `None` in headless mode
+    pub renderer: Option<Renderer>,
     pub window_builder: WindowBuilder,
     pub on_window_event: OnEventFn,
+    runner: Option<RunnerFn>,
 }
 
 impl Flatbox {
     pub fn init(window_builder: WindowBuilder) -> Flatbox {
-        FlatboxLogger::init_with_level(window_builder.logger_level);
+        FlatboxLogger::init_with_config(LoggerConfig {
+            level: window_builder.logger_level,
+            file: window_builder.log_file.clone(),
+            ..Default::default()
+        });
 
-        let context = Context::new(&window_builder);
-        let renderer = Renderer::init(&context).expect("Cannot initialize renderer");
+        let (context, renderer) = if window_builder.headless {
+            (None, None)
+        } else {
+            let context = Context::new(&window_builder);
+            let renderer = Renderer::init(&context).expect("Cannot initialize renderer");
+
+            (Some(context), Some(renderer))
+        };
 
         Flatbox {
             world: World::new(),
             schedules: Schedules::new(),
+            render_graph: RenderGraph::new(),
+            events: Events::new(),
+            resources: Resources::new(),
             extensions: Extensions::new(),
             context,
             renderer,
             window_builder,
             on_window_event: Box::new(on_event_empty),
+            runner: None,
         }
     }
 
@@ -84,6 +140,43 @@ impl Flatbox {
         self
     }
 
+    /// Add system as a named pass of the [`RenderGraph`], which reads and writes the given targets
+    /// (e.g. [`SCENE_TARGET`](flatbox_render::renderer::graph::SCENE_TARGET)). Pass with the same name is replaced
+    pub fn add_render_pass<Args, Ret, S>(
+        &mut self,
+        name: impl Into<String>,
+        reads: &[&str],
+        writes: &[&str],
+        system: S,
+    ) -> &mut Self
+    where
+        S: 'static + System<Args, Ret> + Send,
+    {
+        let mut schedule = Schedule::builder();
+        schedule.add_system(ReaderSystem::new(system));
+
+        self.render_graph.add_pass(name, reads, writes, schedule.build());
+        self
+    }
+
+    /// Register handler of `E` events in the app's [`Events`]. Handlers are also registered on the first send
+    pub fn add_event<E: Event>(&mut self) -> &mut Self {
+        self.events.push_handler::<E>();
+        self
+    }
+
+    /// Insert resource, replacing the previous one of the same type
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) -> &mut Self {
+        self.resources.insert(resource);
+        self
+    }
+
+    /// Add custom stage, which is executed right after the `after` stage. See [`Schedules::add_stage`]
+    pub fn add_stage(&mut self, name: &'static str, after: SystemStage) -> &mut Self {
+        self.schedules.add_stage(name, after);
+        self
+    }
+
     pub fn flush_systems(&mut self, system_stage: SystemStage) -> &mut Self {
         self.schedules.flush_systems(system_stage);
         self
@@ -94,17 +187,52 @@ impl Flatbox {
         self
     }
 
-    pub fn apply_extension<E: Extension + 'static>(&mut self, extension: E) -> &mut Self {
+    /// Replace the default loop, which is executed by [`Flatbox::run`], e.g. with
+    /// a test runner: `app.set_runner(|app| app.run_frames(10))`
+    pub fn set_runner<F: FnOnce(&mut Flatbox) -> FlatboxResult<()> + 'static>(&mut self, runner: F) -> &mut Self {
+        self.runner = Some(Box::new(runner));
+        self
+    }
+
+    pub fn apply_extension<E: Extension>(&mut self, extension: E) -> &mut Self {
         if self.extensions.contains(&TypeId::of::<E>()) {
             panic!("Extension `{}` is already added!", pretty_type_name::<E>());
         } else {
-            extension.apply(self);
+            extension.build(self);
+            self.extensions.push(extension);
         }
 
         self
     }
 
+    /// Clean up all extensions in reverse order of adding
+    fn cleanup_extensions(&mut self) {
+        let extensions = std::mem::take(&mut self.extensions);
+
+        for extension in extensions.iter().rev() {
+            extension.cleanup(self);
+        }
+
+        self.extensions = extensions;
+    }
+
+    /// Notify all extensions about the crash in reverse order of adding
+    fn crash_extensions(&mut self) {
+        let extensions = std::mem::take(&mut self.extensions);
+
+        for extension in extensions.iter().rev() {
+            extension.on_crash(self);
+        }
+
+        self.extensions = extensions;
+    }
+
+    /// Apply default rendering extensions. Does nothing in headless mode
     pub fn default_extensions(&mut self) -> &mut Self {
+        if self.window_builder.headless {
+            return self;
+        }
+
         self
             .apply_extension(BaseRenderExtension)
             .apply_extension(RenderMaterialExtension::<DefaultMaterial>::new())
@@ -113,65 +241,539 @@ impl Flatbox {
         self
     }
 
-    pub fn run(&mut self){
+    /// Run the app with the runner, set with [`Flatbox::set_runner`], or with the default
+    /// one, which is either windowed or headless depending on [`WindowBuilder`].
+    /// `Shutdown` systems and extensions' cleanup are executed on exit, even if the runner failed
+    pub fn run(&mut self) -> FlatboxResult<()> {
+        let runner = self.runner.take();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| match runner {
+            Some(runner) => runner(self),
+            None if self.window_builder.headless => self.run_headless(),
+            None => self.run_windowed(),
+        }));
+
+        let result = match result {
+            Ok(result) => result,
+            Err(payload) => {
+                self.crash_extensions();
+                panic::resume_unwind(payload);
+            },
+        };
+
+        let shutdown_result = self.shutdown();
+        self.cleanup_extensions();
+
+        result.and(shutdown_result)
+    }
+
+    /// Execute `Shutdown` systems
+    fn shutdown(&mut self) -> FlatboxResult<()> {
+        profile_scope!("shutdown");
+
+        let mut shutdown_schedule = self.schedules.build_stage(Shutdown);
+
+        match self.renderer.as_mut() {
+            Some(renderer) => shutdown_schedule.execute_seq((&mut self.world, renderer, &mut self.events, &mut self.resources))?,
+            None => shutdown_schedule.execute_seq((&mut self.world, &mut self.events, &mut self.resources))?,
+        }
+
+        Ok(())
+    }
+
+    /// Default windowed loop
+    pub fn run_windowed(&mut self) -> FlatboxResult<()> {
+        let context = self.context.as_mut().expect("Context is not initialized");
+        let renderer = self.renderer.as_mut().expect("Renderer is not initialized");
         let on_window_event = std::mem::replace(&mut self.on_window_event, Box::new(on_event_empty));
-        let mut setup_schedule = self.schedules.get_systems(Setup).unwrap().build();
-        let mut update_schedule = self.schedules.get_systems(Update).unwrap().build();
-        let mut pre_render_schedule = self.schedules.get_systems(PreRender).unwrap().build();
-        let mut render_schedule = self.schedules.get_systems(Render).unwrap().build();
-        let mut post_render_schedule = self.schedules.get_systems(PostRender).unwrap().build();
+        let mut setup_schedule = self.schedules.build_stage(Setup);
+        let mut update_schedule = self.schedules.build_stage(Update);
+        let mut pre_render_schedule = self.schedules.build_stage(PreRender);
+        let mut render_schedule = self.schedules.build_stage(Render);
+        let mut post_render_schedule = self.schedules.build_stage(PostRender);
+        let mut paused_schedule = self.schedules.build_stage(Paused);
+        let mut render_graph = std::mem::take(&mut self.render_graph);
+        let mut frame_end_schedule = frame_end_schedule(self.window_builder.exit_on_close);
 
         #[cfg(feature = "egui")]
-        self.world.spawn((EguiBackend::new(&self.context),));
+        self.world.spawn((EguiBackend::new(context), Persistent));
+
+        {
+            profile_scope!("setup");
+            setup_schedule.execute_seq((
+                &mut self.world,
+                &mut *renderer,
+                &mut self.events,
+                &mut self.resources,
+            ))?;
+        }
 
-        setup_schedule.execute_seq((
-            &mut self.world,
-            &mut self.renderer,
-        )).expect("Cannot execute setup systems");
+        let mut window_commands = context.window_commands();
+        let mut window_info = context.window_info();
+        let mut gestures = GestureRecognizer::new();
+        let mut input = Input::new();
+        // Input, gathered since the previous update. Frames may have no updates,
+        // so per-frame `input` would drop presses before `Update` systems see them
+        let mut update_input = Input::new();
+        let mut time_scale = context.time_scale();
+        let main_control_flow = context.control_flow();
+        let mut result = Ok::<(), FlatboxError>(());
+        // Whether `Update` or `Paused` systems have been executed during the frame
+        let mut updated = false;
+
+        context.run(|event|{
+            // Stop handling events after the first error and leave the loop
+            if result.is_err() {
+                main_control_flow.exit();
+                return;
+            }
 
-        self.context.run(|event|{
             match event {
                 ContextEvent::ResizeEvent(extent) => {
-                    self.renderer.set_extent(extent);
+                    renderer.set_extent(extent);
                 },
-                ContextEvent::UpdateEvent => {
-                    update_schedule.execute((
+                ContextEvent::UpdateEvent(mut time) => {
+                    profile_scope!("update");
+                    updated = true;
+                    execute_parallel(&mut update_schedule, (
                         &mut self.world,
-                        &mut self.renderer,
-                    )).expect("Cannot execute update systems");
+                        &mut *renderer,
+                        &mut time,
+                        &mut window_commands,
+                        &mut window_info,
+                        &mut self.events,
+                        &mut self.resources,
+                        &mut update_input,
+                        &mut time_scale,
+                    )).unwrap_or_else(|e| result = Err(e.into()));
+
+                    update_input.clear();
                 },
-                ContextEvent::RenderEvent(mut display, mut control_flow) => { 
-                    pre_render_schedule.execute_seq((
-                        &mut display,
-                        &mut control_flow,
-                        &mut self.world,
-                        &mut self.renderer,
-                    )).expect("Cannot execute pre-render systems");
+                ContextEvent::RenderEvent(mut display, mut control_flow, mut time) => { 
+                    profile_frame!();
+                    renderer.begin_frame();
+
+                    {
+                        profile_scope!("pre_render");
+                        if let Err(e) = pre_render_schedule.execute_seq((
+                            &mut display,
+                            &mut control_flow,
+                            &mut self.world,
+                            &mut *renderer,
+                            &mut time,
+                            &mut window_commands,
+                            &mut window_info,
+                            &mut self.events,
+                            &mut self.resources,
+                            &mut input,
+                            &mut time_scale,
+                        )) {
+                            result = Err(e.into());
+                            return;
+                        }
+                    }
+
+                    {
+                        profile_scope!("render");
+                        let graph_result = render_graph.execute(|pass| {
+                            profile_scope!(pass.name().to_owned());
+                            renderer.begin_pass_timer(pass.name());
+                            pass.pass.execute_seq((
+                                &mut display,
+                                &mut control_flow,
+                                &mut self.world,
+                                &mut *renderer,
+                                &mut time,
+                                &mut window_commands,
+                                &mut window_info,
+                                &mut self.events,
+                                &mut self.resources,
+                                &mut input,
+                                &mut time_scale,
+                            )).map_err(FlatboxError::from)
+                        });
+                        renderer.end_pass_timer();
 
-                    render_schedule.execute_seq((
-                        &mut display,
-                        &mut control_flow,
+                        if let Err(e) = graph_result {
+                            result = Err(e);
+                            return;
+                        }
+
+                        if let Err(e) = render_schedule.execute_seq((
+                            &mut display,
+                            &mut control_flow,
+                            &mut self.world,
+                            &mut *renderer,
+                            &mut time,
+                            &mut window_commands,
+                            &mut window_info,
+                            &mut self.events,
+                            &mut self.resources,
+                            &mut input,
+                            &mut time_scale,
+                        )) {
+                            result = Err(e.into());
+                            return;
+                        }
+                    }
+
+                    {
+                        profile_scope!("post_render");
+                        if let Err(e) = post_render_schedule.execute_seq((
+                            &mut display,
+                            &mut control_flow,
+                            &mut self.world,
+                            &mut *renderer,
+                            &mut time,
+                            &mut window_commands,
+                            &mut window_info,
+                            &mut self.events,
+                            &mut self.resources,
+                            &mut input,
+                            &mut time_scale,
+                        )) {
+                            result = Err(e.into());
+                        }
+                    }
+                },
+                ContextEvent::FrameEndEvent(control_flow) => {
+                    apply_pause_events(&self.events, &time_scale);
+
+                    // Systems can't veto the close request before they see it,
+                    // so it's kept until the frame with updates
+                    let deferred_close = match std::mem::take(&mut updated) {
+                        true => None,
+                        false => self.events.get_handler_mut::<CloseRequested>().and_then(|handler| handler.take()),
+                    };
+
+                    frame_end_schedule.execute_seq((
                         &mut self.world,
-                        &mut self.renderer,
-                    )).expect("Cannot execute render systems");
+                        &mut self.events,
+                        &mut input,
+                    )).unwrap_or_else(|e| result = Err(e.into()));
+
+                    if let Some(close) = deferred_close {
+                        self.events.send(close);
+                    }
+
+                    if self.world.query::<&AppExit>().iter().next().is_some() {
+                        control_flow.exit();
+                    }
 
-                    post_render_schedule.execute_seq((
-                        &mut display,
-                        &mut control_flow,
+                    input.clear();
+                },
+                ContextEvent::MouseMotion(delta) => {
+                    input.handle_mouse_motion(delta);
+                    update_input.handle_mouse_motion(delta);
+                },
+                ContextEvent::User(event) => self.events.send::<UserEvent>(event),
+                ContextEvent::PauseEvent => self.events.send(WindowPaused),
+                ContextEvent::ResumeEvent => self.events.send(WindowResumed),
+                ContextEvent::PausedUpdateEvent(mut time) => {
+                    profile_scope!("paused");
+                    updated = true;
+                    execute_parallel(&mut paused_schedule, (
                         &mut self.world,
-                        &mut self.renderer,
-                    )).expect("Cannot execute post-render systems");
+                        &mut *renderer,
+                        &mut time,
+                        &mut window_commands,
+                        &mut window_info,
+                        &mut self.events,
+                        &mut self.resources,
+                        &mut update_input,
+                        &mut time_scale,
+                    )).unwrap_or_else(|e| result = Err(e.into()));
+
+                    update_input.clear();
+                },
+                ContextEvent::WindowInfoChanged(info) => {
+                    if info.scale_factor != window_info.scale_factor {
+                        self.events.send(ScaleFactorChanged { scale_factor: info.scale_factor });
+                    }
+
+                    window_info = info;
                 },
                 ContextEvent::WindowEvent(display, event) => {
+                    input.handle_window_event(&event);
+                    update_input.handle_window_event(&event);
+
+                    if let WindowEvent::Touch(touch) = &event {
+                        let touch = TouchEvent::from(touch);
+
+                        if let Some(gesture) = gestures.handle(&touch) {
+                            self.events.send(gesture);
+                        }
+
+                        self.events.send(touch);
+                    }
+
+                    for text_input in TextInputEvent::from_window_event(&event) {
+                        self.events.send(text_input);
+                    }
+
+                    if let WindowEvent::CloseRequested = event {
+                        self.events.send(CloseRequested::new());
+                    }
+
                     if on_window_event(&mut self.world, event) {
                         display.lock().window().request_redraw();
                     }
                 },
             }
-        });
+        })?;
+
+        result
+    }
+
+    /// Run `Setup` and `Update` schedules in a plain fixed-step loop without window
+    /// and rendering, until [`AppExit`] is spawned. `Update` is executed every step,
+    /// even while the game time is paused (with zero time step), and `Paused`
+    /// stage is executed after it while paused
+    pub fn run_headless(&mut self) -> FlatboxResult<()> {
+        self.run_headless_loop(None, true)
+    }
+
+    /// Run `Setup` and then exactly `frames` fixed updates as fast as possible without
+    /// window and rendering (or less, if [`AppExit`] is spawned earlier). Useful for tests
+    /// and benchmarks
+    pub fn run_frames(&mut self, frames: u64) -> FlatboxResult<()> {
+        self.run_headless_loop(Some(frames), false)
+    }
+
+    /// Run `Setup` and then replay the recording without window and rendering: one
+    /// fixed update per recorded tick with the recorded input, as fast as possible.
+    /// [`Rng`] singletons are reseeded with the seed of the recording (or spawned, if
+    /// there are none), so deterministic runs can be reproduced, e.g. in tests
+    pub fn run_replay(&mut self, recording: &InputRecording) -> FlatboxResult<()> {
+        let mut setup_schedule = self.schedules.build_stage(Setup);
+        let mut update_schedule = self.schedules.build_stage(Update);
+        let mut frame_end_schedule = frame_end_schedule(self.window_builder.exit_on_close);
+        let mut input = Input::new();
+        let mut time_scale = TimeScale::default();
+        let mut time = Time::new();
+        let fixed_delta_time = if recording.fixed_delta_time.is_zero() {
+            Duration::from_secs_f64(1.0 / self.window_builder.updates_per_second as f64)
+        } else {
+            recording.fixed_delta_time
+        };
+
+        time.set_fixed_delta_time(fixed_delta_time);
+
+        let mut reseeded = false;
+        for (_, mut rng) in &mut self.world.query::<&mut Rng>() {
+            rng.reseed(recording.seed);
+            reseeded = true;
+        }
+
+        if !reseeded {
+            self.world.spawn((Rng::new(recording.seed), Persistent));
+        }
+
+        {
+            profile_scope!("setup");
+            setup_schedule.execute_seq((
+                &mut self.world,
+                &mut self.events,
+                &mut self.resources,
+            ))?;
+        }
+
+        for tick in &recording.ticks {
+            if self.world.query::<&AppExit>().iter().next().is_some() {
+                break;
+            }
+
+            profile_frame!();
+            input.clone_from(tick);
+            time.set_time_scale(time_scale.scale());
+            time.advance(fixed_delta_time);
+
+            execute_parallel(&mut update_schedule, (
+                &mut self.world,
+                &mut time,
+                &mut self.events,
+                &mut self.resources,
+                &mut input,
+                &mut time_scale,
+            ))?;
+
+            time.advance_fixed();
+            apply_pause_events(&self.events, &time_scale);
+
+            frame_end_schedule.execute_seq((
+                &mut self.world,
+                &mut self.events,
+                &mut input,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn run_headless_loop(&mut self, frames: Option<u64>, real_time: bool) -> FlatboxResult<()> {
+        let mut setup_schedule = self.schedules.build_stage(Setup);
+        let mut update_schedule = self.schedules.build_stage(Update);
+        let mut paused_schedule = self.schedules.build_stage(Paused);
+        let mut frame_end_schedule = frame_end_schedule(self.window_builder.exit_on_close);
+        let mut input = Input::new();
+        let mut time_scale = TimeScale::default();
+        let mut time = Time::new();
+        let mut accumulated_time = Duration::ZERO;
+        let mut frame_count = 0;
+        let fixed_delta_time = Duration::from_secs_f64(1.0 / self.window_builder.updates_per_second as f64);
+
+        {
+            profile_scope!("setup");
+            setup_schedule.execute_seq((
+                &mut self.world,
+                &mut self.events,
+                &mut self.resources,
+            ))?;
+        }
+
+        while self.world.query::<&AppExit>().iter().next().is_none() {
+            if frames.is_some_and(|frames| frame_count >= frames) {
+                break;
+            }
+
+            let frame_start = Instant::now();
+
+            profile_frame!();
+            frame_count += 1;
+            time.set_time_scale(time_scale.scale());
+            time.advance(fixed_delta_time);
+            accumulated_time += time.unscaled_delta_time();
+
+            // Updates keep ticking while paused, so systems can still resume or exit,
+            // but the game time of each update is scaled
+            time.set_fixed_delta_time(fixed_delta_time.mul_f64(time.time_scale()));
+
+            while accumulated_time >= fixed_delta_time {
+                profile_scope!("update");
+                accumulated_time -= fixed_delta_time;
+
+                execute_parallel(&mut update_schedule, (
+                    &mut self.world,
+                    &mut time,
+                    &mut self.events,
+                    &mut self.resources,
+                    &mut input,
+                    &mut time_scale,
+                ))?;
+
+                time.advance_fixed();
+            }
+
+            if time_scale.is_paused() {
+                profile_scope!("paused");
+                execute_parallel(&mut paused_schedule, (
+                    &mut self.world,
+                    &mut time,
+                    &mut self.events,
+                    &mut self.resources,
+                    &mut input,
+                    &mut time_scale,
+                ))?;
+            }
+
+            apply_pause_events(&self.events, &time_scale);
+
+            frame_end_schedule.execute_seq((
+                &mut self.world,
+                &mut self.events,
+                &mut input,
+            ))?;
+
+            if !real_time {
+                continue;
+            }
+
+            if let Some(remaining) = fixed_delta_time.checked_sub(frame_start.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Systems, which are executed at the end of each frame, after pause events are applied
+fn frame_end_schedule(exit_on_close: bool) -> Schedule {
+    let mut builder = Schedule::builder();
+
+    if exit_on_close {
+        builder.add_system(exit_on_window_close);
+    }
+
+    builder.add_system(clear_events);
+    builder.build()
+}
+
+/// Pause or resume the game time on [`Pause`] and [`Resume`] events
+fn apply_pause_events(events: &Events, time_scale: &TimeScale) {
+    if events.read::<Pause>().is_some() {
+        time_scale.pause();
+    }
+
+    if events.read::<Resume>().is_some() {
+        time_scale.resume();
     }
 }
 
 pub type OnEventFn = Box<dyn Fn(&mut World, WindowEvent) -> bool>;
+pub type RunnerFn = Box<dyn FnOnce(&mut Flatbox) -> FlatboxResult<()>>;
+
+fn on_event_empty(_: &mut World, _: WindowEvent) -> bool { false }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use flatbox_core::logger::LoggerLevel;
+    use flatbox_ecs::{CommandBuffer, Read, Write, event::EventWriter};
+
+    use super::*;
+
+    #[test]
+    fn headless_pause_resume_exit() {
+        let mut app = Flatbox::init(WindowBuilder {
+            headless: true,
+            logger_level: LoggerLevel::None,
+            ..Default::default()
+        });
+
+        let log = Arc::new(Mutex::new(vec![]));
+
+        let update_log = log.clone();
+        let mut updates = 0;
+        app.add_system(Update, move |time: Read<Time>, mut pause: EventWriter<Pause>, mut cmd: Write<CommandBuffer>| {
+            update_log.lock().unwrap().push(if time.delta_time().is_zero() { "paused update" } else { "update" });
+            updates += 1;
 
-fn on_event_empty(_: &mut World, _: WindowEvent) -> bool { false }
\ No newline at end of file
+            match updates {
+                2 => pause.send(Pause),
+                6 => { cmd.spawn((AppExit,)); },
+                _ => {},
+            }
+        });
+
+        let paused_log = log.clone();
+        let mut paused = 0;
+        app.add_system(Paused, move |mut resume: EventWriter<Resume>| {
+            paused_log.lock().unwrap().push("paused");
+            paused += 1;
+
+            if paused == 2 {
+                resume.send(Resume);
+            }
+        });
+
+        app.run().unwrap();
+
+        assert_eq!(*log.lock().unwrap(), [
+            "update", "update",
+            "paused update", "paused",
+            "paused update", "paused",
+            "update", "update",
+        ]);
+    }
+}